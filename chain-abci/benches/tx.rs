@@ -1,5 +1,5 @@
 use abci::{Application, RequestCheckTx, RequestInitChain};
-use chain_abci::app::ChainNodeApp;
+use chain_abci::app::{compute_council_state_hash, ChainNodeApp};
 use chain_abci::storage::account::AccountStorage;
 use chain_abci::storage::account::AccountWrapper;
 use chain_abci::storage::tx::StarlingFixedKey;
@@ -8,10 +8,12 @@ use chain_core::common::MerkleTree;
 use chain_core::compute_app_hash;
 use chain_core::init::config::AccountType;
 use chain_core::init::config::InitNetworkParameters;
+use chain_core::init::config::SlashingParameters;
 use chain_core::init::config::{InitialValidator, ValidatorKeyType};
 use chain_core::init::{address::RedeemAddress, coin::Coin, config::InitConfig};
 use chain_core::state::account::*;
-use chain_core::tx::fee::{LinearFee, Milli};
+use chain_core::tx::fee::{FeePolicy, LinearFee, Milli};
+use chain_core::tx::witness::tree::RawPubkey;
 use chain_core::tx::witness::EcdsaSignature;
 use chain_core::tx::witness::TxInWitness;
 use chain_core::tx::TransactionId;
@@ -26,6 +28,7 @@ use chain_core::tx::{
     },
     TxAux,
 };
+use chain_tx_validation::witness::SchnorrBatch;
 use criterion::Criterion;
 use criterion::{criterion_group, criterion_main};
 use kvdb::KeyValueDB;
@@ -33,6 +36,7 @@ use kvdb_memorydb::create;
 use parity_codec::Encode;
 use secp256k1::{
     key::{PublicKey, SecretKey},
+    schnorrsig::schnorr_sign,
     Message, Secp256k1, Signing,
 };
 use std::collections::BTreeMap;
@@ -86,9 +90,18 @@ fn init_chain_for(addresses: &Vec<RedeemAddress>) -> ChainNodeApp {
     );
 
     let params = InitNetworkParameters {
-        initial_fee_policy: LinearFee::new(Milli::new(1, 1), Milli::new(1, 1)),
+        initial_fee_policy: FeePolicy::Linear(LinearFee::new(Milli::new(1, 1), Milli::new(1, 1))),
         required_council_node_stake: remaining,
         unbonding_period: 1,
+        block_reward_share: Milli::new(0, 500),
+        slashing_config: SlashingParameters {
+            byzantine_slash_ratio: Milli::new(0, 200),
+            liveness_slash_ratio: Milli::new(0, 10),
+            block_signing_window: 100,
+            missed_block_threshold: 50,
+            jail_duration: 86400,
+        },
+        chain_id_hash_required_from: None,
     };
     let c = InitConfig::new(
         distribution,
@@ -121,7 +134,13 @@ fn init_chain_for(addresses: &Vec<RedeemAddress>) -> ChainNodeApp {
             )
             .expect("initial insert");
 
-        let genesis_app_hash = compute_app_hash(&tx_tree, &new_account_root, &rp);
+        let genesis_app_hash = compute_app_hash(
+            &tx_tree,
+            &new_account_root,
+            &rp,
+            &c.network_params.governed_params(),
+            &compute_council_state_hash(&c.network_params.slashing_config, &[], &[]),
+        );
 
         let example_hash = hex::encode_upper(genesis_app_hash);
         let mut app = ChainNodeApp::new_with_storage(
@@ -186,6 +205,56 @@ fn check_x_tx(app: &mut ChainNodeApp, reqs: &Vec<RequestCheckTx>) {
     }
 }
 
+fn prepare_tree_sig_witnesses(upper: u8) -> (TxId, ExtendedAddr, Vec<TxInWitness>) {
+    let secp = Secp256k1::new();
+    let secret_keys: Vec<SecretKey> = (0x01..upper)
+        .map(|x| SecretKey::from_slice(&[x; 32]).unwrap())
+        .collect();
+    let public_keys: Vec<RawPubkey> = secret_keys
+        .iter()
+        .map(|secret_key| {
+            RawPubkey::from(PublicKey::from_secret_key(&secp, secret_key).serialize())
+        })
+        .collect();
+    let merkle_tree = MerkleTree::new(public_keys.clone());
+    let txid = Tx::new().id();
+    let message = Message::from_slice(&txid).unwrap();
+    let witnesses = secret_keys
+        .iter()
+        .zip(public_keys.into_iter())
+        .map(|(secret_key, public_key)| {
+            TxInWitness::TreeSig(
+                schnorr_sign(&secp, &message, secret_key).0,
+                merkle_tree.generate_proof(public_key).unwrap(),
+            )
+        })
+        .collect();
+    (
+        txid,
+        ExtendedAddr::OrTree(merkle_tree.root_hash()),
+        witnesses,
+    )
+}
+
+fn verify_witnesses_one_by_one(txid: &TxId, address: &ExtendedAddr, witnesses: &[TxInWitness]) {
+    let secp = Secp256k1::verification_only();
+    for (i, witness) in witnesses.iter().enumerate() {
+        // a batch of one signature is checked with a single `schnorr_verify`
+        let mut single = SchnorrBatch::new(&secp);
+        single.add(i, witness, txid, address).unwrap();
+        single.verify().unwrap();
+    }
+}
+
+fn verify_witnesses_batch(txid: &TxId, address: &ExtendedAddr, witnesses: &[TxInWitness]) {
+    let secp = Secp256k1::verification_only();
+    let mut batch = SchnorrBatch::new(&secp);
+    for (i, witness) in witnesses.iter().enumerate() {
+        batch.add(i, witness, txid, address).unwrap();
+    }
+    batch.verify().unwrap();
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let (mut app, txs) = prepare_app_valid_txs(0x05);
     let reqs = txs
@@ -197,6 +266,14 @@ fn criterion_benchmark(c: &mut Criterion) {
         })
         .collect();
     c.bench_function("checktx x", move |b| b.iter(|| check_x_tx(&mut app, &reqs)));
+    let (txid, address, witnesses) = prepare_tree_sig_witnesses(0x41);
+    let (txid2, address2, witnesses2) = (txid, address.clone(), witnesses.clone());
+    c.bench_function("verify 64 witnesses one by one", move |b| {
+        b.iter(|| verify_witnesses_one_by_one(&txid, &address, &witnesses))
+    });
+    c.bench_function("verify 64 witnesses batch", move |b| {
+        b.iter(|| verify_witnesses_batch(&txid2, &address2, &witnesses2))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -1,3 +1,5 @@
+use super::slashing::LivenessTracker;
+use super::{MempoolState, SnapshotConfig, SnapshotWriter};
 use crate::enclave_bridge::EnclaveProxy;
 use crate::storage::account::AccountStorage;
 use crate::storage::account::AccountWrapper;
@@ -5,21 +7,27 @@ use crate::storage::tx::get_account;
 use crate::storage::tx::StarlingFixedKey;
 use crate::storage::*;
 use abci::*;
+use blake2::Blake2s;
+use chain_core::common::hash256;
 use chain_core::common::MerkleTree;
 use chain_core::common::Timespec;
 use chain_core::common::{H256, HASH_SIZE_256};
-use chain_core::compute_app_hash;
 use chain_core::init::address::RedeemAddress;
 use chain_core::init::coin::Coin;
 use chain_core::init::config::AccountType;
 use chain_core::init::config::InitConfig;
 use chain_core::init::config::InitNetworkParameters;
+use chain_core::init::config::SlashingParameters;
 use chain_core::state::account::{StakedState, StakedStateAddress};
+use chain_core::state::governance::{NetworkParameters, ParamChangeProposal};
 use chain_core::state::tendermint::{BlockHeight, TendermintVotePower};
 use chain_core::state::CouncilNode;
 use chain_core::state::RewardsPoolState;
-use chain_core::tx::{fee::LinearFee, TxAux};
+use chain_core::tx::data::attribute::chain_id_hash;
+use chain_core::tx::{fee::Milli, TxAux};
+use chain_core::{compute_app_hash, AppStateRoots};
 use enclave_protocol::{EnclaveRequest, EnclaveResponse};
+use integer_encoding::VarInt;
 use kvdb::DBTransaction;
 use log::{info, warn};
 use parity_codec::{Decode, Encode};
@@ -40,14 +48,20 @@ pub struct ChainNodeState {
     pub last_account_root_hash: StarlingFixedKey,
     /// last rewards pool state
     pub rewards_pool: RewardsPoolState,
-    /// fee policy to apply -- TODO: change to be against T: FeeAlgorithm
-    pub fee_policy: LinearFee,
-    /// time when unbonded stake can be withdrawn
-    pub unbonding_period: u32,
-    /// (minimal?) amount required to be bonded in validator-associated accounts
-    pub required_council_node_stake: Coin,
+    /// network parameters changeable by council node governance (fee policy, unbonding period etc.)
+    pub network_params: NetworkParameters,
     /// council nodes metadata
     pub council_nodes: Vec<CouncilNode>,
+    /// share of the remaining rewards pool paid out in each block
+    pub block_reward_share: Milli,
+    /// punishment of council nodes for misbehaviour / being offline
+    pub slashing_config: SlashingParameters,
+    /// blocks missed by council nodes in the recent block signing window
+    pub validator_liveness: Vec<(StakedStateAddress, LivenessTracker)>,
+    /// pending network parameter change proposals
+    pub proposals: Vec<ParamChangeProposal>,
+    /// block height from which transactions have to commit to the full chain ID (None == it stays optional)
+    pub chain_id_hash_required_from: Option<BlockHeight>,
 }
 
 impl ChainNodeState {
@@ -65,12 +79,44 @@ impl ChainNodeState {
             block_time: genesis_time,
             last_account_root_hash,
             rewards_pool,
-            fee_policy: network_params.initial_fee_policy,
-            unbonding_period: network_params.unbonding_period,
-            required_council_node_stake: network_params.required_council_node_stake,
+            network_params: network_params.governed_params(),
             council_nodes,
+            block_reward_share: network_params.block_reward_share,
+            slashing_config: network_params.slashing_config,
+            validator_liveness: Vec::new(),
+            proposals: Vec::new(),
+            chain_id_hash_required_from: network_params.chain_id_hash_required_from,
         }
     }
+
+    /// whether transactions in the next block have to commit to the full chain ID
+    pub fn chain_id_hash_required(&self) -> bool {
+        self.chain_id_hash_required_from
+            .map_or(false, |height| self.last_block_height + 1 >= height)
+    }
+
+    /// hash of the council state kept outside of the account trie (committed to in the app hash)
+    pub fn council_state_hash(&self) -> H256 {
+        compute_council_state_hash(
+            &self.slashing_config,
+            &self.validator_liveness,
+            &self.proposals,
+        )
+    }
+}
+
+/// computes the hash of the council state kept outside of the account trie
+/// (currently blake2s(scale bytes(slashing parameters) || scale bytes(liveness trackers)
+/// || scale bytes(pending proposals with their votes)))
+pub fn compute_council_state_hash(
+    slashing_config: &SlashingParameters,
+    validator_liveness: &[(StakedStateAddress, LivenessTracker)],
+    proposals: &[ParamChangeProposal],
+) -> H256 {
+    let mut bs = slashing_config.encode();
+    validator_liveness.encode_to(&mut bs);
+    proposals.encode_to(&mut bs);
+    hash256::<Blake2s>(&bs)
 }
 
 /// The global ABCI state
@@ -81,12 +127,16 @@ pub struct ChainNodeApp<T: EnclaveProxy> {
     pub accounts: AccountStorage,
     /// valid transactions after DeliverTx before EndBlock/Commit
     pub delivered_txs: Vec<TxAux>,
+    /// inputs and nonces used by the transactions accepted in CheckTx since the last commit
+    pub mempool_state: MempoolState,
     /// root hash of the sparse merkle patricia trie of staking account states after DeliverTx before EndBlock/Commit
     pub uncommitted_account_root_hash: StarlingFixedKey,
     /// a reference to genesis (used when there is no committed state)
     pub genesis_app_hash: H256,
     /// last two hex digits in chain_id
     pub chain_hex_id: u8,
+    /// hash of the full chain_id (transactions may commit to it)
+    pub chain_id_hash: H256,
     /// last application state snapshot (if any)
     pub last_state: Option<ChainNodeState>,
     /// validator voting power
@@ -95,11 +145,20 @@ pub struct ChainNodeApp<T: EnclaveProxy> {
     pub validator_pubkeys: BTreeMap<StakedStateAddress, PubKey>,
     /// validator addresses whose bonded amount changed in the current block
     pub power_changed_in_block: BTreeMap<StakedStateAddress, TendermintVotePower>,
+    /// whether a network parameter change was activated (or the dynamic fee was adjusted) in the current block
+    pub params_changed_in_block: bool,
     /// proxy for processing transaction validation requests
     pub tx_validator: T,
+    /// configuration of periodic state snapshots (if enabled)
+    pub snapshot_config: Option<SnapshotConfig>,
+    /// background writer of the periodic state snapshots
+    pub snapshot_writer: SnapshotWriter,
+    /// number of the most recent committed heights whose account roots are kept for historical queries (None == all are kept)
+    pub account_root_history: Option<BlockHeight>,
 }
 
-fn get_validator_key(node: &CouncilNode) -> PubKey {
+/// Tendermint public key of the council node's consensus key
+pub fn get_validator_key(node: &CouncilNode) -> PubKey {
     let mut pk = PubKey::new();
     let (keytype, key) = node.consensus_pubkey.to_validator_update();
     pk.set_field_type(keytype);
@@ -125,7 +184,9 @@ fn get_validator_mapping(
             accounts,
         )
         .expect("council node staking account should be in the account state");
-        if account.bonded < last_app_state.required_council_node_stake {
+        if account.total_stake() < last_app_state.network_params.required_council_node_stake
+            || account.is_jailed()
+        {
             validator_voting_power.insert(
                 node.staking_account_address,
                 TendermintVotePower::from(Coin::zero()),
@@ -133,7 +194,7 @@ fn get_validator_mapping(
         } else {
             validator_voting_power.insert(
                 node.staking_account_address,
-                TendermintVotePower::from(account.bonded),
+                TendermintVotePower::from(account.total_stake()),
             );
         }
     }
@@ -197,6 +258,11 @@ fn store_valid_genesis_state(
     let encoded = last_state.encode();
     inittx.put(COL_NODE_INFO, LAST_STATE_KEY, &encoded);
     inittx.put(COL_EXTRA, b"init_chain_state", &encoded);
+    inittx.put(
+        COL_ACCOUNT_ROOTS,
+        &i64::encode_var_vec(0),
+        &last_state.last_account_root_hash,
+    );
     last_state
 }
 
@@ -243,14 +309,20 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
             storage,
             accounts,
             delivered_txs: Vec::new(),
+            mempool_state: MempoolState::default(),
             uncommitted_account_root_hash: last_app_state.last_account_root_hash,
             chain_hex_id,
+            chain_id_hash: chain_id_hash(chain_id),
             genesis_app_hash,
             last_state: Some(last_app_state),
             validator_voting_power,
             validator_pubkeys,
             power_changed_in_block: BTreeMap::new(),
+            params_changed_in_block: false,
             tx_validator,
+            snapshot_config: None,
+            snapshot_writer: SnapshotWriter::default(),
+            account_root_history: None,
         }
     }
 
@@ -293,6 +365,14 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
             .expect("app state lookup")
         {
             info!("last app state stored");
+            // the last app state (and other stored data) can only be decoded in the supported format
+            let stored_version = storage.get_version();
+            if stored_version != STORAGE_VERSION {
+                panic!(
+                    "stored data format version: {} does not match the supported version: {} (the node needs to be synced from genesis or restored from a snapshot into an empty storage)",
+                    stored_version, STORAGE_VERSION
+                );
+            }
             let data = last_app_state.to_vec();
             let last_state =
                 ChainNodeState::decode(&mut data.as_slice()).expect("deserialize app state");
@@ -309,6 +389,11 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
             let mut inittx = storage.db.transaction();
             inittx.put(COL_NODE_INFO, GENESIS_APP_HASH_KEY, &genesis_app_hash);
             inittx.put(COL_EXTRA, CHAIN_ID_KEY, chain_id.as_bytes());
+            inittx.put(
+                COL_NODE_INFO,
+                STORAGE_VERSION_KEY,
+                &STORAGE_VERSION.encode(),
+            );
             storage
                 .db
                 .write(inittx)
@@ -317,14 +402,20 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
                 storage,
                 accounts,
                 delivered_txs: Vec::new(),
+                mempool_state: MempoolState::default(),
                 uncommitted_account_root_hash: [0u8; 32],
                 chain_hex_id,
+                chain_id_hash: chain_id_hash(chain_id),
                 genesis_app_hash,
                 last_state: None,
                 validator_voting_power: BTreeMap::new(),
                 validator_pubkeys: BTreeMap::new(),
                 power_changed_in_block: BTreeMap::new(),
+                params_changed_in_block: false,
                 tx_validator,
+                snapshot_config: None,
+                snapshot_writer: SnapshotWriter::default(),
+                account_root_history: None,
             }
         }
     }
@@ -389,13 +480,33 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
                     &mut wrapped.iter().collect::<Vec<_>>(),
                 )
                 .expect("initial insert");
+            self.accounts
+                .collect_uncommitted(None, &new_account_root)
+                .expect("account trie garbage collection");
 
-            let genesis_app_hash = compute_app_hash(&tx_tree, &new_account_root, &rp);
+            let network_params = conf.network_params.governed_params();
+            let council_state_hash =
+                compute_council_state_hash(&conf.network_params.slashing_config, &[], &[]);
+            let genesis_app_hash = compute_app_hash(
+                &tx_tree,
+                &new_account_root,
+                &rp,
+                &network_params,
+                &council_state_hash,
+            );
             if self.genesis_app_hash != genesis_app_hash {
                 panic!("initchain resulting genesis app hash: {:?} does not match the expected genesis app hash: {:?}", genesis_app_hash, self.genesis_app_hash);
             }
 
             let mut inittx = db.transaction();
+            let roots = AppStateRoots {
+                valid_tx_root: tx_tree.root_hash(),
+                account_state_root: new_account_root,
+                rewards_pool_hash: rp.hash(),
+                network_params_hash: network_params.hash(),
+                council_state_hash,
+            };
+            inittx.put(COL_APP_STATE_ROOTS, &genesis_app_hash, &roots.encode());
             check_and_store_consensus_params(
                 _req.consensus_params.as_ref(),
                 &nodes,
@@ -411,6 +522,9 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
                 let pk = get_validator_key(&node);
                 self.validator_pubkeys
                     .insert(node.staking_account_address, pk.clone());
+                // same as `get_validator_mapping` after a restart
+                self.validator_voting_power
+                    .insert(node.staking_account_address, power);
                 validator.set_pub_key(pk);
                 validators.push(validator);
             }
@@ -1,27 +1,29 @@
 use super::ChainNodeApp;
 use crate::app::spend_utxos;
 use crate::enclave_bridge::EnclaveProxy;
+use crate::storage::tx::TxMeta;
 use crate::storage::*;
 use abci::*;
-use bit_vec::BitVec;
 use chain_core::common::MerkleTree;
-use chain_core::compute_app_hash;
 use chain_core::tx::data::input::{TxoIndex, TxoPointer};
 use chain_core::tx::data::TxId;
 use chain_core::tx::PlainTxAux;
 use chain_core::tx::TxAux;
-use chain_tx_validation::TxWithOutputs;
+use chain_core::AppStateRoots;
+use chain_tx_validation::{TxInclusion, TxWithOutputs};
 use integer_encoding::VarInt;
 use kvdb::{DBTransaction, KeyValueDB};
 use parity_codec::{Decode, Encode};
 use std::sync::Arc;
 
 /// Given a db and a DB transaction, it will go through TX inputs and mark them as spent
-/// in the TX_META storage and it will create a new entry for TX in TX_META with all outputs marked as unspent.
+/// in the TX_META storage and it will create a new entry for TX in TX_META with all outputs marked as unspent
+/// (and the block it was included in).
 pub fn update_utxos_commit(
     inputs: &[TxoPointer],
     no_of_outputs: TxoIndex,
     txid: TxId,
+    inclusion: TxInclusion,
     db: Arc<dyn KeyValueDB>,
     dbtx: &mut DBTransaction,
 ) {
@@ -29,7 +31,7 @@ pub fn update_utxos_commit(
     dbtx.put(
         COL_TX_META,
         &txid,
-        &BitVec::from_elem(no_of_outputs as usize, false).to_bytes(),
+        &TxMeta::new(inclusion, no_of_outputs as usize).encode(),
     );
 }
 
@@ -38,15 +40,34 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
     pub fn commit_handler(&mut self, _req: &RequestCommit) -> ResponseCommit {
         let orig_state = self.last_state.clone();
         let mut new_state = orig_state.expect("executing block commit, but no app state stored (i.e. no initchain or recovery was executed)");
+        let previous_account_root = new_state.last_account_root_hash;
         let mut resp = ResponseCommit::new();
         let mut inittx = self.storage.db.transaction();
-        if !self.delivered_txs.is_empty() {
+        // block rewards may change the account state (and the rewards pool) in blocks without transactions
+        // (as well as network parameter changes and council state changes, e.g. in liveness tracking)
+        let council_state_hash = new_state.council_state_hash();
+        let last_roots = self
+            .storage
+            .db
+            .get(COL_APP_STATE_ROOTS, &new_state.last_apphash[..])
+            .expect("app state roots lookup")
+            .map(|roots| AppStateRoots::decode(&mut &roots[..]).expect("app state roots"))
+            .expect("app state roots of the last app hash should be stored");
+        if !self.delivered_txs.is_empty()
+            || self.uncommitted_account_root_hash != new_state.last_account_root_hash
+            || self.params_changed_in_block
+            || last_roots.council_state_hash != council_state_hash
+        {
             let ids: Vec<TxId> = self
                 .delivered_txs
                 .iter()
                 .map(chain_core::tx::TxAux::tx_id)
                 .collect();
             let tree = MerkleTree::new(ids);
+            let inclusion = TxInclusion {
+                block_height: new_state.last_block_height,
+                block_time: new_state.block_time,
+            };
             for txaux in self.delivered_txs.iter() {
                 let txid: TxId = txaux.tx_id();
                 match &txaux {
@@ -70,6 +91,7 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
                             &inputs,
                             *no_of_outputs,
                             txid,
+                            inclusion,
                             self.storage.db.clone(),
                             &mut inittx,
                         );
@@ -86,6 +108,41 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
                         inittx.put(COL_WITNESS, &txid[..], &witness.encode());
                         // account should be already updated in deliver_tx
                     }
+                    TxAux::NodeJoinTx(tx, witness) => {
+                        inittx.put(COL_BODIES, &txid[..], &tx.encode());
+                        inittx.put(COL_WITNESS, &txid[..], &witness.encode());
+                        // account and council nodes should be already updated in deliver_tx
+                    }
+                    TxAux::UnjailTx(tx, witness) => {
+                        inittx.put(COL_BODIES, &txid[..], &tx.encode());
+                        inittx.put(COL_WITNESS, &txid[..], &witness.encode());
+                        // account should be already updated in deliver_tx
+                    }
+                    TxAux::NodeLeaveTx(tx, witness) => {
+                        inittx.put(COL_BODIES, &txid[..], &tx.encode());
+                        inittx.put(COL_WITNESS, &txid[..], &witness.encode());
+                        // account and council nodes should be already updated in deliver_tx
+                    }
+                    TxAux::ProposalTx(tx, witness) => {
+                        inittx.put(COL_BODIES, &txid[..], &tx.encode());
+                        inittx.put(COL_WITNESS, &txid[..], &witness.encode());
+                        // account and proposals should be already updated in deliver_tx
+                    }
+                    TxAux::VoteTx(tx, witness) => {
+                        inittx.put(COL_BODIES, &txid[..], &tx.encode());
+                        inittx.put(COL_WITNESS, &txid[..], &witness.encode());
+                        // account and proposals should be already updated in deliver_tx
+                    }
+                    TxAux::DelegateTx(tx, witness) => {
+                        inittx.put(COL_BODIES, &txid[..], &tx.encode());
+                        inittx.put(COL_WITNESS, &txid[..], &witness.encode());
+                        // delegator and council node accounts should be already updated in deliver_tx
+                    }
+                    TxAux::UndelegateTx(tx, witness) => {
+                        inittx.put(COL_BODIES, &txid[..], &tx.encode());
+                        inittx.put(COL_WITNESS, &txid[..], &witness.encode());
+                        // delegator and council node accounts should be already updated in deliver_tx
+                    }
                     TxAux::WithdrawUnbondedStakeTx(tx, witness) => {
                         inittx.put(
                             COL_BODIES,
@@ -97,19 +154,37 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
                         inittx.put(
                             COL_TX_META,
                             &txid[..],
-                            &BitVec::from_elem(tx.outputs.len(), false).to_bytes(),
+                            &TxMeta::new(inclusion, tx.outputs.len()).encode(),
+                        );
+                    }
+                    TxAux::ClaimRewardsTx(tx, witness) => {
+                        inittx.put(
+                            COL_BODIES,
+                            &txid[..],
+                            &TxWithOutputs::RewardsClaim(tx.clone()).encode(),
+                        );
+                        inittx.put(COL_WITNESS, &txid[..], &witness.encode());
+                        // account should be already updated in deliver_tx
+                        inittx.put(
+                            COL_TX_META,
+                            &txid[..],
+                            &TxMeta::new(inclusion, tx.outputs.len()).encode(),
                         );
                     }
                 }
             }
             new_state.rewards_pool.last_block_height = new_state.last_block_height;
             new_state.last_account_root_hash = self.uncommitted_account_root_hash;
-            let app_hash = compute_app_hash(
-                &tree,
-                &new_state.last_account_root_hash,
-                &new_state.rewards_pool,
-            );
+            let roots = AppStateRoots {
+                valid_tx_root: tree.root_hash(),
+                account_state_root: new_state.last_account_root_hash,
+                rewards_pool_hash: new_state.rewards_pool.hash(),
+                network_params_hash: new_state.network_params.hash(),
+                council_state_hash,
+            };
+            let app_hash = roots.app_hash();
             inittx.put(COL_MERKLE_PROOFS, &app_hash[..], &tree.encode());
+            inittx.put(COL_APP_STATE_ROOTS, &app_hash[..], &roots.encode());
             new_state.last_apphash = app_hash;
         }
 
@@ -118,14 +193,74 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
             &i64::encode_var_vec(new_state.last_block_height),
             &new_state.last_apphash,
         );
+        inittx.put(
+            COL_ACCOUNT_ROOTS,
+            &i64::encode_var_vec(new_state.last_block_height),
+            &new_state.last_account_root_hash,
+        );
+        // the account root out of the history is removed from the trie (after the write)
+        // unless the next height has the same one (i.e. there were no account changes)
+        let mut pruned_account_root = None;
+        if let Some(history) = self.account_root_history {
+            let pruned_height = new_state.last_block_height - history;
+            if pruned_height >= 0 {
+                let pruned_key = i64::encode_var_vec(pruned_height);
+                let pruned_root = self.get_stored_account_root(pruned_height);
+                let next_root = if pruned_height + 1 == new_state.last_block_height {
+                    Some(new_state.last_account_root_hash)
+                } else {
+                    self.get_stored_account_root(pruned_height + 1)
+                };
+                if let (Some(pruned), Some(next)) = (pruned_root, next_root) {
+                    if pruned != next {
+                        pruned_account_root = Some(pruned);
+                    }
+                }
+                inittx.delete(COL_ACCOUNT_ROOTS, &pruned_key);
+            }
+        }
+        let spent_inputs: Vec<TxoPointer> = self
+            .delivered_txs
+            .iter()
+            .flat_map(|txaux| match txaux {
+                TxAux::TransferTx { inputs, .. } => inputs.clone(),
+                TxAux::DepositStakeTx(tx, _) => tx.inputs.clone(),
+                _ => vec![],
+            })
+            .collect();
+        self.storage.prune(
+            &mut inittx,
+            new_state.last_block_height,
+            &new_state.last_apphash,
+            &spent_inputs,
+        );
         inittx.put(COL_NODE_INFO, LAST_STATE_KEY, &new_state.encode());
+        // a snapshot still being created needs the storage unchanged
+        let storage_lock = self.snapshot_writer.storage_lock.clone();
+        let storage_guard = storage_lock.write().expect("snapshot storage lock");
         let wr = self.storage.db.write(inittx);
         if wr.is_err() {
             panic!("db write error: {}", wr.err().unwrap());
         } else {
+            // superseded account tries are collected only once the new state is persisted
+            if let Some(root) = pruned_account_root {
+                self.accounts
+                    .remove(&root)
+                    .expect("account trie garbage collection");
+            }
+            self.accounts
+                .collect_uncommitted(
+                    Some(&previous_account_root),
+                    &new_state.last_account_root_hash,
+                )
+                .expect("account trie garbage collection");
             resp.data = new_state.last_apphash.to_vec();
             self.last_state = Some(new_state);
             self.delivered_txs.clear();
+            self.mempool_state.clear();
+            self.params_changed_in_block = false;
+            drop(storage_guard);
+            self.store_snapshot_if_due();
         }
 
         resp
@@ -0,0 +1,138 @@
+use super::ChainNodeApp;
+use crate::enclave_bridge::EnclaveProxy;
+use crate::storage::tx::get_account;
+use chain_core::state::account::StakedStateAddress;
+use chain_core::state::governance::{
+    NetworkParameterChange, ParamChangeProposal, ParamChangeProposalTx, ParamChangeVoteTx,
+    ProposalId,
+};
+use chain_core::state::tendermint::{BlockHeight, TendermintVotePower};
+use log::info;
+use std::collections::BTreeMap;
+
+impl<T: EnclaveProxy> ChainNodeApp<T> {
+    /// Records a new network parameter change proposal (implicitly approved by the proposer)
+    pub fn add_proposal(
+        &mut self,
+        id: ProposalId,
+        proposer: StakedStateAddress,
+        tx: &ParamChangeProposalTx,
+    ) {
+        self.last_state
+            .as_mut()
+            .expect("adding a proposal, but no app state")
+            .proposals
+            .push(ParamChangeProposal::new(
+                id,
+                proposer,
+                tx.change,
+                tx.activation_height,
+            ));
+    }
+
+    /// Records the council node's vote on a pending proposal
+    pub fn add_vote(&mut self, voter: StakedStateAddress, tx: &ParamChangeVoteTx) {
+        if let Some(proposal) = self
+            .last_state
+            .as_mut()
+            .expect("adding a vote, but no app state")
+            .proposals
+            .iter_mut()
+            .find(|proposal| proposal.id == tx.proposal_id)
+        {
+            proposal.vote(voter, tx.approve);
+        }
+    }
+
+    /// Tallies the votes on the proposals that activate at the block height (or before) and applies the changes
+    /// approved by council nodes with more than 2/3 of the bonded stake (jailed council nodes aren't counted).
+    pub fn activate_proposals(&mut self, block_height: BlockHeight) {
+        let state = self
+            .last_state
+            .as_mut()
+            .expect("activating proposals, but no app state");
+        if !state
+            .proposals
+            .iter()
+            .any(|proposal| proposal.activation_height <= block_height)
+        {
+            return;
+        }
+        let mut stakes = BTreeMap::new();
+        for node in state.council_nodes.iter() {
+            let account = get_account(
+                &node.staking_account_address,
+                &self.uncommitted_account_root_hash,
+                &self.accounts,
+            )
+            .expect("council node account should be in the account state");
+            if !account.is_jailed() {
+                stakes.insert(
+                    account.address,
+                    u128::from(u64::from(account.total_stake())),
+                );
+            }
+        }
+        let total_stake: u128 = stakes.values().sum();
+        let (activated, pending) = state
+            .proposals
+            .drain(..)
+            .partition::<Vec<_>, _>(|proposal| proposal.activation_height <= block_height);
+        state.proposals = pending;
+        let mut required_stake_changed = false;
+        for proposal in activated {
+            let approved_stake: u128 = proposal
+                .votes
+                .iter()
+                .filter(|(_, approve)| *approve)
+                .filter_map(|(voter, _)| stakes.get(voter))
+                .sum();
+            if approved_stake * 3 > total_stake * 2 {
+                info!("network parameter change approved: {}", proposal.change);
+                state.network_params.apply(&proposal.change);
+                self.params_changed_in_block = true;
+                if let NetworkParameterChange::RequiredCouncilNodeStake(_) = proposal.change {
+                    required_stake_changed = true;
+                }
+            } else {
+                info!("network parameter change rejected: {}", proposal.change);
+            }
+        }
+        if required_stake_changed {
+            self.update_council_powers();
+        }
+    }
+
+    /// Records the voting power changes of all council nodes after the required council node stake changed
+    fn update_council_powers(&mut self) {
+        let state = self
+            .last_state
+            .as_ref()
+            .expect("updating council powers, but no app state");
+        let min_power = TendermintVotePower::from(state.network_params.required_council_node_stake);
+        for node in state.council_nodes.iter() {
+            let address = node.staking_account_address;
+            let account = get_account(
+                &address,
+                &self.uncommitted_account_root_hash,
+                &self.accounts,
+            )
+            .expect("council node account should be in the account state");
+            let power = TendermintVotePower::from(account.total_stake());
+            let new_power = if account.is_jailed() || power < min_power {
+                TendermintVotePower::zero()
+            } else {
+                power
+            };
+            let old_power = self
+                .power_changed_in_block
+                .get(&address)
+                .or_else(|| self.validator_voting_power.get(&address))
+                .cloned()
+                .unwrap_or_else(TendermintVotePower::zero);
+            if new_power != old_power {
+                self.power_changed_in_block.insert(address, new_power);
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use super::ChainNodeApp;
+use crate::enclave_bridge::EnclaveProxy;
+use abci::*;
+use chain_core::state::account::{Nonce, StakedState, StakedStateAddress};
+use chain_core::tx::data::input::TxoPointer;
+use chain_core::tx::data::TxId;
+use chain_core::tx::rejection::RejectionCode;
+use chain_core::tx::TxAux;
+use log::info;
+use std::collections::BTreeMap;
+
+/// Inputs and staked state nonces used by the transactions accepted in CheckTx since the last commit
+/// (CheckTx validates against the committed state, so without it, conflicting transactions could all enter the mempool)
+#[derive(Debug, Default)]
+pub struct MempoolState {
+    /// spent transaction inputs -> ID of the accepted transaction spending them
+    spent_inputs: BTreeMap<TxoPointer, TxId>,
+    /// used staked state nonces -> ID of the accepted transaction using them
+    used_nonces: BTreeMap<(StakedStateAddress, Nonce), TxId>,
+}
+
+/// Staked state nonce used by the transaction (deposits don't have one)
+fn used_nonce(txaux: &TxAux) -> Option<Nonce> {
+    match txaux {
+        TxAux::TransferTx { .. } | TxAux::DepositStakeTx(_, _) => None,
+        TxAux::UnbondStakeTx(tx, _) => Some(tx.nonce),
+        TxAux::WithdrawUnbondedStakeTx(tx, _) => Some(tx.nonce),
+        TxAux::NodeJoinTx(tx, _) => Some(tx.nonce),
+        TxAux::UnjailTx(tx, _) => Some(tx.nonce),
+        TxAux::NodeLeaveTx(tx, _) => Some(tx.nonce),
+        TxAux::ProposalTx(tx, _) => Some(tx.nonce),
+        TxAux::VoteTx(tx, _) => Some(tx.nonce),
+        TxAux::DelegateTx(tx, _) => Some(tx.nonce),
+        TxAux::UndelegateTx(tx, _) => Some(tx.nonce),
+        TxAux::ClaimRewardsTx(tx, _) => Some(tx.nonce),
+    }
+}
+
+impl MempoolState {
+    /// Records the inputs and nonce used by the valid transaction (`account` is the updated staked state returned in its verification),
+    /// or returns the ID of an already accepted transaction it conflicts with (a resubmitted transaction doesn't conflict with itself)
+    pub fn add(&mut self, txaux: &TxAux, account: Option<&StakedState>) -> Result<(), TxId> {
+        let txid = txaux.tx_id();
+        let inputs: &[TxoPointer] = match txaux {
+            TxAux::TransferTx { inputs, .. } => inputs,
+            TxAux::DepositStakeTx(tx, _) => &tx.inputs,
+            _ => &[],
+        };
+        let nonce = match (used_nonce(txaux), account) {
+            (Some(nonce), Some(account)) => Some((account.address, nonce)),
+            _ => None,
+        };
+        let conflict = inputs
+            .iter()
+            .filter_map(|input| self.spent_inputs.get(input))
+            .chain(nonce.iter().filter_map(|key| self.used_nonces.get(key)))
+            .find(|other| **other != txid);
+        if let Some(other) = conflict {
+            return Err(*other);
+        }
+        for input in inputs.iter() {
+            self.spent_inputs.insert(input.clone(), txid);
+        }
+        if let Some(key) = nonce {
+            self.used_nonces.insert(key, txid);
+        }
+        Ok(())
+    }
+
+    /// Whether no transaction was accepted since the last commit
+    pub fn is_empty(&self) -> bool {
+        self.spent_inputs.is_empty() && self.used_nonces.is_empty()
+    }
+
+    /// Forgets all the accepted transactions (after a block is committed, Tendermint rechecks the ones remaining in its mempool)
+    pub fn clear(&mut self) {
+        self.spent_inputs.clear();
+        self.used_nonces.clear();
+    }
+}
+
+impl<T: EnclaveProxy> ChainNodeApp<T> {
+    /// Validates the transaction against the committed state and the other transactions in the mempool
+    /// (used both for new transactions and for the ones rechecked after a commit)
+    pub fn check_tx_handler(&mut self, req: &RequestCheckTx) -> ResponseCheckTx {
+        if req.get_field_type() == CheckTxType::Recheck {
+            info!("rechecking mempool tx after commit");
+        }
+        let mut resp = ResponseCheckTx::new();
+        if let Some((txaux, (_, account))) = self.validate_tx_req(req, &mut resp) {
+            if let Err(other) = self.mempool_state.add(&txaux, account.as_ref()) {
+                resp.set_code(RejectionCode::MempoolConflict.code());
+                resp.add_log(&format!(
+                    "conflicts with transaction {} in mempool",
+                    hex::encode(&other[..])
+                ));
+            }
+        }
+        resp
+    }
+}
@@ -1,27 +1,41 @@
 mod app_init;
 mod commit;
+mod governance;
+mod mempool;
 mod query;
+mod rewards;
+mod slashing;
+mod snapshot;
 mod validate_tx;
 
 use abci::*;
 use chain_tx_filter::BlockFilter;
 use log::info;
 
-pub use self::app_init::{ChainNodeApp, ChainNodeState};
+use self::app_init::get_validator_key;
+pub use self::app_init::{compute_council_state_hash, ChainNodeApp, ChainNodeState};
+pub use self::mempool::MempoolState;
+pub use self::slashing::LivenessTracker;
+pub use self::snapshot::{
+    SnapshotChunk, SnapshotConfig, SnapshotHeader, SnapshotPart, SnapshotWriter, TrustedAppHash,
+    SNAPSHOT_CHUNK_SIZE,
+};
 use crate::enclave_bridge::EnclaveProxy;
 use crate::storage::account::AccountStorage;
 use crate::storage::account::AccountWrapper;
-use crate::storage::tx::StarlingFixedKey;
-use crate::storage::COL_TX_META;
-use bit_vec::BitVec;
+use crate::storage::tx::{get_account, StarlingFixedKey, TxMeta};
+use crate::storage::{COL_ACCOUNT_ROOTS, COL_TX_META};
 use chain_core::common::TendermintEventType;
-use chain_core::state::account::StakedState;
-use chain_core::state::tendermint::TendermintVotePower;
+use chain_core::state::account::{StakedState, StakedStateAddress};
+use chain_core::state::tendermint::{BlockHeight, TendermintValidatorPubKey, TendermintVotePower};
+use chain_core::state::CouncilNode;
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::{PlainTxAux, TxAux};
+use integer_encoding::VarInt;
 use kvdb::{DBTransaction, KeyValueDB};
-use parity_codec::Decode;
+use parity_codec::{Decode, Encode};
 use protobuf::RepeatedField;
+use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 use std::sync::Arc;
 
@@ -33,12 +47,13 @@ pub fn spend_utxos(txins: &[TxoPointer], db: Arc<dyn KeyValueDB>, dbtx: &mut DBT
         updated_txs
             .entry(txin.id)
             .or_insert_with(|| {
-                BitVec::from_bytes(&db.get(COL_TX_META, &txin.id[..]).unwrap().unwrap())
+                let meta = db.get(COL_TX_META, &txin.id[..]).unwrap().unwrap();
+                TxMeta::decode(&mut meta.to_vec().as_slice()).unwrap()
             })
-            .set(txin.index as usize, true);
+            .spend(txin.index as usize);
     }
-    for (txid, bv) in &updated_txs {
-        dbtx.put(COL_TX_META, &txid[..], &bv.to_bytes());
+    for (txid, meta) in &updated_txs {
+        dbtx.put(COL_TX_META, &txid[..], &meta.encode());
     }
 }
 
@@ -61,6 +76,136 @@ pub fn update_account(
     )
 }
 
+/// Tendermint validator address: the first 20 bytes of SHA256 of the (Ed25519) public key
+fn get_validator_address(pub_key: &PubKey) -> [u8; 20] {
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&Sha256::digest(&pub_key.data)[..20]);
+    address
+}
+
+impl<T: EnclaveProxy> ChainNodeApp<T> {
+    /// Helper to find the account trie root stored for the given height (if it wasn't pruned)
+    fn get_stored_account_root(&self, height: BlockHeight) -> Option<StarlingFixedKey> {
+        let stored = self
+            .storage
+            .db
+            .get(COL_ACCOUNT_ROOTS, &i64::encode_var_vec(height))
+            .expect("account root lookup")?;
+        let mut root = StarlingFixedKey::default();
+        if stored.len() != root.len() {
+            return None;
+        }
+        root.copy_from_slice(&stored[..]);
+        Some(root)
+    }
+
+    /// Finds the staking account address of the council node with the given Tendermint validator address
+    fn get_council_node_address(&self, validator_address: &[u8]) -> Option<StakedStateAddress> {
+        self.validator_pubkeys
+            .iter()
+            .find(|(_, pk)| get_validator_address(pk)[..] == validator_address[..])
+            .map(|(address, _)| *address)
+    }
+
+    /// Registers a new council node
+    /// (its voting power is set in `update_validator_power` after its account is updated)
+    fn add_council_node(
+        &mut self,
+        address: StakedStateAddress,
+        consensus_pubkey: &TendermintValidatorPubKey,
+    ) {
+        let node = CouncilNode::new(address, consensus_pubkey.clone());
+        self.validator_pubkeys
+            .insert(address, get_validator_key(&node));
+        self.validator_voting_power
+            .entry(address)
+            .or_insert_with(TendermintVotePower::zero);
+        self.last_state
+            .as_mut()
+            .expect("adding a council node, but no app state")
+            .council_nodes
+            .push(node);
+    }
+
+    /// Removes the council node (it's removed from the validator set in EndBlock)
+    fn remove_council_node(&mut self, address: &StakedStateAddress) {
+        let state = self
+            .last_state
+            .as_mut()
+            .expect("removing a council node, but no app state");
+        state
+            .council_nodes
+            .retain(|node| node.staking_account_address != *address);
+        state
+            .validator_liveness
+            .retain(|(tracked, _)| tracked != address);
+        self.power_changed_in_block
+            .insert(*address, TendermintVotePower::zero());
+    }
+
+    /// Stores the delegator's updated account and then adds (or removes) the delegated amount
+    /// to (or from) the council node's account; returns the new uncommitted account root
+    /// (the delegator's voting power is updated here, as it may be a council node too)
+    fn update_delegation(
+        &mut self,
+        delegator: StakedState,
+        council_node: &StakedStateAddress,
+        update: impl FnOnce(&mut StakedState),
+    ) -> StarlingFixedKey {
+        let (next_account_root, _) = update_account(
+            delegator.clone(),
+            &self.uncommitted_account_root_hash,
+            &mut self.accounts,
+        );
+        self.uncommitted_account_root_hash = next_account_root;
+        self.update_validator_power(&delegator);
+        let mut node = get_account(
+            council_node,
+            &self.uncommitted_account_root_hash,
+            &self.accounts,
+        )
+        .expect("council node account should be in the account state");
+        update(&mut node);
+        let (next_account_root, _) = update_account(
+            node.clone(),
+            &self.uncommitted_account_root_hash,
+            &mut self.accounts,
+        );
+        self.update_validator_power(&node);
+        next_account_root
+    }
+
+    /// Records the new voting power of the council node associated with the updated account (if any),
+    /// so that it's sent in the validator updates in EndBlock
+    /// (jailed council nodes stay without voting power until they are unjailed,
+    /// and the ones that left the council in this block are removed in EndBlock)
+    fn update_validator_power(&mut self, account: &StakedState) {
+        let state = self
+            .last_state
+            .as_ref()
+            .expect("updating validator power, but no app state");
+        if account.is_jailed()
+            || !state
+                .council_nodes
+                .iter()
+                .any(|node| node.staking_account_address == account.address)
+        {
+            return;
+        }
+        let min_power = TendermintVotePower::from(state.network_params.required_council_node_stake);
+        // the council node's own bonded stake plus the stake delegated to it
+        let power = TendermintVotePower::from(account.total_stake());
+        let new_power = if power < min_power {
+            TendermintVotePower::zero()
+        } else {
+            power
+        };
+        // EndBlock only reports it if it differs from the current voting power
+        self.power_changed_in_block
+            .insert(account.address, new_power);
+    }
+}
+
 /// TODO: sanity checks in abci https://github.com/tendermint/rust-abci/issues/49
 impl<T: EnclaveProxy> abci::Application for ChainNodeApp<T> {
     /// Query Connection: Called on startup from Tendermint.  The application should normally
@@ -89,11 +234,10 @@ impl<T: EnclaveProxy> abci::Application for ChainNodeApp<T> {
     /// Mempool Connection:  Used to validate incoming transactions.  If the application reponds
     /// with a non-zero value, the transaction is added to Tendermint's mempool for processing
     /// on the deliver_tx call below.
+    /// (Tendermint also calls it to recheck the transactions remaining in its mempool after each commit.)
     fn check_tx(&mut self, _req: &RequestCheckTx) -> ResponseCheckTx {
         info!("received checktx request");
-        let mut resp = ResponseCheckTx::new();
-        ChainNodeApp::validate_tx_req(self, _req, &mut resp);
-        resp
+        ChainNodeApp::check_tx_handler(self, _req)
     }
 
     /// Consensus Connection:  Called once on startup. Usually used to establish initial (genesis)
@@ -111,18 +255,22 @@ impl<T: EnclaveProxy> abci::Application for ChainNodeApp<T> {
     /// commit()
     fn begin_block(&mut self, req: &RequestBeginBlock) -> ResponseBeginBlock {
         info!("received beginblock request");
-        // TODO: process RequestBeginBlock -- e.g. rewards for validators? + punishment for malicious ByzantineValidators
         // TODO: Check security implications once https://github.com/tendermint/tendermint/issues/2653 is closed
-        let block_time = req
+        let header = req
             .header
             .as_ref()
-            .expect("Begin block request does not have header")
+            .expect("Begin block request does not have header");
+        let block_time = header
             .time
             .as_ref()
             .expect("Header does not have a timestamp")
             .seconds;
         self.last_state.as_mut().map(|mut x| x.block_time = block_time)
             .expect("executing begin block, but no app state stored (i.e. no initchain or recovery was executed)");
+        self.slash_byzantine_validators(req.get_byzantine_validators());
+        self.track_liveness(req.get_last_commit_info(), header.height);
+        self.distribute_block_rewards(req.get_last_commit_info(), header.height);
+        self.activate_proposals(header.height);
         ResponseBeginBlock::new()
     }
 
@@ -165,27 +313,92 @@ impl<T: EnclaveProxy> abci::Application for ChainNodeApp<T> {
                     &self.uncommitted_account_root_hash,
                     &mut self.accounts,
                 ),
-            };
-            match maccount {
-                Some(ref account) if self.validator_voting_power.contains_key(&account.address) => {
-                    let min_power = TendermintVotePower::from(
-                        self.last_state
-                            .as_ref()
-                            .expect("delivertx should have app state")
-                            .required_council_node_stake,
-                    );
-                    let new_power = TendermintVotePower::from(account.bonded);
-                    let old_power = self.validator_voting_power[&account.address];
-                    if new_power > old_power && new_power >= min_power {
-                        self.power_changed_in_block
-                            .insert(account.address, new_power);
-                    } else if old_power >= min_power && new_power < old_power {
-                        self.power_changed_in_block
-                            .insert(account.address, TendermintVotePower::zero());
-                    }
+                TxAux::ClaimRewardsTx(_, _) => update_account(
+                    fee_acc
+                        .1
+                        .expect("account returned in reward claim verification"),
+                    &self.uncommitted_account_root_hash,
+                    &mut self.accounts,
+                ),
+                TxAux::NodeJoinTx(tx, _) => {
+                    let account = fee_acc
+                        .1
+                        .expect("account returned in node join verification");
+                    self.add_council_node(account.address, &tx.consensus_pubkey);
+                    update_account(
+                        account,
+                        &self.uncommitted_account_root_hash,
+                        &mut self.accounts,
+                    )
+                }
+                TxAux::UnjailTx(_, _) => update_account(
+                    fee_acc.1.expect("account returned in unjail verification"),
+                    &self.uncommitted_account_root_hash,
+                    &mut self.accounts,
+                ),
+                TxAux::NodeLeaveTx(_, _) => {
+                    let account = fee_acc
+                        .1
+                        .expect("account returned in node leave verification");
+                    self.remove_council_node(&account.address);
+                    update_account(
+                        account,
+                        &self.uncommitted_account_root_hash,
+                        &mut self.accounts,
+                    )
+                }
+                TxAux::ProposalTx(tx, _) => {
+                    let account = fee_acc
+                        .1
+                        .expect("account returned in proposal verification");
+                    self.add_proposal(txaux.tx_id(), account.address, tx);
+                    update_account(
+                        account,
+                        &self.uncommitted_account_root_hash,
+                        &mut self.accounts,
+                    )
+                }
+                TxAux::VoteTx(tx, _) => {
+                    let account = fee_acc.1.expect("account returned in vote verification");
+                    self.add_vote(account.address, tx);
+                    update_account(
+                        account,
+                        &self.uncommitted_account_root_hash,
+                        &mut self.accounts,
+                    )
+                }
+                TxAux::DelegateTx(tx, _) => {
+                    let account = fee_acc
+                        .1
+                        .expect("account returned in delegation verification");
+                    let value = tx.value;
+                    let next_account_root =
+                        self.update_delegation(account, &tx.council_node, |node| {
+                            let shares = node
+                                .delegation_shares_for(value)
+                                .expect("delegation shares checked in verification");
+                            node.add_delegated(value, shares)
+                        });
+                    (next_account_root, None)
+                }
+                TxAux::UndelegateTx(tx, _) => {
+                    let account = fee_acc
+                        .1
+                        .expect("account returned in undelegation verification");
+                    let value = tx.value;
+                    let next_account_root =
+                        self.update_delegation(account, &tx.council_node, |node| {
+                            let shares = node
+                                .undelegation_shares_for(value)
+                                .expect("undelegation shares checked in verification");
+                            node.remove_delegated(value, shares)
+                        });
+                    (next_account_root, None)
                 }
-                _ => {}
             };
+            if let Some(ref account) = maccount {
+                self.update_validator_power(account);
+            }
             // as self.accounts allows querying against different tree roots
             // the modifications done with "update_account" _should_ be safe, as the final tree root will
             // be persisted in commit.
@@ -240,6 +453,11 @@ impl<T: EnclaveProxy> abci::Application for ChainNodeApp<T> {
                         filter.add_view_key(&view.view_key);
                     }
                 }
+                TxAux::ClaimRewardsTx(tx, _) => {
+                    for view in tx.attributes.allowed_view.iter() {
+                        filter.add_view_key(&view.view_key);
+                    }
+                }
                 _ => {}
             };
         }
@@ -252,20 +470,58 @@ impl<T: EnclaveProxy> abci::Application for ChainNodeApp<T> {
             event.attributes.push(kvpair);
             resp.events.push(event);
         }
+        // the dynamic fee is adjusted according to the size of the transactions in this block
+        let block_size = self
+            .delivered_txs
+            .iter()
+            .map(|txaux| txaux.encode().len() as u64)
+            .sum();
+        if self
+            .last_state
+            .as_mut()
+            .expect("executing end block, but no app state")
+            .network_params
+            .fee_policy
+            .adjust(block_size)
+        {
+            self.params_changed_in_block = true;
+        }
         // TODO: skipchain-based validator changes?
         if !self.power_changed_in_block.is_empty() {
             let mut validators = Vec::with_capacity(self.power_changed_in_block.len());
             for (address, new_power) in self.power_changed_in_block.iter() {
-                let old_power = self.validator_voting_power[&address];
+                // untracked council nodes have no voting power in Tendermint
+                let old_power = self
+                    .validator_voting_power
+                    .get(address)
+                    .cloned()
+                    .unwrap_or_else(TendermintVotePower::zero);
                 // sanity check, as multiple transactions/events may have cancelled out the vote power change
                 if old_power != *new_power {
-                    let mut validator = ValidatorUpdate::default();
-                    validator.set_power(i64::from(*new_power));
-                    validator.set_pub_key(self.validator_pubkeys[&address].clone());
-                    validators.push(validator);
+                    if let Some(pubkey) = self.validator_pubkeys.get(address) {
+                        let mut validator = ValidatorUpdate::default();
+                        validator.set_power(i64::from(*new_power));
+                        validator.set_pub_key(pubkey.clone());
+                        validators.push(validator);
+                    }
                 }
                 self.validator_voting_power.insert(*address, *new_power);
             }
+            // council nodes that left are no longer tracked
+            let council_nodes = &self
+                .last_state
+                .as_ref()
+                .expect("executing end block, but no app state")
+                .council_nodes;
+            for address in self.power_changed_in_block.keys() {
+                if !council_nodes
+                    .iter()
+                    .any(|node| node.staking_account_address == *address)
+                {
+                    self.validator_voting_power.remove(address);
+                    self.validator_pubkeys.remove(address);
+                }
+            }
             resp.set_validator_updates(RepeatedField::from(validators));
             self.power_changed_in_block.clear();
         }
@@ -1,14 +1,18 @@
-use super::ChainNodeApp;
+use super::{ChainNodeApp, ChainNodeState};
 use crate::enclave_bridge::EnclaveProxy;
 use crate::storage::tx::get_account;
+use crate::storage::tx::StarlingFixedKey;
 use crate::storage::*;
 use abci::*;
 use chain_core::common::{MerkleTree, Proof as MerkleProof, H256, HASH_SIZE_256};
-use chain_core::state::account::StakedStateAddress;
+use chain_core::state::account::{to_stake_key, StakedStateAddress};
+use chain_core::state::account_proof::AppHashParts;
+use chain_core::state::tendermint::BlockHeight;
 use chain_core::tx::data::input::TxoIndex;
 use chain_core::tx::data::{txid_hash, TXID_HASH_ID};
 use chain_core::tx::TransactionId;
 use chain_core::tx::{PlainTxAux, TxAux};
+use chain_core::AppStateRoots;
 use chain_tx_validation::TxWithOutputs;
 use enclave_protocol::{
     DecryptionRequest, DecryptionRequestBody, DecryptionResponse, EncryptionRequest,
@@ -42,6 +46,86 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
         }
     }
 
+    /// Helper to find transaction data under a column in KV DB, or log an error (both stored in the response)
+    /// -- the data of pruned transactions is reported as such rather than as not found.
+    fn lookup_tx(&self, resp: &mut ResponseQuery, column: Option<u32>, txid: &[u8]) {
+        if self.storage.is_pruned(txid) {
+            resp.log += "tx pruned (its outputs were spent before the retention window)";
+            resp.code = 1;
+        } else {
+            self.lookup(resp, column, txid, "tx not found");
+        }
+    }
+
+    /// Helper to find the account trie root committed at the given height (0 == the last committed height).
+    /// Returns None if there is no committed state at that height or its account root was pruned.
+    fn get_account_root(
+        &self,
+        state: &ChainNodeState,
+        height: BlockHeight,
+    ) -> Option<StarlingFixedKey> {
+        if height == 0 || height == state.last_block_height {
+            return Some(state.last_account_root_hash);
+        }
+        self.get_stored_account_root(height)
+    }
+
+    /// Helper to generate the proof of the account (non-)inclusion in the account trie under `account_root`
+    /// (+ the other parts of the app hash committed at `height`, if they are still stored and have that account root).
+    fn get_account_proof(
+        &self,
+        state: &ChainNodeState,
+        height: BlockHeight,
+        account_root: &StarlingFixedKey,
+        address: &StakedStateAddress,
+    ) -> Option<Proof> {
+        let account_proof = self
+            .accounts
+            .generate_proof(account_root, &to_stake_key(address))
+            .ok()?;
+        let mut account_op = ProofOp::new();
+        account_op.set_field_type("account".into());
+        account_op.set_key(account_root.to_vec());
+        account_op.set_data(account_proof.encode());
+        let mut proof_ops = vec![account_op];
+        let app_hash = if height == state.last_block_height {
+            Some(state.last_apphash.to_vec())
+        } else {
+            self.storage
+                .db
+                .get(COL_APP_STATES, &i64::encode_var_vec(height))
+                .expect("app state lookup")
+                .map(|app_hash| app_hash.to_vec())
+        };
+        // blocks without transactions keep the previous app hash (and its roots), and the roots of pruned app hashes
+        // are removed -- the app hash part is then omitted (only the account trie part can be checked)
+        let roots = app_hash.as_ref().and_then(|app_hash| {
+            self.storage
+                .db
+                .get(COL_APP_STATE_ROOTS, &app_hash[..])
+                .expect("app state roots lookup")
+                .map(|roots| AppStateRoots::decode(&mut &roots[..]).expect("app state roots"))
+        });
+        if let (Some(app_hash), Some(roots)) = (app_hash, roots) {
+            if roots.account_state_root == *account_root {
+                let parts = AppHashParts {
+                    valid_tx_root: roots.valid_tx_root,
+                    rewards_pool_hash: roots.rewards_pool_hash,
+                    network_params_hash: roots.network_params_hash,
+                    council_state_hash: roots.council_state_hash,
+                };
+                let mut app_hash_op = ProofOp::new();
+                app_hash_op.set_field_type("app_hash".into());
+                app_hash_op.set_key(app_hash);
+                app_hash_op.set_data(parts.encode());
+                proof_ops.push(app_hash_op);
+            }
+        }
+        let mut proof = Proof::new();
+        proof.set_ops(proof_ops.into());
+        Some(proof)
+    }
+
     /// Responds to query requests -- note that path is hex-encoded in the original request on the client side
     /// e.g. "store" == 0x73746f7265.
     pub fn query_handler(&self, _req: &RequestQuery) -> ResponseQuery {
@@ -106,7 +190,7 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
                 }
             }
             "store" => {
-                self.lookup(&mut resp, COL_BODIES, &_req.data[..], "tx not found");
+                self.lookup_tx(&mut resp, COL_BODIES, &_req.data[..]);
                 if _req.prove && resp.code == 0 {
                     let mwitness = self.storage.db.get(COL_WITNESS, &_req.data[..]);
                     match mwitness {
@@ -124,13 +208,19 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
                                 .get(COL_APP_STATES, &i64::encode_var_vec(height))
                                 .unwrap()
                                 .unwrap();
-                            let data = self
+                            let data = match self
                                 .storage
                                 .db
                                 .get(COL_MERKLE_PROOFS, &app_hash[..])
                                 .unwrap()
-                                .unwrap()
-                                .to_vec();
+                            {
+                                Some(data) => data.to_vec(),
+                                None => {
+                                    resp.log += "proof error: merkle tree not found or pruned";
+                                    resp.code = 2;
+                                    return resp;
+                                }
+                            };
                             let tree =
                                 MerkleTree::decode(&mut data.as_slice()).expect("merkle tree");
 
@@ -138,13 +228,26 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
                             txid.copy_from_slice(&_req.data[..]);
 
                             // TODO: Change this in future to include individual ops?
-                            let proof_ops = match tree.generate_proof(txid) {
+                            let mut proof_ops = match tree.generate_proof(txid) {
                                 None => vec![get_witness_proof_op(&witness[..])],
                                 Some(merkle_proof) => vec![
                                     into_proof_op(tree.root_hash(), merkle_proof),
                                     get_witness_proof_op(&witness[..]),
                                 ],
                             };
+                            // the parts of the app hash (for checking the TX merkle tree root against it)
+                            if let Some(roots) = self
+                                .storage
+                                .db
+                                .get(COL_APP_STATE_ROOTS, &app_hash[..])
+                                .expect("app state roots lookup")
+                            {
+                                let mut op = ProofOp::new();
+                                op.set_field_type("app_state".into());
+                                op.set_key(app_hash.to_vec());
+                                op.set_data(roots.to_vec());
+                                proof_ops.push(op);
+                            }
 
                             let mut proof = Proof::new();
                             proof.set_ops(proof_ops.into());
@@ -158,10 +261,10 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
                 }
             }
             "meta" => {
-                self.lookup(&mut resp, COL_TX_META, &_req.data[..], "tx not found");
+                self.lookup_tx(&mut resp, COL_TX_META, &_req.data[..]);
             }
             "witness" => {
-                self.lookup(&mut resp, COL_WITNESS, &_req.data[..], "tx not found");
+                self.lookup_tx(&mut resp, COL_WITNESS, &_req.data[..]);
             }
             "merkle" => {
                 self.lookup(
@@ -174,15 +277,37 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
             "account" => {
                 let account_address = StakedStateAddress::try_from(_req.data.as_slice());
                 if let (Some(state), Ok(address)) = (&self.last_state, account_address) {
-                    let account =
-                        get_account(&address, &state.last_account_root_hash, &self.accounts);
-                    match account {
-                        Ok(a) => {
-                            resp.value = a.encode();
-                            // TODO: inclusion proof
+                    match self.get_account_root(state, _req.height) {
+                        Some(account_root) => {
+                            resp.height = if _req.height == 0 {
+                                state.last_block_height
+                            } else {
+                                _req.height
+                            };
+                            let account = get_account(&address, &account_root, &self.accounts);
+                            match account {
+                                Ok(a) => {
+                                    resp.value = a.encode();
+                                }
+                                Err(e) => {
+                                    resp.log += format!("account lookup failed: {}", e).as_ref();
+                                    resp.code = 1;
+                                }
+                            }
+                            // for missing accounts, the proof is of their non-inclusion
+                            if _req.prove {
+                                if let Some(proof) = self.get_account_proof(
+                                    state,
+                                    resp.height,
+                                    &account_root,
+                                    &address,
+                                ) {
+                                    resp.set_proof(proof);
+                                }
+                            }
                         }
-                        Err(e) => {
-                            resp.log += format!("account lookup failed: {}", e).as_ref();
+                        None => {
+                            resp.log += "account lookup failed: no account state at the requested height (not committed or pruned)";
                             resp.code = 1;
                         }
                     }
@@ -191,6 +316,25 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
                     resp.code = 3;
                 }
             }
+            // the chunk index is in the data (as a decimal string; empty == 0)
+            "snapshot" => {
+                let index = if _req.data.is_empty() {
+                    Some(0)
+                } else {
+                    std::str::from_utf8(&_req.data)
+                        .ok()
+                        .and_then(|index| index.parse::<u64>().ok())
+                };
+                match index.and_then(|index| self.load_snapshot_chunk(_req.height, index)) {
+                    Some(chunk) => {
+                        resp.value = chunk.encode();
+                    }
+                    None => {
+                        resp.log += "snapshot chunk not found";
+                        resp.code = 1;
+                    }
+                }
+            }
             _ => {
                 resp.log += "invalid path";
                 resp.code = 1;
@@ -0,0 +1,76 @@
+use super::{update_account, ChainNodeApp};
+use crate::enclave_bridge::EnclaveProxy;
+use crate::storage::tx::get_account;
+use abci::LastCommitInfo;
+use chain_core::init::coin::Coin;
+use chain_core::state::account::StakedStateAddress;
+use chain_core::state::tendermint::BlockHeight;
+
+impl<T: EnclaveProxy> ChainNodeApp<T> {
+    /// Pays out `block_reward_share` of the remaining rewards pool to council nodes that signed the previous block
+    /// (split proportionally to their voting power in the last commit). The rewards accrue in their staked states
+    /// until they are claimed with `ClaimRewardsTx` (compounded into the bonded amount or withdrawn to UTXOs).
+    /// The rounded-down remainder (and the shares of jailed council nodes) stays in the rewards pool.
+    pub fn distribute_block_rewards(
+        &mut self,
+        last_commit_info: &LastCommitInfo,
+        block_height: BlockHeight,
+    ) {
+        let signers: Vec<(StakedStateAddress, u64)> = last_commit_info
+            .get_votes()
+            .iter()
+            .filter(|vote| vote.signed_last_block && vote.get_validator().power > 0)
+            .filter_map(|vote| {
+                let validator = vote.get_validator();
+                self.get_council_node_address(&validator.address)
+                    .map(|address| (address, validator.power as u64))
+            })
+            .collect();
+        let total_power: u64 = signers.iter().map(|(_, power)| power).sum();
+        let state = self
+            .last_state
+            .as_ref()
+            .expect("distributing block rewards, but no app state");
+        let total_reward = u128::from(u64::from(state.rewards_pool.remaining))
+            * u128::from(state.block_reward_share.as_millis())
+            / 1000;
+        if total_power == 0 || total_reward == 0 {
+            return;
+        }
+
+        let mut paid = Coin::zero();
+        for (address, power) in signers {
+            let reward = (total_reward * u128::from(power) / u128::from(total_power)) as u64;
+            if reward == 0 {
+                continue;
+            }
+            let reward = Coin::new(reward).expect("block reward is a part of the rewards pool");
+            let mut account = get_account(
+                &address,
+                &self.uncommitted_account_root_hash,
+                &self.accounts,
+            )
+            .expect("council node account should be in the account state");
+            if account.is_jailed() {
+                continue;
+            }
+            account.add_reward(reward);
+            let (next_account_root, _) = update_account(
+                account,
+                &self.uncommitted_account_root_hash,
+                &mut self.accounts,
+            );
+            self.uncommitted_account_root_hash = next_account_root;
+            paid = (paid + reward).expect("block rewards are a part of the rewards pool");
+        }
+
+        let rewards_pool = &mut self
+            .last_state
+            .as_mut()
+            .expect("distributing block rewards, but no app state")
+            .rewards_pool;
+        rewards_pool.remaining = (rewards_pool.remaining - paid)
+            .expect("block rewards should not exceed the rewards pool");
+        rewards_pool.last_block_height = block_height;
+    }
+}
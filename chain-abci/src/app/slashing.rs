@@ -0,0 +1,158 @@
+use super::{update_account, ChainNodeApp};
+use crate::enclave_bridge::EnclaveProxy;
+use crate::storage::tx::get_account;
+use abci::{Evidence, LastCommitInfo};
+use chain_core::init::coin::Coin;
+use chain_core::state::account::StakedStateAddress;
+use chain_core::state::tendermint::{BlockHeight, TendermintVotePower};
+use chain_core::tx::fee::Milli;
+use log::info;
+use parity_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+/// Tracks which of the recent blocks (in the block signing window) a council node didn't sign
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Encode, Decode)]
+pub struct LivenessTracker {
+    /// whether the block was missed -- indexed by block height modulo the window size
+    missed_blocks: Vec<bool>,
+}
+
+impl LivenessTracker {
+    /// creates a new tracker (with no missed blocks) for the given window size
+    pub fn new(block_signing_window: u16) -> Self {
+        LivenessTracker {
+            missed_blocks: vec![false; usize::from(block_signing_window)],
+        }
+    }
+
+    /// records whether the block at the given height was signed
+    pub fn update(&mut self, block_height: BlockHeight, signed: bool) {
+        let index = (block_height as u64 % self.missed_blocks.len() as u64) as usize;
+        self.missed_blocks[index] = !signed;
+    }
+
+    /// the number of missed blocks in the window
+    pub fn missed_count(&self) -> usize {
+        self.missed_blocks.iter().filter(|missed| **missed).count()
+    }
+}
+
+impl<T: EnclaveProxy> ChainNodeApp<T> {
+    /// Slashes and jails the council nodes with the reported byzantine faults (e.g. double signing).
+    pub fn slash_byzantine_validators(&mut self, evidence: &[Evidence]) {
+        let slash_ratio = self
+            .last_state
+            .as_ref()
+            .expect("processing evidence, but no app state")
+            .slashing_config
+            .byzantine_slash_ratio;
+        for ev in evidence.iter() {
+            match self.get_council_node_address(&ev.get_validator().address) {
+                Some(address) => {
+                    info!(
+                        "byzantine fault evidence ({}) for {}",
+                        ev.field_type, address
+                    );
+                    self.slash(&address, slash_ratio);
+                }
+                None => {
+                    info!("evidence for an unknown validator");
+                }
+            }
+        }
+    }
+
+    /// Records which council nodes signed the previous block and slashes + jails those that missed
+    /// more than `missed_block_threshold` blocks in the block signing window.
+    pub fn track_liveness(&mut self, last_commit_info: &LastCommitInfo, block_height: BlockHeight) {
+        let votes: Vec<(StakedStateAddress, bool)> = last_commit_info
+            .get_votes()
+            .iter()
+            .filter(|vote| vote.get_validator().power > 0)
+            .filter_map(|vote| {
+                self.get_council_node_address(&vote.get_validator().address)
+                    .map(|address| (address, vote.signed_last_block))
+            })
+            .collect();
+        let state = self
+            .last_state
+            .as_mut()
+            .expect("tracking liveness, but no app state");
+        let config = state.slashing_config;
+        let mut offline = Vec::new();
+        for (address, signed) in votes {
+            let position = state
+                .validator_liveness
+                .iter()
+                .position(|(tracked, _)| *tracked == address);
+            let tracker = match position {
+                Some(i) => &mut state.validator_liveness[i].1,
+                None => {
+                    state
+                        .validator_liveness
+                        .push((address, LivenessTracker::new(config.block_signing_window)));
+                    &mut state
+                        .validator_liveness
+                        .last_mut()
+                        .expect("tracker was just inserted")
+                        .1
+                }
+            };
+            tracker.update(block_height, signed);
+            if tracker.missed_count() > usize::from(config.missed_block_threshold) {
+                offline.push(address);
+            }
+        }
+        for address in offline {
+            info!("{} missed too many blocks", address);
+            self.slash(&address, config.liveness_slash_ratio);
+        }
+    }
+
+    /// Takes `slash_ratio` of the council node's bonded amount and of the stake delegated to it (moved to the rewards pool;
+    /// the delegators' shares lose value in proportion),
+    /// jails it for `jail_duration` and removes it from the validator set (until it's unjailed with `UnjailTx`).
+    /// Council nodes that are already jailed are not slashed again.
+    fn slash(&mut self, address: &StakedStateAddress, slash_ratio: Milli) {
+        let mut account = get_account(address, &self.uncommitted_account_root_hash, &self.accounts)
+            .expect("council node account should be in the account state");
+        let state = self
+            .last_state
+            .as_mut()
+            .expect("slashing, but no app state");
+        if account.is_jailed() {
+            return;
+        }
+        let amount =
+            u128::from(u64::from(account.bonded)) * u128::from(slash_ratio.as_millis()) / 1000;
+        let amount =
+            Coin::new(amount as u64).expect("slashed amount is a part of the bonded amount");
+        let delegated_amount =
+            u128::from(u64::from(account.delegated)) * u128::from(slash_ratio.as_millis()) / 1000;
+        let delegated_amount = Coin::new(delegated_amount as u64)
+            .expect("slashed amount is a part of the delegated amount");
+        account.slash(
+            amount,
+            delegated_amount,
+            state.block_time + i64::from(state.slashing_config.jail_duration),
+        );
+        state.rewards_pool.remaining = (state.rewards_pool.remaining + amount)
+            .and_then(|remaining| remaining + delegated_amount)
+            .expect("rewards pool + slashed amount greater than max coin?");
+        if let Some((_, tracker)) = state
+            .validator_liveness
+            .iter_mut()
+            .find(|(tracked, _)| tracked == address)
+        {
+            *tracker = LivenessTracker::new(state.slashing_config.block_signing_window);
+        }
+        let (next_account_root, _) = update_account(
+            account,
+            &self.uncommitted_account_root_hash,
+            &mut self.accounts,
+        );
+        self.uncommitted_account_root_hash = next_account_root;
+        self.power_changed_in_block
+            .insert(*address, TendermintVotePower::zero());
+    }
+}
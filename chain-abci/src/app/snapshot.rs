@@ -0,0 +1,550 @@
+use super::{ChainNodeApp, ChainNodeState};
+use crate::enclave_bridge::EnclaveProxy;
+use crate::storage::account::AccountWrapper;
+use crate::storage::account::{visit_stored_accounts, AccountStorage};
+use crate::storage::tx::StarlingFixedKey;
+use crate::storage::*;
+use chain_core::common::{MerkleTree, H256, HASH_SIZE_256};
+use chain_core::compute_app_hash;
+use chain_core::state::account::StakedState;
+use chain_core::state::tendermint::BlockHeight;
+use integer_encoding::VarInt;
+use kvdb::KeyValueDB;
+use log::{info, warn};
+use parity_codec::{Decode, Encode};
+use starling::traits::Exception;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, RwLock};
+use std::thread::{self, JoinHandle};
+
+/// snapshot files are named "<prefix><block height>"
+const SNAPSHOT_FILE_PREFIX: &str = "snapshot_";
+
+/// snapshot files are served in chunks of this size in the "snapshot" query
+pub const SNAPSHOT_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// Configuration of periodically produced state snapshots
+#[derive(Debug, Clone)]
+pub struct SnapshotConfig {
+    /// directory where snapshot files are stored
+    pub dir: PathBuf,
+    /// a snapshot is produced after each commit at a block height divisible by this number
+    pub interval: BlockHeight,
+}
+
+impl SnapshotConfig {
+    pub fn new(dir: PathBuf, interval: BlockHeight) -> Self {
+        SnapshotConfig { dir, interval }
+    }
+
+    /// path of the snapshot file for the given block height
+    pub fn snapshot_path(&self, height: BlockHeight) -> PathBuf {
+        self.dir.join(format!("{}{}", SNAPSHOT_FILE_PREFIX, height))
+    }
+
+    /// the highest block height for which there is a snapshot file stored (if any)
+    pub fn latest_snapshot_height(&self) -> Option<BlockHeight> {
+        fs::read_dir(&self.dir)
+            .ok()?
+            .filter_map(|entry| {
+                entry
+                    .ok()?
+                    .file_name()
+                    .to_str()?
+                    .trim_start_matches(SNAPSHOT_FILE_PREFIX)
+                    .parse::<BlockHeight>()
+                    .ok()
+            })
+            .max()
+    }
+}
+
+/// The block height and app hash (from a source other than the snapshot, e.g. a light client verified header)
+/// that a snapshot is checked against when it's restored
+#[derive(Debug, Clone, Copy)]
+pub struct TrustedAppHash {
+    /// the block height of the snapshot
+    pub height: BlockHeight,
+    /// the app hash after executing the block at `height` (in the header of the next block)
+    pub app_hash: H256,
+}
+
+/// Writes snapshot files in a background thread, so that commits don't wait for them
+#[derive(Default)]
+pub struct SnapshotWriter {
+    /// held for reading while a snapshot is being created and for writing by commits when they change the storage,
+    /// so that the snapshot sees the state at its height (a commit only waits if the snapshot is still in progress)
+    pub storage_lock: Arc<RwLock<()>>,
+    /// the thread writing the last snapshot (if any)
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SnapshotWriter {
+    /// waits until the last snapshot is written
+    pub fn join(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                warn!("snapshot thread panicked");
+            }
+        }
+    }
+}
+
+/// A part of a snapshot file returned in the "snapshot" query
+/// (the file is the concatenation of `data` in all chunks from index 0 to `chunks - 1`)
+#[derive(Debug, PartialEq, Eq, Encode, Decode)]
+pub struct SnapshotChunk {
+    /// block height of the snapshot
+    pub height: BlockHeight,
+    /// index of this chunk
+    pub index: u64,
+    /// the number of chunks of the snapshot file
+    pub chunks: u64,
+    /// up to `SNAPSHOT_CHUNK_SIZE` bytes of the snapshot file
+    pub data: Vec<u8>,
+}
+
+/// the number of column entries or accounts in one `SnapshotPart`
+const SNAPSHOT_PART_ENTRIES: usize = 1024;
+
+/// The start of a snapshot of the node state at a given committed `ChainNodeState`.
+/// A snapshot file consists of the storage format version it was created from (`STORAGE_VERSION`),
+/// the header and `SnapshotPart`s up to `SnapshotPart::End` (the header and parts are each prefixed
+/// with the length of their encoding as little-endian u64), so that it can be written and restored
+/// one part at a time.
+/// NOTE: it only covers the ABCI application state; Tendermint's block store / state need to be obtained separately
+#[derive(Debug, Encode, Decode)]
+pub struct SnapshotHeader {
+    /// genesis app hash of the network
+    pub genesis_app_hash: H256,
+    /// the full chain ID
+    pub chain_id: Vec<u8>,
+    /// the committed app state
+    pub state: ChainNodeState,
+}
+
+/// A part of the snapshot contents following its header
+#[derive(Debug, Encode, Decode)]
+pub enum SnapshotPart {
+    /// key-value pairs in a node storage column (the column number + up to `SNAPSHOT_PART_ENTRIES` pairs)
+    Column(u32, Vec<(Vec<u8>, Vec<u8>)>),
+    /// staked states in the account trie under `last_account_root_hash` (up to `SNAPSHOT_PART_ENTRIES` in the key order)
+    Accounts(Vec<StakedState>),
+    /// the last part (so that a truncated snapshot isn't mistaken for a complete one)
+    End,
+}
+
+fn write_item<W: Write, I: Encode>(writer: &mut W, item: &I) -> io::Result<()> {
+    let data = item.encode();
+    writer.write_all(&(data.len() as u64).to_le_bytes())?;
+    writer.write_all(&data)
+}
+
+fn read_item<R: Read, I: Decode>(reader: &mut R) -> Result<I, String> {
+    let mut len = [0u8; 8];
+    reader
+        .read_exact(&mut len)
+        .map_err(|e| format!("failed to read snapshot: {}", e))?;
+    let len = u64::from_le_bytes(len);
+    let mut data = Vec::new();
+    reader
+        .by_ref()
+        .take(len)
+        .read_to_end(&mut data)
+        .map_err(|e| format!("failed to read snapshot: {}", e))?;
+    if data.len() as u64 != len {
+        return Err("snapshot is truncated".to_string());
+    }
+    I::decode(&mut data.as_slice()).ok_or_else(|| "failed to decode snapshot".to_string())
+}
+
+impl SnapshotHeader {
+    /// writes the storage format version followed by the header
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&STORAGE_VERSION.encode())?;
+        write_item(writer, self)
+    }
+
+    /// reads the header of a snapshot created from the same storage format version
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, String> {
+        let mut version = [0u8; 4];
+        reader
+            .read_exact(&mut version)
+            .map_err(|e| format!("failed to read snapshot: {}", e))?;
+        let version = u32::decode(&mut &version[..]).expect("snapshot data format version");
+        if version != STORAGE_VERSION {
+            return Err(format!(
+                "snapshot data format version: {} does not match the supported version: {}",
+                version, STORAGE_VERSION
+            ));
+        }
+        read_item(reader)
+    }
+}
+
+impl SnapshotPart {
+    /// writes the part
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_item(writer, self)
+    }
+
+    /// reads the next part
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, String> {
+        read_item(reader)
+    }
+}
+
+/// writes a snapshot of the committed state in `header`: the node storage `db` columns are iterated
+/// and the account trie storage `accounts` is traversed part by part
+/// (the node info column is skipped, as the restored node writes its own)
+fn write_snapshot<W: Write>(
+    writer: &mut W,
+    header: &SnapshotHeader,
+    db: &dyn KeyValueDB,
+    accounts: &Storage,
+) -> Result<(), String> {
+    let write_err = |e: io::Error| format!("failed to write snapshot: {}", e);
+    header.write(writer).map_err(write_err)?;
+    for column in 0..NUM_COLUMNS.expect("number of columns") {
+        if Some(column) == COL_NODE_INFO {
+            continue;
+        }
+        let mut entries = Vec::new();
+        for (key, value) in db.iter(Some(column)) {
+            entries.push((key.into_vec(), value.into_vec()));
+            if entries.len() == SNAPSHOT_PART_ENTRIES {
+                SnapshotPart::Column(column, mem::replace(&mut entries, Vec::new()))
+                    .write(writer)
+                    .map_err(write_err)?;
+            }
+        }
+        if !entries.is_empty() {
+            SnapshotPart::Column(column, entries)
+                .write(writer)
+                .map_err(write_err)?;
+        }
+    }
+    let mut batch = Vec::new();
+    visit_stored_accounts(accounts, &header.state.last_account_root_hash, |account| {
+        batch.push(account);
+        if batch.len() == SNAPSHOT_PART_ENTRIES {
+            SnapshotPart::Accounts(mem::replace(&mut batch, Vec::new()))
+                .write(writer)
+                .map_err(|e| Exception::new(&write_err(e)))?;
+        }
+        Ok(())
+    })
+    .map_err(|e| format!("account trie traversal failed: {}", e))?;
+    if !batch.is_empty() {
+        SnapshotPart::Accounts(batch)
+            .write(writer)
+            .map_err(write_err)?;
+    }
+    SnapshotPart::End.write(writer).map_err(write_err)?;
+    writer.flush().map_err(write_err)
+}
+
+/// What is restored from the snapshot parts and needed to check them against the snapshot state
+#[derive(Default)]
+struct RestoredParts {
+    /// the root hash of the rebuilt account trie
+    account_root: Option<StarlingFixedKey>,
+    /// the transaction merkle tree stored under the last app hash (if any)
+    tx_tree: Option<Vec<u8>>,
+    /// the app hash committed at the snapshot height
+    committed_app_hash: Option<Vec<u8>>,
+}
+
+impl RestoredParts {
+    /// writes the parts read from `reader` into the node storage and rebuilds the account trie from them
+    /// (one part at a time), keeping the entries the committed `state` is checked against
+    fn restore<R: Read>(
+        reader: &mut R,
+        state: &ChainNodeState,
+        storage: &Storage,
+        accounts: &mut AccountStorage,
+    ) -> Result<Self, String> {
+        let height_key = i64::encode_var_vec(state.last_block_height);
+        let mut restored = RestoredParts::default();
+        loop {
+            match SnapshotPart::read(reader)? {
+                SnapshotPart::Column(column, entries) => {
+                    if column >= NUM_COLUMNS.expect("number of columns") {
+                        return Err(format!("unknown snapshot column: {}", column));
+                    }
+                    let column = Some(column);
+                    let mut inittx = storage.db.transaction();
+                    for (key, value) in entries.into_iter() {
+                        // the node info is written by this node and only the account trie at the snapshot height is restored
+                        if column == COL_NODE_INFO
+                            || (column == COL_ACCOUNT_ROOTS && key != height_key)
+                        {
+                            continue;
+                        }
+                        if column == COL_MERKLE_PROOFS && key[..] == state.last_apphash[..] {
+                            restored.tx_tree = Some(value.clone());
+                        }
+                        if column == COL_APP_STATES && key == height_key {
+                            restored.committed_app_hash = Some(value.clone());
+                        }
+                        inittx.put(column, &key, &value);
+                    }
+                    storage
+                        .db
+                        .write(inittx)
+                        .map_err(|e| format!("failed to store snapshot column: {}", e))?;
+                }
+                SnapshotPart::Accounts(batch) => {
+                    let keys: Vec<StarlingFixedKey> = batch.iter().map(StakedState::key).collect();
+                    let wrapped: Vec<AccountWrapper> =
+                        batch.into_iter().map(AccountWrapper).collect();
+                    let account_root = accounts
+                        .insert(
+                            restored.account_root.as_ref(),
+                            &mut keys.iter().collect::<Vec<_>>(),
+                            &mut wrapped.iter().collect::<Vec<_>>(),
+                        )
+                        .map_err(|e| format!("failed to insert accounts: {}", e))?;
+                    restored.account_root = Some(account_root);
+                }
+                SnapshotPart::End => return Ok(restored),
+            }
+        }
+    }
+
+    /// checks the committed `state` (the rebuilt account trie, transaction tree, rewards pool,
+    /// network parameters and council state) hashes to the trusted app hash
+    /// and returns the root hash of the rebuilt account trie
+    fn check(
+        &self,
+        state: &ChainNodeState,
+        trusted: &TrustedAppHash,
+    ) -> Result<StarlingFixedKey, String> {
+        let account_root = self
+            .account_root
+            .ok_or_else(|| "no accounts in the snapshot".to_string())?;
+        if account_root != state.last_account_root_hash {
+            return Err(format!(
+                "restored account root: {:?} does not match the snapshot state: {:?}",
+                account_root, state.last_account_root_hash
+            ));
+        }
+
+        // blocks without transactions keep the previous app hash (and there is no stored tree for genesis)
+        let tx_tree = match &self.tx_tree {
+            Some(data) => MerkleTree::decode(&mut data.as_slice())
+                .ok_or_else(|| "failed to decode the transaction merkle tree".to_string())?,
+            None => MerkleTree::empty(),
+        };
+        let app_hash = compute_app_hash(
+            &tx_tree,
+            &account_root,
+            &state.rewards_pool,
+            &state.network_params,
+            &state.council_state_hash(),
+        );
+        if app_hash != trusted.app_hash {
+            return Err(format!(
+                "restored app hash: {:?} does not match the trusted app hash: {:?}",
+                app_hash, trusted.app_hash
+            ));
+        }
+        if app_hash != state.last_apphash {
+            return Err(format!(
+                "restored app hash: {:?} does not match the snapshot state: {:?}",
+                app_hash, state.last_apphash
+            ));
+        }
+        if state.last_block_height > 0
+            && self.committed_app_hash.as_ref().map(Vec::as_slice) != Some(&app_hash[..])
+        {
+            return Err("app hash committed at the snapshot height does not match".to_string());
+        }
+        Ok(account_root)
+    }
+}
+
+impl<T: EnclaveProxy> ChainNodeApp<T> {
+    /// Writes a snapshot of the last committed state into `writer` (fails if there is none).
+    /// It should only be called after commit (i.e. not in the middle of the block processing).
+    pub fn create_snapshot<W: Write>(&self, writer: &mut W) -> Result<(), String> {
+        let header = self
+            .snapshot_header()
+            .ok_or_else(|| "no committed state".to_string())?;
+        write_snapshot(writer, &header, &*self.storage.db, self.accounts.db())
+    }
+
+    fn snapshot_header(&self) -> Option<SnapshotHeader> {
+        Some(SnapshotHeader {
+            genesis_app_hash: self.genesis_app_hash,
+            chain_id: self.get_chain_id()?,
+            state: self.last_state.clone()?,
+        })
+    }
+
+    fn get_chain_id(&self) -> Option<Vec<u8>> {
+        Some(
+            self.storage
+                .db
+                .get(COL_EXTRA, CHAIN_ID_KEY)
+                .expect("chain id lookup")?
+                .to_vec(),
+        )
+    }
+
+    /// Starts writing a snapshot file in a background thread
+    /// if snapshots are enabled and the last committed height is at the configured interval
+    /// (the previous snapshot needs to be finished first)
+    pub fn store_snapshot_if_due(&mut self) {
+        let (config, height) = match (&self.snapshot_config, &self.last_state) {
+            (Some(config), Some(state)) => (config.clone(), state.last_block_height),
+            _ => return,
+        };
+        if config.interval <= 0 || height % config.interval != 0 {
+            return;
+        }
+        let header = match self.snapshot_header() {
+            Some(header) => header,
+            None => return,
+        };
+        self.snapshot_writer.join();
+        let db = self.storage.db.clone();
+        let accounts = self.accounts.db().clone();
+        let storage_lock = self.snapshot_writer.storage_lock.clone();
+        let (started, wait_started) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            let _guard = storage_lock.read().expect("snapshot storage lock");
+            started.send(()).expect("snapshot thread start");
+            let path = config.snapshot_path(height);
+            // written under a different name first, so that an incomplete file isn't served
+            let partial_path = path.with_extension("partial");
+            let result = fs::create_dir_all(&config.dir)
+                .and_then(|_| File::create(&partial_path))
+                .map_err(|e| e.to_string())
+                .and_then(|file| {
+                    write_snapshot(&mut BufWriter::new(file), &header, &*db, &accounts)
+                })
+                .and_then(|_| fs::rename(&partial_path, &path).map_err(|e| e.to_string()));
+            match result {
+                Ok(_) => info!("snapshot stored: {:?}", path),
+                Err(e) => warn!("failed to store snapshot {:?}: {}", path, e),
+            }
+        });
+        // the storage must not change before the thread holds the lock
+        wait_started.recv().expect("snapshot thread start");
+        self.snapshot_writer.thread = Some(thread);
+    }
+
+    /// Reads a chunk of a stored snapshot at the given height (0 == the latest one)
+    pub fn load_snapshot_chunk(&self, height: BlockHeight, index: u64) -> Option<SnapshotChunk> {
+        let config = self.snapshot_config.as_ref()?;
+        let height = if height == 0 {
+            config.latest_snapshot_height()?
+        } else {
+            height
+        };
+        let mut file = File::open(config.snapshot_path(height)).ok()?;
+        let size = file.metadata().ok()?.len();
+        let chunks = (size + SNAPSHOT_CHUNK_SIZE - 1) / SNAPSHOT_CHUNK_SIZE;
+        if index >= chunks {
+            return None;
+        }
+        let mut data = Vec::new();
+        file.seek(SeekFrom::Start(index * SNAPSHOT_CHUNK_SIZE))
+            .ok()?;
+        file.take(SNAPSHOT_CHUNK_SIZE).read_to_end(&mut data).ok()?;
+        Some(SnapshotChunk {
+            height,
+            index,
+            chunks,
+            data,
+        })
+    }
+
+    /// Creates a new App with its (empty) storage bootstrapped from a snapshot read from `snapshot` part by part.
+    /// The snapshot is checked against the local storage format version, the provided genesis app hash and chain id,
+    /// and the account trie + app hash are recomputed and checked against the trusted app hash
+    /// (which needs to come from a source other than the snapshot, e.g. a light client verified header).
+    /// If it panics on an invalid snapshot, the storage may contain some of the snapshot parts
+    /// (but no app state) and should be discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_validator` - ZMQ proxy to enclave TX validator
+    /// * `gah` - hex-encoded genesis app hash
+    /// * `chain_id` - the chain ID set in Tendermint genesis.json
+    /// * `storage` - underlying storage to be used (should be empty)
+    /// * `accounts` - underlying storage for account tries to be used (should be empty)
+    /// * `snapshot` - the snapshot to restore from
+    /// * `trusted` - the block height of the snapshot and the app hash after executing it
+    pub fn new_from_snapshot<R: Read>(
+        tx_validator: T,
+        gah: &str,
+        chain_id: &str,
+        storage: Storage,
+        mut accounts: AccountStorage,
+        snapshot: &mut R,
+        trusted: TrustedAppHash,
+    ) -> Self {
+        if storage
+            .db
+            .get(COL_NODE_INFO, LAST_STATE_KEY)
+            .expect("app state lookup")
+            .is_some()
+        {
+            panic!("app state already stored, snapshot can only be restored into an empty storage");
+        }
+        let header =
+            SnapshotHeader::read(snapshot).unwrap_or_else(|e| panic!("invalid snapshot: {}", e));
+        let decoded_gah = hex::decode(gah).expect("failed to decode genesis app hash");
+        let mut genesis_app_hash = [0u8; HASH_SIZE_256];
+        genesis_app_hash.copy_from_slice(&decoded_gah[..]);
+        if header.genesis_app_hash != genesis_app_hash {
+            panic!(
+                "snapshot genesis app hash: {:?} does not match the provided genesis app hash: {:?}",
+                header.genesis_app_hash, genesis_app_hash
+            );
+        }
+        if header.chain_id != chain_id.as_bytes() {
+            panic!(
+                "snapshot chain id: {:?} does not match the provided chain id: {:?}",
+                header.chain_id, chain_id
+            );
+        }
+        let state = header.state;
+        if state.last_block_height != trusted.height {
+            panic!(
+                "snapshot height: {} does not match the trusted height: {}",
+                state.last_block_height, trusted.height
+            );
+        }
+        match RestoredParts::restore(snapshot, &state, &storage, &mut accounts)
+            .and_then(|restored| restored.check(&state, &trusted))
+        {
+            Ok(account_root) => accounts
+                .collect_uncommitted(None, &account_root)
+                .expect("account trie garbage collection"),
+            Err(e) => panic!("invalid snapshot: {}", e),
+        }
+
+        let mut inittx = storage.db.transaction();
+        inittx.put(COL_NODE_INFO, GENESIS_APP_HASH_KEY, &genesis_app_hash);
+        inittx.put(COL_EXTRA, CHAIN_ID_KEY, chain_id.as_bytes());
+        inittx.put(
+            COL_NODE_INFO,
+            STORAGE_VERSION_KEY,
+            &STORAGE_VERSION.encode(),
+        );
+        inittx.put(COL_NODE_INFO, LAST_STATE_KEY, &state.encode());
+        storage.db.write(inittx).expect("snapshot should be stored");
+        info!(
+            "restored from snapshot at height {}",
+            state.last_block_height
+        );
+
+        ChainNodeApp::new_with_storage(tx_validator, gah, chain_id, storage, accounts)
+    }
+}
@@ -4,8 +4,9 @@ use crate::storage::tx::verify;
 use abci::*;
 use chain_core::state::account::StakedState;
 use chain_core::tx::fee::{Fee, FeeAlgorithm};
+use chain_core::tx::rejection::RejectionCode;
 use chain_core::tx::TxAux;
-use chain_tx_validation::ChainInfo;
+use chain_tx_validation::{ChainInfo, CouncilInfo};
 use parity_codec::Decode;
 
 /// Wrapper to astract over CheckTx and DeliverTx requests
@@ -63,24 +64,40 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
         let dtx = TxAux::decode(&mut data.as_slice());
         match dtx {
             None => {
-                resp.set_code(1);
+                resp.set_code(RejectionCode::InvalidFormat.code());
                 resp.add_log("failed to deserialize tx");
                 None
             }
             Some(txaux) => {
                 let state = self.last_state.as_ref().expect("the app state is expected");
-                let min_fee = state
-                    .fee_policy
-                    .calculate_fee(_req.tx().len())
-                    .expect("invalid fee policy");
+                // e.g. the fee of a huge transaction at a high dynamic fee multiplier can't be paid
+                let min_fee = match state.network_params.fee_policy.calculate_for_txaux(&txaux) {
+                    Ok(fee) => fee,
+                    Err(e) => {
+                        resp.set_code(RejectionCode::InvalidSum.code());
+                        resp.add_log(&format!("fee computation failed: {}", e));
+                        return None;
+                    }
+                };
                 let fee_paid = verify(
                     &self.tx_validator,
                     &txaux,
                     ChainInfo {
                         min_fee_computed: min_fee,
                         chain_hex_id: self.chain_hex_id,
+                        chain_id_hash: self.chain_id_hash,
+                        chain_id_hash_required: state.chain_id_hash_required(),
                         previous_block_time: state.block_time,
-                        unbonding_period: state.unbonding_period,
+                        previous_block_height: state.last_block_height,
+                        unbonding_period: state.network_params.unbonding_period,
+                    },
+                    CouncilInfo {
+                        required_council_node_stake: state
+                            .network_params
+                            .required_council_node_stake,
+                        council_nodes: &state.council_nodes,
+                        last_block_height: state.last_block_height,
+                        proposals: &state.proposals,
                     },
                     &self.uncommitted_account_root_hash,
                     self.storage.db.clone(),
@@ -90,8 +107,9 @@ impl<T: EnclaveProxy> ChainNodeApp<T> {
                     resp.set_code(0);
                     Some((txaux, fee_paid.unwrap()))
                 } else {
-                    resp.set_code(1);
-                    resp.add_log(&format!("verification failed: {}", fee_paid.unwrap_err()));
+                    let err = fee_paid.unwrap_err();
+                    resp.set_code(err.rejection_code().code());
+                    resp.add_log(&format!("verification failed: {}", err));
                     None
                 }
             }
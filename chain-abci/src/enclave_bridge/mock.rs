@@ -30,7 +30,10 @@ impl EnclaveProxy for MockClient {
                 inputs,
                 min_fee_computed,
                 previous_block_time,
+                previous_block_height,
                 unbonding_period,
+                chain_id_hash,
+                chain_id_hash_required,
             } => {
                 // FIXME
                 let plain_tx = PlainTxAux::decode(&mut txpayload.as_slice());
@@ -39,7 +42,10 @@ impl EnclaveProxy for MockClient {
                         let info = ChainInfo {
                             min_fee_computed,
                             chain_hex_id: self.chain_hex_id,
+                            chain_id_hash,
+                            chain_id_hash_required,
                             previous_block_time,
+                            previous_block_height,
                             unbonding_period,
                         };
                         let result = verify_transfer(&maintx, &witness, info, inputs);
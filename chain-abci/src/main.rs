@@ -5,12 +5,56 @@ mod storage;
 use clap::load_yaml;
 use clap::App;
 use log::info;
+use parity_codec::Decode;
+use std::fs;
+use std::io::BufReader;
 use std::net::SocketAddr;
+use std::path::Path;
 use zmq::{Context, REQ};
 
-use crate::app::ChainNodeApp;
+use crate::app::{ChainNodeApp, ChainNodeState, SnapshotConfig, TrustedAppHash};
 use crate::enclave_bridge::ZmqEnclaveClient;
+use crate::storage::account::{reclaimable_space, AccountStorage};
+use crate::storage::tx::StarlingFixedKey;
 use crate::storage::*;
+use chain_core::common::HASH_SIZE_256;
+
+/// Prints how much space could be reclaimed in the account trie storage
+/// (i.e. the nodes unreachable from the stored account roots of the retained heights)
+fn report_reclaimable_space(
+    node_storage_config: &StorageConfig<'_>,
+    account_storage_config: &StorageConfig<'_>,
+) {
+    let storage = Storage::new(node_storage_config);
+    let accounts =
+        AccountStorage::new(Storage::new(account_storage_config), 20).expect("account db");
+    let mut roots: Vec<StarlingFixedKey> = storage
+        .db
+        .iter(COL_ACCOUNT_ROOTS)
+        .filter_map(|(_, stored)| {
+            let mut root = StarlingFixedKey::default();
+            if stored.len() != root.len() {
+                return None;
+            }
+            root.copy_from_slice(&stored[..]);
+            Some(root)
+        })
+        .collect();
+    if let Some(state) = storage
+        .db
+        .get(COL_NODE_INFO, LAST_STATE_KEY)
+        .expect("app state lookup")
+    {
+        let state = ChainNodeState::decode(&mut &state[..]).expect("app state");
+        roots.push(state.last_account_root_hash);
+    }
+    let space = reclaimable_space(&accounts, &roots).expect("account trie traversal");
+    println!("account trie nodes: {}", space.total_nodes);
+    println!(
+        "unreachable nodes: {} ({} bytes can be reclaimed)",
+        space.nodes, space.bytes
+    );
+}
 
 fn main() {
     // TODO
@@ -32,16 +76,78 @@ fn main() {
     let genesis_app_hash = matches.value_of("genesis_app_hash").unwrap();
     let chain_id = matches.value_of("chain_id").unwrap();
 
+    let snapshot_interval = matches
+        .value_of("snapshot_interval")
+        .unwrap_or("0")
+        .parse()
+        .expect("invalid snapshot interval");
+    let snapshot_dir = matches
+        .value_of("snapshot_dir")
+        .map(|dir| Path::new(dir).to_path_buf())
+        .unwrap_or_else(|| Path::new(data).join("snapshots"));
+
     let addr = SocketAddr::new(host.parse().unwrap(), port.parse().unwrap());
     info!("starting up");
-    abci::run(
-        addr,
-        ChainNodeApp::new(
+    let node_storage_config = StorageConfig::new(data, StorageType::Node);
+    let account_storage_config = StorageConfig::new(data, StorageType::AccountTrie);
+    if matches.is_present("report_reclaimable_space") {
+        report_reclaimable_space(&node_storage_config, &account_storage_config);
+        return;
+    }
+    let mut app = match matches.value_of("restore_snapshot") {
+        Some(snapshot_path) => {
+            let mut snapshot = BufReader::new(
+                fs::File::open(snapshot_path).expect("failed to open snapshot file"),
+            );
+            let height = matches
+                .value_of("snapshot_height")
+                .expect("trusted snapshot height")
+                .parse()
+                .expect("invalid snapshot height");
+            let decoded_app_hash = hex::decode(
+                matches
+                    .value_of("snapshot_app_hash")
+                    .expect("trusted app hash"),
+            )
+            .expect("invalid snapshot app hash");
+            assert_eq!(
+                HASH_SIZE_256,
+                decoded_app_hash.len(),
+                "invalid snapshot app hash length"
+            );
+            let mut app_hash = [0u8; HASH_SIZE_256];
+            app_hash.copy_from_slice(&decoded_app_hash);
+            ChainNodeApp::new_from_snapshot(
+                proxy,
+                &genesis_app_hash,
+                &chain_id,
+                Storage::new(&node_storage_config),
+                AccountStorage::new(Storage::new(&account_storage_config), 20).expect("account db"),
+                &mut snapshot,
+                TrustedAppHash { height, app_hash },
+            )
+        }
+        None => ChainNodeApp::new(
             proxy,
             &genesis_app_hash,
             &chain_id,
-            &StorageConfig::new(data, StorageType::Node),
-            &StorageConfig::new(data, StorageType::AccountTrie),
+            &node_storage_config,
+            &account_storage_config,
         ),
-    );
+    };
+    if snapshot_interval > 0 {
+        app.snapshot_config = Some(SnapshotConfig::new(snapshot_dir, snapshot_interval));
+    }
+    app.account_root_history = matches
+        .value_of("account_root_history")
+        .map(|history| history.parse().expect("invalid account root history"));
+    if let Some(retention) = matches.value_of("pruning_retention") {
+        let retention = retention.parse().expect("invalid pruning retention");
+        app.storage.pruning = PruningMode::Retain(retention);
+        assert!(
+            retention > 0,
+            "pruning retention needs to be at least one block"
+        );
+    }
+    abci::run(addr, app);
 }
@@ -80,13 +80,56 @@ impl Database for Storage {
     }
 }
 
+/// Calls `visit` with each staked state in the account trie under `root_hash` (in the key order),
+/// reading the tree nodes directly from `db` (e.g. while another thread keeps inserting new ones)
+pub fn visit_stored_accounts<F>(
+    db: &Storage,
+    root_hash: &[u8; KEY_LEN],
+    mut visit: F,
+) -> Result<(), Exception>
+where
+    F: FnMut(StakedState) -> Result<(), Exception>,
+{
+    tree::visit_stored_entries::<AccountWrapper, _, _>(db, root_hash, |_, account| visit(account.0))
+}
+
+/// Space taken by the account trie nodes that are unreachable from the retained roots
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReclaimableSpace {
+    /// the number of all stored nodes
+    pub total_nodes: usize,
+    /// the number of unreachable nodes
+    pub nodes: usize,
+    /// the size of their keys and values
+    pub bytes: usize,
+}
+
+/// Reports how much space could be reclaimed in the account storage:
+/// the (mark-and-sweep) nodes that can't be reached from any of `roots`
+/// (e.g. left over from restarts in the middle of a block or changes of the retained history).
+pub fn reclaimable_space(
+    accounts: &AccountStorage,
+    roots: &[[u8; KEY_LEN]],
+) -> Result<ReclaimableSpace, Exception> {
+    let reachable = accounts.reachable_nodes(roots)?;
+    let mut space = ReclaimableSpace::default();
+    for (key, value) in accounts.db().db.iter(None) {
+        space.total_nodes += 1;
+        if key.len() != KEY_LEN || !reachable.contains(&key[..]) {
+            space.nodes += 1;
+            space.bytes += key.len() + value.len();
+        }
+    }
+    Ok(space)
+}
+
 #[cfg(test)]
 mod test {
 
     use super::*;
     use chain_core::init::address::RedeemAddress;
     use chain_core::init::coin::Coin;
-    use chain_core::state::account::StakedState;
+    use chain_core::state::account::{to_stake_key, StakedState};
     use kvdb_memorydb::create;
     use std::sync::Arc;
 
@@ -162,4 +205,111 @@ mod test {
         assert_eq!(old_items[&key], None);
     }
 
+    #[test]
+    fn test_superseded_roots_should_be_collected() {
+        let mut tree = AccountStorage::new(create_db(), 20).expect("account db");
+        let accounts: Vec<StakedState> = (1..=3u8)
+            .map(|i| {
+                StakedState::new(
+                    0,
+                    Coin::unit(),
+                    Coin::unit(),
+                    0,
+                    RedeemAddress::from([i; 20]).into(),
+                )
+            })
+            .collect();
+        let keys: Vec<[u8; KEY_LEN]> = accounts.iter().map(StakedState::key).collect();
+        let wrapped: Vec<AccountWrapper> = accounts.iter().cloned().map(AccountWrapper).collect();
+        let committed_root = tree
+            .insert(
+                None,
+                &mut keys.iter().collect::<Vec<_>>(),
+                &mut wrapped.iter().collect::<Vec<_>>(),
+            )
+            .expect("insert");
+        tree.collect_uncommitted(None, &committed_root)
+            .expect("collect");
+
+        let mut root = committed_root;
+        let mut intermediate_roots = Vec::new();
+        for nonce in 1..=2 {
+            let mut account = accounts[0].clone();
+            account.nonce = nonce;
+            root = tree
+                .insert(
+                    Some(&root),
+                    &mut [&keys[0]],
+                    &mut [&AccountWrapper(account)],
+                )
+                .expect("update");
+            intermediate_roots.push(root);
+        }
+        assert_ne!(
+            0,
+            reclaimable_space(&tree, &[committed_root, root])
+                .unwrap()
+                .nodes
+        );
+        tree.collect_uncommitted(Some(&committed_root), &root)
+            .expect("collect");
+
+        let space = reclaimable_space(&tree, &[committed_root, root]).unwrap();
+        assert_eq!(0, space.nodes);
+        assert_eq!(0, space.bytes);
+        let items = tree
+            .get(&intermediate_roots[0], &mut [&keys[0]])
+            .expect("get");
+        assert_eq!(items[&keys[0]], None);
+        let items = tree.get(&committed_root, &mut [&keys[0]]).expect("get");
+        assert_eq!(items[&keys[0]], Some(wrapped[0].clone()));
+        for (key, account) in keys.iter().zip(wrapped.iter()).skip(1) {
+            let items = tree.get(&root, &mut [key]).expect("get");
+            assert_eq!(items[key].as_ref(), Some(account));
+        }
+
+        // the old committed root (e.g. out of the retained history)
+        tree.remove(&committed_root).expect("remove");
+        let space = reclaimable_space(&tree, &[root]).unwrap();
+        assert_eq!(0, space.nodes);
+        assert_ne!(0, space.total_nodes);
+        let items = tree.get(&root, &mut [&keys[0]]).expect("get");
+        assert_eq!(items[&keys[0]].as_ref().map(|a| a.0.nonce), Some(2));
+    }
+
+    #[test]
+    fn test_account_proofs_should_verify() {
+        let mut tree = AccountStorage::new(create_db(), 20).expect("account db");
+        let accounts: Vec<StakedState> = (1..=3u8)
+            .map(|i| {
+                StakedState::new(
+                    0,
+                    Coin::unit(),
+                    Coin::unit(),
+                    0,
+                    RedeemAddress::from([i; 20]).into(),
+                )
+            })
+            .collect();
+        let keys: Vec<[u8; KEY_LEN]> = accounts.iter().map(StakedState::key).collect();
+        let wrapped: Vec<AccountWrapper> = accounts.iter().cloned().map(AccountWrapper).collect();
+        let root = tree
+            .insert(
+                None,
+                &mut keys.iter().collect::<Vec<_>>(),
+                &mut wrapped.iter().collect::<Vec<_>>(),
+            )
+            .expect("insert");
+        for (key, account) in keys.iter().zip(accounts.iter()) {
+            let proof = tree.generate_proof(&root, key).expect("proof");
+            assert!(proof.verify(&root, &account.address, Some(account)));
+            assert!(!proof.verify(&root, &account.address, None));
+        }
+        let missing = RedeemAddress::from([4u8; 20]).into();
+        let proof = tree
+            .generate_proof(&root, &to_stake_key(&missing))
+            .expect("proof");
+        assert!(proof.verify(&root, &missing, None));
+        assert!(!proof.verify(&root, &missing, Some(&accounts[0])));
+    }
 }
@@ -6,6 +6,9 @@
 use blake2::{Blake2s, Digest};
 use chain_core::common::H256;
 use chain_core::state::account::Count;
+use chain_core::state::account_proof::{
+    is_zero_bit, AccountProof, AccountTrieBranch, AccountTrieLeaf,
+};
 use parity_codec::{Decode as ScaleDecode, Encode as ScaleEncode, Input, Output};
 use starling::constants::KEY_LEN;
 use starling::merkle_bit::{BinaryMerkleTreeResult, MerkleBIT};
@@ -14,7 +17,7 @@ use starling::traits::{
     Branch, Data, Database, Decode, Encode, Exception, Leaf, Node, NodeVariant,
 };
 use starling::tree::tree_data::TreeData;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 
 #[derive(Clone)]
@@ -325,24 +328,180 @@ impl Node<TreeBranch, TreeLeaf, TreeData> for TreeNode {
     }
 }
 
+/// Gets all the keys and values reachable from `root_hash` in the tree nodes stored in `db` (in the key order).
+pub fn stored_entries<ValueType, DatabaseType>(
+    db: &DatabaseType,
+    root_hash: &[u8; KEY_LEN],
+) -> BinaryMerkleTreeResult<Vec<([u8; KEY_LEN], ValueType)>>
+where
+    ValueType: Decode,
+    DatabaseType: Database<NodeType = TreeNode>,
+{
+    let mut entries = Vec::new();
+    visit_stored_entries(db, root_hash, |key, value| {
+        entries.push((key, value));
+        Ok(())
+    })?;
+    Ok(entries)
+}
+
+/// Calls `visit` with each key and value reachable from `root_hash` in the tree nodes stored in `db` (in the key order),
+/// without collecting them (stops at the first error returned by `visit`).
+pub fn visit_stored_entries<ValueType, DatabaseType, F>(
+    db: &DatabaseType,
+    root_hash: &[u8; KEY_LEN],
+    mut visit: F,
+) -> BinaryMerkleTreeResult<()>
+where
+    ValueType: Decode,
+    DatabaseType: Database<NodeType = TreeNode>,
+    F: FnMut([u8; KEY_LEN], ValueType) -> BinaryMerkleTreeResult<()>,
+{
+    let mut pending = vec![*root_hash];
+    while let Some(location) = pending.pop() {
+        let node = db
+            .get_node(&location)?
+            .ok_or_else(|| Exception::new("tree node not found"))?;
+        match node.node {
+            NodeVariant::Branch(branch) => {
+                pending.push(branch.one);
+                pending.push(branch.zero);
+            }
+            NodeVariant::Leaf(leaf) => {
+                let data = db
+                    .get_node(&leaf.data)?
+                    .ok_or_else(|| Exception::new("data node not found"))?;
+                match data.node {
+                    NodeVariant::Data(value) => {
+                        visit(leaf.key, ValueType::decode(value.get_value())?)?;
+                    }
+                    _ => return Err(Exception::new("leaf does not point to a data node")),
+                }
+            }
+            NodeVariant::Data(_) => return Err(Exception::new("unexpected data node")),
+        }
+    }
+    Ok(())
+}
+
 pub struct HashTree<ValueType, DatabaseType>
 where
     ValueType: Encode + Decode + Sync + Send,
-    DatabaseType: Database<NodeType = TreeNode>,
+    DatabaseType: Database<NodeType = TreeNode> + Clone,
 {
     tree: MerkleBIT<DatabaseType, TreeBranch, TreeLeaf, TreeData, TreeNode, TreeHasher, ValueType>,
+    /// A handle to the same database for reading the raw tree nodes
+    db: DatabaseType,
+    /// The roots inserted since the last collection (each holds a reference to its nodes)
+    uncommitted_roots: Vec<[u8; KEY_LEN]>,
 }
 
 impl<ValueType, DatabaseType> HashTree<ValueType, DatabaseType>
 where
     ValueType: Encode + Decode + Sync + Send,
-    DatabaseType: Database<NodeType = TreeNode>,
+    DatabaseType: Database<NodeType = TreeNode> + Clone,
 {
     /// Creates a new `HashTree`.
     #[inline]
     pub fn new(db: DatabaseType, depth: usize) -> BinaryMerkleTreeResult<Self> {
-        let tree = MerkleBIT::from_db(db, depth)?;
-        Ok(Self { tree })
+        let tree = MerkleBIT::from_db(db.clone(), depth)?;
+        Ok(Self {
+            tree,
+            db,
+            uncommitted_roots: Vec::new(),
+        })
+    }
+
+    /// Gets the underlying database.
+    #[inline]
+    pub fn db(&self) -> &DatabaseType {
+        &self.db
+    }
+
+    /// Gets the raw tree node stored at `location`.
+    #[inline]
+    pub fn get_node(&self, location: &[u8; KEY_LEN]) -> BinaryMerkleTreeResult<Option<TreeNode>> {
+        self.db.get_node(location)
+    }
+
+    /// Gets all the keys and values reachable from `root_hash` (in the key order).
+    #[inline]
+    pub fn entries(
+        &self,
+        root_hash: &[u8; KEY_LEN],
+    ) -> BinaryMerkleTreeResult<Vec<([u8; KEY_LEN], ValueType)>> {
+        stored_entries(&self.db, root_hash)
+    }
+
+    /// Gets the locations of all the nodes reachable from `roots` (missing roots are skipped).
+    pub fn reachable_nodes(
+        &self,
+        roots: &[[u8; KEY_LEN]],
+    ) -> BinaryMerkleTreeResult<HashSet<[u8; KEY_LEN]>> {
+        let mut reachable = HashSet::new();
+        let mut pending = roots.to_vec();
+        while let Some(location) = pending.pop() {
+            if reachable.contains(&location) {
+                continue;
+            }
+            let node = match self.get_node(&location)? {
+                Some(node) => node,
+                None if roots.contains(&location) => continue,
+                None => return Err(Exception::new("tree node not found")),
+            };
+            reachable.insert(location);
+            match node.node {
+                NodeVariant::Branch(branch) => {
+                    pending.push(branch.one);
+                    pending.push(branch.zero);
+                }
+                NodeVariant::Leaf(leaf) => {
+                    pending.push(leaf.data);
+                }
+                NodeVariant::Data(_) => {}
+            }
+        }
+        Ok(reachable)
+    }
+
+    /// Generates a proof of inclusion (or non-inclusion) of `key` in the tree under `root_hash`:
+    /// the branches on the path decided by the key bits + the leaf at its end.
+    pub fn generate_proof(
+        &self,
+        root_hash: &[u8; KEY_LEN],
+        key: &[u8; KEY_LEN],
+    ) -> BinaryMerkleTreeResult<AccountProof> {
+        let mut branches = Vec::new();
+        let mut location = *root_hash;
+        loop {
+            let node = self
+                .get_node(&location)?
+                .ok_or_else(|| Exception::new("tree node not found"))?;
+            match node.node {
+                NodeVariant::Branch(branch) => {
+                    location = if is_zero_bit(key, branch.split_index) {
+                        branch.zero
+                    } else {
+                        branch.one
+                    };
+                    branches.push(AccountTrieBranch {
+                        split_index: branch.split_index,
+                        zero: branch.zero,
+                        one: branch.one,
+                    });
+                }
+                NodeVariant::Leaf(leaf) => {
+                    return Ok(AccountProof {
+                        branches,
+                        leaf: AccountTrieLeaf {
+                            key: leaf.key,
+                            data: leaf.data,
+                        },
+                    });
+                }
+                NodeVariant::Data(_) => return Err(Exception::new("unexpected data node")),
+            }
+        }
     }
 
     /// Gets the values associated with `keys` from the tree.
@@ -364,7 +523,29 @@ where
         keys: &mut [&[u8; KEY_LEN]],
         values: &mut [&ValueType],
     ) -> BinaryMerkleTreeResult<[u8; KEY_LEN]> {
-        self.tree.insert(previous_root, keys, values)
+        let root = self.tree.insert(previous_root, keys, values)?;
+        self.uncommitted_roots.push(root);
+        Ok(root)
+    }
+
+    /// Removes the roots inserted since the last collection that were superseded by `committed_root`
+    /// (e.g. the intermediate states within a block). `committed_root` keeps one reference,
+    /// unless it's the same as `previous_root` (which already holds one).
+    pub fn collect_uncommitted(
+        &mut self,
+        previous_root: Option<&[u8; KEY_LEN]>,
+        committed_root: &[u8; KEY_LEN],
+    ) -> BinaryMerkleTreeResult<()> {
+        let mut keep = previous_root != Some(committed_root);
+        let roots = std::mem::replace(&mut self.uncommitted_roots, Vec::new());
+        for root in roots.iter() {
+            if keep && root == committed_root {
+                keep = false;
+            } else {
+                self.tree.remove(root)?;
+            }
+        }
+        Ok(())
     }
 
     /// Removes a root from the tree.  This will remove all elements with less than two references
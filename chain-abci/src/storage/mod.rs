@@ -1,12 +1,21 @@
 pub mod account;
 pub mod tx;
 
-use kvdb::KeyValueDB;
+use crate::storage::tx::TxMeta;
+use chain_core::common::H256;
+use chain_core::state::tendermint::BlockHeight;
+use chain_core::tx::data::input::TxoPointer;
+use chain_core::tx::data::TxId;
+use chain_tx_validation::TxWithOutputs;
+use integer_encoding::VarInt;
+use kvdb::{DBTransaction, KeyValueDB};
+use parity_codec::{Decode, Encode};
+use std::collections::BTreeSet;
 use std::path::Path;
 use std::sync::Arc;
 
 // database columns
-/// Column for UTXOs: TxId => BitVec (where each bit indicates whether the output was spent or not, e.g. b[0] == true if output 0 was spent in a given TX)
+/// Column for UTXOs: TxId => TxMeta (the block the TX was included in and BitVec where each bit indicates whether the output was spent or not, e.g. b[0] == true if output 0 was spent in a given TX)
 pub const COL_TX_META: Option<u32> = Some(0);
 /// Column for TX witnesses: TxId => TxWitness
 pub const COL_WITNESS: Option<u32> = Some(1);
@@ -20,12 +29,27 @@ pub const COL_NODE_INFO: Option<u32> = Some(4);
 pub const COL_MERKLE_PROOFS: Option<u32> = Some(5);
 /// Column for tracking app states: height => root hash
 pub const COL_APP_STATES: Option<u32> = Some(6);
+/// Column for tracking account trie roots (for historical queries): height => account root hash
+pub const COL_ACCOUNT_ROOTS: Option<u32> = Some(7);
+/// Column for the parts each app hash is computed from (for proofs against it): app hash => AppStateRoots
+pub const COL_APP_STATE_ROOTS: Option<u32> = Some(8);
+/// Column for transactions waiting to be pruned: height (when they became fully spent) => Vec<TxId>
+pub const COL_SPENT_TXS: Option<u32> = Some(9);
+/// Column for markers of pruned transactions (so that they aren't reported as unknown): TxId => height they were pruned at
+pub const COL_PRUNED_TXS: Option<u32> = Some(10);
 /// Number of columns in DB
-pub const NUM_COLUMNS: Option<u32> = Some(7);
+pub const NUM_COLUMNS: Option<u32> = Some(11);
 
 pub const CHAIN_ID_KEY: &[u8] = b"chain_id";
 pub const GENESIS_APP_HASH_KEY: &[u8] = b"genesis_app_hash";
 pub const LAST_STATE_KEY: &[u8] = b"last_state";
+/// Key of the version of the stored data format (in `COL_NODE_INFO`)
+pub const STORAGE_VERSION_KEY: &[u8] = b"storage_version";
+/// Version of the stored data format -- nodes can only continue from storage in the same format
+/// (1: `COL_TX_META` stores `TxMeta` with the block the TX was included in instead of the spent BitVec only,
+/// 2: `ChainNodeState` includes the height from which the full chain ID is required in transactions,
+/// 3: `AppStateRoots` include the hash of the council state kept outside of the account trie)
+pub const STORAGE_VERSION: u32 = 3;
 
 pub enum StorageType {
     Node,
@@ -63,17 +87,33 @@ impl<'a> StorageConfig<'a> {
     }
 }
 
-/// Storage wrapper -- currently only holds the reference to KV DB.
+/// Which data of the past blocks is kept in the storage
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PruningMode {
+    /// everything is kept
+    Archive,
+    /// only the data needed for validation and proofs in the given number of the most recent heights is kept:
+    /// bodies, witnesses and metadata of transactions fully spent before that are removed
+    /// (as well as the merkle trees of the older app states)
+    Retain(BlockHeight),
+}
+
+/// Storage wrapper -- currently only holds the reference to KV DB (and how it's pruned).
 /// It may hold caches or other look ups (TODO: reconsider whether necessary and if db could be moved up to App)
+#[derive(Clone)]
 pub struct Storage {
     pub db: Arc<dyn KeyValueDB>,
+    pub pruning: PruningMode,
 }
 
 impl Storage {
     /// initializes Storage with a provided reference to KV DB (used in testing / benches -- in-mem KVDB)
     #[allow(dead_code)]
     pub fn new_db(db: Arc<dyn KeyValueDB>) -> Self {
-        Storage { db }
+        Storage {
+            db,
+            pruning: PruningMode::Archive,
+        }
     }
 
     /// inititalizes Storage based on the provided config
@@ -85,6 +125,111 @@ impl Storage {
             )
             .expect("failed to open db"),
         );
-        Storage { db }
+        Storage {
+            db,
+            pruning: PruningMode::Archive,
+        }
+    }
+
+    /// checks if the transaction's data was removed by pruning
+    pub fn is_pruned(&self, txid: &[u8]) -> bool {
+        self.db
+            .get(COL_PRUNED_TXS, txid)
+            .expect("pruned tx lookup")
+            .is_some()
+    }
+
+    /// Given the inputs spent in the block committed at `height` (with `app_hash`), it schedules the transactions
+    /// that became fully spent for pruning and removes the data that fell out of the retention window
+    /// (nothing is done in the archive mode).
+    /// Transactions fully spent while the storage was in the archive mode are kept.
+    pub fn prune(
+        &self,
+        dbtx: &mut DBTransaction,
+        height: BlockHeight,
+        app_hash: &H256,
+        spent_inputs: &[TxoPointer],
+    ) {
+        let retention = match self.pruning {
+            PruningMode::Archive => {
+                return;
+            }
+            PruningMode::Retain(retention) => retention,
+        };
+        // inputs were already marked as spent in deliver_tx
+        let spent_txids: BTreeSet<TxId> = spent_inputs.iter().map(|txin| txin.id).collect();
+        let fully_spent: Vec<TxId> = spent_txids
+            .into_iter()
+            .filter(|txid| self.is_fully_spent(txid))
+            .collect();
+        if !fully_spent.is_empty() {
+            dbtx.put(
+                COL_SPENT_TXS,
+                &i64::encode_var_vec(height),
+                &fully_spent.encode(),
+            );
+        }
+
+        let pruned_height = height - retention;
+        if pruned_height <= 0 {
+            return;
+        }
+        let pruned_key = i64::encode_var_vec(pruned_height);
+        if let Some(txids) = self
+            .db
+            .get(COL_SPENT_TXS, &pruned_key)
+            .expect("spent txs lookup")
+        {
+            let txids = Vec::<TxId>::decode(&mut &txids[..]).expect("spent txs");
+            for txid in txids.iter() {
+                dbtx.delete(COL_BODIES, &txid[..]);
+                dbtx.delete(COL_WITNESS, &txid[..]);
+                dbtx.delete(COL_TX_META, &txid[..]);
+                dbtx.put(COL_PRUNED_TXS, &txid[..], &i64::encode_var_vec(height));
+            }
+            dbtx.delete(COL_SPENT_TXS, &pruned_key);
+        }
+        // blocks without transactions keep the previous app hash, so its merkle tree
+        // is only removed once it's no longer the app hash of the next height
+        let pruned_app_hash = self
+            .db
+            .get(COL_APP_STATES, &pruned_key)
+            .expect("app state lookup");
+        let next_app_hash = if pruned_height + 1 == height {
+            Some(app_hash.to_vec())
+        } else {
+            self.db
+                .get(COL_APP_STATES, &i64::encode_var_vec(pruned_height + 1))
+                .expect("app state lookup")
+                .map(|hash| hash.to_vec())
+        };
+        if let (Some(pruned), Some(next)) = (pruned_app_hash, next_app_hash) {
+            if pruned[..] != next[..] {
+                dbtx.delete(COL_MERKLE_PROOFS, &pruned[..]);
+                dbtx.delete(COL_APP_STATE_ROOTS, &pruned[..]);
+            }
+        }
+    }
+
+    /// returns the version of the stored data format (0 if the data was stored before the format was versioned)
+    pub fn get_version(&self) -> u32 {
+        self.db
+            .get(COL_NODE_INFO, STORAGE_VERSION_KEY)
+            .expect("storage version lookup")
+            .map(|version| u32::decode(&mut &version[..]).expect("storage version"))
+            .unwrap_or(0)
+    }
+
+    fn is_fully_spent(&self, txid: &TxId) -> bool {
+        let meta = self.db.get(COL_TX_META, &txid[..]).expect("tx meta lookup");
+        let body = self.db.get(COL_BODIES, &txid[..]).expect("tx body lookup");
+        match (meta, body) {
+            (Some(meta), Some(body)) => {
+                let meta = TxMeta::decode(&mut &meta[..]).expect("tx meta");
+                let tx = TxWithOutputs::decode(&mut &body[..]).expect("tx body");
+                meta.is_fully_spent(tx.outputs().len())
+            }
+            _ => false,
+        }
     }
 }
@@ -1,7 +1,7 @@
 use crate::enclave_bridge::EnclaveProxy;
 use crate::storage::account::AccountStorage;
 use crate::storage::account::AccountWrapper;
-use crate::storage::{COL_BODIES, COL_TX_META};
+use crate::storage::{COL_BODIES, COL_PRUNED_TXS, COL_TX_META};
 use bit_vec::BitVec;
 use chain_core::state::account::{to_stake_key, StakedState, StakedStateAddress};
 use chain_core::tx::data::input::TxoPointer;
@@ -9,18 +9,57 @@ use chain_core::tx::fee::Fee;
 use chain_core::tx::TransactionId;
 use chain_core::tx::TxAux;
 use chain_tx_validation::{
-    verify_bonded_deposit, verify_unbonded_withdraw, verify_unbonding,
-    witness::verify_tx_recover_address, ChainInfo, Error, TxWithOutputs,
+    verify_bonded_deposit, verify_delegation, verify_node_join, verify_node_leave, verify_proposal,
+    verify_reward_claim, verify_unbonded_withdraw, verify_unbonding, verify_undelegation,
+    verify_unjailing, verify_vote, witness::verify_tx_recover_address, ChainInfo, CouncilInfo,
+    Error, TxInclusion, TxWithOutputs,
 };
 use enclave_protocol::{EnclaveRequest, EnclaveResponse};
 use kvdb::KeyValueDB;
-use parity_codec::Decode;
+use parity_codec::{Decode, Encode};
 use starling::constants::KEY_LEN;
 use std::sync::Arc;
 
 /// key type for looking up accounts/staked states in the merkle tree storage
 pub type StarlingFixedKey = [u8; KEY_LEN];
 
+/// UTXO metadata of a transaction with outputs (stored in `COL_TX_META`)
+#[derive(Encode, Decode)]
+pub struct TxMeta {
+    /// the block the transaction was included in (relative timelocks of its outputs count from it)
+    pub inclusion: TxInclusion,
+    /// BitVec bytes where each bit indicates whether the output was spent or not
+    spent: Vec<u8>,
+}
+
+impl TxMeta {
+    /// creates the metadata of a newly included transaction (i.e. with all outputs unspent)
+    pub fn new(inclusion: TxInclusion, no_of_outputs: usize) -> Self {
+        TxMeta {
+            inclusion,
+            spent: BitVec::from_elem(no_of_outputs, false).to_bytes(),
+        }
+    }
+
+    /// returns the spent flags of outputs (e.g. b[0] == true if output 0 was spent)
+    pub fn spent(&self) -> BitVec {
+        BitVec::from_bytes(&self.spent)
+    }
+
+    /// marks the output as spent
+    pub fn spend(&mut self, index: usize) {
+        let mut spent = self.spent();
+        spent.set(index, true);
+        self.spent = spent.to_bytes();
+    }
+
+    /// checks if all the transaction's outputs were spent
+    /// (the number of outputs is needed, as the stored flags are padded to whole bytes)
+    pub fn is_fully_spent(&self, no_of_outputs: usize) -> bool {
+        self.spent().iter().take(no_of_outputs).all(|spent| spent)
+    }
+}
+
 /// checks that the account can be retrieved from the trie storage
 pub fn get_account(
     account_address: &StakedStateAddress,
@@ -45,7 +84,7 @@ pub fn get_account(
 fn check_spent_input_lookup(
     inputs: &[TxoPointer],
     db: Arc<dyn KeyValueDB>,
-) -> Result<Vec<TxWithOutputs>, Error> {
+) -> Result<Vec<(TxWithOutputs, TxInclusion)>, Error> {
     // check that there are inputs
     if inputs.is_empty() {
         return Err(Error::NoInputs);
@@ -56,7 +95,8 @@ fn check_spent_input_lookup(
         match txo {
             Ok(Some(v)) => {
                 let input_index = txin.index as usize;
-                let bv = BitVec::from_bytes(&v).get(input_index);
+                let meta = TxMeta::decode(&mut v.to_vec().as_slice()).unwrap();
+                let bv = meta.spent().get(input_index);
                 if bv.is_none() {
                     return Err(Error::InvalidInput);
                 }
@@ -66,9 +106,13 @@ fn check_spent_input_lookup(
                 let txdata = db.get(COL_BODIES, &txin.id[..]).unwrap().unwrap().to_vec();
                 // only TxWithOutputs should have an entry in COL_TX_META
                 let tx = TxWithOutputs::decode(&mut txdata.as_slice()).unwrap();
-                result.push(tx);
+                result.push((tx, meta.inclusion));
             }
             Ok(None) => {
+                // the data of fully spent transactions may have been pruned
+                if let Ok(Some(_)) = db.get(COL_PRUNED_TXS, &txin.id[..]) {
+                    return Err(Error::InputSpent);
+                }
                 return Err(Error::InvalidInput);
             }
             Err(e) => {
@@ -85,6 +129,7 @@ pub fn verify<T: EnclaveProxy>(
     tx_validator: &T,
     txaux: &TxAux,
     extra_info: ChainInfo,
+    council_info: CouncilInfo,
     last_account_root_hash: &StarlingFixedKey,
     db: Arc<dyn KeyValueDB>,
     accounts: &AccountStorage,
@@ -99,7 +144,10 @@ pub fn verify<T: EnclaveProxy>(
                 inputs: input_transactions,
                 min_fee_computed: extra_info.min_fee_computed,
                 previous_block_time: extra_info.previous_block_time,
+                previous_block_height: extra_info.previous_block_height,
                 unbonding_period: extra_info.unbonding_period,
+                chain_id_hash: extra_info.chain_id_hash,
+                chain_id_hash_required: extra_info.chain_id_hash_required,
             });
             match response {
                 EnclaveResponse::VerifyTx(Ok(fee)) => (fee, None),
@@ -139,6 +187,73 @@ pub fn verify<T: EnclaveProxy>(
             let account = get_account(&account_address.unwrap(), last_account_root_hash, accounts)?;
             verify_unbonded_withdraw(maintx, extra_info, account)?
         }
+        TxAux::NodeJoinTx(maintx, witness) => {
+            let account_address = verify_tx_recover_address(&witness, &maintx.id());
+            if let Err(e) = account_address {
+                return Err(Error::EcdsaCrypto(e));
+            }
+            let account = get_account(&account_address.unwrap(), last_account_root_hash, accounts)?;
+            verify_node_join(maintx, extra_info, council_info, account)?
+        }
+        TxAux::UnjailTx(maintx, witness) => {
+            let account_address = verify_tx_recover_address(&witness, &maintx.id());
+            if let Err(e) = account_address {
+                return Err(Error::EcdsaCrypto(e));
+            }
+            let account = get_account(&account_address.unwrap(), last_account_root_hash, accounts)?;
+            verify_unjailing(maintx, extra_info, account)?
+        }
+        TxAux::NodeLeaveTx(maintx, witness) => {
+            let account_address = verify_tx_recover_address(&witness, &maintx.id());
+            if let Err(e) = account_address {
+                return Err(Error::EcdsaCrypto(e));
+            }
+            let account = get_account(&account_address.unwrap(), last_account_root_hash, accounts)?;
+            verify_node_leave(maintx, extra_info, council_info, account)?
+        }
+        TxAux::ProposalTx(maintx, witness) => {
+            let account_address = verify_tx_recover_address(&witness, &maintx.id());
+            if let Err(e) = account_address {
+                return Err(Error::EcdsaCrypto(e));
+            }
+            let account = get_account(&account_address.unwrap(), last_account_root_hash, accounts)?;
+            verify_proposal(maintx, extra_info, council_info, account)?
+        }
+        TxAux::VoteTx(maintx, witness) => {
+            let account_address = verify_tx_recover_address(&witness, &maintx.id());
+            if let Err(e) = account_address {
+                return Err(Error::EcdsaCrypto(e));
+            }
+            let account = get_account(&account_address.unwrap(), last_account_root_hash, accounts)?;
+            verify_vote(maintx, extra_info, council_info, account)?
+        }
+        TxAux::DelegateTx(maintx, witness) => {
+            let account_address = verify_tx_recover_address(&witness, &maintx.id());
+            if let Err(e) = account_address {
+                return Err(Error::EcdsaCrypto(e));
+            }
+            let account = get_account(&account_address.unwrap(), last_account_root_hash, accounts)?;
+            let council_node = get_account(&maintx.council_node, last_account_root_hash, accounts)?;
+            verify_delegation(maintx, extra_info, council_info, account, &council_node)?
+        }
+        TxAux::UndelegateTx(maintx, witness) => {
+            let account_address = verify_tx_recover_address(&witness, &maintx.id());
+            if let Err(e) = account_address {
+                return Err(Error::EcdsaCrypto(e));
+            }
+            let account = get_account(&account_address.unwrap(), last_account_root_hash, accounts)?;
+            let council_node = get_account(&maintx.council_node, last_account_root_hash, accounts)?;
+            verify_undelegation(maintx, extra_info, account, &council_node)?
+        }
+        TxAux::ClaimRewardsTx(maintx, witness) => {
+            // FIXME: move to the enclave side
+            let account_address = verify_tx_recover_address(&witness, &maintx.id());
+            if let Err(e) = account_address {
+                return Err(Error::EcdsaCrypto(e));
+            }
+            let account = get_account(&account_address.unwrap(), last_account_root_hash, accounts)?;
+            verify_reward_claim(maintx, extra_info, account)?
+        }
     };
     Ok(paid_fee)
 }
@@ -148,21 +263,29 @@ pub mod tests {
     use super::*;
     use crate::enclave_bridge::mock::MockClient;
     use crate::storage::{Storage, COL_TX_META, NUM_COLUMNS};
-    use chain_core::common::{MerkleTree, Timespec};
+    use chain_core::common::{MerkleTree, Timespec, H256};
     use chain_core::init::address::RedeemAddress;
     use chain_core::init::coin::{Coin, CoinError};
     use chain_core::state::account::StakedStateOpAttributes;
     use chain_core::state::account::{
-        DepositBondTx, StakedStateOpWitness, UnbondTx, WithdrawUnbondedTx,
+        ClaimRewardsTx, DelegateTx, DepositBondTx, NodeJoinRequestTx, NodeLeaveTx,
+        StakedStateOpWitness, UnbondTx, UndelegateTx, UnjailTx, WithdrawUnbondedTx,
+    };
+    use chain_core::state::governance::{
+        NetworkParameterChange, ParamChangeProposal, ParamChangeProposalTx, ParamChangeVoteTx,
     };
+    use chain_core::state::tendermint::TendermintValidatorPubKey;
+    use chain_core::state::vesting::{Vesting, VestingSchedule};
+    use chain_core::state::CouncilNode;
+    use chain_core::tx::data::htlc::{hash_lock, HashTimeLock};
     use chain_core::tx::data::{
         address::ExtendedAddr,
-        attribute::TxAttributes,
+        attribute::{TxAttributes, TxExpiry},
         input::{TxoIndex, TxoPointer},
-        output::TxOut,
+        output::{RelativeTimelock, TxOut},
     };
     use chain_core::tx::data::{Tx, TxId};
-    use chain_core::tx::fee::FeeAlgorithm;
+    use chain_core::tx::fee::{Fee, FeeAlgorithm};
     use chain_core::tx::fee::{LinearFee, Milli};
     use chain_core::tx::witness::tree::RawPubkey;
     use chain_core::tx::witness::{TxInWitness, TxWitness};
@@ -209,6 +332,15 @@ pub mod tests {
         MockClient::new(DEFAULT_CHAIN_ID)
     }
 
+    fn get_council_info() -> CouncilInfo<'static> {
+        CouncilInfo {
+            required_council_node_stake: Coin::unit(),
+            council_nodes: &[],
+            last_block_height: 0,
+            proposals: &[],
+        }
+    }
+
     fn get_old_tx(addr: ExtendedAddr, timelocked: bool) -> Tx {
         let mut old_tx = Tx::new();
 
@@ -220,6 +352,21 @@ pub mod tests {
         old_tx
     }
 
+    fn get_tx_inclusion() -> TxInclusion {
+        TxInclusion {
+            block_height: 0,
+            block_time: 0,
+        }
+    }
+
+    fn get_tx_meta(spent: bool) -> TxMeta {
+        let mut meta = TxMeta::new(get_tx_inclusion(), 1);
+        if spent {
+            meta.spend(0);
+        }
+        meta
+    }
+
     fn get_address<C: Signing>(
         secp: &Secp256k1<C>,
         secret_key: &SecretKey,
@@ -256,11 +403,7 @@ pub mod tests {
             &TxWithOutputs::Transfer(old_tx).encode(),
         );
 
-        inittx.put(
-            COL_TX_META,
-            &old_tx_id[..],
-            &BitVec::from_elem(1, false).to_bytes(),
-        );
+        inittx.put(COL_TX_META, &old_tx_id[..], &get_tx_meta(false).encode());
         db.write(inittx).unwrap();
         let txp = TxoPointer::new(old_tx_id, 0);
         (db, txp, addr, merkle_tree, secret_key)
@@ -333,13 +476,17 @@ pub mod tests {
                 .calculate_for_txaux(&txaux)
                 .expect("invalid fee policy"),
             chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
             previous_block_time: 0,
+            previous_block_height: 0,
             unbonding_period: 1,
         };
         let result = verify(
             &get_enclave_bridge_mock(),
             &txaux,
             extra_info,
+            get_council_info(),
             &last_account_root_hash,
             create_db(),
             &accounts,
@@ -357,7 +504,10 @@ pub mod tests {
                 .calculate_for_txaux(&txaux)
                 .expect("invalid fee policy"),
             chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
             previous_block_time: 0,
+            previous_block_height: 0,
             unbonding_period: 1,
         };
         let mock_bridge = get_enclave_bridge_mock();
@@ -369,6 +519,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -381,6 +532,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &[0; 32],
                 db.clone(),
                 &accounts,
@@ -399,6 +551,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -417,6 +570,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -435,6 +589,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -490,13 +645,17 @@ pub mod tests {
                 .calculate_for_txaux(&txaux)
                 .expect("invalid fee policy"),
             chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
             previous_block_time: 0,
+            previous_block_height: 0,
             unbonding_period: 1,
         };
         let result = verify(
             &get_enclave_bridge_mock(),
             &txaux,
             extra_info,
+            get_council_info(),
             &last_account_root_hash,
             create_db(),
             &accounts,
@@ -514,7 +673,10 @@ pub mod tests {
                 .calculate_for_txaux(&txaux)
                 .expect("invalid fee policy"),
             chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
             previous_block_time: 0,
+            previous_block_height: 0,
             unbonding_period: 1,
         };
         let mock_bridge = get_enclave_bridge_mock();
@@ -526,6 +688,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -542,6 +705,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -558,6 +722,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -578,6 +743,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -599,6 +765,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -611,6 +778,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &[0; 32],
                 db.clone(),
                 &accounts,
@@ -629,6 +797,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -647,6 +816,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -661,6 +831,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -669,6 +840,103 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_vesting_withdraw_verify() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+        let addr = RedeemAddress::from(&PublicKey::from_secret_key(&secp, &secret_key));
+        let mut account =
+            StakedState::new(1, Coin::zero(), Coin::new(100).unwrap(), 0, addr.into());
+        account.vesting = Some(Vesting::new(
+            Coin::new(100).unwrap(),
+            VestingSchedule::Linear {
+                start_time: 0,
+                end_time: 100,
+            },
+        ));
+        let outputs = vec![TxOut::new_with_timelock(
+            get_address(&secp, &secret_key).0,
+            Coin::new(40).unwrap(),
+            0,
+        )];
+        let tx = WithdrawUnbondedTx::new(1, outputs, TxAttributes::new(DEFAULT_CHAIN_ID));
+        let mut extra_info = ChainInfo {
+            min_fee_computed: Fee::new(Coin::zero()),
+            chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
+            previous_block_time: 0,
+            previous_block_height: 0,
+            unbonding_period: 1,
+        };
+        // nothing vested yet
+        let result = verify_unbonded_withdraw(&tx, extra_info, account.clone());
+        expect_error(&result, Error::AccountVestingLocked);
+        // not enough vested
+        extra_info.previous_block_time = 30;
+        let result = verify_unbonded_withdraw(&tx, extra_info, account.clone());
+        expect_error(&result, Error::InputOutputDoNotMatch);
+        // the part that isn't vested yet remains unbonded
+        extra_info.previous_block_time = 50;
+        let (fee, account) = verify_unbonded_withdraw(&tx, extra_info, account).unwrap();
+        assert_eq!(fee.to_coin(), Coin::new(10).unwrap());
+        assert_eq!(account.unwrap().unbonded, Coin::new(50).unwrap());
+    }
+
+    #[test]
+    fn test_multiple_unbondings_withdraw_verify() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+        let addr = RedeemAddress::from(&PublicKey::from_secret_key(&secp, &secret_key));
+        let account = StakedState::new(0, Coin::new(100).unwrap(), Coin::zero(), 0, addr.into());
+        let mut extra_info = ChainInfo {
+            min_fee_computed: Fee::new(Coin::zero()),
+            chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
+            previous_block_time: 0,
+            previous_block_height: 0,
+            unbonding_period: 10,
+        };
+        let unbond = |nonce, extra_info, account| {
+            let tx = UnbondTx::new(
+                Coin::new(30).unwrap(),
+                nonce,
+                StakedStateOpAttributes::new(DEFAULT_CHAIN_ID),
+            );
+            verify_unbonding(&tx, extra_info, account)
+                .unwrap()
+                .1
+                .unwrap()
+        };
+        let account = unbond(0, extra_info, account);
+        extra_info.previous_block_time = 5;
+        // the later unbonding doesn't postpone the earlier one
+        let account = unbond(1, extra_info, account);
+        assert_eq!(account.unbonded, Coin::new(60).unwrap());
+        assert_eq!(account.unbonding.len(), 2);
+        assert_eq!(account.last_matured(12), Some(10));
+
+        let withdraw_tx = |matured_at| {
+            let outputs = vec![TxOut::new_with_timelock(
+                get_address(&secp, &secret_key).0,
+                Coin::new(30).unwrap(),
+                matured_at,
+            )];
+            WithdrawUnbondedTx::new(2, outputs, TxAttributes::new(DEFAULT_CHAIN_ID))
+        };
+        extra_info.previous_block_time = 12;
+        // the second unbonding isn't matured yet
+        let result = verify_unbonded_withdraw(&withdraw_tx(15), extra_info, account.clone());
+        expect_error(&result, Error::AccountNotUnbonded);
+        // the first one is withdrawn, the second one remains
+        let (_, account) = verify_unbonded_withdraw(&withdraw_tx(10), extra_info, account).unwrap();
+        let account = account.unwrap();
+        assert_eq!(account.unbonded, Coin::new(30).unwrap());
+        assert_eq!(account.last_matured(12), None);
+        assert_eq!(account.last_matured(15), Some(15));
+    }
+
     fn prepare_app_valid_deposit_tx(
         timelocked: bool,
     ) -> (
@@ -701,6 +969,7 @@ pub mod tests {
     }
 
     const DEFAULT_CHAIN_ID: u8 = 0;
+    const DEFAULT_CHAIN_ID_HASH: H256 = [0xcc; 32];
 
     #[test]
     fn existing_utxo_input_tx_should_verify() {
@@ -717,7 +986,10 @@ pub mod tests {
                 .calculate_for_txaux(&txaux)
                 .expect("invalid fee policy"),
             chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
             previous_block_time: 0,
+            previous_block_height: 0,
             unbonding_period: 1,
         };
         let mock_bridge = get_enclave_bridge_mock();
@@ -726,6 +998,7 @@ pub mod tests {
             &mock_bridge,
             &txaux,
             extra_info,
+            get_council_info(),
             &last_account_root_hash,
             db,
             &accounts,
@@ -736,6 +1009,7 @@ pub mod tests {
             &mock_bridge,
             &txaux,
             extra_info,
+            get_council_info(),
             &last_account_root_hash,
             db,
             &accounts,
@@ -743,6 +1017,219 @@ pub mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_hash_time_lock_verify() {
+        let secp = Secp256k1::new();
+        let recipient_key =
+            SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+        let refund_key = SecretKey::from_slice(&[0xde; 32]).expect("32 bytes, within curve order");
+        let preimage = [0xab; 32];
+        let lock = HashTimeLock::new(
+            hash_lock(&preimage),
+            RedeemAddress::from(&PublicKey::from_secret_key(&secp, &recipient_key)),
+            RedeemAddress::from(&PublicKey::from_secret_key(&secp, &refund_key)),
+            10,
+        );
+        let mut old_tx = Tx::new();
+        old_tx.add_output(TxOut::new(lock.address(), Coin::one()));
+        let mut tx = Tx::new();
+        tx.add_input(TxoPointer::new(old_tx.id(), 0));
+        tx.add_output(TxOut::new(
+            get_address(&secp, &recipient_key).0,
+            Coin::one(),
+        ));
+        let message = Message::from_slice(&tx.id()).unwrap();
+        let extra_info = ChainInfo {
+            min_fee_computed: Fee::new(Coin::zero()),
+            chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
+            previous_block_time: 0,
+            previous_block_height: 0,
+            unbonding_period: 1,
+        };
+        // redeemed by the recipient with the preimage
+        {
+            let witness: TxWitness = vec![TxInWitness::HashTimeLock(
+                lock.clone(),
+                Some(preimage),
+                secp.sign_recoverable(&message, &recipient_key),
+            )]
+            .into();
+            let result = verify_transfer(
+                &tx,
+                &witness,
+                extra_info,
+                vec![(TxWithOutputs::Transfer(old_tx.clone()), get_tx_inclusion())],
+            );
+            assert!(result.is_ok());
+        }
+        // refunded before and after the timeout
+        {
+            let witness: TxWitness = vec![TxInWitness::HashTimeLock(
+                lock.clone(),
+                None,
+                secp.sign_recoverable(&message, &refund_key),
+            )]
+            .into();
+            let result = verify_transfer(
+                &tx,
+                &witness,
+                extra_info,
+                vec![(TxWithOutputs::Transfer(old_tx.clone()), get_tx_inclusion())],
+            );
+            expect_error(&result, Error::OutputInTimelock);
+            let mut extra_info = extra_info.clone();
+            extra_info.previous_block_time = 10;
+            let result = verify_transfer(
+                &tx,
+                &witness,
+                extra_info,
+                vec![(TxWithOutputs::Transfer(old_tx.clone()), get_tx_inclusion())],
+            );
+            assert!(result.is_ok());
+        }
+    }
+
+    #[test]
+    fn test_relative_timelock_verify() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+        let (addr, merkle_tree) = get_address(&secp, &secret_key);
+        let inclusion = TxInclusion {
+            block_height: 5,
+            block_time: 100,
+        };
+        for relative_lock in [RelativeTimelock::Seconds(10), RelativeTimelock::Blocks(2)].iter() {
+            let mut old_tx = Tx::new();
+            old_tx.add_output(TxOut::new_with_relative_timelock(
+                addr.clone(),
+                Coin::one(),
+                *relative_lock,
+            ));
+            let mut tx = Tx::new();
+            tx.add_input(TxoPointer::new(old_tx.id(), 0));
+            tx.add_output(TxOut::new(addr.clone(), Coin::one()));
+            let witness: TxWitness = vec![get_tx_witness(
+                secp.clone(),
+                &tx.id(),
+                &secret_key,
+                &merkle_tree,
+            )]
+            .into();
+            let mut extra_info = ChainInfo {
+                min_fee_computed: Fee::new(Coin::zero()),
+                chain_hex_id: DEFAULT_CHAIN_ID,
+                chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+                chain_id_hash_required: false,
+                previous_block_time: 109,
+                previous_block_height: 6,
+                unbonding_period: 1,
+            };
+            let result = verify_transfer(
+                &tx,
+                &witness,
+                extra_info,
+                vec![(TxWithOutputs::Transfer(old_tx.clone()), inclusion)],
+            );
+            expect_error(&result, Error::OutputInTimelock);
+            extra_info.previous_block_time = 110;
+            extra_info.previous_block_height = 7;
+            let result = verify_transfer(
+                &tx,
+                &witness,
+                extra_info,
+                vec![(TxWithOutputs::Transfer(old_tx.clone()), inclusion)],
+            );
+            assert!(result.is_ok());
+        }
+    }
+
+    #[test]
+    fn test_tx_expiry_verify() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+        let (addr, merkle_tree) = get_address(&secp, &secret_key);
+        let mut old_tx = Tx::new();
+        old_tx.add_output(TxOut::new(addr.clone(), Coin::one()));
+        let mut tx = Tx::new();
+        tx.add_input(TxoPointer::new(old_tx.id(), 0));
+        tx.add_output(TxOut::new(addr.clone(), Coin::one()));
+        tx.attributes = TxAttributes::new(DEFAULT_CHAIN_ID).with_expiry(TxExpiry::Height(7));
+        let witness: TxWitness = vec![get_tx_witness(
+            secp.clone(),
+            &tx.id(),
+            &secret_key,
+            &merkle_tree,
+        )]
+        .into();
+        let mut extra_info = ChainInfo {
+            min_fee_computed: Fee::new(Coin::zero()),
+            chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
+            previous_block_time: 0,
+            previous_block_height: 6,
+            unbonding_period: 1,
+        };
+        let inputs = || vec![(TxWithOutputs::Transfer(old_tx.clone()), get_tx_inclusion())];
+        assert!(verify_transfer(&tx, &witness, extra_info, inputs()).is_ok());
+        extra_info.previous_block_height = 7;
+        let result = verify_transfer(&tx, &witness, extra_info, inputs());
+        expect_error(&result, Error::TxExpired);
+
+        let staking_address = RedeemAddress::from(&PublicKey::from_secret_key(&secp, &secret_key));
+        let account = StakedState::new(1, Coin::one(), Coin::zero(), 0, staking_address.into());
+        let unbond_tx = UnbondTx::new(
+            Coin::one(),
+            1,
+            StakedStateOpAttributes::new(DEFAULT_CHAIN_ID).with_expiry(TxExpiry::Time(100)),
+        );
+        extra_info.previous_block_time = 99;
+        assert!(verify_unbonding(&unbond_tx, extra_info, account.clone()).is_ok());
+        extra_info.previous_block_time = 100;
+        let result = verify_unbonding(&unbond_tx, extra_info, account);
+        expect_error(&result, Error::TxExpired);
+    }
+
+    #[test]
+    fn test_chain_id_binding_verify() {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+        let staking_address = RedeemAddress::from(&PublicKey::from_secret_key(&secp, &secret_key));
+        let account = StakedState::new(1, Coin::one(), Coin::zero(), 0, staking_address.into());
+        let extra_info = ChainInfo {
+            min_fee_computed: Fee::new(Coin::zero()),
+            chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
+            previous_block_time: 0,
+            previous_block_height: 0,
+            unbonding_period: 1,
+        };
+        let mut attributes = StakedStateOpAttributes::new(DEFAULT_CHAIN_ID);
+        attributes.chain_id_hash = Some(DEFAULT_CHAIN_ID_HASH);
+        let tx = UnbondTx::new(Coin::one(), 1, attributes.clone());
+        assert!(verify_unbonding(&tx, extra_info, account.clone()).is_ok());
+
+        // same chain hex ID, but a different full chain ID
+        attributes.chain_id_hash = Some([0xdd; 32]);
+        let tx = UnbondTx::new(Coin::one(), 1, attributes.clone());
+        let result = verify_unbonding(&tx, extra_info, account.clone());
+        expect_error(&result, Error::WrongChainId);
+
+        // the full chain ID is optional until it's required
+        attributes.chain_id_hash = None;
+        let tx = UnbondTx::new(Coin::one(), 1, attributes);
+        assert!(verify_unbonding(&tx, extra_info, account.clone()).is_ok());
+        let extra_info = ChainInfo {
+            chain_id_hash_required: true,
+            ..extra_info
+        };
+        let result = verify_unbonding(&tx, extra_info, account);
+        expect_error(&result, Error::MissingChainId);
+    }
+
     fn expect_error<T, Error>(res: &Result<T, Error>, expected: Error)
     where
         Error: Debug,
@@ -762,7 +1249,10 @@ pub mod tests {
                 .calculate_for_txaux(&txaux)
                 .expect("invalid fee policy"),
             chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
             previous_block_time: 0,
+            previous_block_height: 0,
             unbonding_period: 1,
         };
         let mock_bridge = get_enclave_bridge_mock();
@@ -775,6 +1265,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -790,6 +1281,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -806,6 +1298,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -822,6 +1315,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -835,6 +1329,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -847,7 +1342,7 @@ pub mod tests {
             inittx.put(
                 COL_TX_META,
                 &tx.inputs[0].id[..],
-                &BitVec::from_elem(1, true).to_bytes(),
+                &get_tx_meta(true).encode(),
             );
             db.write(inittx).unwrap();
 
@@ -855,6 +1350,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -865,7 +1361,7 @@ pub mod tests {
             reset.put(
                 COL_TX_META,
                 &tx.inputs[0].id[..],
-                &BitVec::from_elem(1, false).to_bytes(),
+                &get_tx_meta(false).encode(),
             );
             db.write(reset).unwrap();
         }
@@ -890,6 +1386,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -905,6 +1402,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 create_db(),
                 &accounts,
@@ -919,6 +1417,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -959,7 +1458,10 @@ pub mod tests {
                 .calculate_for_txaux(&txaux)
                 .expect("invalid fee policy"),
             chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
             previous_block_time: 0,
+            previous_block_height: 0,
             unbonding_period: 1,
         };
         let mock_bridge = get_enclave_bridge_mock();
@@ -972,6 +1474,7 @@ pub mod tests {
                 &MockClient::new(DEFAULT_CHAIN_ID + 1),
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -990,6 +1493,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -1008,6 +1512,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -1027,6 +1532,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -1045,6 +1551,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -1064,6 +1571,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -1080,6 +1588,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -1106,6 +1615,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -1118,7 +1628,7 @@ pub mod tests {
             inittx.put(
                 COL_TX_META,
                 &tx.inputs[0].id[..],
-                &BitVec::from_elem(1, true).to_bytes(),
+                &get_tx_meta(true).encode(),
             );
             db.write(inittx).unwrap();
 
@@ -1126,6 +1636,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -1136,7 +1647,7 @@ pub mod tests {
             reset.put(
                 COL_TX_META,
                 &tx.inputs[0].id[..],
-                &BitVec::from_elem(1, false).to_bytes(),
+                &get_tx_meta(false).encode(),
             );
             db.write(reset).unwrap();
         }
@@ -1163,7 +1674,7 @@ pub mod tests {
                 &tx,
                 &witness,
                 extra_info,
-                vec![TxWithOutputs::Transfer(input_tx)],
+                vec![(TxWithOutputs::Transfer(input_tx), get_tx_inclusion())],
             );
             expect_error(
                 &result,
@@ -1175,6 +1686,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -1187,6 +1699,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 create_db(),
                 &accounts,
@@ -1207,6 +1720,7 @@ pub mod tests {
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -1230,13 +1744,14 @@ pub mod tests {
                 &tx,
                 &witness,
                 extra_info,
-                vec![TxWithOutputs::Transfer(input_tx)],
+                vec![(TxWithOutputs::Transfer(input_tx), get_tx_inclusion())],
             );
             expect_error(&result, Error::OutputInTimelock);
             let result = verify(
                 &mock_bridge,
                 &txaux,
                 extra_info,
+                get_council_info(),
                 &last_account_root_hash,
                 db.clone(),
                 &accounts,
@@ -1244,4 +1759,826 @@ pub mod tests {
             assert!(result.is_err());
         }
     }
+
+    fn prepare_app_valid_council_account(
+        jailed_until: Option<Timespec>,
+    ) -> (
+        StakedStateAddress,
+        SecretKey,
+        AccountStorage,
+        StarlingFixedKey,
+    ) {
+        let mut tree = AccountStorage::new(Storage::new_db(create_db()), 20).expect("account db");
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+        let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+
+        let addr = RedeemAddress::from(&public_key);
+        let mut account = StakedState::new(1, Coin::one(), Coin::zero(), 0, addr.into());
+        account.jailed_until = jailed_until;
+        let key = account.key();
+        let wrapped = AccountWrapper(account);
+        let new_root = tree
+            .insert(None, &mut [&key], &mut vec![&wrapped])
+            .expect("insert");
+        (addr.into(), secret_key, tree, new_root)
+    }
+
+    fn get_node_join_txaux(nonce: u64, secret_key: &SecretKey) -> TxAux {
+        let tx = NodeJoinRequestTx::new(
+            nonce,
+            TendermintValidatorPubKey::Ed25519([0xaa; 32]),
+            StakedStateOpAttributes::new(DEFAULT_CHAIN_ID),
+        );
+        let witness = get_account_op_witness(Secp256k1::new(), &tx.id(), secret_key);
+        TxAux::NodeJoinTx(tx, witness)
+    }
+
+    fn get_chain_info(txaux: &TxAux, previous_block_time: Timespec) -> ChainInfo {
+        ChainInfo {
+            min_fee_computed: LinearFee::new(Milli::new(1, 1), Milli::new(1, 1))
+                .calculate_for_txaux(&txaux)
+                .expect("invalid fee policy"),
+            chain_hex_id: DEFAULT_CHAIN_ID,
+            chain_id_hash: DEFAULT_CHAIN_ID_HASH,
+            chain_id_hash_required: false,
+            previous_block_time,
+            previous_block_height: 0,
+            unbonding_period: 1,
+        }
+    }
+
+    #[test]
+    fn node_join_tx_should_verify() {
+        let (_, secret_key, accounts, last_account_root_hash) =
+            prepare_app_valid_council_account(None);
+        let txaux = get_node_join_txaux(1, &secret_key);
+        let result = verify(
+            &get_enclave_bridge_mock(),
+            &txaux,
+            get_chain_info(&txaux, 0),
+            get_council_info(),
+            &last_account_root_hash,
+            create_db(),
+            &accounts,
+        );
+        let (fee, account) = result.expect("valid node join");
+        let account = account.expect("updated account");
+        assert_eq!(2, account.nonce);
+        assert_eq!((Coin::one() - fee.to_coin()).unwrap(), account.bonded);
+    }
+
+    #[test]
+    fn test_node_join_verify_fail() {
+        let db = create_db();
+        let mock_bridge = get_enclave_bridge_mock();
+        let (address, secret_key, accounts, last_account_root_hash) =
+            prepare_app_valid_council_account(None);
+        let txaux = get_node_join_txaux(1, &secret_key);
+        let extra_info = get_chain_info(&txaux, 0);
+        // AccountIncorrectNonce
+        {
+            let txaux = get_node_join_txaux(0, &secret_key);
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                extra_info,
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::AccountIncorrectNonce);
+        }
+        // DuplicateCouncilNode
+        {
+            let nodes = [
+                CouncilNode::new(address, TendermintValidatorPubKey::Ed25519([0xbb; 32])),
+                CouncilNode::new(
+                    StakedStateAddress::BasicRedeem(RedeemAddress::default()),
+                    TendermintValidatorPubKey::Ed25519([0xaa; 32]),
+                ),
+            ];
+            for node in nodes.iter() {
+                let council_info = CouncilInfo {
+                    required_council_node_stake: Coin::unit(),
+                    council_nodes: &[node.clone()],
+                    last_block_height: 0,
+                    proposals: &[],
+                };
+                let result = verify(
+                    &mock_bridge,
+                    &txaux,
+                    extra_info,
+                    council_info,
+                    &last_account_root_hash,
+                    db.clone(),
+                    &accounts,
+                );
+                expect_error(&result, Error::DuplicateCouncilNode);
+            }
+        }
+        // AccountNotEnoughCouncilNodeStake
+        {
+            let council_info = CouncilInfo {
+                required_council_node_stake: Coin::one(),
+                council_nodes: &[],
+                last_block_height: 0,
+                proposals: &[],
+            };
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                extra_info,
+                council_info,
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::AccountNotEnoughCouncilNodeStake);
+        }
+        // AccountJailed
+        {
+            let (_, secret_key, accounts, last_account_root_hash) =
+                prepare_app_valid_council_account(Some(10));
+            let txaux = get_node_join_txaux(1, &secret_key);
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                extra_info,
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::AccountJailed);
+        }
+    }
+
+    #[test]
+    fn test_unjail_verify() {
+        let db = create_db();
+        let mock_bridge = get_enclave_bridge_mock();
+        let (_, secret_key, accounts, last_account_root_hash) =
+            prepare_app_valid_council_account(Some(10));
+        let tx = UnjailTx::new(1, StakedStateOpAttributes::new(DEFAULT_CHAIN_ID));
+        let witness = get_account_op_witness(Secp256k1::new(), &tx.id(), &secret_key);
+        let txaux = TxAux::UnjailTx(tx, witness);
+        // AccountJailed
+        {
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 9),
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::AccountJailed);
+        }
+        // AccountNotJailed
+        {
+            let (_, secret_key, accounts, last_account_root_hash) =
+                prepare_app_valid_council_account(None);
+            let tx = UnjailTx::new(1, StakedStateOpAttributes::new(DEFAULT_CHAIN_ID));
+            let witness = get_account_op_witness(Secp256k1::new(), &tx.id(), &secret_key);
+            let txaux = TxAux::UnjailTx(tx, witness);
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 10),
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::AccountNotJailed);
+        }
+        let result = verify(
+            &mock_bridge,
+            &txaux,
+            get_chain_info(&txaux, 10),
+            get_council_info(),
+            &last_account_root_hash,
+            db.clone(),
+            &accounts,
+        );
+        let account = result.expect("valid unjail").1.expect("updated account");
+        assert_eq!(None, account.jailed_until);
+        assert_eq!(2, account.nonce);
+    }
+
+    #[test]
+    fn test_node_leave_verify() {
+        let db = create_db();
+        let mock_bridge = get_enclave_bridge_mock();
+        let (address, secret_key, accounts, last_account_root_hash) =
+            prepare_app_valid_council_account(None);
+        let tx = NodeLeaveTx::new(1, StakedStateOpAttributes::new(DEFAULT_CHAIN_ID));
+        let witness = get_account_op_witness(Secp256k1::new(), &tx.id(), &secret_key);
+        let txaux = TxAux::NodeLeaveTx(tx, witness);
+        // NotCouncilNode
+        {
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::NotCouncilNode);
+        }
+        let nodes = [CouncilNode::new(
+            address,
+            TendermintValidatorPubKey::Ed25519([0xaa; 32]),
+        )];
+        let council_info = CouncilInfo {
+            required_council_node_stake: Coin::unit(),
+            council_nodes: &nodes,
+            last_block_height: 0,
+            proposals: &[],
+        };
+        let result = verify(
+            &mock_bridge,
+            &txaux,
+            get_chain_info(&txaux, 0),
+            council_info,
+            &last_account_root_hash,
+            db.clone(),
+            &accounts,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_proposal_verify() {
+        let db = create_db();
+        let mock_bridge = get_enclave_bridge_mock();
+        let (address, secret_key, accounts, last_account_root_hash) =
+            prepare_app_valid_council_account(None);
+        let get_proposal_txaux = |activation_height| {
+            let tx = ParamChangeProposalTx::new(
+                1,
+                NetworkParameterChange::UnbondingPeriod(10),
+                activation_height,
+                StakedStateOpAttributes::new(DEFAULT_CHAIN_ID),
+            );
+            let witness = get_account_op_witness(Secp256k1::new(), &tx.id(), &secret_key);
+            TxAux::ProposalTx(tx, witness)
+        };
+        let txaux = get_proposal_txaux(7);
+        // NotCouncilNode
+        {
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::NotCouncilNode);
+        }
+        let nodes = [CouncilNode::new(
+            address,
+            TendermintValidatorPubKey::Ed25519([0xaa; 32]),
+        )];
+        let council_info = CouncilInfo {
+            required_council_node_stake: Coin::unit(),
+            council_nodes: &nodes,
+            last_block_height: 5,
+            proposals: &[],
+        };
+        // InvalidActivationHeight
+        {
+            let txaux = get_proposal_txaux(6);
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                council_info,
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::InvalidActivationHeight);
+        }
+        let result = verify(
+            &mock_bridge,
+            &txaux,
+            get_chain_info(&txaux, 0),
+            council_info,
+            &last_account_root_hash,
+            db.clone(),
+            &accounts,
+        );
+        let (fee, account) = result.expect("valid proposal");
+        let account = account.expect("updated account");
+        assert_eq!(2, account.nonce);
+        assert_eq!((Coin::one() - fee.to_coin()).unwrap(), account.bonded);
+    }
+
+    #[test]
+    fn test_vote_verify() {
+        let db = create_db();
+        let mock_bridge = get_enclave_bridge_mock();
+        let (address, secret_key, accounts, last_account_root_hash) =
+            prepare_app_valid_council_account(None);
+        let tx = ParamChangeVoteTx::new(
+            1,
+            [0xcc; 32],
+            true,
+            StakedStateOpAttributes::new(DEFAULT_CHAIN_ID),
+        );
+        let witness = get_account_op_witness(Secp256k1::new(), &tx.id(), &secret_key);
+        let txaux = TxAux::VoteTx(tx, witness);
+        let nodes = [CouncilNode::new(
+            address,
+            TendermintValidatorPubKey::Ed25519([0xaa; 32]),
+        )];
+        let proposals = [ParamChangeProposal::new(
+            [0xcc; 32],
+            address,
+            NetworkParameterChange::UnbondingPeriod(10),
+            7,
+        )];
+        // ProposalNotFound
+        for (proposals, last_block_height) in [(&[][..], 0), (&proposals[..], 6)].iter() {
+            let council_info = CouncilInfo {
+                required_council_node_stake: Coin::unit(),
+                council_nodes: &nodes,
+                last_block_height: *last_block_height,
+                proposals,
+            };
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                council_info,
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::ProposalNotFound);
+        }
+        let council_info = CouncilInfo {
+            required_council_node_stake: Coin::unit(),
+            council_nodes: &nodes,
+            last_block_height: 5,
+            proposals: &proposals,
+        };
+        let result = verify(
+            &mock_bridge,
+            &txaux,
+            get_chain_info(&txaux, 0),
+            council_info,
+            &last_account_root_hash,
+            db.clone(),
+            &accounts,
+        );
+        assert!(result.is_ok());
+    }
+
+    fn get_delegation_txaux(
+        undelegate: bool,
+        nonce: u64,
+        council_node: StakedStateAddress,
+        value: Coin,
+        secret_key: &SecretKey,
+    ) -> TxAux {
+        let attributes = StakedStateOpAttributes::new(DEFAULT_CHAIN_ID);
+        if undelegate {
+            let tx = UndelegateTx::new(value, nonce, council_node, attributes);
+            let witness = get_account_op_witness(Secp256k1::new(), &tx.id(), secret_key);
+            TxAux::UndelegateTx(tx, witness)
+        } else {
+            let tx = DelegateTx::new(value, nonce, council_node, attributes);
+            let witness = get_account_op_witness(Secp256k1::new(), &tx.id(), secret_key);
+            TxAux::DelegateTx(tx, witness)
+        }
+    }
+
+    fn insert_council_node_account(
+        accounts: &mut AccountStorage,
+        last_account_root_hash: &StarlingFixedKey,
+        council_node: StakedStateAddress,
+        delegated: Coin,
+    ) -> StarlingFixedKey {
+        let mut account = StakedState::new(0, Coin::unit(), Coin::zero(), 0, council_node);
+        account.add_delegated(delegated, u64::from(delegated));
+        let key = account.key();
+        accounts
+            .insert(
+                Some(last_account_root_hash),
+                &mut [&key],
+                &mut vec![&AccountWrapper(account)],
+            )
+            .expect("insert")
+    }
+
+    #[test]
+    fn test_delegation_verify() {
+        let db = create_db();
+        let mock_bridge = get_enclave_bridge_mock();
+        let (address, secret_key, mut accounts, last_account_root_hash) =
+            prepare_app_valid_council_account(None);
+        let council_node = StakedStateAddress::BasicRedeem(RedeemAddress::default());
+        let last_account_root_hash = insert_council_node_account(
+            &mut accounts,
+            &last_account_root_hash,
+            council_node,
+            Coin::zero(),
+        );
+        let value = Coin::new(1000).unwrap();
+        let txaux = get_delegation_txaux(false, 1, council_node, value, &secret_key);
+        // NotCouncilNode
+        {
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::NotCouncilNode);
+        }
+        let nodes = [CouncilNode::new(
+            council_node,
+            TendermintValidatorPubKey::Ed25519([0xaa; 32]),
+        )];
+        let council_info = CouncilInfo {
+            required_council_node_stake: Coin::unit(),
+            council_nodes: &nodes,
+            last_block_height: 0,
+            proposals: &[],
+        };
+        // ZeroCoin
+        {
+            let txaux = get_delegation_txaux(false, 1, council_node, Coin::zero(), &secret_key);
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                council_info,
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::ZeroCoin);
+        }
+        // SelfDelegation
+        {
+            let nodes = [
+                nodes[0].clone(),
+                CouncilNode::new(address, TendermintValidatorPubKey::Ed25519([0xbb; 32])),
+            ];
+            let txaux = get_delegation_txaux(false, 1, address, value, &secret_key);
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                CouncilInfo {
+                    council_nodes: &nodes,
+                    ..council_info
+                },
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::SelfDelegation);
+        }
+        // InputOutputDoNotMatch
+        {
+            let txaux = get_delegation_txaux(false, 1, council_node, Coin::one(), &secret_key);
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                council_info,
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::InputOutputDoNotMatch);
+        }
+        let result = verify(
+            &mock_bridge,
+            &txaux,
+            get_chain_info(&txaux, 0),
+            council_info,
+            &last_account_root_hash,
+            db.clone(),
+            &accounts,
+        );
+        let (fee, account) = result.expect("valid delegation");
+        let account = account.expect("updated account");
+        assert_eq!(2, account.nonce);
+        assert_eq!(
+            ((Coin::one() - value).unwrap() - fee.to_coin()).unwrap(),
+            account.bonded
+        );
+        assert_eq!(
+            u64::from(value),
+            account.delegation_shares_to(&council_node)
+        );
+        assert_eq!(Coin::zero(), account.delegated);
+
+        // CouncilNodeJailed
+        {
+            let mut node = get_account(&council_node, &last_account_root_hash, &accounts).unwrap();
+            node.jailed_until = Some(0);
+            let key = node.key();
+            let jailed_root = accounts
+                .insert(
+                    Some(&last_account_root_hash),
+                    &mut [&key],
+                    &mut vec![&AccountWrapper(node)],
+                )
+                .expect("insert");
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                council_info,
+                &jailed_root,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::CouncilNodeJailed);
+        }
+
+        // DelegationSlashed
+        let mut node = get_account(&council_node, &last_account_root_hash, &accounts).unwrap();
+        node.add_delegated(value, u64::from(value));
+        node.slash(Coin::zero(), value, 0);
+        let key = node.key();
+        let last_account_root_hash = accounts
+            .insert(
+                Some(&last_account_root_hash),
+                &mut [&key],
+                &mut vec![&AccountWrapper(node)],
+            )
+            .expect("insert");
+        let result = verify(
+            &mock_bridge,
+            &txaux,
+            get_chain_info(&txaux, 0),
+            council_info,
+            &last_account_root_hash,
+            db.clone(),
+            &accounts,
+        );
+        expect_error(&result, Error::DelegationSlashed);
+    }
+
+    #[test]
+    fn test_undelegation_verify() {
+        let db = create_db();
+        let mock_bridge = get_enclave_bridge_mock();
+        let (address, secret_key, mut accounts, last_account_root_hash) =
+            prepare_app_valid_council_account(None);
+        let council_node = StakedStateAddress::BasicRedeem(RedeemAddress::default());
+        let value = Coin::new(1000).unwrap();
+        let last_account_root_hash = insert_council_node_account(
+            &mut accounts,
+            &last_account_root_hash,
+            council_node,
+            Coin::zero(),
+        );
+        // DelegationNotFound
+        {
+            let txaux = get_delegation_txaux(true, 1, council_node, value, &secret_key);
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::DelegationNotFound);
+        }
+        let mut account = get_account(&address, &last_account_root_hash, &accounts).unwrap();
+        account.delegate(value, Coin::zero(), council_node, u64::from(value));
+        let key = account.key();
+        let last_account_root_hash = accounts
+            .insert(
+                Some(&last_account_root_hash),
+                &mut [&key],
+                &mut vec![&AccountWrapper(account)],
+            )
+            .expect("insert");
+        let last_account_root_hash = insert_council_node_account(
+            &mut accounts,
+            &last_account_root_hash,
+            council_node,
+            value,
+        );
+        // DelegationNotFound (more than delegated)
+        {
+            let txaux = get_delegation_txaux(
+                true,
+                2,
+                council_node,
+                (value + Coin::unit()).unwrap(),
+                &secret_key,
+            );
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::DelegationNotFound);
+        }
+        // the council node may have already left the council
+        let txaux = get_delegation_txaux(true, 2, council_node, value, &secret_key);
+        let result = verify(
+            &mock_bridge,
+            &txaux,
+            get_chain_info(&txaux, 5),
+            get_council_info(),
+            &last_account_root_hash,
+            db.clone(),
+            &accounts,
+        );
+        let (fee, account) = result.expect("valid undelegation");
+        let account = account.expect("updated account");
+        assert_eq!(3, account.nonce);
+        assert!(account.delegations.is_empty());
+        assert_eq!((value - fee.to_coin()).unwrap(), account.unbonded);
+        assert_eq!(6, account.unbonding[0].unbonded_from);
+
+        // the council node was slashed: its delegators' shares are worth half as much
+        let half = Coin::new(500).unwrap();
+        let mut node = get_account(&council_node, &last_account_root_hash, &accounts).unwrap();
+        node.slash(Coin::zero(), half, 0);
+        let key = node.key();
+        let last_account_root_hash = accounts
+            .insert(
+                Some(&last_account_root_hash),
+                &mut [&key],
+                &mut vec![&AccountWrapper(node)],
+            )
+            .expect("insert");
+        let result = verify(
+            &mock_bridge,
+            &txaux,
+            get_chain_info(&txaux, 5),
+            get_council_info(),
+            &last_account_root_hash,
+            db.clone(),
+            &accounts,
+        );
+        expect_error(&result, Error::DelegationNotFound);
+        let txaux = get_delegation_txaux(true, 2, council_node, half, &secret_key);
+        let result = verify(
+            &mock_bridge,
+            &txaux,
+            get_chain_info(&txaux, 5),
+            get_council_info(),
+            &last_account_root_hash,
+            db.clone(),
+            &accounts,
+        );
+        let (fee, account) = result.expect("valid undelegation");
+        let account = account.expect("updated account");
+        assert!(account.delegations.is_empty());
+        assert_eq!((half - fee.to_coin()).unwrap(), account.unbonded);
+    }
+
+    fn get_reward_claim_txaux(outputs: Vec<TxOut>, secret_key: &SecretKey) -> TxAux {
+        let tx = ClaimRewardsTx::new(1, outputs, TxAttributes::new(DEFAULT_CHAIN_ID));
+        let witness = get_account_op_witness(Secp256k1::new(), &tx.id(), secret_key);
+        TxAux::ClaimRewardsTx(tx, witness)
+    }
+
+    #[test]
+    fn test_reward_claim_verify() {
+        let db = create_db();
+        let mock_bridge = get_enclave_bridge_mock();
+        let (address, secret_key, mut accounts, last_account_root_hash) =
+            prepare_app_valid_council_account(None);
+        let compound_txaux = get_reward_claim_txaux(Vec::new(), &secret_key);
+        // ZeroCoin
+        {
+            let result = verify(
+                &mock_bridge,
+                &compound_txaux,
+                get_chain_info(&compound_txaux, 0),
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::ZeroCoin);
+        }
+        let mut account = get_account(&address, &last_account_root_hash, &accounts).unwrap();
+        account.add_reward(Coin::one());
+        let key = account.key();
+        let last_account_root_hash = accounts
+            .insert(
+                Some(&last_account_root_hash),
+                &mut [&key],
+                &mut vec![&AccountWrapper(account)],
+            )
+            .expect("insert");
+        // compounding into bonded
+        {
+            let result = verify(
+                &mock_bridge,
+                &compound_txaux,
+                get_chain_info(&compound_txaux, 0),
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            let (fee, account) = result.expect("valid reward claim");
+            let account = account.expect("updated account");
+            assert_eq!(2, account.nonce);
+            assert_eq!(Coin::zero(), account.rewards);
+            assert_eq!(
+                ((Coin::one() + Coin::one()).unwrap() - fee.to_coin()).unwrap(),
+                account.bonded
+            );
+        }
+        let addr = ExtendedAddr::OrTree([0xbb; 32]);
+        // AccountWithdrawOutputNotLocked
+        for output in [
+            TxOut::new(addr.clone(), Coin::unit()),
+            TxOut::new_with_timelock(addr.clone(), Coin::unit(), 100),
+            TxOut::new_with_relative_timelock(
+                addr.clone(),
+                Coin::unit(),
+                RelativeTimelock::Seconds(0),
+            ),
+            TxOut::new_with_relative_timelock(
+                addr.clone(),
+                Coin::unit(),
+                RelativeTimelock::Blocks(1),
+            ),
+        ]
+        .iter()
+        {
+            let txaux = get_reward_claim_txaux(vec![output.clone()], &secret_key);
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::AccountWithdrawOutputNotLocked);
+        }
+        // InputOutputDoNotMatch
+        {
+            let output = TxOut::new_with_relative_timelock(
+                addr.clone(),
+                Coin::one(),
+                RelativeTimelock::Seconds(1),
+            );
+            let txaux = get_reward_claim_txaux(vec![output], &secret_key);
+            let result = verify(
+                &mock_bridge,
+                &txaux,
+                get_chain_info(&txaux, 0),
+                get_council_info(),
+                &last_account_root_hash,
+                db.clone(),
+                &accounts,
+            );
+            expect_error(&result, Error::InputOutputDoNotMatch);
+        }
+        let amount = Coin::new(9000_0000).unwrap();
+        let output = TxOut::new_with_relative_timelock(addr, amount, RelativeTimelock::Seconds(1));
+        let txaux = get_reward_claim_txaux(vec![output], &secret_key);
+        let result = verify(
+            &mock_bridge,
+            &txaux,
+            get_chain_info(&txaux, 0),
+            get_council_info(),
+            &last_account_root_hash,
+            db.clone(),
+            &accounts,
+        );
+        let (fee, account) = result.expect("valid reward claim");
+        let account = account.expect("updated account");
+        assert_eq!((Coin::one() - amount).unwrap(), fee.to_coin());
+        assert_eq!(2, account.nonce);
+        assert_eq!(Coin::zero(), account.rewards);
+        assert_eq!(Coin::one(), account.bonded);
+    }
 }
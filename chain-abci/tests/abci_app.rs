@@ -3,24 +3,31 @@ use abci::*;
 use bit_vec::BitVec;
 use chain_abci::app::*;
 use chain_abci::enclave_bridge::mock::MockClient;
-use chain_abci::storage::account::AccountStorage;
 use chain_abci::storage::account::AccountWrapper;
-use chain_abci::storage::tx::StarlingFixedKey;
+use chain_abci::storage::account::{reclaimable_space, AccountStorage};
+use chain_abci::storage::tx::{StarlingFixedKey, TxMeta};
 use chain_abci::storage::*;
 use chain_core::common::{MerkleTree, Proof, H256, HASH_SIZE_256};
-use chain_core::compute_app_hash;
 use chain_core::init::address::RedeemAddress;
 use chain_core::init::coin::Coin;
 use chain_core::init::config::AccountType;
 use chain_core::init::config::InitConfig;
 use chain_core::init::config::InitNetworkParameters;
+use chain_core::init::config::SlashingParameters;
 use chain_core::init::config::{InitialValidator, ValidatorKeyType};
 use chain_core::state::account::{
-    to_stake_key, DepositBondTx, StakedState, StakedStateAddress, StakedStateOpAttributes,
-    StakedStateOpWitness, UnbondTx, WithdrawUnbondedTx,
+    to_stake_key, DelegateTx, DepositBondTx, NodeJoinRequestTx, NodeLeaveTx, StakedState,
+    StakedStateAddress, StakedStateOpAttributes, StakedStateOpWitness, UnbondTx, UndelegateTx,
+    WithdrawUnbondedTx,
+};
+use chain_core::state::account_proof::{AccountProof, AppHashParts};
+use chain_core::state::governance::{
+    NetworkParameterChange, NetworkParameters, ParamChangeProposalTx, ParamChangeVoteTx,
 };
+use chain_core::state::tendermint::{TendermintValidatorPubKey, TendermintVotePower};
 use chain_core::state::RewardsPoolState;
-use chain_core::tx::fee::{LinearFee, Milli};
+use chain_core::tx::fee::{FeePolicy, LinearFee, Milli};
+use chain_core::tx::rejection::RejectionCode;
 use chain_core::tx::witness::tree::RawPubkey;
 use chain_core::tx::witness::EcdsaSignature;
 use chain_core::tx::PlainTxAux;
@@ -29,7 +36,7 @@ use chain_core::tx::{
     data::{
         access::{TxAccess, TxAccessPolicy},
         address::ExtendedAddr,
-        attribute::TxAttributes,
+        attribute::{chain_id_hash, TxAttributes},
         input::{TxoIndex, TxoPointer},
         output::TxOut,
         txid_hash, Tx, TxId,
@@ -37,6 +44,7 @@ use chain_core::tx::{
     witness::{TxInWitness, TxWitness},
     TxAux,
 };
+use chain_core::{compute_app_hash, AppStateRoots};
 use chain_tx_filter::BlockFilter;
 use chain_tx_validation::TxWithOutputs;
 use hex::decode;
@@ -45,6 +53,7 @@ use kvdb_memorydb::create;
 use parity_codec::{Decode, Encode};
 use secp256k1::schnorrsig::schnorr_sign;
 use secp256k1::{key::PublicKey, key::SecretKey, Message, Secp256k1, Signing};
+use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::sync::Arc;
@@ -94,6 +103,7 @@ fn proper_hash_and_chainid_should_be_stored() {
     assert_eq!(decoded_gah, stored_genesis);
     let chain_id = db.get(COL_EXTRA, CHAIN_ID_KEY).unwrap().unwrap();
     assert_eq!(chain_id, TEST_CHAIN_ID.as_bytes());
+    assert_eq!(_app.chain_id_hash, chain_id_hash(TEST_CHAIN_ID));
 }
 
 #[test]
@@ -144,14 +154,42 @@ fn get_dummy_app_state(app_hash: H256) -> ChainNodeState {
         last_apphash: app_hash,
         block_time: 0,
         rewards_pool: RewardsPoolState::new(1.into(), 0),
-        fee_policy: LinearFee::new(Milli::new(1, 1), Milli::new(1, 1)),
+        network_params: NetworkParameters {
+            fee_policy: FeePolicy::Linear(LinearFee::new(Milli::new(1, 1), Milli::new(1, 1))),
+            unbonding_period: 1,
+            required_council_node_stake: Coin::unit(),
+        },
         last_account_root_hash: [0u8; 32],
         council_nodes: vec![],
-        required_council_node_stake: Coin::unit(),
-        unbonding_period: 1,
+        block_reward_share: Milli::new(0, 500),
+        slashing_config: get_slashing_config(),
+        validator_liveness: vec![],
+        proposals: vec![],
+        chain_id_hash_required_from: None,
+    }
+}
+
+fn get_slashing_config() -> SlashingParameters {
+    SlashingParameters {
+        byzantine_slash_ratio: Milli::new(0, 200),
+        liveness_slash_ratio: Milli::new(0, 10),
+        block_signing_window: 3,
+        missed_block_threshold: 1,
+        jail_duration: 10,
     }
 }
 
+#[test]
+fn chain_id_hash_should_be_required_from_activation_height() {
+    let mut state = get_dummy_app_state([0u8; 32]);
+    assert!(!state.chain_id_hash_required());
+    state.chain_id_hash_required_from = Some(2);
+    assert!(!state.chain_id_hash_required());
+    // transactions are validated for the next block
+    state.last_block_height = 1;
+    assert!(state.chain_id_hash_required());
+}
+
 #[test]
 #[should_panic]
 fn previously_stored_hash_should_match() {
@@ -162,6 +200,11 @@ fn previously_stored_hash_should_match() {
     genesis_app_hash.copy_from_slice(&decoded_gah[..]);
     let mut inittx = db.transaction();
     inittx.put(COL_NODE_INFO, GENESIS_APP_HASH_KEY, &genesis_app_hash);
+    inittx.put(
+        COL_NODE_INFO,
+        STORAGE_VERSION_KEY,
+        &STORAGE_VERSION.encode(),
+    );
     inittx.put(
         COL_NODE_INFO,
         LAST_STATE_KEY,
@@ -178,9 +221,43 @@ fn previously_stored_hash_should_match() {
     );
 }
 
+#[test]
+#[should_panic(expected = "stored data format version: 0 does not match")]
+fn previously_stored_data_format_should_match() {
+    let db = create_db();
+    let example_hash = "F5E8DFBF717082D6E9508E1A5A5C9B8EAC04A39F69C40262CB733C920DA10962";
+    let decoded_gah = decode(example_hash).unwrap();
+    let mut genesis_app_hash = [0u8; HASH_SIZE_256];
+    genesis_app_hash.copy_from_slice(&decoded_gah[..]);
+    // stored before the data format was versioned
+    let mut inittx = db.transaction();
+    inittx.put(COL_NODE_INFO, GENESIS_APP_HASH_KEY, &genesis_app_hash);
+    inittx.put(COL_EXTRA, CHAIN_ID_KEY, TEST_CHAIN_ID.as_bytes());
+    inittx.put(
+        COL_NODE_INFO,
+        LAST_STATE_KEY,
+        &get_dummy_app_state(genesis_app_hash).encode(),
+    );
+    db.write(inittx).unwrap();
+    let _app = ChainNodeApp::new_with_storage(
+        get_enclave_bridge_mock(),
+        example_hash,
+        TEST_CHAIN_ID,
+        Storage::new_db(db.clone()),
+        create_account_db(),
+    );
+}
+
 fn init_chain_for(address: RedeemAddress) -> ChainNodeApp<MockClient> {
+    init_chain_with_rewards_pool(address, Coin::zero())
+}
+
+fn init_chain_with_rewards_pool(
+    address: RedeemAddress,
+    rewards_pool: Coin,
+) -> ChainNodeApp<MockClient> {
     let db = create_db();
-    let total = (Coin::max() - Coin::unit()).unwrap();
+    let total = (Coin::max() - Coin::unit() - rewards_pool).unwrap();
     let validator_addr = "0x0e7c045110b8dbf29765047380898919c5cb56f4"
         .parse::<RedeemAddress>()
         .unwrap();
@@ -193,16 +270,19 @@ fn init_chain_for(address: RedeemAddress) -> ChainNodeApp<MockClient> {
         ),
         (
             RedeemAddress::default(),
-            (Coin::zero(), AccountType::Contract),
+            (rewards_pool, AccountType::Contract),
         ),
     ]
     .iter()
     .cloned()
     .collect();
     let params = InitNetworkParameters {
-        initial_fee_policy: LinearFee::new(Milli::new(1, 1), Milli::new(1, 1)),
+        initial_fee_policy: FeePolicy::Linear(LinearFee::new(Milli::new(1, 1), Milli::new(1, 1))),
         required_council_node_stake: Coin::unit(),
         unbonding_period: 1,
+        block_reward_share: Milli::new(0, 500),
+        slashing_config: get_slashing_config(),
+        chain_id_hash_required_from: None,
     };
     let c = InitConfig::new(
         distribution,
@@ -235,7 +315,13 @@ fn init_chain_for(address: RedeemAddress) -> ChainNodeApp<MockClient> {
             )
             .expect("initial insert");
 
-        let genesis_app_hash = compute_app_hash(&tx_tree, &new_account_root, &rp);
+        let genesis_app_hash = compute_app_hash(
+            &tx_tree,
+            &new_account_root,
+            &rp,
+            &c.network_params.governed_params(),
+            &compute_council_state_hash(&c.network_params.slashing_config, &[], &[]),
+        );
 
         let example_hash = hex::encode_upper(genesis_app_hash);
         let mut app = ChainNodeApp::new_with_storage(
@@ -286,6 +372,24 @@ fn init_chain_should_create_db_items() {
     );
 }
 
+#[test]
+fn init_chain_should_track_validator_voting_power() {
+    let address = "0xfe7c045110b8dbf29765047380898919c5cb56f9"
+        .parse()
+        .unwrap();
+    let app = init_chain_for(address);
+    let validator = StakedStateAddress::from(
+        "0x0e7c045110b8dbf29765047380898919c5cb56f4"
+            .parse::<RedeemAddress>()
+            .unwrap(),
+    );
+    assert_eq!(1, app.validator_voting_power.len());
+    assert_eq!(
+        TendermintVotePower::from(Coin::unit()),
+        app.validator_voting_power[&validator]
+    );
+}
+
 #[test]
 #[should_panic]
 fn init_chain_panics_with_different_app_hash() {
@@ -306,9 +410,12 @@ fn init_chain_panics_with_different_app_hash() {
     .cloned()
     .collect();
     let params = InitNetworkParameters {
-        initial_fee_policy: LinearFee::new(Milli::new(1, 1), Milli::new(1, 1)),
+        initial_fee_policy: FeePolicy::Linear(LinearFee::new(Milli::new(1, 1), Milli::new(1, 1))),
         required_council_node_stake: Coin::unit(),
         unbonding_period: 1,
+        block_reward_share: Milli::new(0, 500),
+        slashing_config: get_slashing_config(),
+        chain_id_hash_required_from: None,
     };
     let c = InitConfig::new(
         distribution,
@@ -360,6 +467,7 @@ fn check_tx_should_reject_empty_tx() {
     let creq = RequestCheckTx::default();
     let cresp = app.check_tx(&creq);
     assert_ne!(0, cresp.code);
+    assert_eq!(RejectionCode::InvalidFormat.code(), cresp.code);
 }
 
 #[test]
@@ -406,6 +514,96 @@ fn check_tx_should_accept_valid_tx() {
     assert_eq!(0, cresp.code);
 }
 
+#[test]
+fn check_tx_should_reject_replayed_tx_with_nonce_code() {
+    let (mut app, txaux) = prepare_app_valid_tx();
+    begin_block(&mut app);
+    let mut dreq = RequestDeliverTx::default();
+    dreq.set_tx(txaux.encode());
+    assert_eq!(0, app.deliver_tx(&dreq).code);
+    let mut creq = RequestCheckTx::default();
+    creq.set_tx(txaux.encode());
+    let cresp = app.check_tx(&creq);
+    assert_eq!(RejectionCode::AccountIncorrectNonce.code(), cresp.code);
+}
+
+/// withdrawal with the same nonce as the one in `prepare_app_valid_tx`, but different outputs
+fn prepare_conflicting_tx() -> TxAux {
+    let secp = Secp256k1::new();
+    let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+    let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+    let tx = WithdrawUnbondedTx::new(
+        0,
+        vec![TxOut::new_with_timelock(
+            ExtendedAddr::OrTree([2; 32]),
+            Coin::one(),
+            0,
+        )],
+        TxAttributes::new_with_access(0, vec![TxAccessPolicy::new(public_key, TxAccess::AllData)]),
+    );
+    let witness = StakedStateOpWitness::new(get_ecdsa_witness(&secp, &tx.id(), &secret_key));
+    TxAux::WithdrawUnbondedStakeTx(tx, witness)
+}
+
+fn check_tx(app: &mut ChainNodeApp<MockClient>, txaux: &TxAux, recheck: bool) -> u32 {
+    let mut creq = RequestCheckTx::default();
+    creq.set_tx(txaux.encode());
+    if recheck {
+        creq.set_field_type(CheckTxType::Recheck);
+    }
+    app.check_tx(&creq).code
+}
+
+#[test]
+fn check_tx_should_reject_conflicting_tx() {
+    let (mut app, txaux) = prepare_app_valid_tx();
+    let conflicting = prepare_conflicting_tx();
+    assert_eq!(0, check_tx(&mut app, &txaux, false));
+    assert_eq!(
+        RejectionCode::MempoolConflict.code(),
+        check_tx(&mut app, &conflicting, false)
+    );
+    // resubmitted transaction doesn't conflict with itself
+    assert_eq!(0, check_tx(&mut app, &txaux, false));
+}
+
+#[test]
+fn mempool_should_be_rechecked_after_commit() {
+    let (mut app, txaux) = prepare_app_valid_tx();
+    let conflicting = prepare_conflicting_tx();
+    assert_eq!(0, check_tx(&mut app, &txaux, false));
+    begin_block(&mut app);
+    let mut endreq = RequestEndBlock::default();
+    endreq.set_height(1);
+    app.end_block(&endreq);
+    app.commit(&RequestCommit::default());
+    // the mempool state is rebuilt from the rechecked transactions (in their mempool order)
+    assert_eq!(0, check_tx(&mut app, &conflicting, true));
+    assert_eq!(
+        RejectionCode::MempoolConflict.code(),
+        check_tx(&mut app, &txaux, true)
+    );
+}
+
+#[test]
+fn rechecked_tx_included_in_block_should_be_rejected() {
+    let (mut app, txaux) = prepare_app_valid_tx();
+    assert_eq!(0, check_tx(&mut app, &txaux, false));
+    begin_block(&mut app);
+    let mut dreq = RequestDeliverTx::default();
+    dreq.set_tx(txaux.encode());
+    assert_eq!(0, app.deliver_tx(&dreq).code);
+    let mut endreq = RequestEndBlock::default();
+    endreq.set_height(1);
+    app.end_block(&endreq);
+    app.commit(&RequestCommit::default());
+    assert_eq!(
+        RejectionCode::AccountIncorrectNonce.code(),
+        check_tx(&mut app, &txaux, true)
+    );
+    assert!(app.mempool_state.is_empty());
+}
+
 #[test]
 #[should_panic]
 fn two_beginblocks_should_panic() {
@@ -646,14 +844,19 @@ fn valid_commit_should_persist() {
         .unwrap()
         .is_some());
     // TODO: check account
-    let new_utxos = BitVec::from_bytes(
-        &app.storage
+    let new_utxos = TxMeta::decode(
+        &mut app
+            .storage
             .db
             .get(COL_TX_META, &tx.id()[..])
             .unwrap()
-            .unwrap(),
-    );
-    assert!(!new_utxos.any());
+            .unwrap()
+            .to_vec()
+            .as_slice(),
+    )
+    .unwrap();
+    assert!(!new_utxos.spent().any());
+    assert_eq!(10, new_utxos.inclusion.block_height);
 }
 
 #[test]
@@ -683,6 +886,136 @@ fn query_should_return_an_account() {
     assert!(account.is_some());
 }
 
+fn query_account_at(
+    app: &mut ChainNodeApp<MockClient>,
+    address: &RedeemAddress,
+    height: i64,
+) -> ResponseQuery {
+    let mut qreq = RequestQuery::new();
+    qreq.data = address.to_vec();
+    qreq.path = "account".into();
+    qreq.height = height;
+    app.query(&qreq)
+}
+
+fn commit_empty_block_before_tx() -> (ChainNodeApp<MockClient>, TxAux, RedeemAddress) {
+    let (mut app, txaux) = prepare_app_valid_tx();
+    let secp = Secp256k1::new();
+    let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+    let address = RedeemAddress::from(&PublicKey::from_secret_key(&secp, &secret_key));
+    begin_block(&mut app);
+    let mut endreq = RequestEndBlock::default();
+    endreq.set_height(1);
+    app.end_block(&endreq);
+    app.commit(&RequestCommit::default());
+    (app, txaux, address)
+}
+
+#[test]
+fn query_should_return_an_account_at_height() {
+    let (mut app, txaux, address) = commit_empty_block_before_tx();
+    block_commit(&mut app, txaux, 2);
+    let old_account =
+        StakedState::decode(&mut query_account_at(&mut app, &address, 1).value.as_slice())
+            .expect("account at height 1");
+    let new_account =
+        StakedState::decode(&mut query_account_at(&mut app, &address, 2).value.as_slice())
+            .expect("account at height 2");
+    assert_eq!(0, old_account.nonce);
+    assert_eq!(1, new_account.nonce);
+    let latest = query_account_at(&mut app, &address, 0);
+    assert_eq!(2, latest.height);
+    assert_eq!(new_account.encode(), latest.value);
+    assert_ne!(0, query_account_at(&mut app, &address, 3).code);
+}
+
+#[test]
+fn pruned_account_roots_should_not_be_queried() {
+    let (mut app, txaux, address) = commit_empty_block_before_tx();
+    app.account_root_history = Some(1);
+    assert_eq!(0, query_account_at(&mut app, &address, 1).code);
+    block_commit(&mut app, txaux, 2);
+    assert_ne!(0, query_account_at(&mut app, &address, 1).code);
+    assert_eq!(0, query_account_at(&mut app, &address, 2).code);
+}
+
+#[test]
+fn superseded_account_roots_should_be_collected() {
+    let (mut app, txaux) = prepare_app_valid_tx();
+    app.account_root_history = Some(1);
+    let genesis_root = app.last_state.as_ref().unwrap().last_account_root_hash;
+    let address = StakedStateAddress::from(
+        "0x0e7c045110b8dbf29765047380898919c5cb56f4"
+            .parse::<RedeemAddress>()
+            .unwrap(),
+    );
+    let key = to_stake_key(&address);
+    assert!(app.accounts.get(&genesis_root, &mut [&key]).unwrap()[&key].is_some());
+    block_commit(&mut app, txaux, 1);
+    let root = app.last_state.as_ref().unwrap().last_account_root_hash;
+    assert_ne!(genesis_root, root);
+    // the genesis state is out of the history, so only the nodes shared with the new root are kept
+    let space = reclaimable_space(&app.accounts, &[root]).expect("account trie");
+    assert_eq!(0, space.nodes);
+    assert!(app.accounts.get(&genesis_root, &mut [&key]).unwrap()[&key].is_none());
+    assert!(app.accounts.get(&root, &mut [&key]).unwrap()[&key].is_some());
+}
+
+#[test]
+fn query_should_return_proof_for_account() {
+    let (mut app, txaux, address) = commit_empty_block_before_tx();
+    block_commit(&mut app, txaux, 2);
+    let staked_address = StakedStateAddress::from(address);
+    let mut qreq = RequestQuery::new();
+    qreq.data = address.to_vec();
+    qreq.path = "account".into();
+    qreq.prove = true;
+    let qresp = app.query(&qreq);
+    let account = StakedState::decode(&mut qresp.value.as_slice()).expect("account");
+    let ops = qresp.get_proof().get_ops();
+    assert_eq!(2, ops.len());
+    assert_eq!("account", ops[0].field_type);
+    assert_eq!("app_hash", ops[1].field_type);
+    let account_proof = AccountProof::decode(&mut ops[0].data.as_slice()).expect("account proof");
+    let parts = AppHashParts::decode(&mut ops[1].data.as_slice()).expect("app hash parts");
+    let state = app.last_state.as_ref().unwrap();
+    assert!(account_proof.verify(
+        &state.last_account_root_hash,
+        &staked_address,
+        Some(&account)
+    ));
+    assert!(parts.verify_account(
+        &state.last_apphash,
+        &account_proof,
+        &staked_address,
+        Some(&account)
+    ));
+
+    // historical state: the empty block at height 1 kept the genesis app hash
+    qreq.height = 1;
+    let qresp = app.query(&qreq);
+    assert_eq!(1, qresp.height);
+    let old_account = StakedState::decode(&mut qresp.value.as_slice()).expect("account");
+    let ops = qresp.get_proof().get_ops();
+    assert_eq!(2, ops.len());
+    let account_proof = AccountProof::decode(&mut ops[0].data.as_slice()).expect("account proof");
+    let mut old_root = [0u8; HASH_SIZE_256];
+    old_root.copy_from_slice(&ops[0].key[..]);
+    assert_ne!(
+        app.last_state.as_ref().unwrap().last_account_root_hash,
+        old_root
+    );
+    assert!(account_proof.verify(&old_root, &staked_address, Some(&old_account)));
+    assert_eq!(&app.genesis_app_hash[..], &ops[1].key[..]);
+    let parts = AppHashParts::decode(&mut ops[1].data.as_slice()).expect("app hash parts");
+    assert!(parts.verify_account(
+        &app.genesis_app_hash,
+        &account_proof,
+        &staked_address,
+        Some(&old_account)
+    ));
+}
+
 #[test]
 fn query_should_return_proof_for_committed_tx() {
     let (mut app, tx, witness, _) = deliver_valid_tx();
@@ -705,7 +1038,7 @@ fn query_should_return_proof_for_committed_tx() {
 
     let proof = qresp.proof.unwrap();
 
-    assert_eq!(proof.ops.len(), 2);
+    assert_eq!(proof.ops.len(), 3);
 
     let mut transaction_root_hash = [0u8; 32];
     transaction_root_hash.copy_from_slice(proof.ops[0].key.as_slice());
@@ -728,6 +1061,508 @@ fn query_should_return_proof_for_committed_tx() {
     let qresp = app.query(&qreq2);
     assert_eq!(qresp.value, witness.encode());
     assert_eq!(proof.ops[1].data, txid_hash(&qresp.value));
+
+    assert_eq!("app_state", proof.ops[2].field_type);
+    assert_eq!(cresp.data, proof.ops[2].key);
+    let roots = AppStateRoots::decode(&mut proof.ops[2].data.as_slice()).unwrap();
+    assert_eq!(transaction_root_hash, roots.valid_tx_root);
+    assert_eq!(&roots.app_hash()[..], &cresp.data[..]);
+}
+
+fn query_tx(
+    app: &mut ChainNodeApp<MockClient>,
+    path: &str,
+    txid: &TxId,
+    height: i64,
+) -> ResponseQuery {
+    let mut qreq = RequestQuery::new();
+    qreq.data = txid.to_vec();
+    qreq.path = path.into();
+    qreq.height = height;
+    qreq.prove = path == "store";
+    app.query(&qreq)
+}
+
+#[test]
+fn fully_spent_txs_and_old_merkle_trees_should_be_pruned() {
+    let secp = Secp256k1::new();
+    let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+    let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+    let addr = RedeemAddress::from(&public_key);
+    let mut app = init_chain_for(addr);
+    app.storage.pruning = PruningMode::Retain(1);
+
+    let merkle_tree = MerkleTree::new(vec![RawPubkey::from(public_key.serialize())]);
+    let eaddr = ExtendedAddr::OrTree(merkle_tree.root_hash());
+    let tx0 = WithdrawUnbondedTx::new(
+        0,
+        vec![TxOut::new_with_timelock(eaddr.clone(), Coin::one(), 0)],
+        TxAttributes::new_with_access(
+            0,
+            vec![TxAccessPolicy::new(public_key.clone(), TxAccess::AllData)],
+        ),
+    );
+    let txid0 = tx0.id();
+    let witness0 = StakedStateOpWitness::new(get_ecdsa_witness(&secp, &txid0, &secret_key));
+    block_commit(&mut app, TxAux::WithdrawUnbondedStakeTx(tx0, witness0), 1);
+
+    let mut tx1 = Tx::new();
+    tx1.add_input(TxoPointer::new(txid0, 0));
+    tx1.add_output(TxOut::new(eaddr.clone(), Coin::from(5000_0000u32)));
+    let txid1 = tx1.id();
+    let witness1 = vec![TxInWitness::TreeSig(
+        schnorr_sign(&secp, &Message::from_slice(&txid1).unwrap(), &secret_key).0,
+        merkle_tree
+            .generate_proof(RawPubkey::from(public_key.serialize()))
+            .unwrap(),
+    )]
+    .into();
+    let transfertx = TxAux::TransferTx {
+        txid: txid1,
+        inputs: tx1.inputs.clone(),
+        no_of_outputs: tx1.outputs.len() as TxoIndex,
+        nonce: [0; 12],
+        txpayload: PlainTxAux::TransferTx(tx1.clone(), witness1).encode(),
+    };
+    block_commit(&mut app, transfertx, 2);
+    // the withdrawal is fully spent, but still in the retention window
+    assert_eq!(0, query_tx(&mut app, "witness", &txid0, 0).code);
+    // the merkle tree at height 1 is out of it
+    assert_eq!(2, query_tx(&mut app, "store", &txid1, 1).code);
+    assert_eq!(0, query_tx(&mut app, "store", &txid1, 2).code);
+
+    begin_block(&mut app);
+    let mut endreq = RequestEndBlock::default();
+    endreq.set_height(3);
+    app.end_block(&endreq);
+    app.commit(&RequestCommit::default());
+    for path in ["store", "witness", "meta"].iter() {
+        let qresp = query_tx(&mut app, path, &txid0, 0);
+        assert_eq!(1, qresp.code);
+        assert!(qresp.log.contains("pruned"));
+    }
+    assert!(app
+        .storage
+        .db
+        .get(COL_TX_META, &txid0[..])
+        .unwrap()
+        .is_none());
+    // the unspent transfer is kept
+    assert_eq!(0, query_tx(&mut app, "store", &txid1, 3).code);
+    assert_eq!(0, query_tx(&mut app, "witness", &txid1, 0).code);
+}
+
+fn commit_and_snapshot() -> (ChainNodeApp<MockClient>, WithdrawUnbondedTx, Vec<u8>) {
+    let (mut app, tx, _, _) = deliver_valid_tx();
+    let mut endreq = RequestEndBlock::default();
+    endreq.set_height(10);
+    app.end_block(&endreq);
+    app.commit(&RequestCommit::default());
+    let mut snapshot = Vec::new();
+    app.create_snapshot(&mut snapshot).expect("committed state");
+    (app, tx, snapshot)
+}
+
+fn trusted_app_hash(app: &ChainNodeApp<MockClient>) -> TrustedAppHash {
+    let state = app.last_state.as_ref().expect("committed state");
+    TrustedAppHash {
+        height: state.last_block_height,
+        app_hash: state.last_apphash,
+    }
+}
+
+/// reads the snapshot parts (up to the end) after its header
+fn read_snapshot(snapshot: &[u8]) -> (SnapshotHeader, Vec<SnapshotPart>) {
+    let mut reader = snapshot;
+    let header = SnapshotHeader::read(&mut reader).expect("snapshot header");
+    let mut parts = Vec::new();
+    loop {
+        match SnapshotPart::read(&mut reader).expect("snapshot part") {
+            SnapshotPart::End => break,
+            part => parts.push(part),
+        }
+    }
+    (header, parts)
+}
+
+fn write_snapshot(header: &SnapshotHeader, parts: &[SnapshotPart]) -> Vec<u8> {
+    let mut snapshot = Vec::new();
+    header.write(&mut snapshot).expect("snapshot header");
+    for part in parts.iter().chain(std::iter::once(&SnapshotPart::End)) {
+        part.write(&mut snapshot).expect("snapshot part");
+    }
+    snapshot
+}
+
+fn snapshot_accounts(snapshot: &[u8]) -> Vec<StakedState> {
+    read_snapshot(snapshot)
+        .1
+        .into_iter()
+        .flat_map(|part| match part {
+            SnapshotPart::Accounts(accounts) => accounts,
+            _ => vec![],
+        })
+        .collect()
+}
+
+fn restore_snapshot(app: &ChainNodeApp<MockClient>, snapshot: &[u8]) -> ChainNodeApp<MockClient> {
+    ChainNodeApp::new_from_snapshot(
+        get_enclave_bridge_mock(),
+        &hex::encode(&app.genesis_app_hash),
+        TEST_CHAIN_ID,
+        Storage::new_db(create_db()),
+        create_account_db(),
+        &mut &snapshot[..],
+        trusted_app_hash(app),
+    )
+}
+
+#[test]
+fn app_should_be_restored_from_snapshot() {
+    let (app, tx, snapshot) = commit_and_snapshot();
+    let restored = restore_snapshot(&app, &snapshot);
+    assert_eq!(app.last_state, restored.last_state);
+    assert!(restored
+        .storage
+        .db
+        .get(COL_BODIES, &tx.id()[..])
+        .unwrap()
+        .is_some());
+    assert_eq!(STORAGE_VERSION, restored.storage.get_version());
+    let mut restored_snapshot = Vec::new();
+    restored
+        .create_snapshot(&mut restored_snapshot)
+        .expect("restored state");
+    let accounts = snapshot_accounts(&snapshot);
+    assert!(!accounts.is_empty());
+    assert_eq!(accounts, snapshot_accounts(&restored_snapshot));
+}
+
+#[test]
+#[should_panic]
+fn tampered_snapshot_should_panic() {
+    let (app, _, snapshot) = commit_and_snapshot();
+    let (header, mut parts) = read_snapshot(&snapshot);
+    for part in parts.iter_mut() {
+        if let SnapshotPart::Accounts(accounts) = part {
+            accounts[0].nonce += 1;
+            break;
+        }
+    }
+    restore_snapshot(&app, &write_snapshot(&header, &parts));
+}
+
+#[test]
+#[should_panic]
+fn snapshot_with_tampered_council_state_should_panic() {
+    let (app, _, snapshot) = commit_and_snapshot();
+    let (mut header, parts) = read_snapshot(&snapshot);
+    header.state.slashing_config.jail_duration += 1;
+    restore_snapshot(&app, &write_snapshot(&header, &parts));
+}
+
+#[test]
+#[should_panic]
+fn snapshot_from_other_storage_version_should_panic() {
+    let (app, _, mut snapshot) = commit_and_snapshot();
+    snapshot[..4].copy_from_slice(&(STORAGE_VERSION - 1).encode());
+    restore_snapshot(&app, &snapshot);
+}
+
+#[test]
+#[should_panic]
+fn truncated_snapshot_should_panic() {
+    let (app, _, snapshot) = commit_and_snapshot();
+    let (header, parts) = read_snapshot(&snapshot);
+    let mut truncated = Vec::new();
+    header.write(&mut truncated).expect("snapshot header");
+    for part in parts.iter() {
+        part.write(&mut truncated).expect("snapshot part");
+    }
+    restore_snapshot(&app, &truncated);
+}
+
+#[test]
+#[should_panic]
+fn snapshot_not_matching_trusted_app_hash_should_panic() {
+    let (app, _, snapshot) = commit_and_snapshot();
+    let mut trusted = trusted_app_hash(&app);
+    trusted.app_hash = [0u8; 32];
+    ChainNodeApp::new_from_snapshot(
+        get_enclave_bridge_mock(),
+        &hex::encode(&app.genesis_app_hash),
+        TEST_CHAIN_ID,
+        Storage::new_db(create_db()),
+        create_account_db(),
+        &mut &snapshot[..],
+        trusted,
+    );
+}
+
+#[test]
+#[should_panic]
+fn snapshot_not_at_trusted_height_should_panic() {
+    let (app, _, snapshot) = commit_and_snapshot();
+    let mut trusted = trusted_app_hash(&app);
+    trusted.height += 1;
+    ChainNodeApp::new_from_snapshot(
+        get_enclave_bridge_mock(),
+        &hex::encode(&app.genesis_app_hash),
+        TEST_CHAIN_ID,
+        Storage::new_db(create_db()),
+        create_account_db(),
+        &mut &snapshot[..],
+        trusted,
+    );
+}
+
+fn query_snapshot_chunk(app: &mut ChainNodeApp<MockClient>, index: u64) -> ResponseQuery {
+    let mut qreq = RequestQuery::new();
+    qreq.data = index.to_string().into_bytes();
+    qreq.path = "snapshot".into();
+    app.query(&qreq)
+}
+
+#[test]
+fn snapshot_should_be_stored_in_background_and_queried_in_chunks() {
+    let (mut app, _, _, _) = deliver_valid_tx();
+    let dir = std::env::temp_dir().join(format!("chain-abci-snapshots-{}", std::process::id()));
+    app.snapshot_config = Some(SnapshotConfig::new(dir.clone(), 10));
+    let mut endreq = RequestEndBlock::default();
+    endreq.set_height(10);
+    app.end_block(&endreq);
+    app.commit(&RequestCommit::default());
+    app.snapshot_writer.join();
+
+    let mut expected = Vec::new();
+    app.create_snapshot(&mut expected).expect("committed state");
+    let mut data = Vec::new();
+    let mut index = 0;
+    loop {
+        let qresp = query_snapshot_chunk(&mut app, index);
+        assert_eq!(0, qresp.code);
+        let chunk = SnapshotChunk::decode(&mut qresp.value.as_slice()).expect("snapshot chunk");
+        assert_eq!(10, chunk.height);
+        assert_eq!(index, chunk.index);
+        assert!(chunk.data.len() as u64 <= SNAPSHOT_CHUNK_SIZE);
+        data.extend(chunk.data);
+        index += 1;
+        if index == chunk.chunks {
+            break;
+        }
+    }
+    assert_eq!(expected, data);
+    assert_eq!(1, query_snapshot_chunk(&mut app, index).code);
+    std::fs::remove_dir_all(dir).expect("remove snapshot directory");
+}
+
+#[test]
+fn begin_block_should_reward_council_nodes_that_signed_last_block() {
+    let validator_addr = "0x0e7c045110b8dbf29765047380898919c5cb56f4"
+        .parse::<RedeemAddress>()
+        .unwrap();
+    let mut app = init_chain_with_rewards_pool(
+        "0xfe7c045110b8dbf29765047380898919c5cb56f9"
+            .parse()
+            .unwrap(),
+        Coin::new(1001).unwrap(),
+    );
+    let bonded = get_account(&validator_addr, &app).bonded;
+    assert_eq!(Coin::zero(), get_account(&validator_addr, &app).rewards);
+    let genesis_app_hash = app.last_state.clone().unwrap().last_apphash;
+
+    let mut validator = Validator::default();
+    // the validator key in `init_chain_for` is 32 "0" characters
+    validator.set_address(Sha256::digest(&[b'0'; 32])[..20].to_vec());
+    validator.set_power(1);
+    let mut vote = VoteInfo::default();
+    vote.set_validator(validator);
+    vote.set_signed_last_block(true);
+    let mut last_commit_info = LastCommitInfo::default();
+    last_commit_info.set_votes(vec![vote].into());
+    let mut bbreq = RequestBeginBlock::default();
+    let mut header = Header::default();
+    header.set_time(::protobuf::well_known_types::Timestamp::new());
+    header.set_height(1);
+    bbreq.set_header(header);
+    bbreq.set_last_commit_info(last_commit_info);
+    app.begin_block(&bbreq);
+    let mut breq = RequestEndBlock::default();
+    breq.set_height(1);
+    app.end_block(&breq);
+    let cresp = app.commit(&RequestCommit::default());
+
+    // block reward share is 0.5 (rounded down); it accrues until it's claimed
+    let account = get_account(&validator_addr, &app);
+    assert_eq!(Coin::new(500).unwrap(), account.rewards);
+    assert_eq!(bonded, account.bonded);
+    let state = app.last_state.clone().unwrap();
+    assert_eq!(Coin::new(501).unwrap(), state.rewards_pool.remaining);
+    assert_eq!(1, state.rewards_pool.last_block_height);
+    assert_ne!(genesis_app_hash.to_vec(), cresp.data);
+}
+
+#[test]
+fn begin_block_should_not_reward_council_nodes_that_did_not_sign() {
+    let mut app = init_chain_with_rewards_pool(
+        "0xfe7c045110b8dbf29765047380898919c5cb56f9"
+            .parse()
+            .unwrap(),
+        Coin::new(1000).unwrap(),
+    );
+    let mut validator = Validator::default();
+    validator.set_address(Sha256::digest(&[b'0'; 32])[..20].to_vec());
+    validator.set_power(1);
+    let mut vote = VoteInfo::default();
+    vote.set_validator(validator);
+    vote.set_signed_last_block(false);
+    let mut last_commit_info = LastCommitInfo::default();
+    last_commit_info.set_votes(vec![vote].into());
+    let mut bbreq = RequestBeginBlock::default();
+    let mut header = Header::default();
+    header.set_time(::protobuf::well_known_types::Timestamp::new());
+    bbreq.set_header(header);
+    bbreq.set_last_commit_info(last_commit_info);
+    app.begin_block(&bbreq);
+    assert_eq!(
+        Coin::new(1000).unwrap(),
+        app.last_state.unwrap().rewards_pool.remaining
+    );
+}
+
+fn begin_block_with_votes(
+    app: &mut ChainNodeApp<MockClient>,
+    block_height: i64,
+    signed_last_block: bool,
+    byzantine_validators: Vec<Evidence>,
+) -> ResponseEndBlock {
+    let mut validator = Validator::default();
+    // the validator key in `init_chain_for` is 32 "0" characters
+    validator.set_address(Sha256::digest(&[b'0'; 32])[..20].to_vec());
+    validator.set_power(1);
+    let mut vote = VoteInfo::default();
+    vote.set_validator(validator);
+    vote.set_signed_last_block(signed_last_block);
+    let mut last_commit_info = LastCommitInfo::default();
+    last_commit_info.set_votes(vec![vote].into());
+    let mut bbreq = RequestBeginBlock::default();
+    let mut header = Header::default();
+    header.set_time(::protobuf::well_known_types::Timestamp::new());
+    header.set_height(block_height);
+    bbreq.set_header(header);
+    bbreq.set_last_commit_info(last_commit_info);
+    bbreq.set_byzantine_validators(byzantine_validators.into());
+    app.begin_block(&bbreq);
+    let mut breq = RequestEndBlock::default();
+    breq.set_height(block_height);
+    let resp = app.end_block(&breq);
+    app.commit(&RequestCommit::default());
+    resp
+}
+
+#[test]
+fn begin_block_should_slash_and_jail_council_nodes_with_byzantine_evidence() {
+    let validator_addr = "0x0e7c045110b8dbf29765047380898919c5cb56f4"
+        .parse::<RedeemAddress>()
+        .unwrap();
+    let mut app = init_chain_for(
+        "0xfe7c045110b8dbf29765047380898919c5cb56f9"
+            .parse()
+            .unwrap(),
+    );
+    let bonded = get_account(&validator_addr, &app).bonded;
+    let mut validator = Validator::default();
+    validator.set_address(Sha256::digest(&[b'0'; 32])[..20].to_vec());
+    validator.set_power(1);
+    let mut evidence = Evidence::default();
+    evidence.set_field_type("duplicate/vote".to_string());
+    evidence.set_validator(validator);
+    let resp = begin_block_with_votes(&mut app, 1, true, vec![evidence]);
+
+    // byzantine slash ratio is 0.2
+    let slashed = Coin::new(u64::from(bonded) / 5).unwrap();
+    let account = get_account(&validator_addr, &app);
+    assert_eq!((bonded - slashed).unwrap(), account.bonded);
+    assert_eq!(Some(10), account.jailed_until);
+    assert!(account.is_jailed());
+    assert_eq!(
+        slashed,
+        app.last_state.clone().unwrap().rewards_pool.remaining
+    );
+    assert_eq!(1, resp.validator_updates.len());
+    assert_eq!(0, resp.validator_updates[0].power);
+
+    // jailed council nodes are not slashed again
+    let mut validator = Validator::default();
+    validator.set_address(Sha256::digest(&[b'0'; 32])[..20].to_vec());
+    let mut evidence = Evidence::default();
+    evidence.set_validator(validator);
+    begin_block_with_votes(&mut app, 2, false, vec![evidence]);
+    assert_eq!(
+        (bonded - slashed).unwrap(),
+        get_account(&validator_addr, &app).bonded
+    );
+}
+
+#[test]
+fn begin_block_should_slash_and_jail_council_nodes_that_missed_too_many_blocks() {
+    let validator_addr = "0x0e7c045110b8dbf29765047380898919c5cb56f4"
+        .parse::<RedeemAddress>()
+        .unwrap();
+    let mut app = init_chain_for(
+        "0xfe7c045110b8dbf29765047380898919c5cb56f9"
+            .parse()
+            .unwrap(),
+    );
+    let bonded = get_account(&validator_addr, &app).bonded;
+    // the missed block threshold is 1 in the window of 3 blocks
+    let resp = begin_block_with_votes(&mut app, 1, false, vec![]);
+    assert!(resp.validator_updates.is_empty());
+    let resp = begin_block_with_votes(&mut app, 2, true, vec![]);
+    assert!(resp.validator_updates.is_empty());
+    assert_eq!(bonded, get_account(&validator_addr, &app).bonded);
+    let resp = begin_block_with_votes(&mut app, 3, false, vec![]);
+
+    // liveness slash ratio is 0.01
+    let slashed = Coin::new(u64::from(bonded) / 100).unwrap();
+    let account = get_account(&validator_addr, &app);
+    assert_eq!((bonded - slashed).unwrap(), account.bonded);
+    assert!(account.is_jailed());
+    assert_eq!(1, resp.validator_updates.len());
+    assert_eq!(0, resp.validator_updates[0].power);
+    let state = app.last_state.unwrap();
+    assert_eq!(0, state.validator_liveness[0].1.missed_count());
+}
+
+#[test]
+fn app_hash_should_commit_to_liveness_tracking() {
+    let mut app = init_chain_for(
+        "0xfe7c045110b8dbf29765047380898919c5cb56f9"
+            .parse()
+            .unwrap(),
+    );
+    let genesis_state = app.last_state.clone().unwrap();
+    // a missed block (below the threshold) only changes the liveness tracking
+    begin_block_with_votes(&mut app, 1, false, vec![]);
+    let state = app.last_state.clone().unwrap();
+    assert_ne!(
+        genesis_state.council_state_hash(),
+        state.council_state_hash()
+    );
+    assert_ne!(genesis_state.last_apphash, state.last_apphash);
+    let roots = AppStateRoots::decode(
+        &mut app
+            .storage
+            .db
+            .get(COL_APP_STATE_ROOTS, &state.last_apphash[..])
+            .unwrap()
+            .unwrap()
+            .to_vec()
+            .as_slice(),
+    )
+    .unwrap();
+    assert_eq!(state.council_state_hash(), roots.council_state_hash);
+    assert_eq!(state.last_apphash, roots.app_hash());
 }
 
 fn block_commit(app: &mut ChainNodeApp<MockClient>, tx: TxAux, block_height: i64) {
@@ -764,7 +1599,10 @@ fn get_account(account_address: &RedeemAddress, app: &ChainNodeApp<MockClient>)
 }
 
 fn get_tx_meta(txid: &TxId, app: &ChainNodeApp<MockClient>) -> BitVec {
-    BitVec::from_bytes(&app.storage.db.get(COL_TX_META, &txid[..]).unwrap().unwrap())
+    let meta = app.storage.db.get(COL_TX_META, &txid[..]).unwrap().unwrap();
+    TxMeta::decode(&mut meta.to_vec().as_slice())
+        .unwrap()
+        .spent()
 }
 
 #[test]
@@ -880,4 +1718,151 @@ fn all_valid_tx_types_should_commit() {
         assert!(account.unbonded > Coin::zero());
         assert_eq!(account.nonce, 3);
     }
+
+    let tx4 = NodeJoinRequestTx::new(
+        3,
+        TendermintValidatorPubKey::Ed25519([0xaa; 32]),
+        StakedStateOpAttributes::new(0),
+    );
+    let witness4 = StakedStateOpWitness::new(get_ecdsa_witness(&secp, &tx4.id(), &secret_key));
+    let jointx = TxAux::NodeJoinTx(tx4, witness4);
+    let staking_address = StakedStateAddress::from(addr);
+    {
+        assert!(!app.validator_pubkeys.contains_key(&staking_address));
+    }
+    block_commit(&mut app, jointx, 5);
+    {
+        let account = get_account(&addr, &app);
+        assert_eq!(account.nonce, 4);
+        let state = app.last_state.clone().unwrap();
+        assert_eq!(2, state.council_nodes.len());
+        assert!(app.validator_pubkeys.contains_key(&staking_address));
+        assert_eq!(
+            TendermintVotePower::from(account.bonded),
+            app.validator_voting_power[&staking_address]
+        );
+    }
+
+    let tx5 = ParamChangeProposalTx::new(
+        4,
+        NetworkParameterChange::UnbondingPeriod(10),
+        8,
+        StakedStateOpAttributes::new(0),
+    );
+    let proposal_id = tx5.id();
+    let witness5 = StakedStateOpWitness::new(get_ecdsa_witness(&secp, &tx5.id(), &secret_key));
+    let proposaltx = TxAux::ProposalTx(tx5, witness5);
+    block_commit(&mut app, proposaltx, 6);
+    {
+        let account = get_account(&addr, &app);
+        assert_eq!(account.nonce, 5);
+        let state = app.last_state.clone().unwrap();
+        assert_eq!(1, state.proposals.len());
+        assert_eq!(proposal_id, state.proposals[0].id);
+    }
+
+    let tx6 = ParamChangeVoteTx::new(5, proposal_id, true, StakedStateOpAttributes::new(0));
+    let witness6 = StakedStateOpWitness::new(get_ecdsa_witness(&secp, &tx6.id(), &secret_key));
+    let votetx = TxAux::VoteTx(tx6, witness6);
+    block_commit(&mut app, votetx, 7);
+    {
+        let account = get_account(&addr, &app);
+        assert_eq!(account.nonce, 6);
+        let state = app.last_state.clone().unwrap();
+        assert_eq!(vec![(staking_address, true)], state.proposals[0].votes);
+        assert_eq!(1, state.network_params.unbonding_period);
+        // pending proposals and their votes are committed to in the app hash
+        let roots = AppStateRoots::decode(
+            &mut app
+                .storage
+                .db
+                .get(COL_APP_STATE_ROOTS, &state.last_apphash[..])
+                .unwrap()
+                .unwrap()
+                .to_vec()
+                .as_slice(),
+        )
+        .unwrap();
+        assert_eq!(state.council_state_hash(), roots.council_state_hash);
+        let mut unvoted_state = state.clone();
+        unvoted_state.proposals[0].votes.clear();
+        assert_ne!(unvoted_state.council_state_hash(), roots.council_state_hash);
+    }
+    // the proposal is approved by the council node with most of the bonded stake
+    let last_app_hash = app.last_state.clone().unwrap().last_apphash;
+    begin_block_with_votes(&mut app, 8, true, vec![]);
+    {
+        let state = app.last_state.clone().unwrap();
+        assert!(state.proposals.is_empty());
+        assert_eq!(10, state.network_params.unbonding_period);
+        assert_ne!(last_app_hash, state.last_apphash);
+        let roots = AppStateRoots::decode(
+            &mut app
+                .storage
+                .db
+                .get(COL_APP_STATE_ROOTS, &state.last_apphash[..])
+                .unwrap()
+                .unwrap()
+                .to_vec()
+                .as_slice(),
+        )
+        .unwrap();
+        assert_eq!(state.network_params.hash(), roots.network_params_hash);
+    }
+
+    let tx7 = NodeLeaveTx::new(6, StakedStateOpAttributes::new(0));
+    let witness7 = StakedStateOpWitness::new(get_ecdsa_witness(&secp, &tx7.id(), &secret_key));
+    let leavetx = TxAux::NodeLeaveTx(tx7, witness7);
+    block_commit(&mut app, leavetx, 9);
+    {
+        let account = get_account(&addr, &app);
+        assert_eq!(account.nonce, 7);
+        let state = app.last_state.clone().unwrap();
+        assert_eq!(1, state.council_nodes.len());
+        assert!(!app.validator_pubkeys.contains_key(&staking_address));
+        assert!(!app.validator_voting_power.contains_key(&staking_address));
+    }
+
+    // delegations to the remaining (genesis) council node count towards its voting power
+    let validator_addr = "0x0e7c045110b8dbf29765047380898919c5cb56f4"
+        .parse::<RedeemAddress>()
+        .unwrap();
+    let council_node = StakedStateAddress::from(validator_addr);
+    let delegated = Coin::from(1000_0000u32);
+    let tx8 = DelegateTx::new(delegated, 7, council_node, StakedStateOpAttributes::new(0));
+    let witness8 = StakedStateOpWitness::new(get_ecdsa_witness(&secp, &tx8.id(), &secret_key));
+    let delegatetx = TxAux::DelegateTx(tx8, witness8);
+    block_commit(&mut app, delegatetx, 10);
+    {
+        let account = get_account(&addr, &app);
+        assert_eq!(account.nonce, 8);
+        assert_eq!(
+            u64::from(delegated),
+            account.delegation_shares_to(&council_node)
+        );
+        let node_account = get_account(&validator_addr, &app);
+        assert_eq!(delegated, node_account.delegated);
+        assert_eq!(
+            TendermintVotePower::from((node_account.bonded + delegated).unwrap()),
+            app.validator_voting_power[&council_node]
+        );
+    }
+
+    let tx9 = UndelegateTx::new(delegated, 8, council_node, StakedStateOpAttributes::new(0));
+    let witness9 = StakedStateOpWitness::new(get_ecdsa_witness(&secp, &tx9.id(), &secret_key));
+    let undelegatetx = TxAux::UndelegateTx(tx9, witness9);
+    let unbonded = get_account(&addr, &app).unbonded;
+    block_commit(&mut app, undelegatetx, 11);
+    {
+        let account = get_account(&addr, &app);
+        assert_eq!(account.nonce, 9);
+        assert!(account.delegations.is_empty());
+        assert!(account.unbonded > unbonded);
+        let node_account = get_account(&validator_addr, &app);
+        assert_eq!(Coin::zero(), node_account.delegated);
+        assert_eq!(
+            TendermintVotePower::from(node_account.bonded),
+            app.validator_voting_power[&council_node]
+        );
+    }
 }
@@ -8,23 +8,57 @@ use crate::init::address::RedeemAddress;
 use crate::init::coin::{sum_coins, Coin, CoinError};
 use crate::init::MAX_COIN;
 use crate::state::account::{StakedState, StakedStateAddress};
-use crate::state::tendermint::{TendermintValidatorPubKey, TendermintVotePower};
+use crate::state::governance::NetworkParameters;
+use crate::state::tendermint::{BlockHeight, TendermintValidatorPubKey, TendermintVotePower};
+use crate::state::vesting::{Vesting, VestingSchedule};
 use crate::state::CouncilNode;
 use crate::state::RewardsPoolState;
-use crate::tx::fee::LinearFee;
+use crate::tx::fee::{FeePolicy, Milli};
+use parity_codec::{Decode, Encode};
 use std::collections::{BTreeMap, HashSet};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct InitNetworkParameters {
-    // Initial fee setting
-    // -- TODO: perhaps change to be against T: FeeAlgorithm
-    // TBD here, the intention would be to "freeze" the genesis config, so not sure generic field is worth it
-    pub initial_fee_policy: LinearFee,
+    // Initial fee setting (can be changed later by council node governance)
+    pub initial_fee_policy: FeePolicy,
     // minimal? council node stake
     pub required_council_node_stake: Coin,
     // stake unbonding time (in seconds)
     pub unbonding_period: u32,
+    // share of the remaining rewards pool paid out in each block to council nodes that signed the previous block
+    pub block_reward_share: Milli,
+    // punishment of council nodes for misbehaviour / being offline
+    pub slashing_config: SlashingParameters,
+    // block height from which transactions have to commit to the full chain ID (if None, it stays optional)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub chain_id_hash_required_from: Option<BlockHeight>,
+}
+
+impl InitNetworkParameters {
+    /// the initial values of the network parameters changeable by council node governance
+    pub fn governed_params(&self) -> NetworkParameters {
+        NetworkParameters {
+            fee_policy: self.initial_fee_policy,
+            unbonding_period: self.unbonding_period,
+            required_council_node_stake: self.required_council_node_stake,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SlashingParameters {
+    // share of the bonded amount slashed for byzantine faults (e.g. double signing)
+    pub byzantine_slash_ratio: Milli,
+    // share of the bonded amount slashed for missing too many blocks
+    pub liveness_slash_ratio: Milli,
+    // number of the most recent blocks in which block signing of council nodes is tracked
+    pub block_signing_window: u16,
+    // maximum number of blocks a council node can miss in the window without being slashed
+    pub missed_block_threshold: u16,
+    // how long a slashed council node is jailed (in seconds)
+    pub jail_duration: u32,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -70,6 +104,9 @@ pub struct InitConfig {
     pub network_params: InitNetworkParameters,
     // initial validators
     pub council_nodes: Vec<InitialValidator>,
+    // lockups of (externally owned) allocations in the distribution: Eth address => vesting schedule
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub vesting: BTreeMap<RedeemAddress, VestingSchedule>,
 }
 
 pub enum DistributionError {
@@ -83,6 +120,10 @@ pub enum DistributionError {
     DuplicateValidatorAccount,
     NoValidators,
     InvalidVotingPower,
+    InvalidBlockRewardShare,
+    InvalidSlashingParameters,
+    InvalidFeePolicy,
+    InvalidVestingSchedule(RedeemAddress),
 }
 
 impl fmt::Display for DistributionError {
@@ -119,6 +160,18 @@ impl fmt::Display for DistributionError {
             DistributionError::InvalidVotingPower => {
                 write!(f, "Invalid voting power")
             },
+            DistributionError::InvalidBlockRewardShare => {
+                write!(f, "Invalid block reward share (should be at most 1.0)")
+            },
+            DistributionError::InvalidSlashingParameters => {
+                write!(f, "Invalid slashing parameters (slash ratios should be at most 1.0 and the missed block threshold should be less than the block signing window)")
+            },
+            DistributionError::InvalidFeePolicy => {
+                write!(f, "Invalid fee policy (the dynamic fee multiplier should be at least 1.0 and at most the maximal multiplier)")
+            },
+            DistributionError::InvalidVestingSchedule(a) => {
+                write!(f, "Invalid vesting schedule of address ({}): it should be an externally owned account and linear vesting should end after it starts", a)
+            },
         }
     }
 }
@@ -140,6 +193,7 @@ impl InitConfig {
             long_term_incentive,
             network_params,
             council_nodes,
+            vesting: BTreeMap::new(),
         }
     }
 
@@ -188,6 +242,22 @@ impl InitConfig {
             || *address == self.long_term_incentive
     }
 
+    fn check_vesting_schedules(&self) -> Result<(), DistributionError> {
+        for (address, schedule) in self.vesting.iter() {
+            match self.distribution.get(address) {
+                Some((_, AccountType::ExternallyOwnedAccount))
+                    if !self.is_rewards_pool_address(address) && schedule.is_valid() => {}
+                Some(_) => {
+                    return Err(DistributionError::InvalidVestingSchedule(*address));
+                }
+                None => {
+                    return Err(DistributionError::AddressNotInDistribution(*address));
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// returns the initial accounts and rewards pool state
     /// assumes one called [validate_config_get_genesis], otherwise it may panic
     fn get_genesis_state(
@@ -201,12 +271,17 @@ impl InitConfig {
             if self.is_rewards_pool_address(address) || *address_type == AccountType::Contract {
                 rewards_pool_amount += u64::from(*amount);
             } else {
-                accounts.push(StakedState::new_init(
+                let mut account = StakedState::new_init(
                     *amount,
                     genesis_time,
                     StakedStateAddress::BasicRedeem(*address),
                     validator_addresses.contains(address),
-                ));
+                );
+                account.vesting = self
+                    .vesting
+                    .get(address)
+                    .map(|schedule| Vesting::new(*amount, *schedule));
+                accounts.push(account);
             }
         }
         (
@@ -222,6 +297,8 @@ impl InitConfig {
     /// - required addresses are present in the distribution
     /// - initial validator configuration is correct
     /// - the total amount doesn't go over the maximum supply
+    /// - vesting schedules apply to externally owned accounts and are well-formed
+    /// - the fee policy is consistent (e.g. the dynamic fee multiplier is within its bounds)
     /// - ...
     /// if valid, it'll return the genesis "state"
     pub fn validate_config_get_genesis(
@@ -231,6 +308,20 @@ impl InitConfig {
         self.check_address(&self.launch_incentive_from)?;
         self.check_address(&self.launch_incentive_to)?;
         self.check_address(&self.long_term_incentive)?;
+        if self.network_params.block_reward_share > Milli::integral(1) {
+            return Err(DistributionError::InvalidBlockRewardShare);
+        }
+        let slashing_config = &self.network_params.slashing_config;
+        if slashing_config.byzantine_slash_ratio > Milli::integral(1)
+            || slashing_config.liveness_slash_ratio > Milli::integral(1)
+            || slashing_config.missed_block_threshold >= slashing_config.block_signing_window
+        {
+            return Err(DistributionError::InvalidSlashingParameters);
+        }
+        if !self.network_params.initial_fee_policy.is_valid() {
+            return Err(DistributionError::InvalidFeePolicy);
+        }
+        self.check_vesting_schedules()?;
         if self.council_nodes.is_empty() {
             return Err(DistributionError::NoValidators);
         }
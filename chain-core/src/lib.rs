@@ -21,22 +21,75 @@ pub mod tx;
 
 use blake2::Blake2s;
 use common::{hash256, MerkleTree, H256};
+use parity_codec::{Decode, Encode};
+use state::governance::NetworkParameters;
 use state::RewardsPoolState;
 
 /// computes the "global" application hash (used by Tendermint to check consistency + block replaying)
-/// currently: app_hash = blake2s(root of valid TX merkle tree || blake2s(scale bytes(rewards pool state)))
-/// TODO: it should include the fee policy / network parameters etc. once that becomes changeable
+/// currently: app_hash = blake2s(root of valid TX merkle tree || root of account trie
+/// || blake2s(scale bytes(rewards pool state)) || blake2s(scale bytes(network parameters))
+/// || hash of the council state kept outside of the account trie (e.g. slashing parameters, liveness tracking
+/// and pending network parameter change proposals))
 /// MUST/TODO: include node whitelists
 pub fn compute_app_hash(
     valid_tx_id_tree: &MerkleTree<H256>,
     account_state_root: &H256,
     reward_pool: &RewardsPoolState,
+    network_params: &NetworkParameters,
+    council_state_hash: &H256,
+) -> H256 {
+    compute_app_hash_from_parts(
+        &valid_tx_id_tree.root_hash(),
+        account_state_root,
+        &reward_pool.hash(),
+        &network_params.hash(),
+        council_state_hash,
+    )
+}
+
+/// computes the "global" application hash from the already computed parts
+/// (e.g. for checking the application hash in light clients without the full TX merkle tree or rewards pool state)
+pub fn compute_app_hash_from_parts(
+    valid_tx_root: &H256,
+    account_state_root: &H256,
+    rewards_pool_hash: &H256,
+    network_params_hash: &H256,
+    council_state_hash: &H256,
 ) -> H256 {
-    let valid_tx_part = valid_tx_id_tree.root_hash();
-    let rewards_pool_part = reward_pool.hash();
     let mut bs = Vec::new();
-    bs.extend(&valid_tx_part);
+    bs.extend(&valid_tx_root[..]);
     bs.extend(&account_state_root[..]);
-    bs.extend(&rewards_pool_part);
+    bs.extend(&rewards_pool_hash[..]);
+    bs.extend(&network_params_hash[..]);
+    bs.extend(&council_state_hash[..]);
     hash256::<Blake2s>(&bs)
 }
+
+/// All the parts the "global" application hash is computed from
+/// (stored for each application hash, so that light clients can check proofs against it)
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+pub struct AppStateRoots {
+    /// root of the valid TX merkle tree
+    pub valid_tx_root: H256,
+    /// root of the account trie
+    pub account_state_root: H256,
+    /// hash of the rewards pool state
+    pub rewards_pool_hash: H256,
+    /// hash of the network parameters
+    pub network_params_hash: H256,
+    /// hash of the council state kept outside of the account trie
+    pub council_state_hash: H256,
+}
+
+impl AppStateRoots {
+    /// the application hash computed from these parts
+    pub fn app_hash(&self) -> H256 {
+        compute_app_hash_from_parts(
+            &self.valid_tx_root,
+            &self.account_state_root,
+            &self.rewards_pool_hash,
+            &self.network_params_hash,
+            &self.council_state_hash,
+        )
+    }
+}
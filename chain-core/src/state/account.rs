@@ -1,8 +1,10 @@
-use crate::common::{hash256, Timespec, HASH_SIZE_256};
+use crate::common::{hash256, Timespec, H256, HASH_SIZE_256};
 use crate::init::address::RedeemAddress;
 use crate::init::coin::Coin;
 use crate::init::coin::{sum_coins, CoinError};
-use crate::tx::data::attribute::TxAttributes;
+use crate::state::tendermint::TendermintValidatorPubKey;
+use crate::state::vesting::Vesting;
+use crate::tx::data::attribute::{chain_hex_id, chain_id_hash, TxAttributes, TxExpiry};
 use crate::tx::data::input::TxoPointer;
 use crate::tx::data::output::TxOut;
 use crate::tx::witness::{tree::RawSignature, EcdsaSignature};
@@ -63,15 +65,57 @@ impl FromStr for StakedStateAddress {
     }
 }
 
+/// an unbonded amount (not yet withdrawn) and the time from which it can be withdrawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UnbondingEntry {
+    pub amount: Coin,
+    pub unbonded_from: Timespec,
+}
+
+/// a stake bonded to a council node by another staked state: the delegator keeps a claim on its shares
+/// of the stake delegated to the council node (their value goes down if the council node is slashed)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Delegation {
+    pub council_node: StakedStateAddress,
+    pub shares: u64,
+}
+
 /// represents the StakedState (account involved in staking)
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Encode, Decode)]
 pub struct StakedState {
     pub nonce: Nonce,
     pub bonded: Coin,
+    /// total of the unbonding entries
     pub unbonded: Coin,
-    pub unbonded_from: Timespec,
+    /// pending unbondings (ordered by the time from which they can be withdrawn)
+    pub unbonding: Vec<UnbondingEntry>,
     pub address: StakedStateAddress,
-    // TODO: slashing + jailing
+    /// if the account holder's validator misbehaved, it is jailed (and can't be a validator) until it's unjailed after this time
+    pub jailed_until: Option<Timespec>,
+    /// if the genesis allocation was locked up, the part that isn't vested yet remains unbonded after withdrawals
+    pub vesting: Option<Vesting>,
+    /// total amount delegated to this staked state's council node by other staked states
+    pub delegated: Coin,
+    /// total number of the delegators' shares of `delegated`
+    pub delegation_shares: u64,
+    /// amounts this staked state delegated to council nodes
+    pub delegations: Vec<Delegation>,
+    /// block rewards accrued to the council node that weren't claimed yet
+    pub rewards: Coin,
+}
+
+/// a single unbonding entry for a non-zero unbonded amount
+fn unbonding_entries(unbonded: Coin, unbonded_from: Timespec) -> Vec<UnbondingEntry> {
+    let mut entries = Vec::new();
+    if unbonded > Coin::zero() {
+        entries.push(UnbondingEntry {
+            amount: unbonded,
+            unbonded_from,
+        });
+    }
+    entries
 }
 
 /// the tree used in StakedState storage db has a hardcoded 32-byte keys,
@@ -109,8 +153,14 @@ impl StakedState {
             nonce,
             bonded,
             unbonded,
-            unbonded_from,
+            unbonding: unbonding_entries(unbonded, unbonded_from),
             address,
+            jailed_until: None,
+            vesting: None,
+            delegated: Coin::zero(),
+            delegation_shares: 0,
+            delegations: Vec::new(),
+            rewards: Coin::zero(),
         }
     }
 
@@ -122,21 +172,9 @@ impl StakedState {
         bonded: bool,
     ) -> Self {
         if bonded {
-            StakedState {
-                nonce: 0,
-                bonded: amount,
-                unbonded: Coin::zero(),
-                unbonded_from: genesis_time,
-                address,
-            }
+            StakedState::new(0, amount, Coin::zero(), genesis_time, address)
         } else {
-            StakedState {
-                nonce: 0,
-                bonded: Coin::zero(),
-                unbonded: amount,
-                unbonded_from: genesis_time,
-                address,
-            }
+            StakedState::new(0, Coin::zero(), amount, genesis_time, address)
         }
     }
 
@@ -147,19 +185,255 @@ impl StakedState {
     }
 
     /// in-place update after unbonding a bonded stake
+    /// (it's added to the unbonding entries, so that the earlier unbondings keep their time)
     pub fn unbond(&mut self, amount: Coin, fee: Coin, unbonded_from: Timespec) {
         self.nonce += 1;
-        self.unbonded_from = unbonded_from;
         self.bonded = (self.bonded - amount)
             .and_then(|x| x - fee)
             .expect("should not go below zero");
+        self.add_unbonding(amount, unbonded_from);
+    }
+
+    fn add_unbonding(&mut self, amount: Coin, unbonded_from: Timespec) {
         self.unbonded = (self.unbonded + amount).expect("should not be over the max supply");
+        match self
+            .unbonding
+            .iter_mut()
+            .find(|entry| entry.unbonded_from == unbonded_from)
+        {
+            Some(entry) => {
+                entry.amount = (entry.amount + amount).expect("should not be over the max supply");
+            }
+            None => {
+                // the unbonding period may be changed by governance, so a new entry isn't necessarily the last one
+                self.unbonding.push(UnbondingEntry {
+                    amount,
+                    unbonded_from,
+                });
+                self.unbonding.sort_by_key(|entry| entry.unbonded_from);
+            }
+        }
+    }
+
+    /// the stake backing the council node associated with this staked state (its own bonded amount and the delegations to it)
+    pub fn total_stake(&self) -> Coin {
+        (self.bonded + self.delegated).expect("should not be over the max supply")
+    }
+
+    /// the shares of the stake delegated to the given council node this staked state holds
+    pub fn delegation_shares_to(&self, council_node: &StakedStateAddress) -> u64 {
+        self.delegations
+            .iter()
+            .find(|delegation| delegation.council_node == *council_node)
+            .map(|delegation| delegation.shares)
+            .unwrap_or(0)
+    }
+
+    /// the current value of the given shares of the stake delegated to this council node
+    pub fn delegated_value(&self, shares: u64) -> Coin {
+        if self.delegation_shares == 0 {
+            return Coin::zero();
+        }
+        let value = u128::from(shares) * u128::from(u64::from(self.delegated))
+            / u128::from(self.delegation_shares);
+        Coin::new(value as u64).expect("a part of the delegated stake")
+    }
+
+    /// the shares of the stake delegated to this council node issued for delegating `amount`
+    /// (None if the delegated stake was fully slashed, as the existing shares would take a part of it)
+    pub fn delegation_shares_for(&self, amount: Coin) -> Option<u64> {
+        if self.delegation_shares == 0 {
+            return Some(u64::from(amount));
+        }
+        if self.delegated == Coin::zero() {
+            return None;
+        }
+        let shares = u128::from(u64::from(amount)) * u128::from(self.delegation_shares)
+            / u128::from(u64::from(self.delegated));
+        if shares > u128::from(u64::max_value()) {
+            None
+        } else {
+            Some(shares as u64)
+        }
+    }
+
+    /// the shares of the stake delegated to this council node that are needed to undelegate `amount` (rounded up)
+    pub fn undelegation_shares_for(&self, amount: Coin) -> Option<u64> {
+        if self.delegated == Coin::zero() {
+            return None;
+        }
+        let delegated = u128::from(u64::from(self.delegated));
+        let shares =
+            (u128::from(u64::from(amount)) * u128::from(self.delegation_shares) + delegated - 1)
+                / delegated;
+        if shares > u128::from(u64::max_value()) {
+            None
+        } else {
+            Some(shares as u64)
+        }
+    }
+
+    /// in-place update after delegating a bonded stake to a council node for `shares` of its delegated stake
+    /// (the fee is paid from the bonded amount)
+    pub fn delegate(
+        &mut self,
+        amount: Coin,
+        fee: Coin,
+        council_node: StakedStateAddress,
+        shares: u64,
+    ) {
+        self.nonce += 1;
+        self.bonded = (self.bonded - amount)
+            .and_then(|x| x - fee)
+            .expect("should not go below zero");
+        match self
+            .delegations
+            .iter_mut()
+            .find(|delegation| delegation.council_node == council_node)
+        {
+            Some(delegation) => {
+                delegation.shares += shares;
+            }
+            None => self.delegations.push(Delegation {
+                council_node,
+                shares,
+            }),
+        }
+    }
+
+    /// in-place update after undelegating a stake (worth `shares` of the council node's delegated stake):
+    /// it goes through the unbonding period as unbonded stake (the fee is paid from the undelegated amount)
+    pub fn undelegate(
+        &mut self,
+        amount: Coin,
+        fee: Coin,
+        council_node: &StakedStateAddress,
+        shares: u64,
+        unbonded_from: Timespec,
+    ) {
+        self.nonce += 1;
+        for delegation in self.delegations.iter_mut() {
+            if delegation.council_node == *council_node {
+                delegation.shares = delegation
+                    .shares
+                    .checked_sub(shares)
+                    .expect("should not go below zero");
+            }
+        }
+        self.delegations.retain(|delegation| delegation.shares > 0);
+        self.add_unbonding(
+            (amount - fee).expect("should not go below zero"),
+            unbonded_from,
+        );
+    }
+
+    /// in-place update of the council node after a delegation of `amount` for `shares` to it (without changing the nonce)
+    pub fn add_delegated(&mut self, amount: Coin, shares: u64) {
+        self.delegated = (self.delegated + amount).expect("should not be over the max supply");
+        self.delegation_shares += shares;
+    }
+
+    /// in-place update of the council node after an undelegation of `amount` for `shares` from it (without changing the nonce)
+    pub fn remove_delegated(&mut self, amount: Coin, shares: u64) {
+        self.delegated = (self.delegated - amount).expect("should not go below zero");
+        self.delegation_shares = self
+            .delegation_shares
+            .checked_sub(shares)
+            .expect("should not go below zero");
+    }
+
+    /// the unbonded amount that can be withdrawn from the given time
+    pub fn matured_unbonded(&self, time: Timespec) -> Coin {
+        sum_coins(
+            self.unbonding
+                .iter()
+                .filter(|entry| entry.unbonded_from <= time)
+                .map(|entry| entry.amount),
+        )
+        .expect("should not be over the max supply")
+    }
+
+    /// the latest time (not after the given time) from which some unbonded amount can be withdrawn
+    /// (withdrawal outputs time-locked to it take all the entries matured at the given time)
+    pub fn last_matured(&self, time: Timespec) -> Option<Timespec> {
+        self.unbonding
+            .iter()
+            .map(|entry| entry.unbonded_from)
+            .filter(|unbonded_from| *unbonded_from <= time)
+            .max()
     }
 
-    /// in-place update after withdrawing unbonded stake
-    pub fn withdraw(&mut self) {
+    /// in-place update after receiving a block reward (accrued until it's claimed, without changing the nonce,
+    /// as the account holder doesn't sign anything)
+    pub fn add_reward(&mut self, amount: Coin) {
+        self.rewards = (self.rewards + amount).expect("should not be over the max supply");
+    }
+
+    /// in-place update after claiming all the accrued rewards:
+    /// the compounded part is added to the bonded amount (the rest was paid to UTXOs or as the fee)
+    pub fn claim_rewards(&mut self, compounded: Coin) {
         self.nonce += 1;
-        self.unbonded = Coin::zero();
+        self.bonded = (self.bonded + compounded).expect("should not be over the max supply");
+        self.rewards = Coin::zero();
+    }
+
+    /// in-place update after the validator misbehaved: the slashed amounts are taken from the bonded amount
+    /// and the stake delegated to it (i.e. from all delegators' shares in proportion)
+    /// and the account is jailed until the given time (without changing the nonce)
+    pub fn slash(&mut self, amount: Coin, delegated_amount: Coin, jailed_until: Timespec) {
+        self.bonded = (self.bonded - amount).expect("should not go below zero");
+        self.delegated = (self.delegated - delegated_amount).expect("should not go below zero");
+        self.jailed_until = Some(jailed_until);
+    }
+
+    /// checks whether the account is jailed (until it's unjailed after `jailed_until`)
+    pub fn is_jailed(&self) -> bool {
+        self.jailed_until.is_some()
+    }
+
+    /// in-place update after a council node joined or left (the fee is paid from the bonded amount)
+    pub fn council_node_op(&mut self, fee: Coin) {
+        self.nonce += 1;
+        self.bonded = (self.bonded - fee).expect("should not go below zero");
+    }
+
+    /// in-place update after the council node was unjailed (the fee is paid from the bonded amount)
+    pub fn unjail(&mut self, fee: Coin) {
+        self.council_node_op(fee);
+        self.jailed_until = None;
+    }
+
+    /// the part of the unbonded amount that can't be withdrawn at the given time, as it isn't vested yet
+    pub fn vesting_locked(&self, time: Timespec) -> Coin {
+        match &self.vesting {
+            Some(vesting) => std::cmp::min(self.unbonded, vesting.locked_amount(time)),
+            None => Coin::zero(),
+        }
+    }
+
+    /// the part of the unbonded amount matured at `matured_at` that can't be withdrawn at the given time, as it isn't vested yet
+    pub fn withdrawal_locked(&self, matured_at: Timespec, time: Timespec) -> Coin {
+        std::cmp::min(self.matured_unbonded(matured_at), self.vesting_locked(time))
+    }
+
+    /// in-place update after withdrawing the entries matured at `matured_at` at the given time
+    /// (the part that isn't vested yet and the entries that aren't matured remain)
+    pub fn withdraw(&mut self, matured_at: Timespec, time: Timespec) {
+        self.nonce += 1;
+        let locked = self.withdrawal_locked(matured_at, time);
+        self.unbonding
+            .retain(|entry| entry.unbonded_from > matured_at);
+        if locked > Coin::zero() {
+            self.unbonding.insert(
+                0,
+                UnbondingEntry {
+                    amount: locked,
+                    unbonded_from: matured_at,
+                },
+            );
+        }
+        self.unbonded = sum_coins(self.unbonding.iter().map(|entry| entry.amount))
+            .expect("should not be over the max supply");
     }
 
     /// the tree used in StakedState storage db has a hardcoded 32-byte keys,
@@ -175,20 +449,61 @@ impl StakedState {
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StakedStateOpAttributes {
     pub chain_hex_id: u8,
+    /// optional hash of the full chain ID (see `chain_id_hash`)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub chain_id_hash: Option<H256>,
+    /// optional expiry of the transaction
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub expiry: Option<TxExpiry>,
     // TODO: Other attributes?
 }
 
 impl StakedStateOpAttributes {
     pub fn new(chain_hex_id: u8) -> Self {
-        StakedStateOpAttributes { chain_hex_id }
+        StakedStateOpAttributes {
+            chain_hex_id,
+            chain_id_hash: None,
+            expiry: None,
+        }
+    }
+
+    /// creates attributes bound to the full chain ID (`None` if it doesn't end with two hex digits)
+    pub fn new_for_chain(chain_id: &str) -> Option<Self> {
+        let mut attributes = StakedStateOpAttributes::new(chain_hex_id(chain_id)?);
+        attributes.chain_id_hash = Some(chain_id_hash(chain_id));
+        Some(attributes)
+    }
+
+    /// sets the expiry of the transaction
+    pub fn with_expiry(mut self, expiry: TxExpiry) -> Self {
+        self.expiry = Some(expiry);
+        self
     }
 }
 
 impl Encode for StakedStateOpAttributes {
     fn encode_to<W: Output>(&self, dest: &mut W) {
-        dest.push_byte(0);
-        dest.push_byte(1);
-        dest.push_byte(self.chain_hex_id);
+        if let Some(chain_id_hash) = &self.chain_id_hash {
+            dest.push_byte(2);
+            dest.push_byte(3);
+            dest.push_byte(self.chain_hex_id);
+            chain_id_hash.encode_to(dest);
+            self.expiry.encode_to(dest);
+            return;
+        }
+        match &self.expiry {
+            None => {
+                dest.push_byte(0);
+                dest.push_byte(1);
+                dest.push_byte(self.chain_hex_id);
+            }
+            Some(expiry) => {
+                dest.push_byte(1);
+                dest.push_byte(2);
+                dest.push_byte(self.chain_hex_id);
+                expiry.encode_to(dest);
+            }
+        }
     }
 }
 
@@ -199,7 +514,21 @@ impl Decode for StakedStateOpAttributes {
         match (tag, constructor_len) {
             (0, 1) => {
                 let chain_hex_id: u8 = input.read_byte()?;
-                Some(StakedStateOpAttributes { chain_hex_id })
+                Some(StakedStateOpAttributes::new(chain_hex_id))
+            }
+            (1, 2) => {
+                let chain_hex_id: u8 = input.read_byte()?;
+                let expiry = TxExpiry::decode(input)?;
+                Some(StakedStateOpAttributes::new(chain_hex_id).with_expiry(expiry))
+            }
+            (2, 3) => {
+                let chain_hex_id: u8 = input.read_byte()?;
+                let chain_id_hash = H256::decode(input)?;
+                let expiry: Option<TxExpiry> = Option::decode(input)?;
+                let mut attributes = StakedStateOpAttributes::new(chain_hex_id);
+                attributes.chain_id_hash = Some(chain_id_hash);
+                attributes.expiry = expiry;
+                Some(attributes)
             }
             _ => None,
         }
@@ -310,6 +639,218 @@ impl fmt::Display for WithdrawUnbondedTx {
     }
 }
 
+/// takes all the accrued rewards of the StakedState (TODO: implicit from the witness?) and either compounds them
+/// into its bonded amount (if there are no outputs) or creates UTXOs locked for the unbonding period
+/// (update's StakedState's rewards + nonce; the fee is paid from the rewards)
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClaimRewardsTx {
+    pub nonce: Nonce,
+    pub outputs: Vec<TxOut>,
+    pub attributes: TxAttributes,
+}
+
+impl TransactionId for ClaimRewardsTx {}
+
+impl ClaimRewardsTx {
+    pub fn new(nonce: Nonce, outputs: Vec<TxOut>, attributes: TxAttributes) -> Self {
+        ClaimRewardsTx {
+            nonce,
+            outputs,
+            attributes,
+        }
+    }
+
+    /// returns the total transaction output amount (sum of all output amounts)
+    pub fn get_output_total(&self) -> Result<Coin, CoinError> {
+        sum_coins(self.outputs.iter().map(|x| x.value))
+    }
+
+    /// whether the rewards are compounded into the bonded amount (instead of paid to outputs)
+    pub fn is_compounding(&self) -> bool {
+        self.outputs.is_empty()
+    }
+}
+
+impl fmt::Display for ClaimRewardsTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_compounding() {
+            writeln!(f, "-> (rewards) -> (bonded) (nonce: {})", self.nonce)?;
+        } else {
+            writeln!(f, "-> (rewards) (nonce: {})", self.nonce)?;
+        }
+        for output in self.outputs.iter() {
+            writeln!(f, "   {} ->", output)?;
+        }
+        write!(f, "")
+    }
+}
+
+/// registers the StakedState (TODO: implicit from the witness?) as a council node
+/// with the given Tendermint consensus public key (the fee is paid from the bonded amount)
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeJoinRequestTx {
+    pub nonce: Nonce,
+    pub consensus_pubkey: TendermintValidatorPubKey,
+    pub attributes: StakedStateOpAttributes,
+}
+
+impl TransactionId for NodeJoinRequestTx {}
+
+impl NodeJoinRequestTx {
+    pub fn new(
+        nonce: Nonce,
+        consensus_pubkey: TendermintValidatorPubKey,
+        attributes: StakedStateOpAttributes,
+    ) -> Self {
+        NodeJoinRequestTx {
+            nonce,
+            consensus_pubkey,
+            attributes,
+        }
+    }
+}
+
+impl fmt::Display for NodeJoinRequestTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "join council: {:?} (nonce: {})",
+            self.consensus_pubkey, self.nonce
+        )?;
+        write!(f, "")
+    }
+}
+
+/// unjails the council node associated with the StakedState (TODO: implicit from the witness?)
+/// after its jail time passed (the fee is paid from the bonded amount)
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UnjailTx {
+    pub nonce: Nonce,
+    pub attributes: StakedStateOpAttributes,
+}
+
+impl TransactionId for UnjailTx {}
+
+impl UnjailTx {
+    pub fn new(nonce: Nonce, attributes: StakedStateOpAttributes) -> Self {
+        UnjailTx { nonce, attributes }
+    }
+}
+
+impl fmt::Display for UnjailTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "unjail (nonce: {})", self.nonce)?;
+        write!(f, "")
+    }
+}
+
+/// removes the council node associated with the StakedState (TODO: implicit from the witness?)
+/// from the council (the fee is paid from the bonded amount)
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeLeaveTx {
+    pub nonce: Nonce,
+    pub attributes: StakedStateOpAttributes,
+}
+
+impl TransactionId for NodeLeaveTx {}
+
+impl NodeLeaveTx {
+    pub fn new(nonce: Nonce, attributes: StakedStateOpAttributes) -> Self {
+        NodeLeaveTx { nonce, attributes }
+    }
+}
+
+impl fmt::Display for NodeLeaveTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "leave council (nonce: {})", self.nonce)?;
+        write!(f, "")
+    }
+}
+
+/// updates the StakedState (TODO: implicit from the witness?) by moving some of the bonded amount - fee
+/// into a delegation to the given council node (which counts towards the council node's voting power)
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DelegateTx {
+    pub value: Coin,
+    pub nonce: Nonce,
+    pub council_node: StakedStateAddress,
+    pub attributes: StakedStateOpAttributes,
+}
+
+impl TransactionId for DelegateTx {}
+
+impl DelegateTx {
+    pub fn new(
+        value: Coin,
+        nonce: Nonce,
+        council_node: StakedStateAddress,
+        attributes: StakedStateOpAttributes,
+    ) -> Self {
+        DelegateTx {
+            value,
+            nonce,
+            council_node,
+            attributes,
+        }
+    }
+}
+
+impl fmt::Display for DelegateTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "delegated: {} to {} (nonce: {})",
+            self.value, self.council_node, self.nonce
+        )?;
+        write!(f, "")
+    }
+}
+
+/// updates the StakedState (TODO: implicit from the witness?) by moving some of its delegation to the given council node
+/// into unbonded (minus fee), setting its unbonded_from to last_block_time+min_unbonding_time (network parameter)
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UndelegateTx {
+    pub value: Coin,
+    pub nonce: Nonce,
+    pub council_node: StakedStateAddress,
+    pub attributes: StakedStateOpAttributes,
+}
+
+impl TransactionId for UndelegateTx {}
+
+impl UndelegateTx {
+    pub fn new(
+        value: Coin,
+        nonce: Nonce,
+        council_node: StakedStateAddress,
+        attributes: StakedStateOpAttributes,
+    ) -> Self {
+        UndelegateTx {
+            value,
+            nonce,
+            council_node,
+            attributes,
+        }
+    }
+}
+
+impl fmt::Display for UndelegateTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "undelegated: {} from {} (nonce: {})",
+            self.value, self.council_node, self.nonce
+        )?;
+        write!(f, "")
+    }
+}
+
 /// A witness for StakedState operations
 #[derive(Debug, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -0,0 +1,282 @@
+//! # Inclusion / non-inclusion proofs of staked states in the account trie
+//! The trie is a binary Merkle patricia trie (Merkle-BIT) where:
+//! - data node hash = blake2s("d" || key || scale bytes(staked state))
+//! - leaf node hash = blake2s("l" || key || data node hash)
+//! - branch node hash = blake2s("b" || zero child hash || one child hash)
+//!
+//! and branches decide on the key bit (most significant bit first) at their split index.
+use crate::common::{hash256, H256};
+use crate::compute_app_hash_from_parts;
+use crate::state::account::{to_stake_key, StakedState, StakedStateAddress};
+use blake2::Blake2s;
+use parity_codec::{Decode, Encode};
+use std::prelude::v1::Vec;
+
+/// Branch node on the path from the trie root towards the proven key
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+pub struct AccountTrieBranch {
+    /// the index of the key bit on which the branch splits
+    pub split_index: u8,
+    /// hash of the child node on the zero side
+    pub zero: H256,
+    /// hash of the child node on the one side
+    pub one: H256,
+}
+
+impl AccountTrieBranch {
+    /// hash of this branch node
+    pub fn hash(&self) -> H256 {
+        let mut bs = Vec::with_capacity(1 + 2 * self.zero.len());
+        bs.push(b'b');
+        bs.extend(&self.zero);
+        bs.extend(&self.one);
+        hash256::<Blake2s>(&bs)
+    }
+}
+
+/// Leaf node reached when following the proven key from the trie root
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+pub struct AccountTrieLeaf {
+    /// the key stored in this leaf (equal to the proven key in inclusion proofs)
+    pub key: H256,
+    /// hash of the data node with the stored staked state
+    pub data: H256,
+}
+
+impl AccountTrieLeaf {
+    /// hash of this leaf node
+    pub fn hash(&self) -> H256 {
+        let mut bs = Vec::with_capacity(1 + 2 * self.key.len());
+        bs.push(b'l');
+        bs.extend(&self.key);
+        bs.extend(&self.data);
+        hash256::<Blake2s>(&bs)
+    }
+}
+
+/// hash of the data node storing the staked state under the key
+pub fn account_data_hash(key: &H256, account: &StakedState) -> H256 {
+    let mut bs = Vec::new();
+    bs.push(b'd');
+    bs.extend(&key[..]);
+    bs.extend(&account.encode());
+    hash256::<Blake2s>(&bs)
+}
+
+/// checks whether the bit at the index (most significant bit first) is zero
+pub fn is_zero_bit(key: &H256, index: u8) -> bool {
+    let index = index as usize;
+    (key[index / 8] >> (7 - index % 8)) & 1 == 0
+}
+
+/// Proof that a staked state is (or is not) stored in the account trie under some root
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+pub struct AccountProof {
+    /// branch nodes from the root to the leaf
+    pub branches: Vec<AccountTrieBranch>,
+    /// the leaf at the end of the path
+    pub leaf: AccountTrieLeaf,
+}
+
+impl AccountProof {
+    /// Computes the account trie root implied by this proof if `account` is stored under `address`
+    /// (or if nothing is stored under `address` in case of `None`).
+    /// Returns `None` if the proof is malformed or doesn't match the account.
+    pub fn compute_root(
+        &self,
+        address: &StakedStateAddress,
+        account: Option<&StakedState>,
+    ) -> Option<H256> {
+        let key = to_stake_key(address);
+        match account {
+            Some(account) => {
+                if account.address != *address
+                    || self.leaf.key != key
+                    || self.leaf.data != account_data_hash(&key, account)
+                {
+                    return None;
+                }
+            }
+            None => {
+                if self.leaf.key == key {
+                    return None;
+                }
+            }
+        }
+        // the split indices need to be increasing on the path and the leaf key needs to be on the same path
+        // as the proven key (otherwise the key could have been stored in a different subtree)
+        let mut last_split_index = None;
+        for branch in self.branches.iter() {
+            if last_split_index.map_or(false, |index| index >= branch.split_index)
+                || is_zero_bit(&key, branch.split_index)
+                    != is_zero_bit(&self.leaf.key, branch.split_index)
+            {
+                return None;
+            }
+            last_split_index = Some(branch.split_index);
+        }
+        let mut node_hash = self.leaf.hash();
+        for branch in self.branches.iter().rev() {
+            let child = if is_zero_bit(&key, branch.split_index) {
+                &branch.zero
+            } else {
+                &branch.one
+            };
+            if *child != node_hash {
+                return None;
+            }
+            node_hash = branch.hash();
+        }
+        Some(node_hash)
+    }
+
+    /// Verifies that `account` is stored under `address` in the account trie with the root `account_root`
+    /// (or that nothing is stored under `address` in case of `None`).
+    pub fn verify(
+        &self,
+        account_root: &H256,
+        address: &StakedStateAddress,
+        account: Option<&StakedState>,
+    ) -> bool {
+        self.compute_root(address, account).as_ref() == Some(account_root)
+    }
+}
+
+/// The other parts (besides the account trie root) the application hash is computed from
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+pub struct AppHashParts {
+    /// root of the valid transaction merkle tree
+    pub valid_tx_root: H256,
+    /// hash of the rewards pool state
+    pub rewards_pool_hash: H256,
+    /// hash of the network parameters
+    pub network_params_hash: H256,
+    /// hash of the council state kept outside of the account trie
+    pub council_state_hash: H256,
+}
+
+impl AppHashParts {
+    /// Verifies that the account state (given its account trie proof) is included in the application state
+    /// with the provided application hash.
+    pub fn verify_account(
+        &self,
+        app_hash: &H256,
+        proof: &AccountProof,
+        address: &StakedStateAddress,
+        account: Option<&StakedState>,
+    ) -> bool {
+        match proof.compute_root(address, account) {
+            Some(account_root) => {
+                compute_app_hash_from_parts(
+                    &self.valid_tx_root,
+                    &account_root,
+                    &self.rewards_pool_hash,
+                    &self.network_params_hash,
+                    &self.council_state_hash,
+                ) == *app_hash
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::init::address::RedeemAddress;
+    use crate::init::coin::Coin;
+
+    fn account_with_key(byte: u8) -> (StakedStateAddress, StakedState) {
+        let address = StakedStateAddress::BasicRedeem(RedeemAddress::from([byte; 20]));
+        let account = StakedState::new(0, Coin::unit(), Coin::zero(), 0, address);
+        (address, account)
+    }
+
+    /// a trie with two accounts (split on the first bit where their keys differ)
+    fn two_account_trie() -> (H256, Vec<(StakedStateAddress, StakedState, AccountProof)>) {
+        let mut leaves = vec![account_with_key(1), account_with_key(2)]
+            .into_iter()
+            .map(|(address, account)| {
+                let key = to_stake_key(&address);
+                let leaf = AccountTrieLeaf {
+                    key,
+                    data: account_data_hash(&key, &account),
+                };
+                (address, account, leaf)
+            })
+            .collect::<Vec<_>>();
+        leaves.sort_by_key(|(_, _, leaf)| leaf.key);
+        let (key0, key1) = (leaves[0].2.key, leaves[1].2.key);
+        let split_index = (0..=255u8)
+            .find(|i| is_zero_bit(&key0, *i) != is_zero_bit(&key1, *i))
+            .expect("different keys");
+        let branch = AccountTrieBranch {
+            split_index,
+            zero: leaves[0].2.hash(),
+            one: leaves[1].2.hash(),
+        };
+        let root = branch.hash();
+        let proofs = leaves
+            .into_iter()
+            .map(|(address, account, leaf)| {
+                (
+                    address,
+                    account,
+                    AccountProof {
+                        branches: vec![branch.clone()],
+                        leaf,
+                    },
+                )
+            })
+            .collect();
+        (root, proofs)
+    }
+
+    #[test]
+    fn inclusion_proof_should_verify() {
+        let (root, proofs) = two_account_trie();
+        for (address, account, proof) in proofs.iter() {
+            assert!(proof.verify(&root, address, Some(account)));
+            assert!(!proof.verify(&root, address, None));
+        }
+        let (address, account, proof) = &proofs[0];
+        let mut modified = account.clone();
+        modified.nonce += 1;
+        assert!(!proof.verify(&root, address, Some(&modified)));
+        assert!(!proof.verify(&[0u8; 32], address, Some(account)));
+    }
+
+    #[test]
+    fn non_inclusion_proof_should_verify() {
+        let (root, proofs) = two_account_trie();
+        let (missing, _) = account_with_key(3);
+        let key = to_stake_key(&missing);
+        for (_, _, proof) in proofs.iter() {
+            let split_index = proof.branches[0].split_index;
+            let on_path =
+                is_zero_bit(&key, split_index) == is_zero_bit(&proof.leaf.key, split_index);
+            assert_eq!(on_path, proof.verify(&root, &missing, None));
+        }
+    }
+
+    #[test]
+    fn app_hash_parts_should_verify_account() {
+        let (root, proofs) = two_account_trie();
+        let parts = AppHashParts {
+            valid_tx_root: [1u8; 32],
+            rewards_pool_hash: [2u8; 32],
+            network_params_hash: [3u8; 32],
+            council_state_hash: [4u8; 32],
+        };
+        let app_hash = compute_app_hash_from_parts(
+            &parts.valid_tx_root,
+            &root,
+            &parts.rewards_pool_hash,
+            &parts.network_params_hash,
+            &parts.council_state_hash,
+        );
+        let (address, account, proof) = &proofs[0];
+        assert!(parts.verify_account(&app_hash, proof, address, Some(account)));
+        assert!(!parts.verify_account(&root, proof, address, Some(account)));
+    }
+}
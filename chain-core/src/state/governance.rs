@@ -0,0 +1,240 @@
+use crate::common::{hash256, H256};
+use crate::init::coin::Coin;
+use crate::state::account::{Nonce, StakedStateAddress, StakedStateOpAttributes};
+use crate::state::tendermint::BlockHeight;
+use crate::tx::data::TxId;
+use crate::tx::fee::FeePolicy;
+use crate::tx::TransactionId;
+use blake2::Blake2s;
+use parity_codec::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::prelude::v1::Vec;
+
+/// Proposals are identified by the ID of the transaction that submitted them
+pub type ProposalId = TxId;
+
+/// Network parameters that can be changed by council node governance after genesis
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NetworkParameters {
+    /// fee policy to apply
+    pub fee_policy: FeePolicy,
+    /// time when unbonded stake can be withdrawn
+    pub unbonding_period: u32,
+    /// (minimal?) amount required to be bonded in validator-associated accounts
+    pub required_council_node_stake: Coin,
+}
+
+impl NetworkParameters {
+    /// retrieves the hash of the current parameters (currently blake2s(scale_code_bytes(network_parameters)))
+    pub fn hash(&self) -> H256 {
+        hash256::<Blake2s>(&self.encode())
+    }
+
+    /// sets the parameter to the value in the change
+    pub fn apply(&mut self, change: &NetworkParameterChange) {
+        match change {
+            NetworkParameterChange::FeePolicy(fee_policy) => {
+                self.fee_policy = *fee_policy;
+            }
+            NetworkParameterChange::UnbondingPeriod(unbonding_period) => {
+                self.unbonding_period = *unbonding_period;
+            }
+            NetworkParameterChange::RequiredCouncilNodeStake(stake) => {
+                self.required_council_node_stake = *stake;
+            }
+        }
+    }
+}
+
+/// A new value of one of the network parameters
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NetworkParameterChange {
+    FeePolicy(FeePolicy),
+    UnbondingPeriod(u32),
+    RequiredCouncilNodeStake(Coin),
+}
+
+impl fmt::Display for NetworkParameterChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkParameterChange::FeePolicy(fee_policy) => {
+                write!(f, "fee policy: {:?}", fee_policy)
+            }
+            NetworkParameterChange::UnbondingPeriod(unbonding_period) => {
+                write!(f, "unbonding period: {}", unbonding_period)
+            }
+            NetworkParameterChange::RequiredCouncilNodeStake(stake) => {
+                write!(f, "required council node stake: {}", stake)
+            }
+        }
+    }
+}
+
+/// A pending network parameter change proposal with the council node votes
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParamChangeProposal {
+    /// ID of the transaction that submitted the proposal
+    pub id: ProposalId,
+    /// the proposed change
+    pub change: NetworkParameterChange,
+    /// block height at which the votes are tallied (and the change is applied if approved)
+    pub activation_height: BlockHeight,
+    /// council node votes (the proposer approves it implicitly)
+    pub votes: Vec<(StakedStateAddress, bool)>,
+}
+
+impl ParamChangeProposal {
+    /// creates a new proposal (approved by the proposer)
+    pub fn new(
+        id: ProposalId,
+        proposer: StakedStateAddress,
+        change: NetworkParameterChange,
+        activation_height: BlockHeight,
+    ) -> Self {
+        ParamChangeProposal {
+            id,
+            change,
+            activation_height,
+            votes: vec![(proposer, true)],
+        }
+    }
+
+    /// records the council node's vote (replacing its previous vote, if any)
+    pub fn vote(&mut self, voter: StakedStateAddress, approve: bool) {
+        match self.votes.iter_mut().find(|(address, _)| *address == voter) {
+            Some(vote) => {
+                vote.1 = approve;
+            }
+            None => {
+                self.votes.push((voter, approve));
+            }
+        }
+    }
+}
+
+/// proposes a network parameter change by the council node associated with the StakedState
+/// (TODO: implicit from the witness?) -- the fee is paid from the bonded amount
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParamChangeProposalTx {
+    pub nonce: Nonce,
+    pub change: NetworkParameterChange,
+    pub activation_height: BlockHeight,
+    pub attributes: StakedStateOpAttributes,
+}
+
+impl TransactionId for ParamChangeProposalTx {}
+
+impl ParamChangeProposalTx {
+    pub fn new(
+        nonce: Nonce,
+        change: NetworkParameterChange,
+        activation_height: BlockHeight,
+        attributes: StakedStateOpAttributes,
+    ) -> Self {
+        ParamChangeProposalTx {
+            nonce,
+            change,
+            activation_height,
+            attributes,
+        }
+    }
+}
+
+impl fmt::Display for ParamChangeProposalTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "propose {} at height {} (nonce: {})",
+            self.change, self.activation_height, self.nonce
+        )?;
+        write!(f, "")
+    }
+}
+
+/// votes on a pending network parameter change proposal by the council node associated with the StakedState
+/// (TODO: implicit from the witness?) -- the fee is paid from the bonded amount
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParamChangeVoteTx {
+    pub nonce: Nonce,
+    pub proposal_id: ProposalId,
+    pub approve: bool,
+    pub attributes: StakedStateOpAttributes,
+}
+
+impl TransactionId for ParamChangeVoteTx {}
+
+impl ParamChangeVoteTx {
+    pub fn new(
+        nonce: Nonce,
+        proposal_id: ProposalId,
+        approve: bool,
+        attributes: StakedStateOpAttributes,
+    ) -> Self {
+        ParamChangeVoteTx {
+            nonce,
+            proposal_id,
+            approve,
+            attributes,
+        }
+    }
+}
+
+impl fmt::Display for ParamChangeVoteTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "vote {} on proposal {} (nonce: {})",
+            if self.approve { "yes" } else { "no" },
+            hex::encode(&self.proposal_id[..]),
+            self.nonce
+        )?;
+        write!(f, "")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::init::address::RedeemAddress;
+    use crate::tx::fee::{LinearFee, Milli};
+
+    #[test]
+    fn change_should_be_applied_and_hashed() {
+        let mut params = NetworkParameters {
+            fee_policy: FeePolicy::Linear(LinearFee::new(Milli::new(1, 1), Milli::new(1, 1))),
+            unbonding_period: 1,
+            required_council_node_stake: Coin::unit(),
+        };
+        let old_hash = params.hash();
+        params.apply(&NetworkParameterChange::UnbondingPeriod(10));
+        assert_eq!(params.unbonding_period, 10);
+        assert_ne!(old_hash, params.hash());
+        params.apply(&NetworkParameterChange::RequiredCouncilNodeStake(
+            Coin::one(),
+        ));
+        assert_eq!(params.required_council_node_stake, Coin::one());
+    }
+
+    #[test]
+    fn vote_should_replace_previous_vote() {
+        let proposer = StakedStateAddress::BasicRedeem(RedeemAddress::from([1u8; 20]));
+        let voter = StakedStateAddress::BasicRedeem(RedeemAddress::from([2u8; 20]));
+        let mut proposal = ParamChangeProposal::new(
+            [0u8; 32],
+            proposer,
+            NetworkParameterChange::UnbondingPeriod(10),
+            5,
+        );
+        proposal.vote(voter, true);
+        proposal.vote(voter, false);
+        proposal.vote(proposer, false);
+        assert_eq!(proposal.votes, vec![(proposer, false), (voter, false)]);
+    }
+}
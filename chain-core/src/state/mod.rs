@@ -1,7 +1,13 @@
 /// data types related to account operations
 pub mod account;
+/// proofs of staked states in the account trie
+pub mod account_proof;
+/// network parameter governance by council nodes
+pub mod governance;
 /// data types related to working with Tendermint
 pub mod tendermint;
+/// lockups of genesis allocations
+pub mod vesting;
 
 use crate::common::{hash256, H256};
 use crate::init::coin::Coin;
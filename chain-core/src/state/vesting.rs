@@ -0,0 +1,137 @@
+use parity_codec::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::common::Timespec;
+use crate::init::coin::Coin;
+
+/// How a locked-up genesis allocation (e.g. of the team or investors) becomes spendable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum VestingSchedule {
+    /// the whole amount vests at once at the release time
+    Cliff { release_time: Timespec },
+    /// the amount vests linearly from the start time until the end time
+    Linear {
+        start_time: Timespec,
+        end_time: Timespec,
+    },
+}
+
+impl VestingSchedule {
+    /// checks that the schedule vests over a non-empty period
+    pub fn is_valid(&self) -> bool {
+        match self {
+            VestingSchedule::Cliff { .. } => true,
+            VestingSchedule::Linear {
+                start_time,
+                end_time,
+            } => start_time < end_time,
+        }
+    }
+
+    /// the part of the amount that isn't vested yet at the given time
+    pub fn locked_amount(&self, amount: Coin, time: Timespec) -> Coin {
+        match self {
+            VestingSchedule::Cliff { release_time } => {
+                if time < *release_time {
+                    amount
+                } else {
+                    Coin::zero()
+                }
+            }
+            VestingSchedule::Linear {
+                start_time,
+                end_time,
+            } => {
+                if time <= *start_time {
+                    amount
+                } else if time >= *end_time {
+                    Coin::zero()
+                } else {
+                    let total = u128::from(u64::from(amount));
+                    let elapsed = (time - start_time) as u128;
+                    let duration = (end_time - start_time) as u128;
+                    // the vested part is rounded down, so the locked part is rounded up
+                    let vested = (total * elapsed / duration) as u64;
+                    (amount - Coin::new(vested).expect("vested amount is below the total amount"))
+                        .expect("vested amount is below the total amount")
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for VestingSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VestingSchedule::Cliff { release_time } => write!(f, "cliff at {}", release_time),
+            VestingSchedule::Linear {
+                start_time,
+                end_time,
+            } => write!(f, "linear from {} to {}", start_time, end_time),
+        }
+    }
+}
+
+/// Vesting of a staked state's genesis allocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Vesting {
+    /// the allocated amount subject to the schedule
+    pub amount: Coin,
+    /// how the amount becomes spendable
+    pub schedule: VestingSchedule,
+}
+
+impl Vesting {
+    pub fn new(amount: Coin, schedule: VestingSchedule) -> Self {
+        Vesting { amount, schedule }
+    }
+
+    /// the part of the allocated amount that isn't vested yet at the given time
+    pub fn locked_amount(&self, time: Timespec) -> Coin {
+        self.schedule.locked_amount(self.amount, time)
+    }
+}
+
+impl fmt::Display for Vesting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.amount, self.schedule)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cliff_should_release_at_once() {
+        let vesting = Vesting::new(
+            Coin::new(100).unwrap(),
+            VestingSchedule::Cliff { release_time: 10 },
+        );
+        assert_eq!(vesting.locked_amount(9), Coin::new(100).unwrap());
+        assert_eq!(vesting.locked_amount(10), Coin::zero());
+    }
+
+    #[test]
+    fn linear_should_release_gradually() {
+        let schedule = VestingSchedule::Linear {
+            start_time: 10,
+            end_time: 13,
+        };
+        assert!(schedule.is_valid());
+        let vesting = Vesting::new(Coin::new(100).unwrap(), schedule);
+        assert_eq!(vesting.locked_amount(10), Coin::new(100).unwrap());
+        assert_eq!(vesting.locked_amount(11), Coin::new(67).unwrap());
+        assert_eq!(vesting.locked_amount(12), Coin::new(34).unwrap());
+        assert_eq!(vesting.locked_amount(13), Coin::zero());
+        assert!(!VestingSchedule::Linear {
+            start_time: 10,
+            end_time: 10
+        }
+        .is_valid());
+    }
+}
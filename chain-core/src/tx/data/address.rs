@@ -4,57 +4,100 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
 
-use crate::common::H256;
-use crate::init::address::{CroAddress, CroAddressError};
+use crate::common::{H256, HASH_SIZE_256};
+use crate::init::address::{CroAddress, CroAddressError, RedeemAddress, REDEEM_ADDRESS_BYTES};
 
 use bech32::{u5, Bech32, FromBase32, ToBase32};
 
 /// TODO: opaque types?
 type TreeRoot = H256;
 
-/// Currently, only Ethereum-style redeem address + MAST of Or operations (records the root).
+/// Currently, only Ethereum-style redeem address + MAST of Or operations (records the root) + hash-time-locks.
 /// TODO: HD-addresses?
 /// TODO: custom Encode/Decode when data structures are finalized (for backwards/forwards compatibility, encoders/decoders should be able to work with old formats)
 #[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ExtendedAddr {
     OrTree(TreeRoot),
+    /// single-key address (spent with an ECDSA signature the public key is recovered from)
+    BasicRedeem(RedeemAddress),
+    /// hash-time-locked address (records the hash of the `HashTimeLock` terms)
+    HashTimeLock(H256),
 }
 
+/// human-readable parts of the bech32-encoded hash-time-locked addresses
+const HTLC_HRP_TESTNET: &str = "crth";
+const HTLC_HRP_MAINNET: &str = "crmh";
+
 impl ExtendedAddr {
-    fn get_string(&self, hash: TreeRoot) -> Bech32 {
-        let checked_data: Vec<u5> = hash.to_vec().to_base32();
-        match crate::init::CURRENT_NETWORK {
-            crate::init::network::Network::Testnet => {
+    fn get_string(&self, payload: &[u8]) -> Bech32 {
+        let checked_data: Vec<u5> = payload.to_vec().to_base32();
+        match (self, crate::init::CURRENT_NETWORK) {
+            (ExtendedAddr::HashTimeLock(_), crate::init::network::Network::Testnet) => {
+                Bech32::new(HTLC_HRP_TESTNET.into(), checked_data).expect("bech32 crth encoding")
+            }
+            (ExtendedAddr::HashTimeLock(_), crate::init::network::Network::Mainnet) => {
+                Bech32::new(HTLC_HRP_MAINNET.into(), checked_data).expect("bech32 crmh encoding")
+            }
+            (_, crate::init::network::Network::Testnet) => {
                 Bech32::new("crtt".into(), checked_data).expect("bech32 crmt encoding")
             }
-            crate::init::network::Network::Mainnet => {
+            (_, crate::init::network::Network::Mainnet) => {
                 Bech32::new("crmt".into(), checked_data).expect("bech32 crmt encoding")
             }
         }
     }
+
+    /// the address variant is determined by the payload length (32-byte tree roots and 20-byte redeem addresses);
+    /// hash-time-locked addresses are only distinguished by their bech32 human-readable part
+    fn from_payload(src: &[u8]) -> Result<Self, CroAddressError> {
+        match src.len() {
+            HASH_SIZE_256 => {
+                let mut a: TreeRoot = [0 as u8; HASH_SIZE_256];
+                a.copy_from_slice(src);
+                Ok(ExtendedAddr::OrTree(a))
+            }
+            REDEEM_ADDRESS_BYTES => RedeemAddress::try_from(src)
+                .map(ExtendedAddr::BasicRedeem)
+                .map_err(|_e| CroAddressError::ConvertError),
+            _ => Err(CroAddressError::ConvertError),
+        }
+    }
 }
 
 impl CroAddress<ExtendedAddr> for ExtendedAddr {
     fn to_cro(&self) -> Result<String, CroAddressError> {
         match self {
             ExtendedAddr::OrTree(hash) => {
-                let encoded = self.get_string(*hash);
+                let encoded = self.get_string(hash);
+                Ok(encoded.to_string())
+            }
+            ExtendedAddr::BasicRedeem(address) => {
+                let encoded = self.get_string(address);
+                Ok(encoded.to_string())
+            }
+            ExtendedAddr::HashTimeLock(hash) => {
+                let encoded = self.get_string(hash);
                 Ok(encoded.to_string())
             }
         }
     }
 
     fn from_cro(encoded: &str) -> Result<Self, CroAddressError> {
-        encoded
+        let decoded = encoded
             .parse::<Bech32>()
-            .map_err(|e| CroAddressError::Bech32Error(e.to_string()))
-            .and_then(|a| Vec::from_base32(&a.data()).map_err(|_e| CroAddressError::ConvertError))
-            .and_then(|src| {
-                let mut a: TreeRoot = [0 as u8; 32];
-                a.copy_from_slice(&src.as_slice());
-                Ok(ExtendedAddr::OrTree(a))
-            })
+            .map_err(|e| CroAddressError::Bech32Error(e.to_string()))?;
+        let src = Vec::from_base32(&decoded.data()).map_err(|_e| CroAddressError::ConvertError)?;
+        if decoded.hrp() == HTLC_HRP_TESTNET || decoded.hrp() == HTLC_HRP_MAINNET {
+            if src.len() != HASH_SIZE_256 {
+                return Err(CroAddressError::ConvertError);
+            }
+            let mut a: H256 = [0 as u8; HASH_SIZE_256];
+            a.copy_from_slice(&src);
+            Ok(ExtendedAddr::HashTimeLock(a))
+        } else {
+            ExtendedAddr::from_payload(&src)
+        }
     }
 
     fn from_hex(s: &str) -> Result<Self, CroAddressError> {
@@ -66,16 +109,14 @@ impl CroAddress<ExtendedAddr> for ExtendedAddr {
 
         hex::decode(&address)
             .map_err(|_e| CroAddressError::ConvertError)
-            .and_then(|src| {
-                let mut a: TreeRoot = [0 as u8; 32];
-                a.copy_from_slice(&src.as_slice());
-                Ok(ExtendedAddr::OrTree(a))
-            })
+            .and_then(|src| ExtendedAddr::from_payload(&src))
     }
 
     fn to_hex(&self) -> Result<String, CroAddressError> {
         match self {
             ExtendedAddr::OrTree(hash) => Ok(format!("0x{}", hex::encode(hash))),
+            ExtendedAddr::BasicRedeem(address) => Ok(format!("0x{}", hex::encode(address.0))),
+            ExtendedAddr::HashTimeLock(hash) => Ok(format!("0x{}", hex::encode(hash))),
         }
     }
 }
@@ -125,4 +166,30 @@ mod test {
         .unwrap();
         assert_eq!(a, b);
     }
+
+    #[test]
+    fn should_be_correct_basic_redeem_address() {
+        let a = ExtendedAddr::from_hex("0x0e7c045110b8dbf29765047380898919c5cb56f4").unwrap();
+        assert_eq!(
+            a,
+            ExtendedAddr::BasicRedeem(
+                RedeemAddress::from_str("0x0e7c045110b8dbf29765047380898919c5cb56f4").unwrap()
+            )
+        );
+        let b = a.to_cro().unwrap();
+        assert_eq!(ExtendedAddr::from_cro(&b).unwrap(), a);
+        assert_eq!(
+            a.to_hex().unwrap(),
+            "0x0e7c045110b8dbf29765047380898919c5cb56f4"
+        );
+        assert!(ExtendedAddr::from_hex("0x0e7c045110b8dbf297650473").is_err());
+    }
+
+    #[test]
+    fn should_be_correct_hash_time_lock_address() {
+        let a = ExtendedAddr::HashTimeLock([0x0e; 32]);
+        let b = a.to_cro().unwrap();
+        assert!(b.starts_with("crmh1"));
+        assert_eq!(ExtendedAddr::from_cro(&b).unwrap(), a);
+    }
 }
@@ -1,25 +1,139 @@
+use blake2::Blake2s;
 use parity_codec::{Decode, Encode, Input, Output};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::prelude::v1::Vec;
+use std::fmt;
+use std::prelude::v1::{String, Vec};
 
+use crate::common::{hash256, Timespec, H256};
+use crate::state::tendermint::BlockHeight;
 use crate::tx::data::access::TxAccessPolicy;
 
+/// maximum length (in bytes) of the memo in tx attributes
+pub const MAX_MEMO_LEN: usize = 64;
+
+/// the last two hex digits of the chain ID (`None` if they aren't hex digits)
+pub fn chain_hex_id(chain_id: &str) -> Option<u8> {
+    let start = chain_id.len().checked_sub(2)?;
+    hex::decode(chain_id.get(start..)?)
+        .ok()
+        .map(|decoded| decoded[0])
+}
+
+/// the hash of the full chain ID: transactions that commit to it (in their attributes and hence in their IDs)
+/// can't be replayed on other chains with the same last two hex digits in their chain IDs
+pub fn chain_id_hash(chain_id: &str) -> H256 {
+    hash256::<Blake2s>(chain_id.as_bytes())
+}
+
+/// Until when a signed transaction can be included in a block
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TxExpiry {
+    /// the transaction expires once the last committed block reached this height
+    Height(BlockHeight),
+    /// the transaction expires once the last committed block time reached this time
+    Time(Timespec),
+}
+
+impl TxExpiry {
+    /// checks if the transaction can no longer be included in the block after the last committed one
+    pub fn is_expired(&self, last_block_height: BlockHeight, last_block_time: Timespec) -> bool {
+        match self {
+            TxExpiry::Height(height) => last_block_height >= *height,
+            TxExpiry::Time(time) => last_block_time >= *time,
+        }
+    }
+}
+
+impl fmt::Display for TxExpiry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxExpiry::Height(height) => write!(f, "height {}", height),
+            TxExpiry::Time(time) => write!(f, "time {}", time),
+        }
+    }
+}
+
 /// Tx extra metadata, e.g. network ID
 #[derive(Debug, Default, PartialEq, Eq, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TxAttributes {
     pub chain_hex_id: u8,
+    /// optional hash of the full chain ID (see `chain_id_hash`)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub chain_id_hash: Option<H256>,
     pub allowed_view: Vec<TxAccessPolicy>,
+    /// optional memo (e.g. a customer reference for exchange deposits), at most `MAX_MEMO_LEN` bytes
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub memo: Option<String>,
+    /// optional expiry of the transaction
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub expiry: Option<TxExpiry>,
     // TODO: other attributes, e.g. versioning info
 }
 
+fn decode_memo<I: Input>(input: &mut I) -> Option<String> {
+    let memo_bytes: Vec<u8> = Vec::decode(input)?;
+    if memo_bytes.len() > MAX_MEMO_LEN {
+        return None;
+    }
+    String::from_utf8(memo_bytes).ok()
+}
+
+fn encode_optional_memo<W: Output>(memo: &Option<String>, dest: &mut W) {
+    match memo {
+        None => dest.push_byte(0),
+        Some(memo) => {
+            dest.push_byte(1);
+            memo.as_bytes().encode_to(dest);
+        }
+    }
+}
+
+fn decode_optional_memo<I: Input>(input: &mut I) -> Option<Option<String>> {
+    match input.read_byte()? {
+        0 => Some(None),
+        1 => Some(Some(decode_memo(input)?)),
+        _ => None,
+    }
+}
+
 impl Encode for TxAttributes {
     fn encode_to<W: Output>(&self, dest: &mut W) {
-        dest.push_byte(0);
-        dest.push_byte(2);
-        dest.push_byte(self.chain_hex_id);
-        self.allowed_view.encode_to(dest);
+        if let Some(chain_id_hash) = &self.chain_id_hash {
+            dest.push_byte(3);
+            dest.push_byte(5);
+            dest.push_byte(self.chain_hex_id);
+            chain_id_hash.encode_to(dest);
+            self.allowed_view.encode_to(dest);
+            encode_optional_memo(&self.memo, dest);
+            self.expiry.encode_to(dest);
+            return;
+        }
+        match (&self.memo, &self.expiry) {
+            (None, None) => {
+                dest.push_byte(0);
+                dest.push_byte(2);
+                dest.push_byte(self.chain_hex_id);
+                self.allowed_view.encode_to(dest);
+            }
+            (Some(memo), None) => {
+                dest.push_byte(1);
+                dest.push_byte(3);
+                dest.push_byte(self.chain_hex_id);
+                self.allowed_view.encode_to(dest);
+                memo.as_bytes().encode_to(dest);
+            }
+            (memo, Some(expiry)) => {
+                dest.push_byte(2);
+                dest.push_byte(4);
+                dest.push_byte(self.chain_hex_id);
+                self.allowed_view.encode_to(dest);
+                encode_optional_memo(memo, dest);
+                expiry.encode_to(dest);
+            }
+        }
     }
 }
 
@@ -33,6 +147,36 @@ impl Decode for TxAttributes {
                 let allowed_view: Vec<TxAccessPolicy> = Vec::decode(input)?;
                 Some(TxAttributes::new_with_access(chain_hex_id, allowed_view))
             }
+            (1, 3) => {
+                let chain_hex_id: u8 = input.read_byte()?;
+                let allowed_view: Vec<TxAccessPolicy> = Vec::decode(input)?;
+                let memo = decode_memo(input)?;
+                let mut attributes = TxAttributes::new_with_access(chain_hex_id, allowed_view);
+                attributes.memo = Some(memo);
+                Some(attributes)
+            }
+            (2, 4) => {
+                let chain_hex_id: u8 = input.read_byte()?;
+                let allowed_view: Vec<TxAccessPolicy> = Vec::decode(input)?;
+                let memo = decode_optional_memo(input)?;
+                let expiry = TxExpiry::decode(input)?;
+                let mut attributes = TxAttributes::new_with_access(chain_hex_id, allowed_view);
+                attributes.memo = memo;
+                attributes.expiry = Some(expiry);
+                Some(attributes)
+            }
+            (3, 5) => {
+                let chain_hex_id: u8 = input.read_byte()?;
+                let chain_id_hash = H256::decode(input)?;
+                let allowed_view: Vec<TxAccessPolicy> = Vec::decode(input)?;
+                let memo = decode_optional_memo(input)?;
+                let expiry: Option<TxExpiry> = Option::decode(input)?;
+                let mut attributes = TxAttributes::new_with_access(chain_hex_id, allowed_view);
+                attributes.chain_id_hash = Some(chain_id_hash);
+                attributes.memo = memo;
+                attributes.expiry = expiry;
+                Some(attributes)
+            }
             _ => None,
         }
     }
@@ -43,7 +187,10 @@ impl TxAttributes {
     pub fn new(chain_hex_id: u8) -> Self {
         TxAttributes {
             chain_hex_id,
+            chain_id_hash: None,
             allowed_view: Vec::new(),
+            memo: None,
+            expiry: None,
         }
     }
 
@@ -51,7 +198,129 @@ impl TxAttributes {
     pub fn new_with_access(chain_hex_id: u8, allowed_view: Vec<TxAccessPolicy>) -> Self {
         TxAttributes {
             chain_hex_id,
+            chain_id_hash: None,
             allowed_view,
+            memo: None,
+            expiry: None,
         }
     }
+
+    /// creates tx attributes bound to the full chain ID (`None` if it doesn't end with two hex digits)
+    pub fn new_for_chain(chain_id: &str) -> Option<Self> {
+        let mut attributes = TxAttributes::new(chain_hex_id(chain_id)?);
+        attributes.chain_id_hash = Some(chain_id_hash(chain_id));
+        Some(attributes)
+    }
+
+    /// adds a memo to tx attributes (`None` if it's longer than `MAX_MEMO_LEN` bytes)
+    pub fn with_memo(mut self, memo: String) -> Option<Self> {
+        if memo.len() > MAX_MEMO_LEN {
+            None
+        } else {
+            self.memo = Some(memo);
+            Some(self)
+        }
+    }
+
+    /// sets the expiry of the transaction
+    pub fn with_expiry(mut self, expiry: TxExpiry) -> Self {
+        self.expiry = Some(expiry);
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn attributes_without_memo_should_keep_encoding() {
+        let attributes = TxAttributes::new(0xab);
+        assert_eq!(vec![0, 2, 0xab, 0], attributes.encode());
+        assert_eq!(
+            Some(attributes.clone()),
+            TxAttributes::decode(&mut attributes.encode().as_slice())
+        );
+    }
+
+    #[test]
+    fn attributes_with_memo_should_roundtrip() {
+        let attributes = TxAttributes::new(0xab)
+            .with_memo("customer 42".to_owned())
+            .unwrap();
+        let encoded = attributes.encode();
+        assert_eq!(&encoded[..2], &[1, 3]);
+        assert_eq!(
+            Some(attributes),
+            TxAttributes::decode(&mut encoded.as_slice())
+        );
+    }
+
+    #[test]
+    fn memo_should_be_bounded() {
+        let memo = "x".repeat(MAX_MEMO_LEN + 1);
+        assert!(TxAttributes::new(0).with_memo(memo.clone()).is_none());
+
+        let mut encoded = vec![1, 3, 0, 0];
+        memo.as_bytes().encode_to(&mut encoded);
+        assert!(TxAttributes::decode(&mut encoded.as_slice()).is_none());
+    }
+
+    #[test]
+    fn attributes_with_expiry_should_roundtrip() {
+        let attributes = TxAttributes::new(0xab).with_expiry(TxExpiry::Height(10));
+        let encoded = attributes.encode();
+        assert_eq!(&encoded[..2], &[2, 4]);
+        assert_eq!(
+            Some(attributes.clone()),
+            TxAttributes::decode(&mut encoded.as_slice())
+        );
+
+        let attributes = attributes
+            .with_memo("customer 42".to_owned())
+            .unwrap()
+            .with_expiry(TxExpiry::Time(1000));
+        assert_eq!(
+            Some(attributes.clone()),
+            TxAttributes::decode(&mut attributes.encode().as_slice())
+        );
+    }
+
+    #[test]
+    fn attributes_bound_to_chain_should_roundtrip() {
+        assert!(TxAttributes::new_for_chain("test-chain-y3m1e6-XY").is_none());
+        let attributes = TxAttributes::new_for_chain("test-chain-y3m1e6-AB").unwrap();
+        assert_eq!(0xab, attributes.chain_hex_id);
+        assert_ne!(
+            attributes.chain_id_hash,
+            TxAttributes::new_for_chain("devnet-AB")
+                .unwrap()
+                .chain_id_hash
+        );
+        let encoded = attributes.encode();
+        assert_eq!(&encoded[..2], &[3, 5]);
+        assert_eq!(
+            Some(attributes.clone()),
+            TxAttributes::decode(&mut encoded.as_slice())
+        );
+
+        let attributes = attributes
+            .with_memo("customer 42".to_owned())
+            .unwrap()
+            .with_expiry(TxExpiry::Height(10));
+        assert_eq!(
+            Some(attributes.clone()),
+            TxAttributes::decode(&mut attributes.encode().as_slice())
+        );
+    }
+
+    #[test]
+    fn expiry_should_count_from_last_block() {
+        let expiry = TxExpiry::Height(10);
+        assert!(!expiry.is_expired(9, 0));
+        assert!(expiry.is_expired(10, 0));
+        let expiry = TxExpiry::Time(1000);
+        assert!(!expiry.is_expired(100, 999));
+        assert!(expiry.is_expired(100, 1000));
+    }
 }
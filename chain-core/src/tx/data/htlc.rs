@@ -0,0 +1,103 @@
+use std::fmt;
+
+use blake2::Blake2s;
+use parity_codec::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::common::{hash256, Timespec, H256};
+use crate::init::address::RedeemAddress;
+use crate::tx::data::address::ExtendedAddr;
+
+/// The secret whose hash locks the output (32 bytes, so that the same secret can be used on other chains)
+pub type HashPreimage = [u8; 32];
+
+/// Calculates the hash lock of the secret (currently sha256, as commonly used by HTLCs on other chains)
+pub fn hash_lock(preimage: &HashPreimage) -> H256 {
+    hash256::<Sha256>(preimage)
+}
+
+/// Terms of a hash-time-locked output (e.g. for cross-chain atomic swaps):
+/// it can be spent by the recipient who reveals the preimage of the hash lock,
+/// or by the sender (refund address) once the timeout has passed.
+/// The address only commits to the hash of these terms, so they are revealed in the witness.
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HashTimeLock {
+    /// hash of the secret preimage
+    pub hash: H256,
+    /// address that can spend the output with the preimage
+    pub recipient: RedeemAddress,
+    /// address that can spend the output after the timeout
+    pub refund: RedeemAddress,
+    /// block time after which the refund address can spend the output
+    pub timeout: Timespec,
+}
+
+impl HashTimeLock {
+    /// creates new hash-time-lock terms
+    pub fn new(
+        hash: H256,
+        recipient: RedeemAddress,
+        refund: RedeemAddress,
+        timeout: Timespec,
+    ) -> Self {
+        HashTimeLock {
+            hash,
+            recipient,
+            refund,
+            timeout,
+        }
+    }
+
+    /// retrieves the hash the address commits to (currently blake2s(scale_code_bytes(terms)))
+    pub fn address_hash(&self) -> H256 {
+        hash256::<Blake2s>(&self.encode())
+    }
+
+    /// the address of outputs locked with these terms
+    pub fn address(&self) -> ExtendedAddr {
+        ExtendedAddr::HashTimeLock(self.address_hash())
+    }
+
+    /// checks if the preimage unlocks the hash lock
+    pub fn verify_preimage(&self, preimage: &HashPreimage) -> bool {
+        hash_lock(preimage) == self.hash
+    }
+}
+
+impl fmt::Display for HashTimeLock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hash: {} recipient: {} refund: {} timeout: {}",
+            hex::encode(&self.hash),
+            self.recipient,
+            self.refund,
+            self.timeout
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn preimage_should_unlock_hash() {
+        let preimage = [7u8; 32];
+        let lock = HashTimeLock::new(
+            hash_lock(&preimage),
+            RedeemAddress::from([1u8; 20]),
+            RedeemAddress::from([2u8; 20]),
+            100,
+        );
+        assert!(lock.verify_preimage(&preimage));
+        assert!(!lock.verify_preimage(&[8u8; 32]));
+        assert_eq!(
+            hex::encode(&lock.hash),
+            "4bb06f8e4e3a7715d201d573d0aa423762e55dabd61a2c02278fa56cc6d294e0"
+        );
+    }
+}
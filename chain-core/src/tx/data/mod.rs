@@ -6,6 +6,8 @@ pub mod access;
 pub mod address;
 /// Miscellaneous TX attributes, such as network ID
 pub mod attribute;
+/// Hash-time-locked outputs (e.g. for atomic swaps)
+pub mod htlc;
 /// Transaction inputs (pointers to previous transaction outputs)
 pub mod input;
 /// Transaction outputs (amount to an address)
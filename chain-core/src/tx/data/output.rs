@@ -8,6 +8,25 @@ use crate::common::Timespec;
 use crate::init::coin::Coin;
 use crate::tx::data::address::ExtendedAddr;
 
+/// Timelock relative to the block in which the output was created
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RelativeTimelock {
+    /// number of seconds after the creating block's time
+    Seconds(u32),
+    /// number of blocks after the creating block
+    Blocks(u32),
+}
+
+impl fmt::Display for RelativeTimelock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelativeTimelock::Seconds(seconds) => write!(f, "{} seconds", seconds),
+            RelativeTimelock::Blocks(blocks) => write!(f, "{} blocks", blocks),
+        }
+    }
+}
+
 /// Tx Output composed of an address and a coin value
 /// TODO: custom Encode/Decode when data structures are finalized (for backwards/forwards compatibility, encoders/decoders should be able to work with old formats)
 #[derive(Debug, PartialEq, Eq, Clone, Encode, Decode)]
@@ -16,6 +35,7 @@ pub struct TxOut {
     pub address: ExtendedAddr,
     pub value: Coin,
     pub valid_from: Option<Timespec>,
+    pub relative_lock: Option<RelativeTimelock>,
 }
 
 impl fmt::Display for TxOut {
@@ -31,6 +51,7 @@ impl TxOut {
             address,
             value,
             valid_from: None,
+            relative_lock: None,
         }
     }
 
@@ -40,6 +61,21 @@ impl TxOut {
             address,
             value,
             valid_from: Some(valid_from),
+            relative_lock: None,
+        }
+    }
+
+    /// creates a TX output with timelock relative to the block it's included in
+    pub fn new_with_relative_timelock(
+        address: ExtendedAddr,
+        value: Coin,
+        relative_lock: RelativeTimelock,
+    ) -> Self {
+        TxOut {
+            address,
+            value,
+            valid_from: None,
+            relative_lock: Some(relative_lock),
         }
     }
 }
@@ -65,6 +65,26 @@ impl Milli {
     pub fn as_millis(self) -> u64 {
         self.0
     }
+
+    /// takes the number of thousandths
+    pub fn from_millis(millis: u64) -> Self {
+        Milli(millis)
+    }
+
+    /// addition that returns None on overflow
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Milli)
+    }
+
+    /// multiplication that returns None if the product doesn't fit
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let v = u128::from(self.0) * u128::from(other.0) / 1000;
+        if v > u128::from(u64::max_value()) {
+            None
+        } else {
+            Some(Milli(v as u64))
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -156,11 +176,23 @@ impl LinearFee {
     }
 
     pub fn estimate(&self, sz: usize) -> Result<Fee, CoinError> {
-        let msz = Milli::integral(sz as u64);
-        let fee = self.constant + self.coefficient * msz;
+        let fee = self.checked_fee(sz).ok_or_else(fee_overflow)?;
         let coin = Coin::new(fee.to_integral())?;
         Ok(Fee(coin))
     }
+
+    /// the fee for the size (None on overflow)
+    fn checked_fee(&self, sz: usize) -> Option<Milli> {
+        let msz = (sz as u64).checked_mul(1000).map(Milli::from_millis)?;
+        self.coefficient
+            .checked_mul(msz)?
+            .checked_add(self.constant)
+    }
+}
+
+/// the error of fee computations that overflow (the fee would be out of the coin bound anyway)
+fn fee_overflow() -> CoinError {
+    CoinError::OutOfBound(u64::max_value())
 }
 
 /// Calculation of fees for a specific chosen algorithm
@@ -169,15 +201,243 @@ pub trait FeeAlgorithm: Send + Sync {
     fn calculate_for_txaux(&self, txaux: &TxAux) -> Result<Fee, CoinError>;
 }
 
+/// the size the fee is computed from (the payload in transfers, otherwise the whole encoded transaction)
+fn txaux_size(txaux: &TxAux) -> usize {
+    match txaux {
+        TxAux::TransferTx { txpayload, .. } => txpayload.len(),
+        _ => txaux.encode().len(),
+    }
+}
+
 impl FeeAlgorithm for LinearFee {
     fn calculate_fee(&self, num_bytes: usize) -> Result<Fee, CoinError> {
         self.estimate(num_bytes)
     }
 
     fn calculate_for_txaux(&self, txaux: &TxAux) -> Result<Fee, CoinError> {
+        self.estimate(txaux_size(txaux))
+    }
+}
+
+/// Separate linear fees for each transaction type
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TxTypeFees {
+    /// value transfers
+    pub transfer: LinearFee,
+    /// deposits to staked states (and delegations to council nodes)
+    pub deposit: LinearFee,
+    /// unbonding of staked amounts (and undelegations from council nodes)
+    pub unbond: LinearFee,
+    /// withdrawals of unbonded amounts (and reward claims)
+    pub withdraw: LinearFee,
+    /// council node operations (joining / leaving the council, unjailing, governance)
+    pub council_node_op: LinearFee,
+}
+
+impl TxTypeFees {
+    /// the linear fee for the transaction type
+    pub fn for_txaux(&self, txaux: &TxAux) -> &LinearFee {
         match txaux {
-            TxAux::TransferTx { txpayload, .. } => self.estimate(txpayload.len()),
-            _ => self.estimate(txaux.encode().len()),
+            TxAux::TransferTx { .. } => &self.transfer,
+            TxAux::DepositStakeTx(..) | TxAux::DelegateTx(..) => &self.deposit,
+            TxAux::UnbondStakeTx(..) | TxAux::UndelegateTx(..) => &self.unbond,
+            TxAux::WithdrawUnbondedStakeTx(..) | TxAux::ClaimRewardsTx(..) => &self.withdraw,
+            TxAux::NodeJoinTx(..)
+            | TxAux::UnjailTx(..)
+            | TxAux::NodeLeaveTx(..)
+            | TxAux::ProposalTx(..)
+            | TxAux::VoteTx(..) => &self.council_node_op,
+        }
+    }
+}
+
+impl FeeAlgorithm for TxTypeFees {
+    /// without the transaction, it's estimated as a transfer
+    fn calculate_fee(&self, num_bytes: usize) -> Result<Fee, CoinError> {
+        self.transfer.estimate(num_bytes)
+    }
+
+    fn calculate_for_txaux(&self, txaux: &TxAux) -> Result<Fee, CoinError> {
+        self.for_txaux(txaux).calculate_for_txaux(txaux)
+    }
+}
+
+/// Linear fee plus a price for each transaction input and output
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InputOutputFee {
+    /// the fee based on the transaction size
+    pub linear: LinearFee,
+    /// price of each spent input
+    pub per_input: Milli,
+    /// price of each created output
+    pub per_output: Milli,
+}
+
+impl InputOutputFee {
+    /// the number of inputs and outputs in the transaction
+    fn count_inputs_outputs(txaux: &TxAux) -> (usize, usize) {
+        match txaux {
+            TxAux::TransferTx {
+                inputs,
+                no_of_outputs,
+                ..
+            } => (inputs.len(), *no_of_outputs as usize),
+            TxAux::DepositStakeTx(tx, _) => (tx.inputs.len(), 0),
+            TxAux::WithdrawUnbondedStakeTx(tx, _) => (0, tx.outputs.len()),
+            TxAux::ClaimRewardsTx(tx, _) => (0, tx.outputs.len()),
+            _ => (0, 0),
+        }
+    }
+}
+
+impl FeeAlgorithm for InputOutputFee {
+    /// without the transaction, only the size-based part is estimated
+    fn calculate_fee(&self, num_bytes: usize) -> Result<Fee, CoinError> {
+        self.linear.estimate(num_bytes)
+    }
+
+    fn calculate_for_txaux(&self, txaux: &TxAux) -> Result<Fee, CoinError> {
+        let (inputs, outputs) = InputOutputFee::count_inputs_outputs(txaux);
+        let inputs_fee = self.per_input.checked_mul(Milli::integral(inputs as u64));
+        let outputs_fee = self.per_output.checked_mul(Milli::integral(outputs as u64));
+        let fee = self
+            .linear
+            .checked_fee(txaux_size(txaux))
+            .and_then(|fee| fee.checked_add(inputs_fee?))
+            .and_then(|fee| fee.checked_add(outputs_fee?))
+            .ok_or_else(fee_overflow)?;
+        Ok(Fee(Coin::new(fee.to_integral())?))
+    }
+}
+
+/// Linear fee scaled by a multiplier that is adjusted after each block,
+/// so that the fee rises when blocks are fuller than the target and falls (down to the base fee) when they are emptier
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DynamicFee {
+    /// the fee at the multiplier of 1
+    pub base: LinearFee,
+    /// the targeted total size of transactions in a block (in bytes)
+    pub target_block_size: u64,
+    /// the current multiplier of the base fee (at least 1)
+    pub multiplier: Milli,
+    /// the multiplier doesn't rise above this value (at least 1)
+    pub max_multiplier: Milli,
+}
+
+impl DynamicFee {
+    /// the maximal change of the multiplier in a block is 1/ADJUSTMENT_DENOMINATOR
+    /// (when the block is empty or twice the target size)
+    pub const ADJUSTMENT_DENOMINATOR: i128 = 8;
+
+    /// creates a new dynamic fee (starting at the base fee)
+    pub fn new(base: LinearFee, target_block_size: u64, max_multiplier: Milli) -> Self {
+        DynamicFee {
+            base,
+            target_block_size,
+            multiplier: Milli::integral(1),
+            max_multiplier,
+        }
+    }
+
+    /// checks the multiplier is between 1 and the maximal multiplier
+    pub fn is_valid(&self) -> bool {
+        Milli::integral(1) <= self.multiplier && self.multiplier <= self.max_multiplier
+    }
+
+    /// adjusts the multiplier by `(block_size - target) / (target * ADJUSTMENT_DENOMINATOR)` of its value
+    /// (the multiplier doesn't fall below 1 or rise above the maximal multiplier)
+    pub fn adjust(&mut self, block_size: u64) {
+        if self.target_block_size == 0 {
+            return;
+        }
+        let multiplier = i128::from(self.multiplier.as_millis());
+        let target = i128::from(self.target_block_size);
+        let delta = multiplier * (i128::from(block_size) - target)
+            / (target * DynamicFee::ADJUSTMENT_DENOMINATOR);
+        let adjusted = (multiplier + delta)
+            .min(i128::from(self.max_multiplier.as_millis()))
+            .max(1000);
+        self.multiplier = Milli::from_millis(adjusted as u64);
+    }
+}
+
+impl FeeAlgorithm for DynamicFee {
+    fn calculate_fee(&self, num_bytes: usize) -> Result<Fee, CoinError> {
+        let fee = self
+            .base
+            .checked_fee(num_bytes)
+            .and_then(|fee| fee.checked_mul(self.multiplier))
+            .ok_or_else(fee_overflow)?;
+        Ok(Fee(Coin::new(fee.to_integral())?))
+    }
+
+    fn calculate_for_txaux(&self, txaux: &TxAux) -> Result<Fee, CoinError> {
+        self.calculate_fee(txaux_size(txaux))
+    }
+}
+
+/// The fee policy of the network (the same fee computation is used in the node and in the wallets)
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FeePolicy {
+    /// the same linear fee for all transactions
+    Linear(LinearFee),
+    /// different linear fees for different transaction types
+    PerTxType(TxTypeFees),
+    /// linear fee plus prices per input and output
+    PerInputOutput(InputOutputFee),
+    /// linear fee that targets a block fullness
+    Dynamic(DynamicFee),
+}
+
+impl FeePolicy {
+    /// checks the fee policy state is consistent (e.g. the dynamic fee multiplier is within its bounds)
+    pub fn is_valid(&self) -> bool {
+        match self {
+            FeePolicy::Dynamic(fee) => fee.is_valid(),
+            _ => true,
+        }
+    }
+
+    /// updates the fee policy state after a block with transactions of the given total size was processed;
+    /// returns true if it changed
+    pub fn adjust(&mut self, block_size: u64) -> bool {
+        match self {
+            FeePolicy::Dynamic(fee) => {
+                let multiplier = fee.multiplier;
+                fee.adjust(block_size);
+                multiplier != fee.multiplier
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<LinearFee> for FeePolicy {
+    fn from(fee: LinearFee) -> Self {
+        FeePolicy::Linear(fee)
+    }
+}
+
+impl FeeAlgorithm for FeePolicy {
+    fn calculate_fee(&self, num_bytes: usize) -> Result<Fee, CoinError> {
+        match self {
+            FeePolicy::Linear(fee) => fee.calculate_fee(num_bytes),
+            FeePolicy::PerTxType(fee) => fee.calculate_fee(num_bytes),
+            FeePolicy::PerInputOutput(fee) => fee.calculate_fee(num_bytes),
+            FeePolicy::Dynamic(fee) => fee.calculate_fee(num_bytes),
+        }
+    }
+
+    fn calculate_for_txaux(&self, txaux: &TxAux) -> Result<Fee, CoinError> {
+        match self {
+            FeePolicy::Linear(fee) => fee.calculate_for_txaux(txaux),
+            FeePolicy::PerTxType(fee) => fee.calculate_for_txaux(txaux),
+            FeePolicy::PerInputOutput(fee) => fee.calculate_for_txaux(txaux),
+            FeePolicy::Dynamic(fee) => fee.calculate_for_txaux(txaux),
         }
     }
 }
@@ -185,6 +445,11 @@ impl FeeAlgorithm for LinearFee {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::state::account::{StakedStateOpWitness, WithdrawUnbondedTx};
+    use crate::tx::data::attribute::TxAttributes;
+    use crate::tx::data::input::{TxoIndex, TxoPointer};
+    use crate::tx::TransactionId;
+    use secp256k1::{Message, Secp256k1, SecretKey};
 
     fn test_milli_add_eq(v1: u64, v2: u64) {
         let v = v1 + v2;
@@ -217,4 +482,131 @@ mod test {
         test_milli_mul_eq(241, 900001_900);
         test_milli_mul_eq(241, 400);
     }
+
+    fn get_transfer_txaux(no_of_inputs: usize, no_of_outputs: TxoIndex) -> TxAux {
+        TxAux::TransferTx {
+            txid: [0; 32],
+            inputs: vec![TxoPointer::new([0; 32], 0); no_of_inputs],
+            no_of_outputs,
+            nonce: [0; 12],
+            txpayload: vec![0; 100],
+        }
+    }
+
+    #[test]
+    fn input_output_fee_should_price_inputs_and_outputs() {
+        let fee = InputOutputFee {
+            linear: LinearFee::new(Milli::new(1, 0), Milli::new(0, 10)),
+            per_input: Milli::new(2, 0),
+            per_output: Milli::new(0, 500),
+        };
+        let txaux = get_transfer_txaux(2, 3);
+        // 1 + 0.01 * 100 + 2 * 2 + 0.5 * 3
+        assert_eq!(
+            Coin::new(8).unwrap(),
+            fee.calculate_for_txaux(&txaux).unwrap().to_coin()
+        );
+    }
+
+    #[test]
+    fn tx_type_fees_should_use_the_fee_of_the_type() {
+        let linear = LinearFee::new(Milli::new(1, 0), Milli::new(0, 0));
+        let fees = TxTypeFees {
+            transfer: LinearFee::new(Milli::new(5, 0), Milli::new(0, 0)),
+            deposit: linear,
+            unbond: linear,
+            withdraw: linear,
+            council_node_op: linear,
+        };
+        let policy = FeePolicy::PerTxType(fees);
+        assert_eq!(
+            Coin::new(5).unwrap(),
+            policy
+                .calculate_for_txaux(&get_transfer_txaux(1, 1))
+                .unwrap()
+                .to_coin()
+        );
+    }
+
+    #[test]
+    fn dynamic_fee_should_target_block_size() {
+        let mut policy = FeePolicy::Dynamic(DynamicFee::new(
+            LinearFee::new(Milli::new(8, 0), Milli::new(0, 0)),
+            1000,
+            Milli::integral(10),
+        ));
+        let txaux = get_transfer_txaux(1, 1);
+        // empty blocks don't lower the fee below the base fee
+        assert!(!policy.adjust(0));
+        assert_eq!(
+            Coin::new(8).unwrap(),
+            policy.calculate_for_txaux(&txaux).unwrap().to_coin()
+        );
+        // full block (2x target) raises the fee by 1/8
+        assert!(policy.adjust(2000));
+        assert_eq!(
+            Coin::new(9).unwrap(),
+            policy.calculate_for_txaux(&txaux).unwrap().to_coin()
+        );
+        assert!(!policy.adjust(1000));
+        assert!(policy.adjust(0));
+        assert_eq!(
+            Coin::new(8).unwrap(),
+            policy.calculate_for_txaux(&txaux).unwrap().to_coin()
+        );
+    }
+
+    #[test]
+    fn dynamic_fee_multiplier_should_be_capped() {
+        let mut fee = DynamicFee::new(
+            LinearFee::new(Milli::new(8, 0), Milli::new(0, 0)),
+            1,
+            Milli::new(1, 500),
+        );
+        assert!(fee.is_valid());
+        for _ in 0..10 {
+            fee.adjust(u64::max_value());
+        }
+        assert_eq!(Milli::new(1, 500), fee.multiplier);
+        assert_eq!(
+            Coin::new(12).unwrap(),
+            fee.calculate_fee(0).unwrap().to_coin()
+        );
+        fee.max_multiplier = Milli::new(0, 500);
+        assert!(!fee.is_valid());
+    }
+
+    #[test]
+    fn fee_overflow_should_be_an_error() {
+        let max = Milli::from_millis(u64::max_value());
+        assert!(max.checked_add(Milli::from_millis(1)).is_none());
+        assert!(max.checked_mul(Milli::new(1, 1)).is_none());
+        assert_eq!(Some(max), max.checked_mul(Milli::integral(1)));
+        let mut fee = DynamicFee::new(LinearFee::new(Milli::new(0, 0), max), 1000, max);
+        fee.multiplier = Milli::integral(2);
+        assert!(fee.calculate_fee(1).is_err());
+        assert!(fee.base.estimate(usize::max_value()).is_err());
+    }
+
+    fn get_withdraw_txaux(attributes: TxAttributes) -> TxAux {
+        let secp = Secp256k1::new();
+        let sk = SecretKey::from_slice(&[0xcc; 32]).unwrap();
+        let tx = WithdrawUnbondedTx::new(0, vec![], attributes);
+        let msg = Message::from_slice(&tx.id()).unwrap();
+        let witness = StakedStateOpWitness::new(secp.sign_recoverable(&msg, &sk));
+        TxAux::WithdrawUnbondedStakeTx(tx, witness)
+    }
+
+    #[test]
+    fn memo_should_be_charged() {
+        let fee = LinearFee::new(Milli::new(1, 0), Milli::new(1, 0));
+        let without_memo = get_withdraw_txaux(TxAttributes::new(0));
+        let with_memo = get_withdraw_txaux(TxAttributes::new(0).with_memo("x".repeat(32)).unwrap());
+        let base = u64::from(fee.calculate_for_txaux(&without_memo).unwrap().to_coin());
+        // 32 memo bytes + 1 byte of its length prefix
+        assert_eq!(
+            base + 33,
+            u64::from(fee.calculate_for_txaux(&with_memo).unwrap().to_coin())
+        );
+    }
 }
@@ -4,6 +4,8 @@ use std::prelude::v1::Vec;
 pub mod data;
 /// Transaction fee calculation
 pub mod fee;
+/// Machine-readable reasons for rejecting transactions
+pub mod rejection;
 /// Witness structures (e.g. signatures) for transactions
 pub mod witness;
 
@@ -13,7 +15,11 @@ use parity_codec::{Decode, Encode};
 
 use self::data::Tx;
 use self::witness::TxWitness;
-use crate::state::account::{DepositBondTx, StakedStateOpWitness, UnbondTx, WithdrawUnbondedTx};
+use crate::state::account::{
+    ClaimRewardsTx, DelegateTx, DepositBondTx, NodeJoinRequestTx, NodeLeaveTx,
+    StakedStateOpWitness, UnbondTx, UndelegateTx, UnjailTx, WithdrawUnbondedTx,
+};
+use crate::state::governance::{ParamChangeProposalTx, ParamChangeVoteTx};
 use crate::tx::data::{txid_hash, TxId};
 use data::input::{TxoIndex, TxoPointer};
 
@@ -56,6 +62,22 @@ pub enum TxAux {
     UnbondStakeTx(UnbondTx, StakedStateOpWitness),
     /// Tx that "creates" utxos out of account state; withdraws unbonded stake (witness for account)
     WithdrawUnbondedStakeTx(WithdrawUnbondedTx, StakedStateOpWitness),
+    /// Tx that registers a staked state with the required bonded amount as a council node (witness for account)
+    NodeJoinTx(NodeJoinRequestTx, StakedStateOpWitness),
+    /// Tx that unjails a council node after its jail time (witness for account)
+    UnjailTx(UnjailTx, StakedStateOpWitness),
+    /// Tx that removes a council node from the council (witness for account)
+    NodeLeaveTx(NodeLeaveTx, StakedStateOpWitness),
+    /// Tx that proposes a network parameter change by a council node (witness for account)
+    ProposalTx(ParamChangeProposalTx, StakedStateOpWitness),
+    /// Tx that votes on a network parameter change proposal by a council node (witness for account)
+    VoteTx(ParamChangeVoteTx, StakedStateOpWitness),
+    /// Tx that modifies account state -- moves some bonded stake into a delegation to a council node (witness for account)
+    DelegateTx(DelegateTx, StakedStateOpWitness),
+    /// Tx that modifies account state -- moves some delegated stake into unbonded (witness for account)
+    UndelegateTx(UndelegateTx, StakedStateOpWitness),
+    /// Tx that claims the accrued rewards of account state -- compounds them into bonded or "creates" utxos (witness for account)
+    ClaimRewardsTx(ClaimRewardsTx, StakedStateOpWitness),
 }
 
 pub trait TransactionId: Encode {
@@ -73,6 +95,14 @@ impl TxAux {
             TxAux::DepositStakeTx(tx, _) => tx.id(),
             TxAux::UnbondStakeTx(tx, _) => tx.id(),
             TxAux::WithdrawUnbondedStakeTx(tx, _) => tx.id(),
+            TxAux::NodeJoinTx(tx, _) => tx.id(),
+            TxAux::UnjailTx(tx, _) => tx.id(),
+            TxAux::NodeLeaveTx(tx, _) => tx.id(),
+            TxAux::ProposalTx(tx, _) => tx.id(),
+            TxAux::VoteTx(tx, _) => tx.id(),
+            TxAux::DelegateTx(tx, _) => tx.id(),
+            TxAux::UndelegateTx(tx, _) => tx.id(),
+            TxAux::ClaimRewardsTx(tx, _) => tx.id(),
         }
     }
 }
@@ -96,6 +126,14 @@ impl fmt::Display for TxAux {
             TxAux::DepositStakeTx(tx, witness) => display_tx_witness(f, tx, witness),
             TxAux::UnbondStakeTx(tx, witness) => display_tx_witness(f, tx, witness),
             TxAux::WithdrawUnbondedStakeTx(tx, witness) => display_tx_witness(f, tx, witness),
+            TxAux::NodeJoinTx(tx, witness) => display_tx_witness(f, tx, witness),
+            TxAux::UnjailTx(tx, witness) => display_tx_witness(f, tx, witness),
+            TxAux::NodeLeaveTx(tx, witness) => display_tx_witness(f, tx, witness),
+            TxAux::ProposalTx(tx, witness) => display_tx_witness(f, tx, witness),
+            TxAux::VoteTx(tx, witness) => display_tx_witness(f, tx, witness),
+            TxAux::DelegateTx(tx, witness) => display_tx_witness(f, tx, witness),
+            TxAux::UndelegateTx(tx, witness) => display_tx_witness(f, tx, witness),
+            TxAux::ClaimRewardsTx(tx, witness) => display_tx_witness(f, tx, witness),
         }
     }
 }
@@ -104,9 +142,11 @@ impl fmt::Display for TxAux {
 pub mod tests {
     use super::*;
     use crate::common::MerkleTree;
+    use crate::init::address::RedeemAddress;
     use crate::init::coin::Coin;
     use crate::tx::data::access::{TxAccess, TxAccessPolicy};
     use crate::tx::data::address::ExtendedAddr;
+    use crate::tx::data::htlc::{hash_lock, HashTimeLock};
     use crate::tx::data::input::TxoPointer;
     use crate::tx::data::output::TxOut;
     use crate::tx::witness::tree::RawPubkey;
@@ -127,6 +167,10 @@ pub mod tests {
         let secp = Secp256k1::new();
         let sk1 = SecretKey::from_slice(&[0xcc; 32][..]).expect("secret key");
         let pk1 = PublicKey::from_secret_key(&secp, &sk1);
+        tx.add_output(TxOut::new(
+            ExtendedAddr::BasicRedeem(RedeemAddress::from(&pk1)),
+            Coin::unit(),
+        ));
         let raw_pk1 = RawPubkey::from(pk1.serialize());
 
         let raw_public_keys = vec![raw_pk1];
@@ -143,7 +187,18 @@ pub mod tests {
             schnorr_sign(&secp, &msg, &sk1).0,
             merkle.generate_proof(raw_public_keys[0].clone()).unwrap(),
         );
-        let txa = PlainTxAux::TransferTx(tx, vec![w1].into());
+        let w2 = TxInWitness::BasicRedeem(secp.sign_recoverable(&msg, &sk1));
+        let w3 = TxInWitness::HashTimeLock(
+            HashTimeLock::new(
+                hash_lock(&[0xdd; 32]),
+                RedeemAddress::from(&pk1),
+                RedeemAddress::default(),
+                10,
+            ),
+            Some([0xdd; 32]),
+            secp.sign_recoverable(&msg, &sk1),
+        );
+        let txa = PlainTxAux::TransferTx(tx, vec![w1, w2, w3].into());
         let mut encoded: Vec<u8> = txa.encode();
         let mut data: &[u8] = encoded.as_mut();
         let decoded = PlainTxAux::decode(&mut data).expect("decode tx aux");
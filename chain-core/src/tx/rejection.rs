@@ -0,0 +1,208 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Reason why a transaction was rejected in CheckTx / DeliverTx
+/// (sent as the response code, so that clients don't need to parse the log).
+/// The numeric codes are stable: new reasons only get new codes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u32)]
+pub enum RejectionCode {
+    /// transaction couldn't be deserialized
+    InvalidFormat = 1,
+    /// chain hex ID does not match
+    WrongChainHexId = 2,
+    /// transaction has no inputs
+    NoInputs = 3,
+    /// transaction has no outputs
+    NoOutputs = 4,
+    /// transaction has duplicated inputs
+    DuplicateInputs = 5,
+    /// output with no credited value
+    ZeroCoin = 6,
+    /// input or output summation error
+    InvalidSum = 7,
+    /// transaction has more witnesses than inputs
+    UnexpectedWitnesses = 8,
+    /// transaction has more inputs than witnesses
+    MissingWitnesses = 9,
+    /// transaction spends an invalid input
+    InvalidInput = 10,
+    /// transaction spends an input that was already spent
+    InputSpent = 11,
+    /// transaction input output coin (plus fee) sums don't match
+    InputOutputDoNotMatch = 12,
+    /// output transaction is in timelock that hasn't passed
+    OutputInTimelock = 13,
+    /// witness (signature or proof) verification failed
+    InvalidWitness = 14,
+    /// storage error in the node
+    StorageError = 15,
+    /// enclave error or invalid transaction
+    EnclaveRejected = 16,
+    /// staked state not found
+    AccountNotFound = 17,
+    /// staked state not unbonded
+    AccountNotUnbonded = 18,
+    /// outputs created out of a staked state are not time-locked to unbonding period
+    AccountWithdrawOutputNotLocked = 19,
+    /// incorrect nonce supplied in staked state operation
+    AccountIncorrectNonce = 20,
+    /// staked state is jailed
+    AccountJailed = 21,
+    /// staked state is not jailed
+    AccountNotJailed = 22,
+    /// staked state doesn't have the required council node stake bonded
+    AccountNotEnoughCouncilNodeStake = 23,
+    /// staked state or the consensus public key is already used by a council node
+    DuplicateCouncilNode = 24,
+    /// staked state is not associated with a council node
+    NotCouncilNode = 25,
+    /// proposal activation height is not after the next block
+    InvalidActivationHeight = 26,
+    /// proposal doesn't exist or its voting already ended
+    ProposalNotFound = 27,
+    /// staked state's unbonded amount is still locked by its vesting schedule
+    AccountVestingLocked = 28,
+    /// transaction expired before it could be included
+    TxExpired = 29,
+    /// hash of the full chain ID does not match
+    WrongChainId = 30,
+    /// transaction spends an input or uses a nonce already used by another transaction in the mempool
+    MempoolConflict = 31,
+    /// staked state didn't delegate enough to the council node
+    DelegationNotFound = 32,
+    /// the stake delegated to the council node was fully slashed (so it can't take new delegations)
+    DelegationSlashed = 33,
+    /// transaction does not commit to the full chain ID (required since the activation height)
+    MissingChainId = 34,
+    /// staked state can't delegate to its own council node
+    SelfDelegation = 35,
+    /// the council node is jailed (so it can't take new delegations)
+    CouncilNodeJailed = 36,
+}
+
+impl RejectionCode {
+    /// the numeric code sent in CheckTx / DeliverTx responses (0 is reserved for accepted transactions)
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+}
+
+impl TryFrom<u32> for RejectionCode {
+    type Error = ();
+
+    fn try_from(code: u32) -> Result<Self, Self::Error> {
+        use self::RejectionCode::*;
+        match code {
+            1 => Ok(InvalidFormat),
+            2 => Ok(WrongChainHexId),
+            3 => Ok(NoInputs),
+            4 => Ok(NoOutputs),
+            5 => Ok(DuplicateInputs),
+            6 => Ok(ZeroCoin),
+            7 => Ok(InvalidSum),
+            8 => Ok(UnexpectedWitnesses),
+            9 => Ok(MissingWitnesses),
+            10 => Ok(InvalidInput),
+            11 => Ok(InputSpent),
+            12 => Ok(InputOutputDoNotMatch),
+            13 => Ok(OutputInTimelock),
+            14 => Ok(InvalidWitness),
+            15 => Ok(StorageError),
+            16 => Ok(EnclaveRejected),
+            17 => Ok(AccountNotFound),
+            18 => Ok(AccountNotUnbonded),
+            19 => Ok(AccountWithdrawOutputNotLocked),
+            20 => Ok(AccountIncorrectNonce),
+            21 => Ok(AccountJailed),
+            22 => Ok(AccountNotJailed),
+            23 => Ok(AccountNotEnoughCouncilNodeStake),
+            24 => Ok(DuplicateCouncilNode),
+            25 => Ok(NotCouncilNode),
+            26 => Ok(InvalidActivationHeight),
+            27 => Ok(ProposalNotFound),
+            28 => Ok(AccountVestingLocked),
+            29 => Ok(TxExpired),
+            30 => Ok(WrongChainId),
+            31 => Ok(MempoolConflict),
+            32 => Ok(DelegationNotFound),
+            33 => Ok(DelegationSlashed),
+            34 => Ok(MissingChainId),
+            35 => Ok(SelfDelegation),
+            36 => Ok(CouncilNodeJailed),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for RejectionCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::RejectionCode::*;
+        let reason = match self {
+            InvalidFormat => "transaction couldn't be deserialized",
+            WrongChainHexId => "chain hex ID does not match",
+            NoInputs => "transaction has no inputs",
+            NoOutputs => "transaction has no outputs",
+            DuplicateInputs => "duplicated inputs",
+            ZeroCoin => "output with no credited value",
+            InvalidSum => "input or output sum error",
+            UnexpectedWitnesses => "transaction has more witnesses than inputs",
+            MissingWitnesses => "transaction has more inputs than witnesses",
+            InvalidInput => "transaction spends an invalid input",
+            InputSpent => "transaction spends an input that was already spent",
+            InputOutputDoNotMatch => "transaction input output coin (plus fee) sums don't match",
+            OutputInTimelock => "output transaction is in timelock",
+            InvalidWitness => "invalid witness",
+            StorageError => "storage error",
+            EnclaveRejected => "enclave error or invalid TX",
+            AccountNotFound => "account not found",
+            AccountNotUnbonded => "account not unbonded for withdrawal",
+            AccountWithdrawOutputNotLocked => {
+                "account withdrawal outputs not time-locked to unbonded_from"
+            }
+            AccountIncorrectNonce => "incorrect transaction count for account operation",
+            AccountJailed => "account is jailed",
+            AccountNotJailed => "account is not jailed",
+            AccountNotEnoughCouncilNodeStake => {
+                "account doesn't have the required council node stake bonded"
+            }
+            DuplicateCouncilNode => {
+                "account or consensus public key is already used by a council node"
+            }
+            NotCouncilNode => "account is not associated with a council node",
+            InvalidActivationHeight => "proposal activation height is not after the next block",
+            ProposalNotFound => "proposal not found or its voting already ended",
+            AccountVestingLocked => {
+                "account's unbonded amount is still locked by its vesting schedule"
+            }
+            TxExpired => "transaction expired",
+            WrongChainId => "full chain ID does not match",
+            MempoolConflict => "transaction conflicts with another transaction in the mempool",
+            DelegationNotFound => "account didn't delegate enough to the council node",
+            DelegationSlashed => "stake delegated to the council node was fully slashed",
+            MissingChainId => "hash of the full chain ID is required",
+            SelfDelegation => "account can't delegate to its own council node",
+            CouncilNodeJailed => "council node is jailed",
+        };
+        write!(f, "{} (code {})", reason, self.code())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn codes_should_roundtrip() {
+        for code in 1..=36 {
+            let rejection = RejectionCode::try_from(code).expect("known code");
+            assert_eq!(rejection.code(), code);
+        }
+        assert!(RejectionCode::try_from(0).is_err());
+        assert!(RejectionCode::try_from(37).is_err());
+    }
+}
@@ -6,9 +6,14 @@ use std::prelude::v1::Vec;
 
 use parity_codec::{Decode, Encode, Input, Output};
 // TODO: switch to normal signatures + explicit public key
-use secp256k1::{self, recovery::RecoverableSignature, schnorrsig::SchnorrSignature};
+use secp256k1::{
+    self,
+    recovery::{RecoverableSignature, RecoveryId},
+    schnorrsig::SchnorrSignature,
+};
 
 use crate::common::Proof;
+use crate::tx::data::htlc::{HashPreimage, HashTimeLock};
 use crate::tx::witness::tree::{RawPubkey, RawSignature};
 
 pub type EcdsaSignature = RecoverableSignature;
@@ -53,6 +58,26 @@ impl ::std::ops::DerefMut for TxWitness {
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum TxInWitness {
     TreeSig(SchnorrSignature, Proof<RawPubkey>),
+    /// spends a single-key address (the public key is recovered from the signature)
+    BasicRedeem(EcdsaSignature),
+    /// spends a hash-time-locked address: with the preimage (by the recipient)
+    /// or without it after the timeout (by the refund address)
+    HashTimeLock(HashTimeLock, Option<HashPreimage>, EcdsaSignature),
+}
+
+fn encode_ecdsa_signature<W: Output>(sig: &EcdsaSignature, dest: &mut W) {
+    let (recovery_id, serialized_sig) = sig.serialize_compact();
+    // recovery_id is one of 0 | 1 | 2 | 3
+    let rid = recovery_id.to_i32() as u8;
+    dest.push_byte(rid);
+    serialized_sig.encode_to(dest);
+}
+
+fn decode_ecdsa_signature<I: Input>(input: &mut I) -> Option<EcdsaSignature> {
+    let rid: u8 = input.read_byte()?;
+    let raw_sig = RawSignature::decode(input)?;
+    let recovery_id = RecoveryId::from_i32(i32::from(rid)).ok()?;
+    RecoverableSignature::from_compact(&raw_sig, recovery_id).ok()
 }
 
 impl fmt::Display for TxInWitness {
@@ -71,6 +96,18 @@ impl Encode for TxInWitness {
                 serialized_sig.encode_to(dest);
                 proof.encode_to(dest);
             }
+            TxInWitness::BasicRedeem(ref sig) => {
+                dest.push_byte(1);
+                dest.push_byte(1);
+                encode_ecdsa_signature(sig, dest);
+            }
+            TxInWitness::HashTimeLock(ref lock, ref preimage, ref sig) => {
+                dest.push_byte(2);
+                dest.push_byte(3);
+                lock.encode_to(dest);
+                preimage.encode_to(dest);
+                encode_ecdsa_signature(sig, dest);
+            }
         }
     }
 }
@@ -86,6 +123,16 @@ impl Decode for TxInWitness {
                 let proof = Proof::decode(input)?;
                 Some(TxInWitness::TreeSig(schnorrsig, proof))
             }
+            (1, 1) => {
+                let sig = decode_ecdsa_signature(input)?;
+                Some(TxInWitness::BasicRedeem(sig))
+            }
+            (2, 3) => {
+                let lock = HashTimeLock::decode(input)?;
+                let preimage = Option::<HashPreimage>::decode(input)?;
+                let sig = decode_ecdsa_signature(input)?;
+                Some(TxInWitness::HashTimeLock(lock, preimage, sig))
+            }
             _ => None,
         }
     }
@@ -1,9 +1,12 @@
 use chain_core::init::address::RedeemAddress;
 use chain_core::init::coin::Coin;
 use chain_core::init::config::{
-    AccountType, InitConfig, InitNetworkParameters, InitialValidator, ValidatorKeyType,
+    AccountType, InitConfig, InitNetworkParameters, InitialValidator, SlashingParameters,
+    ValidatorKeyType,
 };
-use chain_core::tx::fee::{LinearFee, Milli};
+use chain_core::state::account::StakedStateAddress;
+use chain_core::state::vesting::VestingSchedule;
+use chain_core::tx::fee::{DynamicFee, FeePolicy, LinearFee, Milli};
 use serde::Deserialize;
 use std::collections::BTreeMap;
 
@@ -40,9 +43,18 @@ fn test_verify_test_example_snapshot() {
     let coefficient_fee = Milli::new(1, 1);
     let fee_policy = LinearFee::new(constant_fee, coefficient_fee);
     let params = InitNetworkParameters {
-        initial_fee_policy: fee_policy,
+        initial_fee_policy: FeePolicy::Linear(fee_policy),
         required_council_node_stake: Coin::new(50_000_000_0000_0000).unwrap(),
         unbonding_period: 86400,
+        block_reward_share: Milli::new(0, 1),
+        slashing_config: SlashingParameters {
+            byzantine_slash_ratio: Milli::new(0, 200),
+            liveness_slash_ratio: Milli::new(0, 10),
+            block_signing_window: 100,
+            missed_block_threshold: 50,
+            jail_duration: 86400,
+        },
+        chain_id_hash_required_from: None,
     };
     let launch_incentive_from = "0x35f517cab9a37bc31091c2f155d965af84e0bc85"
         .parse::<RedeemAddress>()
@@ -60,7 +72,19 @@ fn test_verify_test_example_snapshot() {
         consensus_pubkey_type: ValidatorKeyType::Ed25519,
         consensus_pubkey_b64: "EIosObgfONUsnWCBGRpFlRFq5lSxjGIChRlVrVWVkcE=".to_string(),
     };
-    let config = InitConfig::new(
+    let vested_address = distribution
+        .eoa
+        .iter()
+        .map(|account| account.address)
+        .find(|address| {
+            *address != example_validator.staking_account_address
+                && *address != launch_incentive_from
+                && *address != launch_incentive_to
+                && *address != long_term_incentive
+        })
+        .expect("externally owned account");
+    let contract_address = distribution.contract[2].address;
+    let mut config = InitConfig::new(
         dist,
         launch_incentive_from,
         launch_incentive_to,
@@ -70,4 +94,41 @@ fn test_verify_test_example_snapshot() {
     );
     let result = config.validate_config_get_genesis(0);
     assert!(result.is_ok());
+
+    let schedule = VestingSchedule::Linear {
+        start_time: 0,
+        end_time: 100,
+    };
+    config.vesting.insert(vested_address, schedule);
+    let (accounts, _, _) = config.validate_config_get_genesis(0).ok().unwrap();
+    let vested_account = accounts
+        .iter()
+        .find(|account| account.address == StakedStateAddress::BasicRedeem(vested_address))
+        .expect("vested account");
+    assert_eq!(vested_account.vesting.unwrap().schedule, schedule);
+    assert_eq!(vested_account.vesting_locked(0), vested_account.unbonded);
+    assert_eq!(vested_account.vesting_locked(100), Coin::zero());
+
+    config.vesting.insert(
+        vested_address,
+        VestingSchedule::Linear {
+            start_time: 100,
+            end_time: 0,
+        },
+    );
+    assert!(config.validate_config_get_genesis(0).is_err());
+    config.vesting.remove(&vested_address);
+    config.vesting.insert(
+        contract_address,
+        VestingSchedule::Cliff { release_time: 100 },
+    );
+    assert!(config.validate_config_get_genesis(0).is_err());
+    config.vesting.remove(&contract_address);
+
+    let mut dynamic_fee = DynamicFee::new(fee_policy, 1000, Milli::integral(10));
+    config.network_params.initial_fee_policy = FeePolicy::Dynamic(dynamic_fee);
+    assert!(config.validate_config_get_genesis(0).is_ok());
+    dynamic_fee.max_multiplier = Milli::new(0, 999);
+    config.network_params.initial_fee_policy = FeePolicy::Dynamic(dynamic_fee);
+    assert!(config.validate_config_get_genesis(0).is_err());
 }
@@ -16,27 +16,43 @@ extern crate sgx_tstd as std;
 
 use std::prelude::v1::Vec;
 
-use chain_core::common::Timespec;
+use chain_core::common::{Timespec, H256};
 use chain_core::init::coin::{Coin, CoinError};
-use chain_core::state::account::{DepositBondTx, StakedState, UnbondTx, WithdrawUnbondedTx};
+use chain_core::state::account::{
+    ClaimRewardsTx, DelegateTx, DepositBondTx, NodeJoinRequestTx, NodeLeaveTx, StakedState,
+    UnbondTx, UndelegateTx, UnjailTx, WithdrawUnbondedTx,
+};
+use chain_core::state::governance::{
+    ParamChangeProposal, ParamChangeProposalTx, ParamChangeVoteTx,
+};
+use chain_core::state::tendermint::BlockHeight;
+use chain_core::state::CouncilNode;
+use chain_core::tx::data::attribute::TxExpiry;
 use chain_core::tx::data::input::TxoPointer;
-use chain_core::tx::data::output::TxOut;
+use chain_core::tx::data::output::{RelativeTimelock, TxOut};
 use chain_core::tx::data::Tx;
 use chain_core::tx::data::TxId;
 use chain_core::tx::fee::Fee;
-use chain_core::tx::witness::TxWitness;
+use chain_core::tx::rejection::RejectionCode;
+use chain_core::tx::witness::{TxInWitness, TxWitness};
 use chain_core::tx::TransactionId;
 use parity_codec::{Decode, Encode};
-use secp256k1;
+use secp256k1::{self, Secp256k1};
 use std::collections::BTreeSet;
 use std::{fmt, io};
-use witness::verify_tx_address;
+use witness::SchnorrBatch;
 
 /// All possible TX validation errors
 #[derive(Debug)]
 pub enum Error {
     /// chain hex ID does not match
     WrongChainHexId,
+    /// hash of the full chain ID does not match
+    WrongChainId,
+    /// hash of the full chain ID is required, but missing
+    MissingChainId,
+    /// transaction expired before it could be included
+    TxExpired,
     /// transaction has no inputs
     NoInputs,
     /// transaction has no outputs
@@ -69,10 +85,34 @@ pub enum Error {
     AccountNotFound,
     /// staked state not unbounded
     AccountNotUnbonded,
+    /// staked state's unbonded amount is still locked by its vesting schedule
+    AccountVestingLocked,
     /// outputs created out of a staked state are not time-locked to unbonding period
     AccountWithdrawOutputNotLocked,
     /// incorrect nonce supplied in staked state operation
     AccountIncorrectNonce,
+    /// staked state is jailed
+    AccountJailed,
+    /// staked state is not jailed
+    AccountNotJailed,
+    /// staked state doesn't have the required council node stake bonded
+    AccountNotEnoughCouncilNodeStake,
+    /// staked state or the consensus public key is already used by a council node
+    DuplicateCouncilNode,
+    /// staked state is not associated with a council node
+    NotCouncilNode,
+    /// proposal activation height is not after the next block
+    InvalidActivationHeight,
+    /// proposal doesn't exist or its voting already ended
+    ProposalNotFound,
+    /// staked state didn't delegate enough to the council node
+    DelegationNotFound,
+    /// stake delegated to the council node was fully slashed (no shares left to undelegate)
+    DelegationSlashed,
+    /// staked state can't delegate to its own council node
+    SelfDelegation,
+    /// the council node is jailed (so it can't take new delegations)
+    CouncilNodeJailed,
 }
 
 impl fmt::Display for Error {
@@ -80,6 +120,9 @@ impl fmt::Display for Error {
         use self::Error::*;
         match self {
             WrongChainHexId => write!(f, "chain hex ID does not match"),
+            WrongChainId => write!(f, "full chain ID does not match"),
+            MissingChainId => write!(f, "hash of the full chain ID is required"),
+            TxExpired => write!(f, "transaction expired"),
             DuplicateInputs => write!(f, "duplicated inputs"),
             UnexpectedWitnesses => write!(f, "transaction has more witnesses than inputs"),
             MissingWitnesses => write!(f, "transaction has more inputs than witnesses"),
@@ -99,11 +142,77 @@ impl fmt::Display for Error {
             EnclaveRejected => write!(f, "enclave error or invalid TX"),
             AccountNotFound => write!(f, "account not found"),
             AccountNotUnbonded => write!(f, "account not unbonded for withdrawal"),
+            AccountVestingLocked => write!(
+                f,
+                "account's unbonded amount is still locked by its vesting schedule"
+            ),
             AccountWithdrawOutputNotLocked => write!(
                 f,
                 "account withdrawal outputs not time-locked to unbonded_from"
             ),
             AccountIncorrectNonce => write!(f, "incorrect transaction count for account operation"),
+            AccountJailed => write!(f, "account is jailed"),
+            AccountNotJailed => write!(f, "account is not jailed"),
+            AccountNotEnoughCouncilNodeStake => write!(
+                f,
+                "account doesn't have the required council node stake bonded (after paying the fee)"
+            ),
+            DuplicateCouncilNode => write!(
+                f,
+                "account or consensus public key is already used by a council node"
+            ),
+            NotCouncilNode => write!(f, "account is not associated with a council node"),
+            InvalidActivationHeight => {
+                write!(f, "proposal activation height is not after the next block")
+            }
+            ProposalNotFound => write!(f, "proposal not found or its voting already ended"),
+            DelegationNotFound => write!(f, "account didn't delegate enough to the council node"),
+            DelegationSlashed => write!(f, "stake delegated to the council node was fully slashed"),
+            SelfDelegation => write!(f, "account can't delegate to its own council node"),
+            CouncilNodeJailed => write!(f, "council node is jailed"),
+        }
+    }
+}
+
+impl Error {
+    /// the stable machine-readable rejection reason (sent as the CheckTx / DeliverTx response code)
+    pub fn rejection_code(&self) -> RejectionCode {
+        use self::Error::*;
+        match self {
+            WrongChainHexId => RejectionCode::WrongChainHexId,
+            WrongChainId => RejectionCode::WrongChainId,
+            MissingChainId => RejectionCode::MissingChainId,
+            TxExpired => RejectionCode::TxExpired,
+            NoInputs => RejectionCode::NoInputs,
+            NoOutputs => RejectionCode::NoOutputs,
+            DuplicateInputs => RejectionCode::DuplicateInputs,
+            ZeroCoin => RejectionCode::ZeroCoin,
+            InvalidSum(_) => RejectionCode::InvalidSum,
+            UnexpectedWitnesses => RejectionCode::UnexpectedWitnesses,
+            MissingWitnesses => RejectionCode::MissingWitnesses,
+            InvalidInput => RejectionCode::InvalidInput,
+            InputSpent => RejectionCode::InputSpent,
+            InputOutputDoNotMatch => RejectionCode::InputOutputDoNotMatch,
+            OutputInTimelock => RejectionCode::OutputInTimelock,
+            EcdsaCrypto(_) => RejectionCode::InvalidWitness,
+            IoError(_) => RejectionCode::StorageError,
+            EnclaveRejected => RejectionCode::EnclaveRejected,
+            AccountNotFound => RejectionCode::AccountNotFound,
+            AccountNotUnbonded => RejectionCode::AccountNotUnbonded,
+            AccountVestingLocked => RejectionCode::AccountVestingLocked,
+            AccountWithdrawOutputNotLocked => RejectionCode::AccountWithdrawOutputNotLocked,
+            AccountIncorrectNonce => RejectionCode::AccountIncorrectNonce,
+            AccountJailed => RejectionCode::AccountJailed,
+            AccountNotJailed => RejectionCode::AccountNotJailed,
+            AccountNotEnoughCouncilNodeStake => RejectionCode::AccountNotEnoughCouncilNodeStake,
+            DuplicateCouncilNode => RejectionCode::DuplicateCouncilNode,
+            NotCouncilNode => RejectionCode::NotCouncilNode,
+            InvalidActivationHeight => RejectionCode::InvalidActivationHeight,
+            ProposalNotFound => RejectionCode::ProposalNotFound,
+            DelegationNotFound => RejectionCode::DelegationNotFound,
+            DelegationSlashed => RejectionCode::DelegationSlashed,
+            SelfDelegation => RejectionCode::SelfDelegation,
+            CouncilNodeJailed => RejectionCode::CouncilNodeJailed,
         }
     }
 }
@@ -115,18 +224,62 @@ pub struct ChainInfo {
     pub min_fee_computed: Fee,
     /// network hexamedical ID
     pub chain_hex_id: u8,
+    /// hash of the full chain ID
+    pub chain_id_hash: H256,
+    /// whether transactions have to commit to the hash of the full chain ID
+    pub chain_id_hash_required: bool,
     /// time in the previous committed block
     pub previous_block_time: Timespec,
+    /// height of the previous committed block
+    pub previous_block_height: BlockHeight,
     /// how much time is required to wait until stake state's unbonded amount can be withdrawn
     pub unbonding_period: u32,
 }
 
-fn check_attributes(tx_chain_hex_id: u8, extra_info: &ChainInfo) -> Result<(), Error> {
+/// External information about the council needed for council node TX validation
+#[derive(Clone, Copy)]
+pub struct CouncilInfo<'a> {
+    /// (minimal) amount required to be bonded in council node accounts
+    pub required_council_node_stake: Coin,
+    /// the current council nodes
+    pub council_nodes: &'a [CouncilNode],
+    /// height of the last processed block
+    pub last_block_height: BlockHeight,
+    /// pending network parameter change proposals
+    pub proposals: &'a [ParamChangeProposal],
+}
+
+fn check_attributes(
+    tx_chain_hex_id: u8,
+    tx_chain_id_hash: Option<H256>,
+    tx_expiry: Option<TxExpiry>,
+    extra_info: &ChainInfo,
+) -> Result<(), Error> {
     // TODO: check other attributes?
     // check that chain IDs match
     if extra_info.chain_hex_id != tx_chain_hex_id {
         return Err(Error::WrongChainHexId);
     }
+    // transactions bound to the full chain ID can't be replayed on chains sharing the hex ID
+    // (after the activation height, all transactions have to be bound to it)
+    match tx_chain_id_hash {
+        Some(chain_id_hash) if extra_info.chain_id_hash != chain_id_hash => {
+            return Err(Error::WrongChainId);
+        }
+        None if extra_info.chain_id_hash_required => {
+            return Err(Error::MissingChainId);
+        }
+        _ => {}
+    }
+    // check that the transaction can still be included in the next block
+    if let Some(expiry) = tx_expiry {
+        if expiry.is_expired(
+            extra_info.previous_block_height,
+            extra_info.previous_block_time,
+        ) {
+            return Err(Error::TxExpired);
+        }
+    }
     Ok(())
 }
 
@@ -161,6 +314,8 @@ pub enum TxWithOutputs {
     Transfer(Tx),
     /// withdrawing unbonded amount from a staked state
     StakeWithdraw(WithdrawUnbondedTx),
+    /// claiming accrued rewards of a staked state
+    RewardsClaim(ClaimRewardsTx),
 }
 
 impl TxWithOutputs {
@@ -169,6 +324,7 @@ impl TxWithOutputs {
         match self {
             TxWithOutputs::Transfer(tx) => &tx.outputs,
             TxWithOutputs::StakeWithdraw(tx) => &tx.outputs,
+            TxWithOutputs::RewardsClaim(tx) => &tx.outputs,
         }
     }
 
@@ -177,6 +333,32 @@ impl TxWithOutputs {
         match self {
             TxWithOutputs::Transfer(tx) => tx.id(),
             TxWithOutputs::StakeWithdraw(tx) => tx.id(),
+            TxWithOutputs::RewardsClaim(tx) => tx.id(),
+        }
+    }
+}
+
+/// the block in which a transaction was included (relative timelocks of its outputs count from it)
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Encode, Decode)]
+pub struct TxInclusion {
+    /// height of the block
+    pub block_height: BlockHeight,
+    /// time of the block
+    pub block_time: Timespec,
+}
+
+impl TxInclusion {
+    /// checks if an output created in this block is still locked by its relative timelock
+    pub fn is_locked(&self, relative_lock: &RelativeTimelock, extra_info: &ChainInfo) -> bool {
+        match relative_lock {
+            RelativeTimelock::Seconds(seconds) => {
+                self.block_time.saturating_add(Timespec::from(*seconds))
+                    > extra_info.previous_block_time
+            }
+            RelativeTimelock::Blocks(blocks) => {
+                self.block_height.saturating_add(BlockHeight::from(*blocks))
+                    > extra_info.previous_block_height
+            }
         }
     }
 }
@@ -186,15 +368,18 @@ fn check_inputs(
     inputs: &[TxoPointer],
     witness: &TxWitness,
     extra_info: &ChainInfo,
-    transaction_inputs: Vec<TxWithOutputs>,
+    transaction_inputs: Vec<(TxWithOutputs, TxInclusion)>,
 ) -> Result<Coin, Error> {
     let mut incoins = Coin::zero();
+    let secp = Secp256k1::verification_only();
+    let mut signatures = SchnorrBatch::new(&secp);
     // verify that txids of inputs correspond to the owner/signer
     // and it'd check they are not spent
     // TODO: zip3 / itertools?
-    for (txin, (tx, in_witness)) in inputs
+    for (i, (txin, ((tx, inclusion), in_witness))) in inputs
         .iter()
         .zip(transaction_inputs.iter().zip(witness.iter()))
+        .enumerate()
     {
         if txin.id != tx.id() {
             return Err(Error::InvalidInput);
@@ -210,7 +395,18 @@ fn check_inputs(
                 return Err(Error::OutputInTimelock);
             }
         }
-        let wv = verify_tx_address(&in_witness, main_txid, &txout.address);
+        if let Some(relative_lock) = &txout.relative_lock {
+            if inclusion.is_locked(relative_lock, extra_info) {
+                return Err(Error::OutputInTimelock);
+            }
+        }
+        // hash-time-locked outputs can only be refunded after the timeout
+        if let TxInWitness::HashTimeLock(lock, None, _) = in_witness {
+            if lock.timeout > extra_info.previous_block_time {
+                return Err(Error::OutputInTimelock);
+            }
+        }
+        let wv = signatures.add(i, in_witness, main_txid, &txout.address);
         if let Err(e) = wv {
             return Err(Error::EcdsaCrypto(e));
         }
@@ -221,6 +417,10 @@ fn check_inputs(
             incoins = sum.unwrap();
         }
     }
+    // all Schnorr signatures of the transaction are verified in a single pass
+    if let Err((_, e)) = signatures.verify() {
+        return Err(Error::EcdsaCrypto(e));
+    }
     Ok(incoins)
 }
 
@@ -266,9 +466,14 @@ pub fn verify_transfer(
     maintx: &Tx,
     witness: &TxWitness,
     extra_info: ChainInfo,
-    transaction_inputs: Vec<TxWithOutputs>,
+    transaction_inputs: Vec<(TxWithOutputs, TxInclusion)>,
 ) -> Result<Fee, Error> {
-    check_attributes(maintx.attributes.chain_hex_id, &extra_info)?;
+    check_attributes(
+        maintx.attributes.chain_hex_id,
+        maintx.attributes.chain_id_hash,
+        maintx.attributes.expiry,
+        &extra_info,
+    )?;
     check_inputs_basic(&maintx.inputs, witness)?;
     check_outputs_basic(&maintx.outputs)?;
     let incoins = check_inputs(
@@ -291,10 +496,15 @@ pub fn verify_bonded_deposit(
     maintx: &DepositBondTx,
     witness: &TxWitness,
     extra_info: ChainInfo,
-    transaction_inputs: Vec<TxWithOutputs>,
+    transaction_inputs: Vec<(TxWithOutputs, TxInclusion)>,
     maccount: Option<StakedState>,
 ) -> Result<(Fee, Option<StakedState>), Error> {
-    check_attributes(maintx.attributes.chain_hex_id, &extra_info)?;
+    check_attributes(
+        maintx.attributes.chain_hex_id,
+        maintx.attributes.chain_id_hash,
+        maintx.attributes.expiry,
+        &extra_info,
+    )?;
     check_inputs_basic(&maintx.inputs, witness)?;
     let incoins = check_inputs(
         &maintx.id(),
@@ -329,7 +539,12 @@ pub fn verify_unbonding(
     extra_info: ChainInfo,
     mut account: StakedState,
 ) -> Result<(Fee, Option<StakedState>), Error> {
-    check_attributes(maintx.attributes.chain_hex_id, &extra_info)?;
+    check_attributes(
+        maintx.attributes.chain_hex_id,
+        maintx.attributes.chain_id_hash,
+        maintx.attributes.expiry,
+        &extra_info,
+    )?;
 
     // checks that account transaction count matches to the one in transaction
     if maintx.nonce != account.nonce {
@@ -356,33 +571,374 @@ pub fn verify_unbonded_withdraw(
     extra_info: ChainInfo,
     mut account: StakedState,
 ) -> Result<(Fee, Option<StakedState>), Error> {
-    check_attributes(maintx.attributes.chain_hex_id, &extra_info)?;
+    check_attributes(
+        maintx.attributes.chain_hex_id,
+        maintx.attributes.chain_id_hash,
+        maintx.attributes.expiry,
+        &extra_info,
+    )?;
     check_outputs_basic(&maintx.outputs)?;
     // checks that account transaction count matches to the one in transaction
     if maintx.nonce != account.nonce {
         return Err(Error::AccountIncorrectNonce);
     }
+    // checks that there is something to wihdraw
+    if account.unbonded == Coin::zero() {
+        return Err(Error::ZeroCoin);
+    }
+    // checks that outputs are locked to the same unbonded time
+    // (the unbonding entries matured at that time are withdrawn, the later ones remain in the staked state)
+    let matured_at = match maintx.outputs[0].valid_from {
+        Some(time) if maintx.outputs.iter().all(|x| x.valid_from == Some(time)) => time,
+        _ => {
+            return Err(Error::AccountWithdrawOutputNotLocked);
+        }
+    };
     // checks that account can withdraw to outputs
-    if account.unbonded_from > extra_info.previous_block_time {
+    let matured = account.matured_unbonded(matured_at);
+    if matured_at > extra_info.previous_block_time || matured == Coin::zero() {
         return Err(Error::AccountNotUnbonded);
     }
-    // checks that there is something to wihdraw
-    if account.unbonded == Coin::zero() {
+    // checks that some of the matured amount is vested (the rest remains in the staked state)
+    let locked = account.withdrawal_locked(matured_at, extra_info.previous_block_time);
+    if matured == locked {
+        return Err(Error::AccountVestingLocked);
+    }
+    let withdrawable = (matured - locked).expect("locked amount is at most matured");
+    let outcoins = maintx.get_output_total();
+    if let Err(coin_err) = outcoins {
+        return Err(Error::InvalidSum(coin_err));
+    }
+    let fee = check_input_output_sums(withdrawable, outcoins.unwrap(), &extra_info)?;
+    account.withdraw(matured_at, extra_info.previous_block_time);
+    Ok((fee, Some(account)))
+}
+
+/// checks claiming all the accrued rewards of a staked state:
+/// they're either compounded into the bonded amount (no outputs) or paid to outputs locked for the unbonding period
+/// NOTE: witness is assumed to be checked in chain-abci
+pub fn verify_reward_claim(
+    maintx: &ClaimRewardsTx,
+    extra_info: ChainInfo,
+    mut account: StakedState,
+) -> Result<(Fee, Option<StakedState>), Error> {
+    check_attributes(
+        maintx.attributes.chain_hex_id,
+        maintx.attributes.chain_id_hash,
+        maintx.attributes.expiry,
+        &extra_info,
+    )?;
+    // checks that account transaction count matches to the one in transaction
+    if maintx.nonce != account.nonce {
+        return Err(Error::AccountIncorrectNonce);
+    }
+    // checks that there is something to claim
+    if account.rewards == Coin::zero() {
         return Err(Error::ZeroCoin);
     }
-    // checks that outputs are locked to the unbonded time
-    if !maintx
-        .outputs
-        .iter()
-        .all(|x| x.valid_from == Some(account.unbonded_from))
-    {
+    if maintx.is_compounding() {
+        // only pay the minimal fee from the rewards; the rest is added to bonded
+        check_input_output_sums(account.rewards, Coin::zero(), &extra_info)?;
+        let compounded = (account.rewards - extra_info.min_fee_computed.to_coin())
+            .expect("rewards cover the fee");
+        account.claim_rewards(compounded);
+        return Ok((extra_info.min_fee_computed, Some(account)));
+    }
+    check_outputs_basic(&maintx.outputs)?;
+    // checks that the claimed rewards can't be spent before the unbonding period passes
+    // (so that claiming isn't a faster way out of the staked amounts than unbonding)
+    let locked = maintx.outputs.iter().all(|x| match x.relative_lock {
+        Some(RelativeTimelock::Seconds(seconds)) => seconds >= extra_info.unbonding_period,
+        _ => false,
+    });
+    if !locked {
         return Err(Error::AccountWithdrawOutputNotLocked);
     }
     let outcoins = maintx.get_output_total();
     if let Err(coin_err) = outcoins {
         return Err(Error::InvalidSum(coin_err));
     }
-    let fee = check_input_output_sums(account.unbonded, outcoins.unwrap(), &extra_info)?;
-    account.withdraw();
+    let fee = check_input_output_sums(account.rewards, outcoins.unwrap(), &extra_info)?;
+    account.claim_rewards(Coin::zero());
+    Ok((fee, Some(account)))
+}
+
+/// checks delegating some bonded amount to a council node
+/// NOTE: witness is assumed to be checked in chain-abci
+pub fn verify_delegation(
+    maintx: &DelegateTx,
+    extra_info: ChainInfo,
+    council_info: CouncilInfo,
+    mut account: StakedState,
+    council_node_account: &StakedState,
+) -> Result<(Fee, Option<StakedState>), Error> {
+    check_attributes(
+        maintx.attributes.chain_hex_id,
+        maintx.attributes.chain_id_hash,
+        maintx.attributes.expiry,
+        &extra_info,
+    )?;
+    // checks that account transaction count matches to the one in transaction
+    if maintx.nonce != account.nonce {
+        return Err(Error::AccountIncorrectNonce);
+    }
+    // check that a non-zero amount is being delegated
+    if maintx.value == Coin::zero() {
+        return Err(Error::ZeroCoin);
+    }
+    // council nodes bond their own stake directly
+    if maintx.council_node == account.address {
+        return Err(Error::SelfDelegation);
+    }
+    if !council_info
+        .council_nodes
+        .iter()
+        .any(|node| node.staking_account_address == maintx.council_node)
+    {
+        return Err(Error::NotCouncilNode);
+    }
+    check_input_output_sums(account.bonded, maintx.value, &extra_info)?;
+    let shares = council_node_account
+        .delegation_shares_for(maintx.value)
+        .ok_or(Error::DelegationSlashed)?;
+    if council_node_account.is_jailed() {
+        return Err(Error::CouncilNodeJailed);
+    }
+    account.delegate(
+        maintx.value,
+        extra_info.min_fee_computed.to_coin(),
+        maintx.council_node,
+        shares,
+    );
+    Ok((extra_info.min_fee_computed, Some(account)))
+}
+
+/// checks moving some delegated amount from a council node to unbonded
+/// (the council node may have left the council in the meantime, and the value of the delegator's shares
+/// is computed from the council node's current delegated stake)
+/// NOTE: witness is assumed to be checked in chain-abci
+pub fn verify_undelegation(
+    maintx: &UndelegateTx,
+    extra_info: ChainInfo,
+    mut account: StakedState,
+    council_node_account: &StakedState,
+) -> Result<(Fee, Option<StakedState>), Error> {
+    check_attributes(
+        maintx.attributes.chain_hex_id,
+        maintx.attributes.chain_id_hash,
+        maintx.attributes.expiry,
+        &extra_info,
+    )?;
+    // checks that account transaction count matches to the one in transaction
+    if maintx.nonce != account.nonce {
+        return Err(Error::AccountIncorrectNonce);
+    }
+    // check that a non-zero amount is being undelegated
+    if maintx.value == Coin::zero() {
+        return Err(Error::ZeroCoin);
+    }
+    let own_shares = account.delegation_shares_to(&maintx.council_node);
+    if council_node_account.delegated_value(own_shares) < maintx.value {
+        return Err(Error::DelegationNotFound);
+    }
+    let shares = council_node_account
+        .undelegation_shares_for(maintx.value)
+        .filter(|shares| *shares <= own_shares)
+        .ok_or(Error::DelegationNotFound)?;
+    // the fee is paid from the undelegated amount
+    check_input_output_sums(maintx.value, Coin::zero(), &extra_info)?;
+    account.undelegate(
+        maintx.value,
+        extra_info.min_fee_computed.to_coin(),
+        &maintx.council_node,
+        shares,
+        extra_info.previous_block_time + i64::from(extra_info.unbonding_period),
+    );
+    Ok((extra_info.min_fee_computed, Some(account)))
+}
+
+/// checks that the fee for a council node operation can be paid from the bonded amount
+fn check_bonded_fee(account: &StakedState, extra_info: &ChainInfo) -> Result<Fee, Error> {
+    if account.bonded < extra_info.min_fee_computed.to_coin() {
+        return Err(Error::InputOutputDoNotMatch);
+    }
+    Ok(extra_info.min_fee_computed)
+}
+
+/// checks registering a staked state as a council node
+/// NOTE: witness is assumed to be checked in chain-abci
+pub fn verify_node_join(
+    maintx: &NodeJoinRequestTx,
+    extra_info: ChainInfo,
+    council_info: CouncilInfo,
+    mut account: StakedState,
+) -> Result<(Fee, Option<StakedState>), Error> {
+    check_attributes(
+        maintx.attributes.chain_hex_id,
+        maintx.attributes.chain_id_hash,
+        maintx.attributes.expiry,
+        &extra_info,
+    )?;
+    // checks that account transaction count matches to the one in transaction
+    if maintx.nonce != account.nonce {
+        return Err(Error::AccountIncorrectNonce);
+    }
+    if account.is_jailed() {
+        return Err(Error::AccountJailed);
+    }
+    if council_info.council_nodes.iter().any(|node| {
+        node.staking_account_address == account.address
+            || node.consensus_pubkey == maintx.consensus_pubkey
+    }) {
+        return Err(Error::DuplicateCouncilNode);
+    }
+    // the required stake needs to remain bonded after paying the fee
+    let required = council_info.required_council_node_stake + extra_info.min_fee_computed.to_coin();
+    match required {
+        Ok(required) if account.bonded >= required => {}
+        Ok(_) => {
+            return Err(Error::AccountNotEnoughCouncilNodeStake);
+        }
+        Err(coin_err) => {
+            return Err(Error::InvalidSum(coin_err));
+        }
+    }
+    account.council_node_op(extra_info.min_fee_computed.to_coin());
+    Ok((extra_info.min_fee_computed, Some(account)))
+}
+
+/// checks unjailing a council node after its jail time passed
+/// NOTE: witness is assumed to be checked in chain-abci
+pub fn verify_unjailing(
+    maintx: &UnjailTx,
+    extra_info: ChainInfo,
+    mut account: StakedState,
+) -> Result<(Fee, Option<StakedState>), Error> {
+    check_attributes(
+        maintx.attributes.chain_hex_id,
+        maintx.attributes.chain_id_hash,
+        maintx.attributes.expiry,
+        &extra_info,
+    )?;
+    // checks that account transaction count matches to the one in transaction
+    if maintx.nonce != account.nonce {
+        return Err(Error::AccountIncorrectNonce);
+    }
+    match account.jailed_until {
+        None => {
+            return Err(Error::AccountNotJailed);
+        }
+        Some(jailed_until) if jailed_until > extra_info.previous_block_time => {
+            return Err(Error::AccountJailed);
+        }
+        Some(_) => {}
+    }
+    let fee = check_bonded_fee(&account, &extra_info)?;
+    account.unjail(fee.to_coin());
+    Ok((fee, Some(account)))
+}
+
+/// checks removing a council node from the council
+/// NOTE: witness is assumed to be checked in chain-abci
+pub fn verify_node_leave(
+    maintx: &NodeLeaveTx,
+    extra_info: ChainInfo,
+    council_info: CouncilInfo,
+    mut account: StakedState,
+) -> Result<(Fee, Option<StakedState>), Error> {
+    check_attributes(
+        maintx.attributes.chain_hex_id,
+        maintx.attributes.chain_id_hash,
+        maintx.attributes.expiry,
+        &extra_info,
+    )?;
+    // checks that account transaction count matches to the one in transaction
+    if maintx.nonce != account.nonce {
+        return Err(Error::AccountIncorrectNonce);
+    }
+    if !council_info
+        .council_nodes
+        .iter()
+        .any(|node| node.staking_account_address == account.address)
+    {
+        return Err(Error::NotCouncilNode);
+    }
+    let fee = check_bonded_fee(&account, &extra_info)?;
+    account.council_node_op(fee.to_coin());
+    Ok((fee, Some(account)))
+}
+
+/// checks that the staked state is associated with a council node that isn't jailed
+fn check_council_node(account: &StakedState, council_info: &CouncilInfo) -> Result<(), Error> {
+    if !council_info
+        .council_nodes
+        .iter()
+        .any(|node| node.staking_account_address == account.address)
+    {
+        return Err(Error::NotCouncilNode);
+    }
+    if account.is_jailed() {
+        return Err(Error::AccountJailed);
+    }
+    Ok(())
+}
+
+/// checks proposing a network parameter change by a council node
+/// NOTE: witness is assumed to be checked in chain-abci
+pub fn verify_proposal(
+    maintx: &ParamChangeProposalTx,
+    extra_info: ChainInfo,
+    council_info: CouncilInfo,
+    mut account: StakedState,
+) -> Result<(Fee, Option<StakedState>), Error> {
+    check_attributes(
+        maintx.attributes.chain_hex_id,
+        maintx.attributes.chain_id_hash,
+        maintx.attributes.expiry,
+        &extra_info,
+    )?;
+    // checks that account transaction count matches to the one in transaction
+    if maintx.nonce != account.nonce {
+        return Err(Error::AccountIncorrectNonce);
+    }
+    check_council_node(&account, &council_info)?;
+    // the transaction will be included in the next block at the earliest,
+    // so council nodes need at least one more block after it to vote
+    if maintx.activation_height <= council_info.last_block_height + 1 {
+        return Err(Error::InvalidActivationHeight);
+    }
+    let fee = check_bonded_fee(&account, &extra_info)?;
+    account.council_node_op(fee.to_coin());
+    Ok((fee, Some(account)))
+}
+
+/// checks voting on a pending network parameter change proposal by a council node
+/// NOTE: witness is assumed to be checked in chain-abci
+pub fn verify_vote(
+    maintx: &ParamChangeVoteTx,
+    extra_info: ChainInfo,
+    council_info: CouncilInfo,
+    mut account: StakedState,
+) -> Result<(Fee, Option<StakedState>), Error> {
+    check_attributes(
+        maintx.attributes.chain_hex_id,
+        maintx.attributes.chain_id_hash,
+        maintx.attributes.expiry,
+        &extra_info,
+    )?;
+    // checks that account transaction count matches to the one in transaction
+    if maintx.nonce != account.nonce {
+        return Err(Error::AccountIncorrectNonce);
+    }
+    check_council_node(&account, &council_info)?;
+    // votes are tallied at the beginning of the activation height block
+    if !council_info.proposals.iter().any(|proposal| {
+        proposal.id == maintx.proposal_id
+            && proposal.activation_height > council_info.last_block_height + 1
+    }) {
+        return Err(Error::ProposalNotFound);
+    }
+    let fee = check_bonded_fee(&account, &extra_info)?;
+    account.council_node_op(fee.to_coin());
     Ok((fee, Some(account)))
 }
@@ -2,8 +2,13 @@ use chain_core::init::address::RedeemAddress;
 use chain_core::state::account::{StakedStateAddress, StakedStateOpWitness};
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::TxId;
-use chain_core::tx::witness::TxInWitness;
-use secp256k1::{schnorrsig::schnorr_verify, Message, PublicKey, Secp256k1};
+use chain_core::tx::witness::{EcdsaSignature, TxInWitness};
+use secp256k1::{
+    schnorrsig::{schnorr_verify, SchnorrSignature},
+    Message, PublicKey, Secp256k1, Verification,
+};
+use std::os::raw::c_void;
+use std::prelude::v1::Vec;
 
 /// verify a given extended address is associated to the witness
 /// and the signature against the given transation `Tx`
@@ -15,24 +20,179 @@ pub fn verify_tx_address(
     address: &ExtendedAddr,
 ) -> Result<(), secp256k1::Error> {
     let secp = Secp256k1::verification_only();
-    let message = Message::from_slice(&txid[..])?;
-
-    match (witness, address) {
-        (TxInWitness::TreeSig(sig, proof), ExtendedAddr::OrTree(root_hash)) => {
-            if !proof.verify(root_hash) {
-                Err(secp256k1::Error::InvalidPublicKey)
-            } else {
-                schnorr_verify(
-                    &secp,
-                    &message,
-                    &sig,
-                    &PublicKey::from_slice(proof.value().as_bytes())?,
-                )
+    let mut batch = SchnorrBatch::new(&secp);
+    batch.add(0, witness, txid, address)?;
+    batch.verify().map_err(|(_, e)| e)
+}
+
+/// Schnorr signatures of `TreeSig` witnesses collected to be verified together
+/// (e.g. of all inputs of a transaction) with a single batch verification.
+/// Merkle proofs and other witness types are checked right away when added.
+pub struct SchnorrBatch<'a, C: Verification> {
+    secp: &'a Secp256k1<C>,
+    signatures: Vec<(usize, Message, &'a SchnorrSignature, PublicKey)>,
+}
+
+impl<'a, C: Verification> SchnorrBatch<'a, C> {
+    /// creates an empty batch using the given verification context
+    pub fn new(secp: &'a Secp256k1<C>) -> Self {
+        SchnorrBatch {
+            secp,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// number of signatures waiting for verification
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// checks if there are no signatures waiting for verification
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// checks that the witness of the input (identified by `index`, e.g. its position in the transaction)
+    /// is associated to the extended address; the Schnorr signature of a `TreeSig` witness is only collected
+    /// (to be checked in `verify`), other signatures are verified against the given transaction `Tx` immediately
+    pub fn add(
+        &mut self,
+        index: usize,
+        witness: &'a TxInWitness,
+        txid: &TxId,
+        address: &ExtendedAddr,
+    ) -> Result<(), secp256k1::Error> {
+        let message = Message::from_slice(&txid[..])?;
+
+        match (witness, address) {
+            (TxInWitness::TreeSig(sig, proof), ExtendedAddr::OrTree(root_hash)) => {
+                if !proof.verify(root_hash) {
+                    Err(secp256k1::Error::InvalidPublicKey)
+                } else {
+                    let pk = PublicKey::from_slice(proof.value().as_bytes())?;
+                    self.signatures.push((index, message, sig, pk));
+                    Ok(())
+                }
+            }
+            (TxInWitness::BasicRedeem(sig), ExtendedAddr::BasicRedeem(redeem_address)) => {
+                verify_redeem_signature(self.secp, &message, sig, redeem_address)
+            }
+            (TxInWitness::HashTimeLock(lock, preimage, sig), ExtendedAddr::HashTimeLock(hash)) => {
+                if lock.address_hash() != *hash {
+                    return Err(secp256k1::Error::InvalidPublicKey);
+                }
+                // the timeout of the refund is checked against the block time in `check_inputs`
+                match preimage {
+                    Some(preimage) if lock.verify_preimage(preimage) => {
+                        verify_redeem_signature(self.secp, &message, sig, &lock.recipient)
+                    }
+                    Some(_) => Err(secp256k1::Error::InvalidMessage),
+                    None => verify_redeem_signature(self.secp, &message, sig, &lock.refund),
+                }
+            }
+            _ => Err(secp256k1::Error::InvalidSignature),
+        }
+    }
+
+    /// verifies all collected signatures in one batch verification;
+    /// if it fails, the signatures are checked one by one to return the index of the (first) failing input
+    pub fn verify(&self) -> Result<(), (usize, secp256k1::Error)> {
+        if self.signatures.len() > 1 {
+            let sigs: Vec<_> = self
+                .signatures
+                .iter()
+                .map(|(_, _, sig, _)| sig.as_ptr() as *const c_void)
+                .collect();
+            let messages: Vec<_> = self
+                .signatures
+                .iter()
+                .map(|(_, message, _, _)| message.as_ptr())
+                .collect();
+            let pks: Vec<_> = self
+                .signatures
+                .iter()
+                .map(|(_, _, _, pk)| pk.as_ptr() as *const c_void)
+                .collect();
+            if batch::verify(*self.secp.ctx() as *const c_void, &sigs, &messages, &pks) {
+                return Ok(());
+            }
+        }
+        for (index, message, sig, pk) in self.signatures.iter() {
+            schnorr_verify(self.secp, message, sig, pk).map_err(|e| (*index, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// `secp256k1_schnorrsig_verify_batch` from the schnorrsig module of libsecp256k1-zkp
+/// (compiled by the secp256k1zkp binding, but not wrapped by it yet)
+#[allow(unsafe_code)]
+mod batch {
+    use std::os::raw::{c_int, c_void};
+
+    /// the scratch space for the multi-scalar multiplication
+    /// (if it's too small for all signatures, the library splits them into several batches)
+    const SCRATCH_SPACE_SIZE: usize = 1024 * 1024;
+
+    extern "C" {
+        fn secp256k1_scratch_space_create(ctx: *const c_void, max_size: usize) -> *mut c_void;
+
+        fn secp256k1_scratch_space_destroy(scratch: *mut c_void);
+
+        fn secp256k1_schnorrsig_verify_batch(
+            ctx: *const c_void,
+            scratch: *mut c_void,
+            sig: *const *const c_void,
+            msg32: *const *const u8,
+            pk: *const *const c_void,
+            n_sigs: usize,
+        ) -> c_int;
+    }
+
+    /// returns true if all signatures are valid
+    /// (false if any of them is invalid or the scratch space couldn't be allocated)
+    pub fn verify(
+        ctx: *const c_void,
+        sigs: &[*const c_void],
+        messages: &[*const u8],
+        pks: &[*const c_void],
+    ) -> bool {
+        assert!(sigs.len() == messages.len() && sigs.len() == pks.len());
+        unsafe {
+            let scratch = secp256k1_scratch_space_create(ctx, SCRATCH_SPACE_SIZE);
+            if scratch.is_null() {
+                return false;
             }
+            let result = secp256k1_schnorrsig_verify_batch(
+                ctx,
+                scratch,
+                sigs.as_ptr(),
+                messages.as_ptr(),
+                pks.as_ptr(),
+                sigs.len(),
+            );
+            secp256k1_scratch_space_destroy(scratch);
+            result == 1
         }
     }
 }
 
+/// verify the signature and that the recovered public key corresponds to the redeem address
+fn verify_redeem_signature<C: Verification>(
+    secp: &Secp256k1<C>,
+    message: &Message,
+    sig: &EcdsaSignature,
+    redeem_address: &RedeemAddress,
+) -> Result<(), secp256k1::Error> {
+    let pk = secp.recover(message, sig)?;
+    secp.verify(message, &sig.to_standard(), &pk)?;
+    if RedeemAddress::from(&pk) != *redeem_address {
+        Err(secp256k1::Error::InvalidPublicKey)
+    } else {
+        Ok(())
+    }
+}
+
 /// verify the signature against the given transation `Tx`
 /// and recovers the address from it
 ///
@@ -59,6 +219,7 @@ pub mod tests {
     use secp256k1::SecretKey;
 
     use chain_core::common::MerkleTree;
+    use chain_core::tx::data::htlc::{hash_lock, HashTimeLock};
     use chain_core::tx::data::Tx;
     use chain_core::tx::witness::tree::RawPubkey;
     use chain_core::tx::TransactionId;
@@ -162,6 +323,140 @@ pub mod tests {
         assert!(verify_tx_address(&witness, &transation.id(), &address).is_err())
     }
 
+    #[test]
+    fn check_basic_redeem_verify() {
+        let transation = Tx::new();
+
+        let secp = Secp256k1::new();
+
+        let secret_keys = [
+            SecretKey::from_slice(&[0xcd; 32]).expect("Unable to create secret key"),
+            SecretKey::from_slice(&[0xde; 32]).expect("Unable to create secret key"),
+        ];
+        let public_key = PublicKey::from_secret_key(&secp, &secret_keys[0]);
+
+        let address = ExtendedAddr::BasicRedeem(RedeemAddress::from(&public_key));
+        let message = Message::from_slice(&transation.id()).unwrap();
+
+        let witness = TxInWitness::BasicRedeem(secp.sign_recoverable(&message, &secret_keys[0]));
+        assert!(verify_tx_address(&witness, &transation.id(), &address).is_ok());
+
+        let wrong_witness =
+            TxInWitness::BasicRedeem(secp.sign_recoverable(&message, &secret_keys[1]));
+        assert!(verify_tx_address(&wrong_witness, &transation.id(), &address).is_err());
+
+        let merkle_tree = MerkleTree::new(vec![RawPubkey::from(public_key.serialize())]);
+        let tree_address = ExtendedAddr::OrTree(merkle_tree.root_hash());
+        assert!(verify_tx_address(&witness, &transation.id(), &tree_address).is_err());
+    }
+
+    #[test]
+    fn check_hash_time_lock_verify() {
+        let transation = Tx::new();
+
+        let secp = Secp256k1::new();
+
+        let recipient_key =
+            SecretKey::from_slice(&[0xcd; 32]).expect("Unable to create secret key");
+        let refund_key = SecretKey::from_slice(&[0xde; 32]).expect("Unable to create secret key");
+        let preimage = [0xab; 32];
+        let lock = HashTimeLock::new(
+            hash_lock(&preimage),
+            RedeemAddress::from(&PublicKey::from_secret_key(&secp, &recipient_key)),
+            RedeemAddress::from(&PublicKey::from_secret_key(&secp, &refund_key)),
+            100,
+        );
+        let address = lock.address();
+        let message = Message::from_slice(&transation.id()).unwrap();
+
+        let redeem = TxInWitness::HashTimeLock(
+            lock.clone(),
+            Some(preimage),
+            secp.sign_recoverable(&message, &recipient_key),
+        );
+        assert!(verify_tx_address(&redeem, &transation.id(), &address).is_ok());
+
+        let refund = TxInWitness::HashTimeLock(
+            lock.clone(),
+            None,
+            secp.sign_recoverable(&message, &refund_key),
+        );
+        assert!(verify_tx_address(&refund, &transation.id(), &address).is_ok());
+
+        let wrong_preimage = TxInWitness::HashTimeLock(
+            lock.clone(),
+            Some([0xac; 32]),
+            secp.sign_recoverable(&message, &recipient_key),
+        );
+        assert!(verify_tx_address(&wrong_preimage, &transation.id(), &address).is_err());
+
+        let wrong_signer = TxInWitness::HashTimeLock(
+            lock.clone(),
+            None,
+            secp.sign_recoverable(&message, &recipient_key),
+        );
+        assert!(verify_tx_address(&wrong_signer, &transation.id(), &address).is_err());
+
+        let mut other_lock = lock.clone();
+        other_lock.timeout = 0;
+        let wrong_terms = TxInWitness::HashTimeLock(
+            other_lock,
+            None,
+            secp.sign_recoverable(&message, &refund_key),
+        );
+        assert!(verify_tx_address(&wrong_terms, &transation.id(), &address).is_err());
+    }
+
+    #[test]
+    fn check_schnorr_batch_verify() {
+        let transation = Tx::new();
+        let message = Message::from_slice(&transation.id()).unwrap();
+        let wrong_message = Message::from_slice(&[0xab; 32]).unwrap();
+
+        let secp = Secp256k1::new();
+
+        let secret_keys: Vec<SecretKey> = (1..4u8)
+            .map(|x| SecretKey::from_slice(&[x; 32]).expect("Unable to create secret key"))
+            .collect();
+        let public_keys: Vec<RawPubkey> = secret_keys
+            .iter()
+            .map(|secret_key| {
+                RawPubkey::from(PublicKey::from_secret_key(&secp, secret_key).serialize())
+            })
+            .collect();
+        let merkle_tree = MerkleTree::new(public_keys.clone());
+        let address = ExtendedAddr::OrTree(merkle_tree.root_hash());
+
+        let witnesses: Vec<TxInWitness> = secret_keys
+            .iter()
+            .zip(public_keys.iter())
+            .enumerate()
+            .map(|(i, (secret_key, public_key))| {
+                let signed = if i == 1 { &wrong_message } else { &message };
+                TxInWitness::TreeSig(
+                    schnorr_sign(&secp, signed, secret_key).0,
+                    merkle_tree.generate_proof(public_key.clone()).unwrap(),
+                )
+            })
+            .collect();
+
+        let mut batch = SchnorrBatch::new(&secp);
+        for (i, witness) in witnesses.iter().enumerate() {
+            // the invalid signature isn't detected until the batch is verified
+            assert!(batch.add(i, witness, &transation.id(), &address).is_ok());
+        }
+        assert_eq!(batch.len(), 3);
+        assert_eq!(batch.verify().unwrap_err().0, 1);
+
+        let mut batch = SchnorrBatch::new(&secp);
+        for i in [0, 2].iter() {
+            assert!(batch
+                .add(*i, &witnesses[*i], &transation.id(), &address)
+                .is_ok());
+        }
+        assert!(batch.verify().is_ok());
+    }
+
     #[test]
     fn check_staked_verify() {
         let transation = Tx::new();
@@ -7,11 +7,13 @@ use prettytable::{cell, format, row, Cell, Row, Table};
 use quest::success;
 use structopt::StructOpt;
 
+use chain_core::init::coin::Coin;
 use chain_core::state::account::StakedStateAddress;
 use client_common::balance::BalanceChange;
 use client_common::storage::SledStorage;
 use client_common::tendermint::{Client, RpcClient};
 use client_common::Result;
+use client_core::service::PendingTransactionStatus;
 use client_core::signer::DefaultSigner;
 use client_core::transaction_builder::DefaultTransactionBuilder;
 use client_core::wallet::{DefaultWalletClient, WalletClient};
@@ -22,7 +24,7 @@ use self::address_command::AddressCommand;
 use self::transaction_command::TransactionCommand;
 use self::wallet_command::WalletCommand;
 
-use crate::{ask_passphrase, storage_path, tendermint_url};
+use crate::{ask_passphrase, storage_path, tendermint_url, verify_sync};
 
 #[derive(Debug, StructOpt)]
 #[structopt(
@@ -63,7 +65,10 @@ pub enum Command {
         address: StakedStateAddress,
     },
     #[structopt(name = "resync", about = "Re-synchronize client with Crypto.com Chain")]
-    Resync,
+    Resync {
+        #[structopt(name = "name", short, long, help = "Name of wallet")]
+        name: String,
+    },
 }
 
 impl Command {
@@ -86,7 +91,7 @@ impl Command {
             Command::Balance { name } => {
                 let storage = SledStorage::new(storage_path())?;
                 let tendermint_client = RpcClient::new(&tendermint_url());
-                let transaction_index = DefaultIndex::new(storage.clone(), tendermint_client);
+                let transaction_index = transaction_index(storage.clone(), tendermint_client);
                 let wallet_client = DefaultWalletClient::builder()
                     .with_wallet(storage)
                     .with_transaction_read(transaction_index)
@@ -96,7 +101,7 @@ impl Command {
             Command::History { name } => {
                 let storage = SledStorage::new(storage_path())?;
                 let tendermint_client = RpcClient::new(&tendermint_url());
-                let transaction_index = DefaultIndex::new(storage.clone(), tendermint_client);
+                let transaction_index = transaction_index(storage.clone(), tendermint_client);
                 let wallet_client = DefaultWalletClient::builder()
                     .with_wallet(storage)
                     .with_transaction_read(transaction_index)
@@ -113,7 +118,7 @@ impl Command {
                 let transaction_builder =
                     DefaultTransactionBuilder::new(signer.clone(), fee_algorithm);
                 let transaction_index =
-                    DefaultIndex::new(storage.clone(), tendermint_client.clone());
+                    transaction_index(storage.clone(), tendermint_client.clone());
                 let wallet_client = DefaultWalletClient::builder()
                     .with_wallet(storage)
                     .with_transaction_read(transaction_index)
@@ -135,7 +140,7 @@ impl Command {
                 let transaction_builder =
                     DefaultTransactionBuilder::new(signer.clone(), fee_algorithm);
                 let transaction_index =
-                    DefaultIndex::new(storage.clone(), tendermint_client.clone());
+                    transaction_index(storage.clone(), tendermint_client.clone());
                 let wallet_client = DefaultWalletClient::builder()
                     .with_wallet(storage)
                     .with_transaction_read(transaction_index)
@@ -149,15 +154,15 @@ impl Command {
                 );
                 Self::get_staked_stake(&network_ops_client, name, address)
             }
-            Command::Resync => {
+            Command::Resync { name } => {
                 let storage = SledStorage::new(storage_path())?;
                 let tendermint_client = RpcClient::new(&tendermint_url());
-                let transaction_index = DefaultIndex::new(storage.clone(), tendermint_client);
+                let transaction_index = transaction_index(storage.clone(), tendermint_client);
                 let wallet_client = DefaultWalletClient::builder()
                     .with_wallet(storage)
                     .with_transaction_read(transaction_index)
                     .build()?;
-                Self::resync(wallet_client)
+                Self::resync(wallet_client, name)
             }
         }
     }
@@ -182,10 +187,45 @@ impl Command {
             Cell::from(&"Unbonded".to_string()),
             Cell::from(&format!("{}", staked_state.unbonded)),
         ]));
-        table.add_row(Row::new(vec![
-            Cell::from(&"Unbonded From".to_string()),
-            Cell::from(&format!("{}", staked_state.unbonded_from)),
-        ]));
+        for entry in staked_state.unbonding.iter() {
+            table.add_row(Row::new(vec![
+                Cell::from(&format!("Unbonded From {}", entry.unbonded_from)),
+                Cell::from(&format!("{}", entry.amount)),
+            ]));
+        }
+        for delegation in staked_state.delegations.iter() {
+            table.add_row(Row::new(vec![
+                Cell::from(&format!(
+                    "Delegated To {} (Shares)",
+                    delegation.council_node
+                )),
+                Cell::from(&format!("{}", delegation.shares)),
+            ]));
+        }
+        if staked_state.delegated != Coin::zero() {
+            table.add_row(Row::new(vec![
+                Cell::from(&"Delegated (Council Node)".to_string()),
+                Cell::from(&format!("{}", staked_state.delegated)),
+            ]));
+        }
+        if staked_state.rewards != Coin::zero() {
+            table.add_row(Row::new(vec![
+                Cell::from(&"Unclaimed Rewards".to_string()),
+                Cell::from(&format!("{}", staked_state.rewards)),
+            ]));
+        }
+        if let Some(jailed_until) = staked_state.jailed_until {
+            table.add_row(Row::new(vec![
+                Cell::from(&"Jailed Until".to_string()),
+                Cell::from(&format!("{}", jailed_until)),
+            ]));
+        }
+        if let Some(vesting) = staked_state.vesting {
+            table.add_row(Row::new(vec![
+                Cell::from(&"Vesting".to_string()),
+                Cell::from(&format!("{}", vesting)),
+            ]));
+        }
 
         table.printstd();
 
@@ -194,7 +234,7 @@ impl Command {
 
     fn get_balance<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
         let passphrase = ask_passphrase()?;
-        wallet_client.sync()?;
+        wallet_client.sync(name, &passphrase)?;
         let balance = wallet_client.balance(name, &passphrase)?;
 
         success(&format!("Wallet balance: {}", balance));
@@ -203,7 +243,7 @@ impl Command {
 
     fn get_history<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
         let passphrase = ask_passphrase()?;
-        wallet_client.sync()?;
+        wallet_client.sync(name, &passphrase)?;
         let history = wallet_client.history(name, &passphrase)?;
 
         if !history.is_empty() {
@@ -216,6 +256,7 @@ impl Command {
                 "In/Out",
                 "Block Height",
                 "Block Time",
+                "Memo",
             ]);
 
             for change in history {
@@ -231,6 +272,7 @@ impl Command {
                     Cell::new(in_out).style_spec(spec),
                     Cell::from(&change.height).style_spec("r"),
                     Cell::from(&change.time),
+                    Cell::new(change.memo.as_ref().map(String::as_str).unwrap_or("")),
                 ]));
             }
 
@@ -239,10 +281,49 @@ impl Command {
             success("No history found!")
         }
 
+        let pending_transactions = wallet_client.pending_transactions(name, &passphrase)?;
+
+        if !pending_transactions.is_empty() {
+            let mut table = Table::new();
+            table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
+            table.set_titles(row!["Pending Transaction ID", "Expiry", "Status"]);
+
+            for pending_transaction in pending_transactions {
+                let (status, spec) = match pending_transaction.status {
+                    PendingTransactionStatus::Pending => ("PENDING", "Fy"),
+                    PendingTransactionStatus::Failed => ("FAILED", "FR"),
+                };
+                let expiry = pending_transaction
+                    .expiry
+                    .map(|expiry| expiry.to_string())
+                    .unwrap_or_default();
+
+                table.add_row(Row::new(vec![
+                    Cell::new(&encode(&pending_transaction.transaction_id)),
+                    Cell::new(&expiry),
+                    Cell::new(status).style_spec(spec),
+                ]));
+            }
+
+            table.printstd();
+        }
+
         Ok(())
     }
 
-    fn resync<T: WalletClient>(wallet_client: T) -> Result<()> {
-        wallet_client.sync_all()
+    fn resync<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
+        let passphrase = ask_passphrase()?;
+        wallet_client.sync_all(name, &passphrase)
+    }
+}
+
+fn transaction_index(
+    storage: SledStorage,
+    tendermint_client: RpcClient,
+) -> DefaultIndex<SledStorage, RpcClient> {
+    if verify_sync() {
+        DefaultIndex::new_verifying(storage, tendermint_client)
+    } else {
+        DefaultIndex::new(storage, tendermint_client)
     }
 }
@@ -1,9 +1,15 @@
 use std::str::FromStr;
 
-use quest::{ask, success};
+use failure::ResultExt;
+use hex::{decode, encode};
+use quest::{ask, success, text};
 use structopt::StructOpt;
 use unicase::eq_ascii;
 
+use chain_core::common::{Timespec, H256, HASH_SIZE_256};
+use chain_core::init::address::RedeemAddress;
+use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::htlc::HashTimeLock;
 use client_common::{Error, ErrorKind, Result};
 use client_core::WalletClient;
 
@@ -12,6 +18,8 @@ use crate::ask_passphrase;
 #[derive(Debug)]
 pub enum AddressType {
     Transfer,
+    Basic,
+    Htlc,
     Staking,
 }
 
@@ -21,6 +29,10 @@ impl FromStr for AddressType {
     fn from_str(s: &str) -> Result<Self> {
         if eq_ascii(s, "transfer") {
             Ok(AddressType::Transfer)
+        } else if eq_ascii(s, "basic") {
+            Ok(AddressType::Basic)
+        } else if eq_ascii(s, "htlc") {
+            Ok(AddressType::Htlc)
         } else if eq_ascii(s, "staking") {
             Ok(AddressType::Staking)
         } else {
@@ -77,6 +89,27 @@ impl AddressCommand {
                 success(&format!("New address: {}", address));
                 Ok(())
             }
+            AddressType::Basic => {
+                let address = wallet_client.new_redeem_transfer_address(name, &passphrase)?;
+                success(&format!("New address: {}", address));
+                Ok(())
+            }
+            AddressType::Htlc => {
+                let recipient = ask_redeem_address("Enter recipient address (single-key): ")?;
+                let hash = ask_hash_lock()?;
+
+                ask("Enter timeout (seconds from UNIX epoch): ");
+                let timeout = text()
+                    .context(ErrorKind::IoError)?
+                    .parse::<Timespec>()
+                    .context(ErrorKind::DeserializationError)?;
+
+                let lock =
+                    wallet_client.new_htlc_address(name, &passphrase, recipient, hash, timeout)?;
+                success(&format!("New address: {}", lock.address()));
+                display_htlc(&lock);
+                Ok(())
+            }
         }
     }
 
@@ -104,8 +137,67 @@ impl AddressCommand {
                     success(&format!("{}", address));
                 }
             }
+            AddressType::Basic => {
+                let addresses = wallet_client.transfer_addresses(name, &passphrase)?;
+
+                for address in addresses {
+                    if let ExtendedAddr::BasicRedeem(_) = address {
+                        ask("Address: ");
+                        success(&format!("{}", address));
+                    }
+                }
+            }
+            AddressType::Htlc => {
+                let htlcs = wallet_client.htlcs(name, &passphrase)?;
+
+                for lock in htlcs {
+                    ask("Address: ");
+                    success(&format!("{}", lock.address()));
+                    display_htlc(&lock);
+                }
+            }
         }
 
         Ok(())
     }
 }
+
+/// Prints the terms which the counterparty needs to redeem or verify the hash-time-locked address
+fn display_htlc(lock: &HashTimeLock) {
+    ask("  Hash: ");
+    success(&encode(&lock.hash));
+    ask("  Recipient: ");
+    success(&format!("{}", ExtendedAddr::BasicRedeem(lock.recipient)));
+    ask("  Refund: ");
+    success(&format!("{}", ExtendedAddr::BasicRedeem(lock.refund)));
+    ask("  Timeout: ");
+    success(&format!("{}", lock.timeout));
+}
+
+fn ask_hash_lock() -> Result<H256> {
+    ask("Enter hash lock (hex-encoded sha256 of the secret): ");
+    let decoded =
+        decode(&text().context(ErrorKind::IoError)?).context(ErrorKind::DeserializationError)?;
+
+    if decoded.len() != HASH_SIZE_256 {
+        return Err(ErrorKind::DeserializationError.into());
+    }
+
+    let mut hash = [0; HASH_SIZE_256];
+    hash.copy_from_slice(&decoded);
+
+    Ok(hash)
+}
+
+pub(crate) fn ask_redeem_address(message: &str) -> Result<RedeemAddress> {
+    ask(message);
+    let address = text()
+        .context(ErrorKind::IoError)?
+        .parse::<ExtendedAddr>()
+        .context(ErrorKind::DeserializationError)?;
+
+    match address {
+        ExtendedAddr::BasicRedeem(redeem_address) => Ok(redeem_address),
+        _ => Err(ErrorKind::InvalidInput.into()),
+    }
+}
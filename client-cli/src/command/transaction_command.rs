@@ -10,15 +10,19 @@ use unicase::eq_ascii;
 use chain_core::common::{Timespec, HASH_SIZE_256};
 use chain_core::init::coin::Coin;
 use chain_core::state::account::{StakedStateAddress, StakedStateOpAttributes};
+use chain_core::state::tendermint::{BlockHeight, TendermintValidatorPubKey};
+use chain_core::tx::data::access::{TxAccess, TxAccessPolicy};
 use chain_core::tx::data::address::ExtendedAddr;
-use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::attribute::{TxAttributes, TxExpiry, MAX_MEMO_LEN};
+use chain_core::tx::data::htlc::{hash_lock, HashTimeLock};
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::output::TxOut;
 use chain_core::tx::TxAux;
-use client_common::{Error, ErrorKind, Result};
+use client_common::{Error, ErrorKind, PublicKey, Result};
 use client_core::WalletClient;
 use client_network::NetworkOpsClient;
 
+use super::address_command::ask_redeem_address;
 use crate::ask_passphrase;
 
 #[derive(Debug)]
@@ -27,6 +31,14 @@ pub enum TransactionType {
     Deposit,
     Unbond,
     Withdraw,
+    Join,
+    Unjail,
+    Leave,
+    Redeem,
+    Refund,
+    Delegate,
+    Undelegate,
+    Claim,
 }
 
 impl FromStr for TransactionType {
@@ -41,6 +53,22 @@ impl FromStr for TransactionType {
             Ok(TransactionType::Unbond)
         } else if eq_ascii(s, "withdraw") {
             Ok(TransactionType::Withdraw)
+        } else if eq_ascii(s, "join") {
+            Ok(TransactionType::Join)
+        } else if eq_ascii(s, "unjail") {
+            Ok(TransactionType::Unjail)
+        } else if eq_ascii(s, "leave") {
+            Ok(TransactionType::Leave)
+        } else if eq_ascii(s, "redeem") {
+            Ok(TransactionType::Redeem)
+        } else if eq_ascii(s, "refund") {
+            Ok(TransactionType::Refund)
+        } else if eq_ascii(s, "delegate") {
+            Ok(TransactionType::Delegate)
+        } else if eq_ascii(s, "undelegate") {
+            Ok(TransactionType::Undelegate)
+        } else if eq_ascii(s, "claim") {
+            Ok(TransactionType::Claim)
         } else {
             Err(ErrorKind::DeserializationError.into())
         }
@@ -55,7 +83,7 @@ pub enum TransactionCommand {
             name = "chain-id",
             short,
             long,
-            help = "Chain ID for transaction (Last two hex digits of chain-id)"
+            help = "Full chain ID for transaction (ending with two hex digits)"
         )]
         chain_id: String,
         #[structopt(name = "name", short, long, help = "Name of wallet")]
@@ -109,19 +137,89 @@ fn new_transaction<T: WalletClient, N: NetworkOpsClient>(
         TransactionType::Withdraw => {
             new_withdraw_transaction(network_ops_client, name, &passphrase, chain_id)
         }
+        TransactionType::Join => {
+            new_node_join_transaction(network_ops_client, name, &passphrase, chain_id)
+        }
+        TransactionType::Unjail => {
+            new_unjail_transaction(network_ops_client, name, &passphrase, chain_id)
+        }
+        TransactionType::Leave => {
+            new_node_leave_transaction(network_ops_client, name, &passphrase, chain_id)
+        }
+        TransactionType::Redeem => {
+            new_htlc_redeem_transaction(wallet_client, name, &passphrase, chain_id)
+        }
+        TransactionType::Refund => {
+            new_htlc_refund_transaction(wallet_client, name, &passphrase, chain_id)
+        }
+        TransactionType::Delegate => {
+            new_delegate_transaction(network_ops_client, name, &passphrase, chain_id)
+        }
+        TransactionType::Undelegate => {
+            new_undelegate_transaction(network_ops_client, name, &passphrase, chain_id)
+        }
+        TransactionType::Claim => {
+            new_claim_transaction(network_ops_client, name, &passphrase, chain_id)
+        }
     }?;
 
     wallet_client.broadcast_transaction(&transaction)
 }
 
+fn new_node_join_transaction<N: NetworkOpsClient>(
+    network_ops_client: &N,
+    name: &str,
+    passphrase: &SecUtf8,
+    chain_id: &str,
+) -> Result<TxAux> {
+    let attributes = StakedStateOpAttributes::new_for_chain(chain_id)
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+    let address = ask_staking_address()?;
+    let consensus_pubkey = ask_consensus_pubkey()?;
+
+    network_ops_client.create_node_join_transaction(
+        name,
+        passphrase,
+        &address,
+        consensus_pubkey,
+        attributes,
+    )
+}
+
+fn new_unjail_transaction<N: NetworkOpsClient>(
+    network_ops_client: &N,
+    name: &str,
+    passphrase: &SecUtf8,
+    chain_id: &str,
+) -> Result<TxAux> {
+    let attributes = StakedStateOpAttributes::new_for_chain(chain_id)
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+    let address = ask_staking_address()?;
+
+    network_ops_client.create_unjail_transaction(name, passphrase, &address, attributes)
+}
+
+fn new_node_leave_transaction<N: NetworkOpsClient>(
+    network_ops_client: &N,
+    name: &str,
+    passphrase: &SecUtf8,
+    chain_id: &str,
+) -> Result<TxAux> {
+    let attributes = StakedStateOpAttributes::new_for_chain(chain_id)
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+    let address = ask_staking_address()?;
+
+    network_ops_client.create_node_leave_transaction(name, passphrase, &address, attributes)
+}
+
 fn new_withdraw_transaction<N: NetworkOpsClient>(
     network_ops_client: &N,
     name: &str,
     passphrase: &SecUtf8,
     chain_id: &str,
 ) -> Result<TxAux> {
-    let attributes =
-        TxAttributes::new(decode(chain_id).context(ErrorKind::DeserializationError)?[0]);
+    let attributes = TxAttributes::new_for_chain(chain_id)
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
     let from_address = ask_staking_address()?;
     let to_address = ask_transfer_address()?;
 
@@ -134,14 +232,35 @@ fn new_withdraw_transaction<N: NetworkOpsClient>(
     )
 }
 
+fn new_claim_transaction<N: NetworkOpsClient>(
+    network_ops_client: &N,
+    name: &str,
+    passphrase: &SecUtf8,
+    chain_id: &str,
+) -> Result<TxAux> {
+    let attributes = TxAttributes::new_for_chain(chain_id)
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+    let address = ask_staking_address()?;
+
+    ask("Compound rewards into bonded stake? [yN] ");
+    let to_address = match yesno(false).context(ErrorKind::IoError)? {
+        None => return Err(ErrorKind::InvalidInput.into()),
+        Some(true) => None,
+        Some(false) => Some(ask_transfer_address()?),
+    };
+
+    network_ops_client
+        .create_claim_rewards_transaction(name, passphrase, &address, to_address, attributes)
+}
+
 fn new_unbond_transaction<N: NetworkOpsClient>(
     network_ops_client: &N,
     name: &str,
     passphrase: &SecUtf8,
     chain_id: &str,
 ) -> Result<TxAux> {
-    let attributes =
-        StakedStateOpAttributes::new(decode(chain_id).context(ErrorKind::DeserializationError)?[0]);
+    let attributes = StakedStateOpAttributes::new_for_chain(chain_id)
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
     let address = ask_staking_address()?;
 
     ask("Enter amount: ");
@@ -154,14 +273,68 @@ fn new_unbond_transaction<N: NetworkOpsClient>(
         .create_unbond_stake_transaction(name, passphrase, &address, value, attributes)
 }
 
+fn new_delegate_transaction<N: NetworkOpsClient>(
+    network_ops_client: &N,
+    name: &str,
+    passphrase: &SecUtf8,
+    chain_id: &str,
+) -> Result<TxAux> {
+    let attributes = StakedStateOpAttributes::new_for_chain(chain_id)
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+    let address = ask_staking_address()?;
+    let council_node = ask_council_node_address()?;
+
+    ask("Enter amount: ");
+    let value = text()
+        .context(ErrorKind::IoError)?
+        .parse::<Coin>()
+        .context(ErrorKind::DeserializationError)?;
+
+    network_ops_client.create_delegate_transaction(
+        name,
+        passphrase,
+        &address,
+        council_node,
+        value,
+        attributes,
+    )
+}
+
+fn new_undelegate_transaction<N: NetworkOpsClient>(
+    network_ops_client: &N,
+    name: &str,
+    passphrase: &SecUtf8,
+    chain_id: &str,
+) -> Result<TxAux> {
+    let attributes = StakedStateOpAttributes::new_for_chain(chain_id)
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+    let address = ask_staking_address()?;
+    let council_node = ask_council_node_address()?;
+
+    ask("Enter amount: ");
+    let value = text()
+        .context(ErrorKind::IoError)?
+        .parse::<Coin>()
+        .context(ErrorKind::DeserializationError)?;
+
+    network_ops_client.create_undelegate_transaction(
+        name,
+        passphrase,
+        &address,
+        council_node,
+        value,
+        attributes,
+    )
+}
+
 fn new_deposit_transaction<N: NetworkOpsClient>(
     network_ops_client: &N,
     name: &str,
     passphrase: &SecUtf8,
     chain_id: &str,
 ) -> Result<TxAux> {
-    let attributes =
-        StakedStateOpAttributes::new(decode(chain_id).context(ErrorKind::DeserializationError)?[0]);
+    let attributes = StakedStateOpAttributes::new_for_chain(chain_id)
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
     let inputs = ask_inputs()?;
     let to_address = ask_staking_address()?;
 
@@ -175,15 +348,85 @@ fn new_transfer_transaction<T: WalletClient>(
     passphrase: &SecUtf8,
     chain_id: &str,
 ) -> Result<TxAux> {
-    let attributes =
-        TxAttributes::new(decode(chain_id).context(ErrorKind::DeserializationError)?[0]);
     let outputs = ask_outputs()?;
-
-    let return_address = wallet_client.new_single_transfer_address(name, &passphrase)?;
+    let view_keys = ask_view_keys()?;
+    let mut attributes = TxAttributes::new_for_chain(chain_id)
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+    attributes.allowed_view = view_keys
+        .into_iter()
+        .map(|view_key| TxAccessPolicy::new(view_key.into(), TxAccess::AllData))
+        .collect();
+    let attributes = ask_memo(attributes)?;
+    let attributes = match ask_expiry()? {
+        Some(expiry) => attributes.with_expiry(expiry),
+        None => attributes,
+    };
+
+    let return_address = wallet_client.new_redeem_transfer_address(name, &passphrase)?;
 
     wallet_client.create_transaction(name, &passphrase, outputs, attributes, None, return_address)
 }
 
+fn new_htlc_redeem_transaction<T: WalletClient>(
+    wallet_client: &T,
+    name: &str,
+    passphrase: &SecUtf8,
+    chain_id: &str,
+) -> Result<TxAux> {
+    let attributes = TxAttributes::new_for_chain(chain_id)
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+
+    ask("Enter secret (hex-encoded preimage of the hash lock): ");
+    let decoded =
+        decode(&text().context(ErrorKind::IoError)?).context(ErrorKind::DeserializationError)?;
+    if decoded.len() != HASH_SIZE_256 {
+        return Err(ErrorKind::DeserializationError.into());
+    }
+    let mut preimage = [0; HASH_SIZE_256];
+    preimage.copy_from_slice(&decoded);
+
+    let recipient = ask_redeem_address("Enter recipient address (single-key): ")?;
+    let refund = ask_redeem_address("Enter refund address (single-key): ")?;
+
+    ask("Enter timeout (seconds from UNIX epoch): ");
+    let timeout = text()
+        .context(ErrorKind::IoError)?
+        .parse::<Timespec>()
+        .context(ErrorKind::DeserializationError)?;
+
+    let lock = HashTimeLock::new(hash_lock(&preimage), recipient, refund, timeout);
+    let return_address = wallet_client.new_redeem_transfer_address(name, passphrase)?;
+
+    wallet_client.create_htlc_redeem_transaction(
+        name,
+        passphrase,
+        lock,
+        preimage,
+        attributes,
+        return_address,
+    )
+}
+
+fn new_htlc_refund_transaction<T: WalletClient>(
+    wallet_client: &T,
+    name: &str,
+    passphrase: &SecUtf8,
+    chain_id: &str,
+) -> Result<TxAux> {
+    let attributes = TxAttributes::new_for_chain(chain_id)
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+    let address = ask_transfer_address()?;
+    let return_address = wallet_client.new_redeem_transfer_address(name, passphrase)?;
+
+    wallet_client.create_htlc_refund_transaction(
+        name,
+        passphrase,
+        &address,
+        attributes,
+        return_address,
+    )
+}
+
 fn ask_outputs() -> Result<Vec<TxOut>> {
     let mut outputs = Vec::new();
 
@@ -230,6 +473,56 @@ fn ask_outputs() -> Result<Vec<TxOut>> {
     Ok(outputs)
 }
 
+fn ask_view_keys() -> Result<Vec<PublicKey>> {
+    let mut view_keys = Vec::new();
+
+    loop {
+        ask("Enter view key of recipient (leave blank to finish): ");
+        let view_key_encoded = text().context(ErrorKind::IoError)?;
+
+        if view_key_encoded.is_empty() {
+            break;
+        }
+
+        let view_key_decoded =
+            decode(&view_key_encoded).context(ErrorKind::DeserializationError)?;
+        view_keys.push(PublicKey::deserialize_from(&view_key_decoded)?);
+    }
+
+    Ok(view_keys)
+}
+
+fn ask_memo(attributes: TxAttributes) -> Result<TxAttributes> {
+    ask(&format!(
+        "Enter memo (at most {} bytes, leave blank for none): ",
+        MAX_MEMO_LEN
+    ));
+    let memo = text().context(ErrorKind::IoError)?;
+
+    if memo.is_empty() {
+        return Ok(attributes);
+    }
+
+    attributes
+        .with_memo(memo)
+        .ok_or_else(|| ErrorKind::InvalidInput.into())
+}
+
+fn ask_expiry() -> Result<Option<TxExpiry>> {
+    ask("Enter block height after which the transaction expires (leave blank for none): ");
+    let height = text().context(ErrorKind::IoError)?;
+
+    if height.is_empty() {
+        return Ok(None);
+    }
+
+    let height = height
+        .parse::<BlockHeight>()
+        .context(ErrorKind::DeserializationError)?;
+
+    Ok(Some(TxExpiry::Height(height)))
+}
+
 fn ask_inputs() -> Result<Vec<TxoPointer>> {
     let mut inputs = Vec::new();
 
@@ -277,6 +570,16 @@ fn ask_staking_address() -> Result<StakedStateAddress> {
     Ok(address)
 }
 
+fn ask_council_node_address() -> Result<StakedStateAddress> {
+    ask("Enter council node staking address: ");
+    let address = text()
+        .context(ErrorKind::IoError)?
+        .parse::<StakedStateAddress>()
+        .context(ErrorKind::DeserializationError)?;
+
+    Ok(address)
+}
+
 fn ask_transfer_address() -> Result<ExtendedAddr> {
     ask("Enter transfer address: ");
     let address = text()
@@ -286,3 +589,18 @@ fn ask_transfer_address() -> Result<ExtendedAddr> {
 
     Ok(address)
 }
+
+fn ask_consensus_pubkey() -> Result<TendermintValidatorPubKey> {
+    ask("Enter Tendermint consensus public key (base64-encoded Ed25519 key): ");
+    let encoded = text().context(ErrorKind::IoError)?;
+    let decoded = base64::decode(&encoded).context(ErrorKind::DeserializationError)?;
+
+    if decoded.len() != 32 {
+        return Err(ErrorKind::DeserializationError.into());
+    }
+
+    let mut key = [0; 32];
+    key.copy_from_slice(&decoded);
+
+    Ok(TendermintValidatorPubKey::Ed25519(key))
+}
@@ -15,6 +15,11 @@ pub enum WalletCommand {
     },
     #[structopt(name = "list", about = "List all wallets")]
     List,
+    #[structopt(name = "view-key", about = "Get view key of a wallet")]
+    ViewKey {
+        #[structopt(name = "name", short, long, help = "Name of wallet")]
+        name: String,
+    },
 }
 
 impl WalletCommand {
@@ -22,6 +27,7 @@ impl WalletCommand {
         match self {
             WalletCommand::New { name } => Self::new_wallet(wallet_client, name),
             WalletCommand::List => Self::list_wallets(wallet_client),
+            WalletCommand::ViewKey { name } => Self::get_view_key(wallet_client, name),
         }
     }
 
@@ -33,6 +39,14 @@ impl WalletCommand {
         Ok(())
     }
 
+    fn get_view_key<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
+        let passphrase = ask_passphrase()?;
+        let view_key = wallet_client.view_key(name, &passphrase)?;
+
+        success(&format!("View key: {}", view_key));
+        Ok(())
+    }
+
     fn list_wallets<T: WalletClient>(wallet_client: T) -> Result<()> {
         let wallets = wallet_client.wallets()?;
 
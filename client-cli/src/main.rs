@@ -45,6 +45,13 @@ pub(crate) fn tendermint_url() -> String {
     }
 }
 
+pub(crate) fn verify_sync() -> bool {
+    match std::env::var("CRYPTO_CLIENT_VERIFY") {
+        Ok(verify) => "true" == verify,
+        Err(_) => false,
+    }
+}
+
 pub(crate) fn ask_passphrase() -> Result<SecUtf8> {
     ask("Enter passphrase: ");
     Ok(password().context(ErrorKind::IoError)?.into())
@@ -26,6 +26,9 @@ pub struct TransactionChange {
     pub height: u64,
     /// Time of block which has this transaction
     pub time: DateTime<Utc>,
+    /// Memo attached to the transaction (if any)
+    #[serde(default)]
+    pub memo: Option<String>,
 }
 
 impl Encode for TransactionChange {
@@ -35,6 +38,7 @@ impl Encode for TransactionChange {
         self.balance_change.encode_to(dest);
         self.height.encode_to(dest);
         self.time.to_rfc3339().encode_to(dest);
+        self.memo.encode_to(dest);
     }
 }
 
@@ -45,12 +49,14 @@ impl Decode for TransactionChange {
         let balance_change = BalanceChange::decode(input)?;
         let height = u64::decode(input)?;
         let time = DateTime::from_str(&String::decode(input)?).ok()?;
+        let memo = Option::<String>::decode(input)?;
         Some(TransactionChange {
             transaction_id,
             address,
             balance_change,
             height,
             time,
+            memo,
         })
     }
 }
@@ -86,6 +92,7 @@ mod tests {
             balance_change,
             height: 0,
             time: DateTime::from(SystemTime::now()),
+            memo: None,
         }
     }
 
@@ -137,4 +144,17 @@ mod tests {
         assert!(coin.is_err(), "Created negative coin")
     }
 
+    #[test]
+    fn check_encode_decode_with_memo() {
+        let mut change = get_transaction_change(BalanceChange::Incoming(
+            Coin::new(30).expect("Unable to create new coin"),
+        ));
+        change.memo = Some("customer 42".to_owned());
+
+        let encoded = change.encode();
+        assert_eq!(
+            change,
+            TransactionChange::decode(&mut encoded.as_slice()).expect("Unable to decode")
+        );
+    }
 }
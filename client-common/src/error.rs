@@ -3,6 +3,8 @@ use std::fmt;
 
 use failure::{Backtrace, Context, Fail};
 
+use chain_core::tx::rejection::RejectionCode;
+
 /// Alias of `Result` objects that return [`Error`]
 ///
 /// [`Error`]: self::Error
@@ -62,6 +64,9 @@ pub enum ErrorKind {
     /// Invalid transaction
     #[fail(display = "Invalid transaction")]
     InvalidTransaction,
+    /// Transaction rejected by the node (with the reason it sent)
+    #[fail(display = "Transaction rejected: {}", _0)]
+    TransactionRejected(RejectionCode),
     /// Transaction not found
     #[fail(display = "Transaction not found")]
     TransactionNotFound,
@@ -107,6 +112,9 @@ pub enum ErrorKind {
     /// Signing error
     #[fail(display = "Signing error")]
     SigningError,
+    /// Verification error
+    #[fail(display = "Verification error")]
+    VerifyError,
 }
 
 impl Fail for Error {
@@ -1,5 +1,6 @@
 //! Tendermint client operations
 mod client;
+pub mod lite;
 #[cfg(feature = "rpc")]
 mod rpc_client;
 
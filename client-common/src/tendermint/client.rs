@@ -16,9 +16,18 @@ pub trait Client: Send + Sync {
     /// Makes `block_results` call to tendermint
     fn block_results(&self, height: u64) -> Result<BlockResults>;
 
-    /// Makes `broadcast_tx_sync` call to tendermint
+    /// Makes `commit` call to tendermint
+    fn commit(&self, height: u64) -> Result<Commit>;
+
+    /// Makes `broadcast_tx_sync` call to tendermint (returns the typed rejection reason if CheckTx fails)
     fn broadcast_transaction(&self, transaction: &[u8]) -> Result<()>;
 
-    /// Get abci query
-    fn query(&self, path: &str, data: &str) -> Result<QueryResult>;
+    /// Get abci query (at the given height or the latest one if `None`, with a proof if `prove` is set)
+    fn query(
+        &self,
+        path: &str,
+        data: &str,
+        height: Option<u64>,
+        prove: bool,
+    ) -> Result<QueryResult>;
 }
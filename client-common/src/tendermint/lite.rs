@@ -0,0 +1,757 @@
+//! Light client verification of Tendermint headers (against tracked validator sets)
+//!
+//! NOTE: header hashes, validator set hashes and vote sign bytes follow the amino encoding of Tendermint 0.32
+use chrono::offset::Utc;
+use chrono::DateTime;
+use ed25519_dalek::{PublicKey, Signature};
+use failure::ResultExt;
+use parity_codec::{Decode, Encode};
+use sha2::{Digest, Sha256};
+
+use crate::tendermint::types::{BlockId, FullHeader, SignedHeader, Vote};
+use crate::tendermint::Client;
+use crate::{ErrorKind, Result};
+
+/// Type of precommit votes (`SignedMsgType` in Tendermint)
+const PRECOMMIT_TYPE: u8 = 2;
+/// Amino prefix of `PubKeyEd25519` (followed by the length of the key)
+const ED25519_AMINO_PREFIX: [u8; 5] = [0x16, 0x24, 0xde, 0x64, 0x20];
+
+const TYP3_VARINT: u64 = 0;
+const TYP3_8BYTE: u64 = 1;
+const TYP3_BYTE_LENGTH: u64 = 2;
+
+/// Tendermint validator (with an Ed25519 consensus public key)
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct Validator {
+    /// Ed25519 consensus public key
+    pub pub_key: [u8; 32],
+    /// Voting power (validators with zero voting power are removed from validator sets)
+    pub voting_power: u64,
+}
+
+impl Validator {
+    /// Returns address of validator (first 20 bytes of SHA256 of its public key)
+    pub fn address(&self) -> [u8; 20] {
+        let mut address = [0u8; 20];
+        address.copy_from_slice(&Sha256::digest(&self.pub_key)[..20]);
+        address
+    }
+
+    /// Amino encoding of validator used in validator set hash
+    fn amino_bytes(&self) -> Vec<u8> {
+        let mut pub_key = ED25519_AMINO_PREFIX.to_vec();
+        pub_key.extend_from_slice(&self.pub_key);
+
+        let mut bytes = Vec::new();
+        encode_bytes_field(1, &pub_key, &mut bytes);
+        encode_uvarint_field(2, self.voting_power, &mut bytes);
+        bytes
+    }
+}
+
+/// Validator set of a block (sorted by validator addresses)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Encode, Decode)]
+pub struct ValidatorSet {
+    validators: Vec<Validator>,
+}
+
+impl ValidatorSet {
+    /// Creates a new validator set (validators with zero voting power are skipped)
+    pub fn new(validators: Vec<Validator>) -> Self {
+        let mut validators = validators
+            .into_iter()
+            .filter(|validator| validator.voting_power > 0)
+            .collect::<Vec<Validator>>();
+        validators.sort_by_key(Validator::address);
+
+        Self { validators }
+    }
+
+    /// Returns validators in this set
+    pub fn validators(&self) -> &[Validator] {
+        &self.validators
+    }
+
+    /// Returns total voting power of this set
+    pub fn total_voting_power(&self) -> u64 {
+        self.validators
+            .iter()
+            .map(|validator| validator.voting_power)
+            .sum()
+    }
+
+    /// Returns hash of this set (as committed to in `validators_hash` of block headers)
+    pub fn hash(&self) -> Vec<u8> {
+        let items = self
+            .validators
+            .iter()
+            .map(Validator::amino_bytes)
+            .collect::<Vec<Vec<u8>>>();
+        simple_hash(&items)
+    }
+
+    /// Returns validator set after applying validator updates from `EndBlock` (zero voting power removes a
+    /// validator)
+    pub fn apply_updates(&self, updates: &[Validator]) -> Self {
+        let mut validators = self.validators.clone();
+
+        for update in updates {
+            validators.retain(|validator| validator.pub_key != update.pub_key);
+            validators.push(update.clone());
+        }
+
+        Self::new(validators)
+    }
+
+    /// Verifies that signed header was committed by more than 2/3 of voting power of this validator set
+    pub fn verify_commit(&self, chain_id: &str, signed_header: &SignedHeader) -> Result<()> {
+        let header = &signed_header.header;
+        let commit = &signed_header.commit;
+
+        if decode_hex(&commit.block_id.hash)? != header_hash(header)?
+            || decode_hex(&header.validators_hash)? != self.hash()
+            || commit.precommits.len() != self.validators.len()
+        {
+            return Err(ErrorKind::VerifyError.into());
+        }
+
+        let height = signed_header.height()?;
+        let mut signed_voting_power = 0;
+
+        for (validator, vote) in self.validators.iter().zip(commit.precommits.iter()) {
+            let vote = match vote {
+                None => continue,
+                Some(vote) => vote,
+            };
+
+            // votes for nil (or for any other block) do not count towards the commit
+            if vote.block_id != commit.block_id {
+                continue;
+            }
+
+            if vote.vote_type != PRECOMMIT_TYPE
+                || parse_u64(&vote.height)? != height
+                || decode_hex(&vote.validator_address)? != validator.address()
+            {
+                return Err(ErrorKind::VerifyError.into());
+            }
+
+            let signature =
+                base64::decode(&vote.signature).context(ErrorKind::DeserializationError)?;
+            let signature = Signature::from_bytes(&signature).context(ErrorKind::VerifyError)?;
+            let public_key =
+                PublicKey::from_bytes(&validator.pub_key).context(ErrorKind::VerifyError)?;
+
+            public_key
+                .verify(&vote_sign_bytes(chain_id, vote)?, &signature)
+                .context(ErrorKind::VerifyError)?;
+
+            signed_voting_power += validator.voting_power;
+        }
+
+        if signed_voting_power > self.total_voting_power() * 2 / 3 {
+            Ok(())
+        } else {
+            Err(ErrorKind::VerifyError.into())
+        }
+    }
+}
+
+/// Trusted state of light client, i.e., the height of next block to verify and validator sets of that block and
+/// the one after it
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct LightClientState {
+    /// Height of next block to verify
+    pub height: u64,
+    /// Validators of block at `height`
+    pub validators: ValidatorSet,
+    /// Validators of block at `height + 1`
+    pub next_validators: ValidatorSet,
+}
+
+impl LightClientState {
+    /// Creates initial light client state from genesis validators
+    pub fn genesis(validators: Vec<Validator>) -> Self {
+        let validators = ValidatorSet::new(validators);
+
+        Self {
+            height: 1,
+            next_validators: validators.clone(),
+            validators,
+        }
+    }
+
+    /// Verifies signed header of block at current height (without changing the trusted state)
+    pub fn verify(&self, chain_id: &str, signed_header: &SignedHeader) -> Result<()> {
+        let header = &signed_header.header;
+
+        if header.chain_id != chain_id
+            || signed_header.height()? != self.height
+            || decode_hex(&header.next_validators_hash)? != self.next_validators.hash()
+        {
+            return Err(ErrorKind::VerifyError.into());
+        }
+
+        self.validators.verify_commit(chain_id, signed_header)
+    }
+
+    /// Moves trusted state to next block height (`validator_updates` are returned in `EndBlock` of the verified
+    /// block at current height and take effect two blocks later)
+    pub fn advance(&mut self, validator_updates: &[Validator]) {
+        let next_next_validators = self.next_validators.apply_updates(validator_updates);
+
+        self.height += 1;
+        self.validators = std::mem::replace(&mut self.next_validators, next_next_validators);
+    }
+}
+
+/// Verifies signed headers (fetched from `client`) from the trusted state up to the block at `height` and returns
+/// the verified signed header of that block (the trusted state is moved to `height` with validator updates from
+/// results of the blocks in between)
+pub fn verify_signed_header<C: Client + ?Sized>(
+    client: &C,
+    chain_id: &str,
+    state: &mut LightClientState,
+    height: u64,
+) -> Result<SignedHeader> {
+    if height < state.height {
+        return Err(ErrorKind::VerifyError.into());
+    }
+
+    loop {
+        let signed_header = client.commit(state.height)?.signed_header;
+        state.verify(chain_id, &signed_header)?;
+
+        if state.height == height {
+            return Ok(signed_header);
+        }
+
+        let block_results = client.block_results(state.height)?;
+        state.advance(&block_results.validator_updates()?);
+    }
+}
+
+/// Returns hash of transactions in a block (as committed to in `data_hash` of block headers)
+pub fn transactions_hash(transactions: &[Vec<u8>]) -> Vec<u8> {
+    let items = transactions
+        .iter()
+        .map(|transaction| Sha256::digest(transaction).to_vec())
+        .collect::<Vec<Vec<u8>>>();
+    simple_hash(&items)
+}
+
+/// Returns hash of transaction results in a block (as committed to in `last_results_hash` of next block header -- only
+/// codes and data of results are committed to, not their events)
+pub fn results_hash(results: &[(u32, Vec<u8>)]) -> Vec<u8> {
+    let items = results
+        .iter()
+        .map(|(code, data)| {
+            let mut bytes = Vec::new();
+            encode_uvarint_field(1, u64::from(*code), &mut bytes);
+            encode_bytes_field(2, data, &mut bytes);
+            bytes
+        })
+        .collect::<Vec<Vec<u8>>>();
+    simple_hash(&items)
+}
+
+/// Returns hash of a block header
+pub fn header_hash(header: &FullHeader) -> Result<Vec<u8>> {
+    let mut version = Vec::new();
+    encode_uvarint_field(1, parse_u64(&header.version.block)?, &mut version);
+    encode_uvarint_field(2, parse_u64(&header.version.app)?, &mut version);
+
+    let items = vec![
+        version,
+        encode_bare_bytes(header.chain_id.as_bytes()),
+        encode_bare_uvarint(parse_u64(&header.height)?),
+        encode_time(&header.time),
+        encode_bare_uvarint(parse_u64(&header.num_txs)?),
+        encode_bare_uvarint(parse_u64(&header.total_txs)?),
+        encode_block_id(&header.last_block_id)?,
+        encode_bare_bytes(&decode_hex(&header.last_commit_hash)?),
+        encode_bare_bytes(&decode_hex(&header.data_hash)?),
+        encode_bare_bytes(&decode_hex(&header.validators_hash)?),
+        encode_bare_bytes(&decode_hex(&header.next_validators_hash)?),
+        encode_bare_bytes(&decode_hex(&header.consensus_hash)?),
+        encode_bare_bytes(&decode_hex(&header.app_hash)?),
+        encode_bare_bytes(&decode_hex(&header.last_results_hash)?),
+        encode_bare_bytes(&decode_hex(&header.evidence_hash)?),
+        encode_bare_bytes(&decode_hex(&header.proposer_address)?),
+    ];
+
+    Ok(simple_hash(&items))
+}
+
+/// Returns bytes signed by validators in a vote (length-prefixed amino encoding of `CanonicalVote`)
+pub fn vote_sign_bytes(chain_id: &str, vote: &Vote) -> Result<Vec<u8>> {
+    let mut canonical_vote = Vec::new();
+    encode_uvarint_field(1, u64::from(vote.vote_type), &mut canonical_vote);
+    encode_fixed64_field(2, parse_u64(&vote.height)?, &mut canonical_vote);
+    encode_fixed64_field(3, parse_u64(&vote.round)?, &mut canonical_vote);
+    encode_bytes_field(
+        4,
+        &encode_canonical_block_id(&vote.block_id)?,
+        &mut canonical_vote,
+    );
+    encode_bytes_field(5, &encode_time(&vote.timestamp), &mut canonical_vote);
+    encode_bytes_field(6, chain_id.as_bytes(), &mut canonical_vote);
+
+    Ok(encode_bare_bytes(&canonical_vote))
+}
+
+/// Tendermint simple merkle tree hash (RFC 6962 style with SHA256)
+fn simple_hash(items: &[Vec<u8>]) -> Vec<u8> {
+    match items.len() {
+        0 => Vec::new(),
+        1 => {
+            let mut hasher = Sha256::new();
+            hasher.input(&[0x00]);
+            hasher.input(&items[0]);
+            hasher.result().to_vec()
+        }
+        len => {
+            let split_point = len.next_power_of_two() / 2;
+
+            let mut hasher = Sha256::new();
+            hasher.input(&[0x01]);
+            hasher.input(&simple_hash(&items[..split_point]));
+            hasher.input(&simple_hash(&items[split_point..]));
+            hasher.result().to_vec()
+        }
+    }
+}
+
+fn encode_block_id(block_id: &BlockId) -> Result<Vec<u8>> {
+    let mut parts = Vec::new();
+    encode_uvarint_field(1, parse_u64(&block_id.parts.total)?, &mut parts);
+    encode_bytes_field(2, &decode_hex(&block_id.parts.hash)?, &mut parts);
+
+    let mut bytes = Vec::new();
+    encode_bytes_field(1, &decode_hex(&block_id.hash)?, &mut bytes);
+    encode_bytes_field(2, &parts, &mut bytes);
+    Ok(bytes)
+}
+
+fn encode_canonical_block_id(block_id: &BlockId) -> Result<Vec<u8>> {
+    let mut parts = Vec::new();
+    encode_bytes_field(1, &decode_hex(&block_id.parts.hash)?, &mut parts);
+    encode_uvarint_field(2, parse_u64(&block_id.parts.total)?, &mut parts);
+
+    let mut bytes = Vec::new();
+    encode_bytes_field(1, &decode_hex(&block_id.hash)?, &mut bytes);
+    encode_bytes_field(2, &parts, &mut bytes);
+    Ok(bytes)
+}
+
+fn encode_time(time: &DateTime<Utc>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    encode_uvarint_field(1, time.timestamp() as u64, &mut bytes);
+    encode_uvarint_field(2, u64::from(time.timestamp_subsec_nanos()), &mut bytes);
+    bytes
+}
+
+fn encode_uvarint(mut value: u64, bytes: &mut Vec<u8>) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn encode_bare_uvarint(value: u64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    encode_uvarint(value, &mut bytes);
+    bytes
+}
+
+/// Length-prefixed bytes (empty for empty input)
+fn encode_bare_bytes(value: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    if !value.is_empty() {
+        encode_uvarint(value.len() as u64, &mut bytes);
+        bytes.extend_from_slice(value);
+    }
+    bytes
+}
+
+// NOTE: amino skips fields with default (zero / empty) values
+
+fn encode_uvarint_field(field: u64, value: u64, bytes: &mut Vec<u8>) {
+    if value != 0 {
+        encode_uvarint((field << 3) | TYP3_VARINT, bytes);
+        encode_uvarint(value, bytes);
+    }
+}
+
+fn encode_fixed64_field(field: u64, value: u64, bytes: &mut Vec<u8>) {
+    if value != 0 {
+        encode_uvarint((field << 3) | TYP3_8BYTE, bytes);
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn encode_bytes_field(field: u64, value: &[u8], bytes: &mut Vec<u8>) {
+    if !value.is_empty() {
+        encode_uvarint((field << 3) | TYP3_BYTE_LENGTH, bytes);
+        encode_uvarint(value.len() as u64, bytes);
+        bytes.extend_from_slice(value);
+    }
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>> {
+    Ok(hex::decode(value).context(ErrorKind::DeserializationError)?)
+}
+
+fn parse_u64(value: &str) -> Result<u64> {
+    Ok(value
+        .parse::<u64>()
+        .context(ErrorKind::DeserializationError)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::str::FromStr;
+
+    use ed25519_dalek::{ExpandedSecretKey, SecretKey};
+
+    use crate::tendermint::types::*;
+
+    const CHAIN_ID: &str = "test-chain-y3m1e6-AB";
+
+    fn secret_key(seed: u8) -> SecretKey {
+        SecretKey::from_bytes(&[seed; 32]).unwrap()
+    }
+
+    fn validator(seed: u8, voting_power: u64) -> Validator {
+        Validator {
+            pub_key: PublicKey::from(&secret_key(seed)).to_bytes(),
+            voting_power,
+        }
+    }
+
+    fn block_id(hash: &[u8]) -> BlockId {
+        BlockId {
+            hash: hex::encode_upper(hash),
+            parts: PartSetHeader {
+                total: "1".to_owned(),
+                hash: hex::encode_upper(&[1u8; 32]),
+            },
+        }
+    }
+
+    fn header(
+        height: u64,
+        validators: &ValidatorSet,
+        next_validators: &ValidatorSet,
+    ) -> FullHeader {
+        FullHeader {
+            version: Version {
+                block: "10".to_owned(),
+                app: "0".to_owned(),
+            },
+            chain_id: CHAIN_ID.to_owned(),
+            height: height.to_string(),
+            time: DateTime::from_str("2019-04-09T09:38:41.735577Z").unwrap(),
+            num_txs: "0".to_owned(),
+            total_txs: "0".to_owned(),
+            last_block_id: block_id(&[2u8; 32]),
+            last_commit_hash: hex::encode_upper(&[3u8; 32]),
+            data_hash: "".to_owned(),
+            validators_hash: hex::encode_upper(validators.hash()),
+            next_validators_hash: hex::encode_upper(next_validators.hash()),
+            consensus_hash: hex::encode_upper(&[4u8; 32]),
+            app_hash: hex::encode_upper(&[5u8; 32]),
+            last_results_hash: "".to_owned(),
+            evidence_hash: "".to_owned(),
+            proposer_address: hex::encode_upper(validators.validators()[0].address()),
+        }
+    }
+
+    /// Signs header by validators with given seeds (validators with other seeds do not vote)
+    fn sign(header: FullHeader, validators: &ValidatorSet, seeds: &[u8]) -> SignedHeader {
+        let commit_block_id = block_id(&header_hash(&header).unwrap());
+        let precommits = validators
+            .validators()
+            .iter()
+            .enumerate()
+            .map(|(index, validator)| {
+                let seed = *seeds
+                    .iter()
+                    .find(|seed| validator.pub_key == self::validator(**seed, 0).pub_key)?;
+                let mut vote = Vote {
+                    vote_type: PRECOMMIT_TYPE,
+                    height: header.height.clone(),
+                    round: "0".to_owned(),
+                    block_id: block_id(&header_hash(&header).unwrap()),
+                    timestamp: DateTime::from_str("2019-04-09T09:38:42.735577Z").unwrap(),
+                    validator_address: hex::encode_upper(validator.address()),
+                    validator_index: index.to_string(),
+                    signature: "".to_owned(),
+                };
+                let secret_key = secret_key(seed);
+                let signature = ExpandedSecretKey::from(&secret_key).sign(
+                    &vote_sign_bytes(CHAIN_ID, &vote).unwrap(),
+                    &PublicKey::from(&secret_key),
+                );
+                vote.signature = base64::encode(&signature.to_bytes()[..]);
+                Some(vote)
+            })
+            .collect();
+
+        SignedHeader {
+            header,
+            commit: CommitSignatures {
+                block_id: commit_block_id,
+                precommits,
+            },
+        }
+    }
+
+    /// Chain where validator 3 joins at height 3 (its update is in results of block 1), unless the update is left
+    /// out of block results
+    struct MockClient {
+        report_update: bool,
+    }
+
+    fn validators_at(height: u64) -> ValidatorSet {
+        if height < 3 {
+            ValidatorSet::new(vec![validator(1, 10), validator(2, 10)])
+        } else {
+            ValidatorSet::new(vec![validator(1, 10), validator(2, 10), validator(3, 10)])
+        }
+    }
+
+    impl Client for MockClient {
+        fn genesis(&self) -> Result<Genesis> {
+            unreachable!()
+        }
+
+        fn status(&self) -> Result<Status> {
+            unreachable!()
+        }
+
+        fn block(&self, _: u64) -> Result<Block> {
+            unreachable!()
+        }
+
+        fn block_results(&self, height: u64) -> Result<BlockResults> {
+            let validator_updates = if height == 1 && self.report_update {
+                Some(vec![ValidatorUpdate {
+                    pub_key: ValidatorPubKey {
+                        key_type: "ed25519".to_owned(),
+                        data: base64::encode(&validator(3, 10).pub_key),
+                    },
+                    power: "10".to_owned(),
+                }])
+            } else {
+                None
+            };
+
+            Ok(BlockResults {
+                height: height.to_string(),
+                results: Results {
+                    deliver_tx: None,
+                    end_block: Some(EndBlock {
+                        validator_updates,
+                        events: Vec::new(),
+                    }),
+                },
+            })
+        }
+
+        fn commit(&self, height: u64) -> Result<Commit> {
+            let validators = validators_at(height);
+            Ok(Commit {
+                signed_header: sign(
+                    header(height, &validators, &validators_at(height + 1)),
+                    &validators,
+                    &[1, 2, 3],
+                ),
+                canonical: true,
+            })
+        }
+
+        fn broadcast_transaction(&self, _: &[u8]) -> Result<()> {
+            unreachable!()
+        }
+
+        fn query(&self, _: &str, _: &str, _: Option<u64>, _: bool) -> Result<QueryResult> {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn check_simple_hash() {
+        assert!(simple_hash(&[]).is_empty());
+        let leaf = |item: &[u8]| {
+            let mut bytes = vec![0x00];
+            bytes.extend_from_slice(item);
+            Sha256::digest(&bytes).to_vec()
+        };
+        let inner = |left: Vec<u8>, right: Vec<u8>| {
+            let mut bytes = vec![0x01];
+            bytes.extend(left);
+            bytes.extend(right);
+            Sha256::digest(&bytes).to_vec()
+        };
+        let items = vec![vec![1u8], vec![2u8], vec![3u8]];
+        assert_eq!(
+            inner(inner(leaf(&[1]), leaf(&[2])), leaf(&[3])),
+            simple_hash(&items)
+        );
+    }
+
+    #[test]
+    fn check_amino_encoding() {
+        let mut bytes = Vec::new();
+        encode_uvarint(300, &mut bytes);
+        assert_eq!(vec![0xac, 0x02], bytes);
+
+        // test vector from Tendermint (`TestVoteSignBytesTestVectors`)
+        let vote = Vote {
+            vote_type: PRECOMMIT_TYPE,
+            height: "1".to_owned(),
+            round: "1".to_owned(),
+            block_id: BlockId {
+                hash: "".to_owned(),
+                parts: PartSetHeader {
+                    total: "0".to_owned(),
+                    hash: "".to_owned(),
+                },
+            },
+            timestamp: DateTime::from_str("0001-01-01T00:00:00Z").unwrap(),
+            validator_address: "".to_owned(),
+            validator_index: "0".to_owned(),
+            signature: "".to_owned(),
+        };
+        assert_eq!(
+            vec![
+                0x21, 0x8, 0x2, 0x11, 0x1, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x19, 0x1, 0x0, 0x0,
+                0x0, 0x0, 0x0, 0x0, 0x0, 0x2a, 0xb, 0x8, 0x80, 0x92, 0xb8, 0xc3, 0x98, 0xfe, 0xff,
+                0xff, 0xff, 0x1
+            ],
+            vote_sign_bytes("", &vote).unwrap()
+        );
+    }
+
+    #[test]
+    fn check_results_hash() {
+        assert!(results_hash(&[]).is_empty());
+        // results without code and data are encoded as empty structs
+        assert_eq!(
+            Sha256::digest(&[0x00]).to_vec(),
+            results_hash(&[(0, Vec::new())])
+        );
+        assert_eq!(
+            Sha256::digest(&[0x00, 0x08, 0x01, 0x12, 0x02, 0x01, 0x02]).to_vec(),
+            results_hash(&[(1, vec![1, 2])])
+        );
+    }
+
+    #[test]
+    fn check_apply_updates() {
+        let validators = ValidatorSet::new(vec![validator(1, 10), validator(2, 10)]);
+        assert_eq!(20, validators.total_voting_power());
+
+        let updated = validators.apply_updates(&[validator(2, 0), validator(3, 5)]);
+        assert_eq!(15, updated.total_voting_power());
+        assert_eq!(
+            ValidatorSet::new(vec![validator(3, 5), validator(1, 10)]),
+            updated
+        );
+        assert_ne!(validators.hash(), updated.hash());
+    }
+
+    #[test]
+    fn check_verify_commit() {
+        let validators =
+            ValidatorSet::new(vec![validator(1, 10), validator(2, 10), validator(3, 10)]);
+
+        let signed_header = sign(header(1, &validators, &validators), &validators, &[1, 2, 3]);
+        assert!(validators.verify_commit(CHAIN_ID, &signed_header).is_ok());
+        assert!(validators
+            .verify_commit("other-chain", &signed_header)
+            .is_err());
+
+        // 2/3 of voting power is not enough
+        let signed_header = sign(header(1, &validators, &validators), &validators, &[1, 2]);
+        assert!(validators.verify_commit(CHAIN_ID, &signed_header).is_err());
+
+        // header signed by other validators
+        let other_validators =
+            ValidatorSet::new(vec![validator(4, 10), validator(5, 10), validator(6, 10)]);
+        let signed_header = sign(
+            header(1, &validators, &validators),
+            &other_validators,
+            &[4, 5, 6],
+        );
+        assert!(validators.verify_commit(CHAIN_ID, &signed_header).is_err());
+
+        // header modified after signing
+        let mut signed_header = sign(header(1, &validators, &validators), &validators, &[1, 2, 3]);
+        signed_header.header.app_hash = hex::encode_upper(&[6u8; 32]);
+        assert!(validators.verify_commit(CHAIN_ID, &signed_header).is_err());
+    }
+
+    #[test]
+    fn check_light_client_state() {
+        let mut state = LightClientState::genesis(vec![validator(1, 10), validator(2, 10)]);
+        let validators = state.validators.clone();
+
+        let signed_header = sign(header(1, &validators, &validators), &validators, &[1, 2]);
+        assert!(state.verify(CHAIN_ID, &signed_header).is_ok());
+
+        state.advance(&[validator(3, 10)]);
+        assert_eq!(2, state.height);
+        assert_eq!(validators, state.validators);
+        let next_validators = validators.apply_updates(&[validator(3, 10)]);
+        assert_eq!(next_validators, state.next_validators);
+
+        // header at height 2 has to commit to updated next validators
+        let signed_header = sign(header(2, &validators, &validators), &validators, &[1, 2]);
+        assert!(state.verify(CHAIN_ID, &signed_header).is_err());
+        let signed_header = sign(
+            header(2, &validators, &next_validators),
+            &validators,
+            &[1, 2],
+        );
+        assert!(state.verify(CHAIN_ID, &signed_header).is_ok());
+
+        // wrong height
+        let signed_header = sign(
+            header(3, &validators, &next_validators),
+            &validators,
+            &[1, 2],
+        );
+        assert!(state.verify(CHAIN_ID, &signed_header).is_err());
+    }
+
+    #[test]
+    fn check_verify_signed_header() {
+        let client = MockClient {
+            report_update: true,
+        };
+        let mut state = LightClientState::genesis(validators_at(1).validators().to_vec());
+
+        let signed_header = verify_signed_header(&client, CHAIN_ID, &mut state, 3).unwrap();
+        assert_eq!(3, signed_header.height().unwrap());
+        assert_eq!(3, state.height);
+        assert_eq!(validators_at(3), state.validators);
+
+        // the trusted state can't be moved back
+        assert!(verify_signed_header(&client, CHAIN_ID, &mut state, 3).is_ok());
+        assert!(verify_signed_header(&client, CHAIN_ID, &mut state, 2).is_err());
+
+        // header at height 2 commits to the validators with the update
+        let client = MockClient {
+            report_update: false,
+        };
+        let mut state = LightClientState::genesis(validators_at(1).validators().to_vec());
+        assert!(verify_signed_header(&client, CHAIN_ID, &mut state, 1).is_ok());
+        assert!(verify_signed_header(&client, CHAIN_ID, &mut state, 3).is_err());
+    }
+}
@@ -56,15 +56,31 @@ impl Client for RpcClient {
         self.call("block_results", &params)
     }
 
+    fn commit(&self, height: u64) -> Result<Commit> {
+        let params = [json!(height.to_string())];
+        self.call("commit", &params)
+    }
+
     fn broadcast_transaction(&self, transaction: &[u8]) -> Result<()> {
         let params = [json!(transaction)];
-        self.call::<serde_json::Value>("broadcast_tx_sync", &params)
-            .map(|_| ())
+        self.call::<BroadcastTxResult>("broadcast_tx_sync", &params)?
+            .check()
     }
 
-    fn query(&self, path: &str, data: &str) -> Result<QueryResult> {
+    fn query(
+        &self,
+        path: &str,
+        data: &str,
+        height: Option<u64>,
+        prove: bool,
+    ) -> Result<QueryResult> {
         // path, data, height, prove
-        let params = [json!(path), json!(data), json!(null), json!(null)];
+        let params = [
+            json!(path),
+            json!(data),
+            json!(height.map(|height| height.to_string())),
+            json!(prove),
+        ];
         self.call("abci_query", &params)
     }
 }
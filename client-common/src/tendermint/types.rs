@@ -1,11 +1,15 @@
 //! Structures used in Tendermint RPC (auto-generated)
 mod block;
 mod block_results;
+mod broadcast_tx_result;
+mod commit;
 mod genesis;
 mod query;
 mod status;
 pub use block::*;
 pub use block_results::*;
+pub use broadcast_tx_result::*;
+pub use commit::*;
 pub use genesis::*;
 pub use query::*;
 pub use status::*;
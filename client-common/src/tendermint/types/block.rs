@@ -35,16 +35,22 @@ pub struct Header {
 impl Block {
     /// Returns transactions in a block (this may also contain invalid transactions)
     pub fn transactions(&self) -> Result<Vec<TxAux>> {
+        self.raw_transactions()?
+            .iter()
+            .map(|bytes| {
+                Ok(TxAux::decode(&mut bytes.as_slice()).ok_or(ErrorKind::DeserializationError)?)
+            })
+            .collect::<Result<Vec<TxAux>>>()
+    }
+
+    /// Returns raw (undecoded) transaction bytes in a block
+    pub fn raw_transactions(&self) -> Result<Vec<Vec<u8>>> {
         match &self.block.data.txs {
             None => Ok(Vec::new()),
             Some(txs) => txs
                 .iter()
                 .map(|raw_tx| Ok(decode(&raw_tx).context(ErrorKind::DeserializationError)?))
-                .map(|bytes: Result<Vec<u8>>| {
-                    Ok(TxAux::decode(&mut bytes?.as_slice())
-                        .ok_or(ErrorKind::DeserializationError)?)
-                })
-                .collect::<Result<Vec<TxAux>>>(),
+                .collect::<Result<Vec<Vec<u8>>>>(),
         }
     }
 
@@ -1,5 +1,6 @@
 #![allow(missing_docs)]
 use std::collections::HashSet;
+use std::convert::TryFrom;
 
 use base64::decode;
 use failure::ResultExt;
@@ -7,7 +8,9 @@ use serde::Deserialize;
 
 use chain_core::common::TendermintEventType;
 use chain_core::tx::data::TxId;
+use chain_tx_filter::BlockFilter;
 
+use crate::tendermint::lite::Validator;
 use crate::{ErrorKind, Result};
 
 #[derive(Debug, Deserialize)]
@@ -19,13 +22,40 @@ pub struct BlockResults {
 #[derive(Debug, Deserialize)]
 pub struct Results {
     pub deliver_tx: Option<Vec<DeliverTx>>,
+    pub end_block: Option<EndBlock>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct DeliverTx {
+    #[serde(default)]
+    pub code: u32,
+    #[serde(default)]
+    pub data: Option<String>,
     pub events: Vec<Event>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct EndBlock {
+    #[serde(default)]
+    pub validator_updates: Option<Vec<ValidatorUpdate>>,
+    #[serde(default)]
+    pub events: Vec<Event>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidatorUpdate {
+    pub pub_key: ValidatorPubKey,
+    #[serde(default)]
+    pub power: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidatorPubKey {
+    #[serde(rename = "type")]
+    pub key_type: String,
+    pub data: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Event {
     #[serde(rename = "type")]
@@ -70,6 +100,85 @@ impl BlockResults {
             }
         }
     }
+
+    /// Returns codes and raw (undecoded) data of transaction results in block results (the parts of results committed
+    /// to in `last_results_hash` of next block header)
+    pub fn raw_results(&self) -> Result<Vec<(u32, Vec<u8>)>> {
+        match &self.results.deliver_tx {
+            None => Ok(Vec::new()),
+            Some(deliver_tx) => deliver_tx
+                .iter()
+                .map(|transaction| {
+                    let data = match &transaction.data {
+                        None => Vec::new(),
+                        Some(data) => decode(data).context(ErrorKind::DeserializationError)?,
+                    };
+                    Ok((transaction.code, data))
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns block filter in block results (an empty filter is returned if no view keys were added in block)
+    pub fn block_filter(&self) -> Result<BlockFilter> {
+        let events = match &self.results.end_block {
+            None => return Ok(BlockFilter::default()),
+            Some(end_block) => &end_block.events,
+        };
+
+        for event in events.iter() {
+            if event.event_type == TendermintEventType::BlockFilter.to_string() {
+                if let Some(attribute) = event.attributes.first() {
+                    let decoded =
+                        decode(&attribute.value).context(ErrorKind::DeserializationError)?;
+
+                    return BlockFilter::try_from(decoded.as_slice())
+                        .map_err(|_| ErrorKind::DeserializationError.into());
+                }
+            }
+        }
+
+        Ok(BlockFilter::default())
+    }
+
+    /// Returns validator updates in block results (validators with zero voting power are removed)
+    pub fn validator_updates(&self) -> Result<Vec<Validator>> {
+        let validator_updates = match &self.results.end_block {
+            Some(EndBlock {
+                validator_updates: Some(validator_updates),
+                ..
+            }) => validator_updates,
+            _ => return Ok(Vec::new()),
+        };
+
+        validator_updates
+            .iter()
+            .map(|validator_update| {
+                let pub_key = &validator_update.pub_key;
+                let decoded = decode(&pub_key.data).context(ErrorKind::DeserializationError)?;
+                if "ed25519" != pub_key.key_type || 32 != decoded.len() {
+                    return Err(ErrorKind::DeserializationError.into());
+                }
+
+                // zero voting power is omitted in JSON
+                let voting_power = if validator_update.power.is_empty() {
+                    0
+                } else {
+                    validator_update
+                        .power
+                        .parse::<u64>()
+                        .context(ErrorKind::DeserializationError)?
+                };
+
+                let mut validator = Validator {
+                    pub_key: [0u8; 32],
+                    voting_power,
+                };
+                validator.pub_key.copy_from_slice(&decoded);
+                Ok(validator)
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -82,6 +191,8 @@ mod tests {
             height: "2".to_owned(),
             results: Results {
                 deliver_tx: Some(vec![DeliverTx {
+                    code: 0,
+                    data: None,
                     events: vec![Event {
                         event_type: TendermintEventType::ValidTransactions.to_string(),
                         attributes: vec![Attribute {
@@ -90,6 +201,7 @@ mod tests {
                         }],
                     }],
                 }]),
+                end_block: None,
             },
         };
         assert_eq!(1, block_results.ids().unwrap().len());
@@ -101,6 +213,8 @@ mod tests {
             height: "2".to_owned(),
             results: Results {
                 deliver_tx: Some(vec![DeliverTx {
+                    code: 0,
+                    data: None,
                     events: vec![Event {
                         event_type: TendermintEventType::ValidTransactions.to_string(),
                         attributes: vec![Attribute {
@@ -109,18 +223,132 @@ mod tests {
                         }],
                     }],
                 }]),
+                end_block: None,
             },
         };
 
         assert!(block_results.ids().is_err());
     }
 
+    #[test]
+    fn check_raw_results() {
+        let block_results = BlockResults {
+            height: "2".to_owned(),
+            results: Results {
+                deliver_tx: Some(vec![
+                    DeliverTx {
+                        code: 0,
+                        data: None,
+                        events: Vec::new(),
+                    },
+                    DeliverTx {
+                        code: 1,
+                        data: Some("AQI=".to_owned()),
+                        events: Vec::new(),
+                    },
+                ]),
+                end_block: None,
+            },
+        };
+        assert_eq!(
+            vec![(0, Vec::new()), (1, vec![1, 2])],
+            block_results.raw_results().unwrap()
+        );
+    }
+
     #[test]
     fn check_null_deliver_tx() {
         let block_results = BlockResults {
             height: "2".to_owned(),
-            results: Results { deliver_tx: None },
+            results: Results {
+                deliver_tx: None,
+                end_block: None,
+            },
         };
         assert_eq!(0, block_results.ids().unwrap().len());
     }
+
+    #[test]
+    fn check_block_filter() {
+        let block_results = BlockResults {
+            height: "2".to_owned(),
+            results: Results {
+                deliver_tx: None,
+                end_block: Some(EndBlock {
+                    validator_updates: None,
+                    events: vec![Event {
+                        event_type: TendermintEventType::BlockFilter.to_string(),
+                        attributes: vec![Attribute {
+                            key: "ZXRoYmxvb20=".to_owned(),
+                            value: base64::encode(&[0u8; 256][..]),
+                        }],
+                    }],
+                }),
+            },
+        };
+        assert!(!block_results.block_filter().unwrap().is_modified());
+    }
+
+    #[test]
+    fn check_validator_updates() {
+        let block_results = BlockResults {
+            height: "2".to_owned(),
+            results: Results {
+                deliver_tx: None,
+                end_block: Some(EndBlock {
+                    validator_updates: Some(vec![
+                        ValidatorUpdate {
+                            pub_key: ValidatorPubKey {
+                                key_type: "ed25519".to_owned(),
+                                data: base64::encode(&[1u8; 32][..]),
+                            },
+                            power: "10".to_owned(),
+                        },
+                        ValidatorUpdate {
+                            pub_key: ValidatorPubKey {
+                                key_type: "ed25519".to_owned(),
+                                data: base64::encode(&[2u8; 32][..]),
+                            },
+                            power: "".to_owned(),
+                        },
+                    ]),
+                    events: vec![],
+                }),
+            },
+        };
+        assert_eq!(
+            vec![
+                Validator {
+                    pub_key: [1u8; 32],
+                    voting_power: 10,
+                },
+                Validator {
+                    pub_key: [2u8; 32],
+                    voting_power: 0,
+                },
+            ],
+            block_results.validator_updates().unwrap()
+        );
+    }
+
+    #[test]
+    fn check_wrong_block_filter() {
+        let block_results = BlockResults {
+            height: "2".to_owned(),
+            results: Results {
+                deliver_tx: None,
+                end_block: Some(EndBlock {
+                    validator_updates: None,
+                    events: vec![Event {
+                        event_type: TendermintEventType::BlockFilter.to_string(),
+                        attributes: vec![Attribute {
+                            key: "ZXRoYmxvb20=".to_owned(),
+                            value: base64::encode(&[0u8; 32][..]),
+                        }],
+                    }],
+                }),
+            },
+        };
+        assert!(block_results.block_filter().is_err());
+    }
 }
@@ -0,0 +1,63 @@
+#![allow(missing_docs)]
+
+use std::convert::TryFrom;
+
+use serde::Deserialize;
+
+use chain_core::tx::rejection::RejectionCode;
+
+use crate::{Error, ErrorKind, Result};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BroadcastTxResult {
+    pub code: u32,
+    #[serde(default)]
+    pub log: String,
+    #[serde(default)]
+    pub hash: String,
+}
+
+impl BroadcastTxResult {
+    /// Returns the rejection reason as a typed error if the transaction wasn't accepted by CheckTx
+    pub fn check(&self) -> Result<()> {
+        if self.code == 0 {
+            return Ok(());
+        }
+        match RejectionCode::try_from(self.code) {
+            Ok(rejection) => Err(Error::from(ErrorKind::TransactionRejected(rejection))),
+            Err(()) => Err(Error::from(ErrorKind::InvalidTransaction)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_accepted_transaction() {
+        let result = BroadcastTxResult::default();
+        assert!(result.check().is_ok());
+    }
+
+    #[test]
+    fn check_rejected_transaction() {
+        let result = BroadcastTxResult {
+            code: RejectionCode::InputSpent.code(),
+            ..Default::default()
+        };
+        assert_eq!(
+            ErrorKind::TransactionRejected(RejectionCode::InputSpent),
+            result.check().unwrap_err().kind()
+        );
+
+        let result = BroadcastTxResult {
+            code: 1000,
+            ..Default::default()
+        };
+        assert_eq!(
+            ErrorKind::InvalidTransaction,
+            result.check().unwrap_err().kind()
+        );
+    }
+}
@@ -0,0 +1,103 @@
+#![allow(missing_docs)]
+use chrono::offset::Utc;
+use chrono::DateTime;
+use failure::ResultExt;
+use serde::Deserialize;
+
+use chain_core::common::H256;
+
+use crate::{ErrorKind, Result};
+
+#[derive(Debug, Deserialize)]
+pub struct Commit {
+    pub signed_header: SignedHeader,
+    #[serde(default)]
+    pub canonical: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignedHeader {
+    pub header: FullHeader,
+    pub commit: CommitSignatures,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FullHeader {
+    pub version: Version,
+    pub chain_id: String,
+    pub height: String,
+    pub time: DateTime<Utc>,
+    pub num_txs: String,
+    pub total_txs: String,
+    pub last_block_id: BlockId,
+    pub last_commit_hash: String,
+    pub data_hash: String,
+    pub validators_hash: String,
+    pub next_validators_hash: String,
+    pub consensus_hash: String,
+    pub app_hash: String,
+    pub last_results_hash: String,
+    pub evidence_hash: String,
+    pub proposer_address: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Version {
+    pub block: String,
+    pub app: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct BlockId {
+    pub hash: String,
+    pub parts: PartSetHeader,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct PartSetHeader {
+    pub total: String,
+    pub hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommitSignatures {
+    pub block_id: BlockId,
+    pub precommits: Vec<Option<Vote>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Vote {
+    #[serde(rename = "type")]
+    pub vote_type: u8,
+    pub height: String,
+    pub round: String,
+    pub block_id: BlockId,
+    pub timestamp: DateTime<Utc>,
+    pub validator_address: String,
+    pub validator_index: String,
+    pub signature: String,
+}
+
+impl SignedHeader {
+    /// Returns height of the signed header
+    pub fn height(&self) -> Result<u64> {
+        Ok(self
+            .header
+            .height
+            .parse::<u64>()
+            .context(ErrorKind::DeserializationError)?)
+    }
+
+    /// Returns the application hash after executing the previous block
+    pub fn app_hash(&self) -> Result<H256> {
+        let decoded =
+            hex::decode(&self.header.app_hash).context(ErrorKind::DeserializationError)?;
+        if 32 != decoded.len() {
+            return Err(ErrorKind::DeserializationError.into());
+        }
+
+        let mut app_hash = [0u8; 32];
+        app_hash.copy_from_slice(&decoded);
+        Ok(app_hash)
+    }
+}
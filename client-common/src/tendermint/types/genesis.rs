@@ -2,10 +2,15 @@
 
 use chrono::offset::Utc;
 use chrono::DateTime;
+use failure::ResultExt;
 use serde::Deserialize;
 
-use chain_core::init::config::InitConfig;
-use chain_core::tx::fee::LinearFee;
+use chain_core::init::config::{InitConfig, ValidatorKeyType};
+use chain_core::state::tendermint::TendermintVotePower;
+use chain_core::tx::fee::FeePolicy;
+
+use crate::tendermint::lite::Validator;
+use crate::{ErrorKind, Result};
 
 #[derive(Debug, Deserialize)]
 pub struct Genesis {
@@ -26,7 +31,50 @@ impl Genesis {
     }
 
     /// Returns initial_fee_policy
-    pub fn fee_policy(&self) -> LinearFee {
+    pub fn fee_policy(&self) -> FeePolicy {
         self.genesis.app_state.network_params.initial_fee_policy
     }
+
+    /// Returns unbonding period in genesis (it may have been changed later by the council)
+    pub fn unbonding_period(&self) -> u32 {
+        self.genesis.app_state.network_params.unbonding_period
+    }
+
+    /// Returns chain id
+    pub fn chain_id(&self) -> &str {
+        &self.genesis.chain_id
+    }
+
+    /// Returns initial validators (the application replaces validators in genesis with council nodes in
+    /// `app_state`)
+    pub fn validators(&self) -> Result<Vec<Validator>> {
+        let app_state = &self.genesis.app_state;
+
+        app_state
+            .council_nodes
+            .iter()
+            .map(|node| {
+                let pub_key = match node.consensus_pubkey_type {
+                    ValidatorKeyType::Ed25519 => base64::decode(&node.consensus_pubkey_b64)
+                        .context(ErrorKind::DeserializationError)?,
+                };
+                if 32 != pub_key.len() {
+                    return Err(ErrorKind::DeserializationError.into());
+                }
+
+                let (amount, _) = app_state
+                    .distribution
+                    .get(&node.staking_account_address)
+                    .ok_or(ErrorKind::DeserializationError)?;
+                let voting_power: i64 = TendermintVotePower::from(*amount).into();
+
+                let mut validator = Validator {
+                    pub_key: [0u8; 32],
+                    voting_power: voting_power as u64,
+                };
+                validator.pub_key.copy_from_slice(&pub_key);
+                Ok(validator)
+            })
+            .collect()
+    }
 }
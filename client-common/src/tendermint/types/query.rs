@@ -6,7 +6,36 @@ pub struct QueryResult {
     pub response: Response,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct Response {
     pub value: String,
+    #[serde(default)]
+    pub height: String,
+    #[serde(default)]
+    pub proof: Option<Proof>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Proof {
+    #[serde(default)]
+    pub ops: Vec<ProofOp>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProofOp {
+    #[serde(rename = "type")]
+    pub field_type: String,
+    pub key: String,
+    pub data: String,
+}
+
+impl Response {
+    /// Returns the proof operation of the given type (if any)
+    pub fn proof_op(&self, field_type: &str) -> Option<&ProofOp> {
+        self.proof
+            .as_ref()?
+            .ops
+            .iter()
+            .find(|op| op.field_type == field_type)
+    }
 }
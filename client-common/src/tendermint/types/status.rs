@@ -1,8 +1,12 @@
 #![allow(missing_docs)]
 
+use chrono::offset::Utc;
+use chrono::DateTime;
 use failure::ResultExt;
 use serde::Deserialize;
 
+use chain_core::common::{H256, HASH_SIZE_256};
+
 use crate::{ErrorKind, Result};
 
 #[derive(Debug, Deserialize)]
@@ -13,6 +17,9 @@ pub struct Status {
 #[derive(Debug, Deserialize)]
 pub struct SyncInfo {
     pub latest_block_height: String,
+    #[serde(default)]
+    pub latest_app_hash: String,
+    pub latest_block_time: DateTime<Utc>,
 }
 
 impl Status {
@@ -24,20 +31,42 @@ impl Status {
             .parse::<u64>()
             .context(ErrorKind::DeserializationError)?)
     }
+
+    /// Returns time of the last block
+    pub fn last_block_time(&self) -> DateTime<Utc> {
+        self.sync_info.latest_block_time
+    }
+
+    /// Returns app hash after the last block
+    pub fn last_app_hash(&self) -> Result<H256> {
+        let decoded = hex::decode(&self.sync_info.latest_app_hash)
+            .context(ErrorKind::DeserializationError)?;
+        if decoded.len() != HASH_SIZE_256 {
+            return Err(ErrorKind::DeserializationError.into());
+        }
+        let mut app_hash = [0u8; HASH_SIZE_256];
+        app_hash.copy_from_slice(&decoded);
+        Ok(app_hash)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::str::FromStr;
+
     #[test]
     fn check_last_block_height() {
         let status = Status {
             sync_info: SyncInfo {
                 latest_block_height: "1".to_owned(),
+                latest_app_hash: "".to_owned(),
+                latest_block_time: DateTime::from_str("2019-04-09T09:38:41.735577Z").unwrap(),
             },
         };
         assert_eq!(1, status.last_block_height().unwrap());
+        assert_eq!(1554802721, status.last_block_time().timestamp());
     }
 
     #[test]
@@ -45,9 +74,36 @@ mod tests {
         let status = Status {
             sync_info: SyncInfo {
                 latest_block_height: "a".to_owned(),
+                latest_app_hash: "".to_owned(),
+                latest_block_time: DateTime::from_str("2019-04-09T09:38:41.735577Z").unwrap(),
             },
         };
 
         assert!(status.last_block_height().is_err());
     }
+
+    #[test]
+    fn check_last_app_hash() {
+        let status = Status {
+            sync_info: SyncInfo {
+                latest_block_height: "1".to_owned(),
+                latest_app_hash: "0101010101010101010101010101010101010101010101010101010101010101"
+                    .to_owned(),
+                latest_block_time: DateTime::from_str("2019-04-09T09:38:41.735577Z").unwrap(),
+            },
+        };
+        assert_eq!([1u8; 32], status.last_app_hash().unwrap());
+    }
+
+    #[test]
+    fn check_wrong_last_app_hash() {
+        let status = Status {
+            sync_info: SyncInfo {
+                latest_block_height: "1".to_owned(),
+                latest_app_hash: "0101".to_owned(),
+                latest_block_time: DateTime::from_str("2019-04-09T09:38:41.735577Z").unwrap(),
+            },
+        };
+        assert!(status.last_app_hash().is_err());
+    }
 }
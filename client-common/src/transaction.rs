@@ -1,7 +1,11 @@
 use parity_codec::{Decode, Encode};
 use serde::{Deserialize, Serialize};
 
-use chain_core::state::account::{DepositBondTx, UnbondTx, WithdrawUnbondedTx};
+use chain_core::state::account::{
+    ClaimRewardsTx, DelegateTx, DepositBondTx, NodeJoinRequestTx, NodeLeaveTx, UnbondTx,
+    UndelegateTx, UnjailTx, WithdrawUnbondedTx,
+};
+use chain_core::state::governance::{ParamChangeProposalTx, ParamChangeVoteTx};
 use chain_core::tx::data::Tx;
 
 /// Enum containing different types of transactions
@@ -16,4 +20,20 @@ pub enum Transaction {
     UnbondStakeTransaction(UnbondTx),
     /// Withdraw unbounded stake transaction
     WithdrawUnbondedStakeTransaction(WithdrawUnbondedTx),
+    /// Council node join transaction
+    NodeJoinTransaction(NodeJoinRequestTx),
+    /// Unjail transaction
+    UnjailTransaction(UnjailTx),
+    /// Council node leave transaction
+    NodeLeaveTransaction(NodeLeaveTx),
+    /// Network parameter change proposal transaction
+    ProposalTransaction(ParamChangeProposalTx),
+    /// Network parameter change vote transaction
+    VoteTransaction(ParamChangeVoteTx),
+    /// Delegation to a council node transaction
+    DelegateTransaction(DelegateTx),
+    /// Undelegation from a council node transaction
+    UndelegateTransaction(UndelegateTx),
+    /// Claim rewards transaction
+    ClaimRewardsTransaction(ClaimRewardsTx),
 }
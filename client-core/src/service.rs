@@ -1,10 +1,14 @@
 //! Management services
 mod key_service;
 mod multi_sig_session_service;
+mod pending_transaction_service;
 mod root_hash_service;
 mod wallet_service;
 
 pub use self::key_service::KeyService;
 pub use self::multi_sig_session_service::MultiSigSessionService;
+pub use self::pending_transaction_service::{
+    PendingTransaction, PendingTransactionService, PendingTransactionStatus,
+};
 pub use self::root_hash_service::RootHashService;
 pub use self::wallet_service::WalletService;
@@ -0,0 +1,184 @@
+use parity_codec::{Decode, Encode};
+use secstr::SecUtf8;
+
+use chain_core::common::Timespec;
+use chain_core::tx::data::attribute::TxExpiry;
+use chain_core::tx::data::TxId;
+use client_common::{Error, ErrorKind, Result, SecureStorage, Storage};
+
+const KEYSPACE: &str = "core_pending_transaction";
+
+/// Status of a broadcasted transaction which is not yet seen in a synced block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum PendingTransactionStatus {
+    /// Transaction may still be included in a block
+    Pending,
+    /// Transaction expired before it was included in a block
+    Failed,
+}
+
+/// Transaction created by a wallet which is not yet seen in a synced block
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct PendingTransaction {
+    /// ID of transaction
+    pub transaction_id: TxId,
+    /// Expiry of transaction (if any)
+    pub expiry: Option<TxExpiry>,
+    /// Status of transaction
+    pub status: PendingTransactionStatus,
+}
+
+impl PendingTransaction {
+    /// Creates a new pending transaction
+    pub fn new(transaction_id: TxId, expiry: Option<TxExpiry>) -> Self {
+        Self {
+            transaction_id,
+            expiry,
+            status: PendingTransactionStatus::Pending,
+        }
+    }
+}
+
+/// Maintains mapping `wallet-name -> pending-transactions`
+#[derive(Debug, Default, Clone)]
+pub struct PendingTransactionService<T: Storage> {
+    storage: T,
+}
+
+impl<T> PendingTransactionService<T>
+where
+    T: Storage,
+{
+    /// Creates a new instance of pending transaction service
+    pub fn new(storage: T) -> Self {
+        Self { storage }
+    }
+
+    /// Returns all the pending (and failed) transactions of given wallet
+    pub fn get(&self, name: &str, passphrase: &SecUtf8) -> Result<Vec<PendingTransaction>> {
+        match self.storage.get_secure(KEYSPACE, name, passphrase)? {
+            Some(bytes) => Vec::<PendingTransaction>::decode(&mut bytes.as_slice())
+                .ok_or_else(|| Error::from(ErrorKind::DeserializationError)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn set(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        pending_transactions: Vec<PendingTransaction>,
+    ) -> Result<()> {
+        self.storage
+            .set_secure(KEYSPACE, name, pending_transactions.encode(), passphrase)?;
+
+        Ok(())
+    }
+
+    /// Adds a new pending transaction to given wallet
+    pub fn add(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        pending_transaction: PendingTransaction,
+    ) -> Result<()> {
+        let mut pending_transactions = self.get(name, passphrase)?;
+        pending_transactions.push(pending_transaction);
+        self.set(name, passphrase, pending_transactions)
+    }
+
+    /// Removes transactions which are included in a block (as checked by `is_included`) and marks the ones which
+    /// expired before last block (with given height and time) as failed
+    pub fn update<F>(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        last_block_height: u64,
+        last_block_time: Timespec,
+        is_included: F,
+    ) -> Result<()>
+    where
+        F: Fn(&TxId) -> Result<bool>,
+    {
+        let mut pending_transactions = Vec::new();
+
+        for mut pending_transaction in self.get(name, passphrase)? {
+            if is_included(&pending_transaction.transaction_id)? {
+                continue;
+            }
+
+            if let Some(expiry) = pending_transaction.expiry {
+                if expiry.is_expired(last_block_height as i64, last_block_time) {
+                    pending_transaction.status = PendingTransactionStatus::Failed;
+                }
+            }
+
+            pending_transactions.push(pending_transaction);
+        }
+
+        self.set(name, passphrase, pending_transactions)
+    }
+
+    /// Clears all storage
+    pub fn clear(&self) -> Result<()> {
+        self.storage.clear(KEYSPACE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use client_common::storage::MemoryStorage;
+
+    #[test]
+    fn check_flow() {
+        let pending_transaction_service = PendingTransactionService::new(MemoryStorage::default());
+        let passphrase = SecUtf8::from("passphrase");
+
+        assert!(pending_transaction_service
+            .get("name", &passphrase)
+            .unwrap()
+            .is_empty());
+
+        let included = PendingTransaction::new([0; 32], None);
+        let expiring = PendingTransaction::new([1; 32], Some(TxExpiry::Height(10)));
+        let unbounded = PendingTransaction::new([2; 32], None);
+        for pending_transaction in vec![included, expiring.clone(), unbounded.clone()] {
+            pending_transaction_service
+                .add("name", &passphrase, pending_transaction)
+                .unwrap();
+        }
+
+        pending_transaction_service
+            .update("name", &passphrase, 9, 0, |id| Ok(*id == [0; 32]))
+            .unwrap();
+        assert_eq!(
+            vec![expiring, unbounded],
+            pending_transaction_service
+                .get("name", &passphrase)
+                .unwrap()
+        );
+
+        pending_transaction_service
+            .update("name", &passphrase, 10, 0, |_| Ok(false))
+            .unwrap();
+        let pending_transactions = pending_transaction_service
+            .get("name", &passphrase)
+            .unwrap();
+        assert_eq!(
+            PendingTransactionStatus::Failed,
+            pending_transactions[0].status
+        );
+        assert_eq!(
+            PendingTransactionStatus::Pending,
+            pending_transactions[1].status
+        );
+
+        assert!(pending_transaction_service.clear().is_ok());
+        assert!(pending_transaction_service
+            .get("name", &passphrase)
+            .unwrap()
+            .is_empty());
+    }
+}
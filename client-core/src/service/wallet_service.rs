@@ -6,14 +6,49 @@ use chain_core::common::H256;
 use chain_core::init::address::RedeemAddress;
 use chain_core::state::account::StakedStateAddress;
 use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::htlc::{HashPreimage, HashTimeLock};
 use client_common::{Error, ErrorKind, PublicKey, Result, SecureStorage, Storage};
 
 const KEYSPACE: &str = "core_wallet";
+const VIEW_KEY_KEYSPACE: &str = "core_wallet_view_key";
 
-#[derive(Debug, Default, Encode, Decode)]
+#[derive(Debug, Default, Encode)]
 struct Wallet {
     pub public_keys: Vec<PublicKey>,
     pub root_hashes: Vec<H256>,
+    pub redeem_addresses: Vec<RedeemAddress>,
+    pub htlcs: Vec<(HashTimeLock, Option<HashPreimage>)>,
+}
+
+impl Wallet {
+    /// Decodes a stored wallet (wallets stored before single-key and hash-time-locked addresses were added end after
+    /// root hashes and are stored in the current format on the next update)
+    fn decode(mut bytes: &[u8]) -> Option<Self> {
+        let public_keys = Decode::decode(&mut bytes)?;
+        let root_hashes = Decode::decode(&mut bytes)?;
+
+        if bytes.is_empty() {
+            return Some(Self {
+                public_keys,
+                root_hashes,
+                ..Default::default()
+            });
+        }
+
+        let redeem_addresses = Decode::decode(&mut bytes)?;
+        let htlcs = Decode::decode(&mut bytes)?;
+
+        if bytes.is_empty() {
+            Some(Self {
+                public_keys,
+                root_hashes,
+                redeem_addresses,
+                htlcs,
+            })
+        } else {
+            None
+        }
+    }
 }
 
 /// Maintains mapping `wallet-name -> wallet-details`
@@ -36,8 +71,7 @@ where
             .storage
             .get_secure(KEYSPACE, name, passphrase)?
             .ok_or_else(|| Error::from(ErrorKind::WalletNotFound))?;
-        Wallet::decode(&mut wallet_bytes.as_slice())
-            .ok_or_else(|| Error::from(ErrorKind::DeserializationError))
+        Wallet::decode(&wallet_bytes).ok_or_else(|| Error::from(ErrorKind::DeserializationError))
     }
 
     fn set_wallet(&self, name: &str, passphrase: &SecUtf8, wallet: Wallet) -> Result<()> {
@@ -86,16 +120,59 @@ where
 
                 Ok(None)
             }
+            ExtendedAddr::BasicRedeem(_) | ExtendedAddr::HashTimeLock(_) => Ok(None),
         }
     }
 
-    /// Creates a new wallet and returns wallet ID
-    pub fn create(&self, name: &str, passphrase: &SecUtf8) -> Result<()> {
+    /// Finds hash-time-lock terms (and the preimage, if known) corresponding to given address
+    pub fn find_htlc(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &ExtendedAddr,
+    ) -> Result<Option<(HashTimeLock, Option<HashPreimage>)>> {
+        let htlcs = self.get_wallet(name, passphrase)?.htlcs;
+
+        Ok(htlcs
+            .into_iter()
+            .find(|(lock, _)| lock.address() == *address))
+    }
+
+    /// Creates a new wallet with given view key
+    pub fn create(&self, name: &str, passphrase: &SecUtf8, view_key: PublicKey) -> Result<()> {
         if self.storage.contains_key(KEYSPACE, name)? {
             return Err(ErrorKind::AlreadyExists.into());
         }
 
-        self.set_wallet(name, passphrase, Wallet::default())
+        self.set_wallet(name, passphrase, Wallet::default())?;
+        self.set_view_key(name, passphrase, &view_key)
+    }
+
+    /// Returns view key of wallet (`None` for wallets created before view keys were added)
+    pub fn view_key(&self, name: &str, passphrase: &SecUtf8) -> Result<Option<PublicKey>> {
+        // To verify if the wallet exists and the passphrase is correct or not
+        self.get_wallet(name, passphrase)?;
+
+        self.storage
+            .get_secure(VIEW_KEY_KEYSPACE, name, passphrase)?
+            .map(|view_key_bytes| {
+                PublicKey::decode(&mut view_key_bytes.as_slice())
+                    .ok_or_else(|| Error::from(ErrorKind::DeserializationError))
+            })
+            .transpose()
+    }
+
+    /// Sets view key of wallet
+    pub fn set_view_key(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        view_key: &PublicKey,
+    ) -> Result<()> {
+        self.storage
+            .set_secure(VIEW_KEY_KEYSPACE, name, view_key.encode(), passphrase)?;
+
+        Ok(())
     }
 
     /// Returns all public keys stored in a wallet
@@ -110,6 +187,18 @@ where
         Ok(wallet.root_hashes)
     }
 
+    /// Returns all single-key transfer addresses stored in a wallet
+    pub fn redeem_addresses(&self, name: &str, passphrase: &SecUtf8) -> Result<Vec<RedeemAddress>> {
+        let wallet = self.get_wallet(name, passphrase)?;
+        Ok(wallet.redeem_addresses)
+    }
+
+    /// Returns all hash-time-lock terms stored in a wallet
+    pub fn htlcs(&self, name: &str, passphrase: &SecUtf8) -> Result<Vec<HashTimeLock>> {
+        let wallet = self.get_wallet(name, passphrase)?;
+        Ok(wallet.htlcs.into_iter().map(|(lock, _)| lock).collect())
+    }
+
     /// Returns all staking addresses stored in a wallet
     pub fn staking_addresses(
         &self,
@@ -123,16 +212,24 @@ where
             .collect())
     }
 
-    /// Returns all tree addresses stored in a wallet
+    /// Returns all tree and single-key transfer addresses stored in a wallet
     pub fn transfer_addresses(
         &self,
         name: &str,
         passphrase: &SecUtf8,
     ) -> Result<Vec<ExtendedAddr>> {
-        Ok(self
-            .root_hashes(name, passphrase)?
+        let wallet = self.get_wallet(name, passphrase)?;
+
+        Ok(wallet
+            .root_hashes
             .into_iter()
             .map(ExtendedAddr::OrTree)
+            .chain(
+                wallet
+                    .redeem_addresses
+                    .into_iter()
+                    .map(ExtendedAddr::BasicRedeem),
+            )
             .collect())
     }
 
@@ -145,9 +242,8 @@ where
     ) -> Result<()> {
         self.storage
             .fetch_and_update_secure(KEYSPACE, name, passphrase, |value| {
-                let mut wallet_bytes =
-                    value.ok_or_else(|| Error::from(ErrorKind::WalletNotFound))?;
-                let mut wallet = Wallet::decode(&mut wallet_bytes)
+                let wallet_bytes = value.ok_or_else(|| Error::from(ErrorKind::WalletNotFound))?;
+                let mut wallet = Wallet::decode(wallet_bytes)
                     .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
                 wallet.public_keys.push(public_key.clone());
 
@@ -160,9 +256,8 @@ where
     pub fn add_root_hash(&self, name: &str, passphrase: &SecUtf8, root_hash: H256) -> Result<()> {
         self.storage
             .fetch_and_update_secure(KEYSPACE, name, passphrase, |value| {
-                let mut wallet_bytes =
-                    value.ok_or_else(|| Error::from(ErrorKind::WalletNotFound))?;
-                let mut wallet = Wallet::decode(&mut wallet_bytes)
+                let wallet_bytes = value.ok_or_else(|| Error::from(ErrorKind::WalletNotFound))?;
+                let mut wallet = Wallet::decode(wallet_bytes)
                     .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
                 wallet.root_hashes.push(root_hash);
 
@@ -171,6 +266,52 @@ where
             .map(|_| ())
     }
 
+    /// Adds a single-key transfer address to given wallet
+    pub fn add_redeem_address(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        redeem_address: RedeemAddress,
+    ) -> Result<()> {
+        self.storage
+            .fetch_and_update_secure(KEYSPACE, name, passphrase, |value| {
+                let wallet_bytes = value.ok_or_else(|| Error::from(ErrorKind::WalletNotFound))?;
+                let mut wallet = Wallet::decode(wallet_bytes)
+                    .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+                wallet.redeem_addresses.push(redeem_address);
+
+                Ok(Some(wallet.encode()))
+            })
+            .map(|_| ())
+    }
+
+    /// Adds hash-time-lock terms to given wallet (or sets the preimage of already stored terms)
+    pub fn add_htlc(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        lock: HashTimeLock,
+        preimage: Option<HashPreimage>,
+    ) -> Result<()> {
+        self.storage
+            .fetch_and_update_secure(KEYSPACE, name, passphrase, |value| {
+                let wallet_bytes = value.ok_or_else(|| Error::from(ErrorKind::WalletNotFound))?;
+                let mut wallet = Wallet::decode(wallet_bytes)
+                    .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+                match wallet.htlcs.iter_mut().find(|(known, _)| *known == lock) {
+                    Some(htlc) => {
+                        if preimage.is_some() {
+                            htlc.1 = preimage;
+                        }
+                    }
+                    None => wallet.htlcs.push((lock.clone(), preimage)),
+                }
+
+                Ok(Some(wallet.encode()))
+            })
+            .map(|_| ())
+    }
+
     /// Retrieves names of all the stored wallets
     pub fn names(&self) -> Result<Vec<String>> {
         let keys = self.storage.keys(KEYSPACE)?;
@@ -182,7 +323,8 @@ where
 
     /// Clears all storage
     pub fn clear(&self) -> Result<()> {
-        self.storage.clear(KEYSPACE)
+        self.storage.clear(KEYSPACE)?;
+        self.storage.clear(VIEW_KEY_KEYSPACE)
     }
 }
 
@@ -205,10 +347,19 @@ mod tests {
 
         assert_eq!(error.kind(), ErrorKind::WalletNotFound);
 
-        assert!(wallet_service.create("name", &passphrase).is_ok());
+        let view_key = PublicKey::from(&PrivateKey::new().unwrap());
+
+        assert!(wallet_service
+            .create("name", &passphrase, view_key.clone())
+            .is_ok());
+
+        assert_eq!(
+            Some(view_key.clone()),
+            wallet_service.view_key("name", &passphrase).unwrap()
+        );
 
         let error = wallet_service
-            .create("name", &SecUtf8::from("new_passphrase"))
+            .create("name", &SecUtf8::from("new_passphrase"), view_key.clone())
             .expect_err("Created duplicate wallet");
 
         assert_eq!(error.kind(), ErrorKind::AlreadyExists);
@@ -222,7 +373,7 @@ mod tests {
         );
 
         let error = wallet_service
-            .create("name", &SecUtf8::from("passphrase_new"))
+            .create("name", &SecUtf8::from("passphrase_new"), view_key)
             .expect_err("Able to create wallet with same name as previously created");
 
         assert_eq!(error.kind(), ErrorKind::AlreadyExists, "Invalid error kind");
@@ -242,6 +393,39 @@ mod tests {
                 .len()
         );
 
+        let redeem_address = RedeemAddress::from(&public_key);
+
+        wallet_service
+            .add_redeem_address("name", &passphrase, redeem_address)
+            .unwrap();
+
+        assert_eq!(
+            vec![ExtendedAddr::BasicRedeem(redeem_address)],
+            wallet_service
+                .transfer_addresses("name", &passphrase)
+                .unwrap()
+        );
+
+        let lock = HashTimeLock::new([0; 32], redeem_address, redeem_address, 0);
+
+        wallet_service
+            .add_htlc("name", &passphrase, lock.clone(), None)
+            .unwrap();
+        wallet_service
+            .add_htlc("name", &passphrase, lock.clone(), Some([1; 32]))
+            .unwrap();
+
+        assert_eq!(
+            vec![lock.clone()],
+            wallet_service.htlcs("name", &passphrase).unwrap()
+        );
+        assert_eq!(
+            Some((lock.clone(), Some([1; 32]))),
+            wallet_service
+                .find_htlc("name", &passphrase, &lock.address())
+                .unwrap()
+        );
+
         wallet_service.clear().unwrap();
 
         let error = wallet_service
@@ -250,4 +434,45 @@ mod tests {
 
         assert_eq!(error.kind(), ErrorKind::WalletNotFound);
     }
+
+    #[test]
+    fn check_wallet_stored_in_previous_format() {
+        let storage = MemoryStorage::default();
+        let wallet_service = WalletService::new(storage.clone());
+
+        let passphrase = SecUtf8::from("passphrase");
+        let public_key = PublicKey::from(&PrivateKey::new().unwrap());
+
+        // wallets were stored without view keys, single-key and hash-time-locked addresses
+        storage
+            .set_secure(
+                KEYSPACE,
+                "name",
+                (vec![public_key.clone()], vec![[1u8; 32]]).encode(),
+                &passphrase,
+            )
+            .unwrap();
+
+        assert_eq!(
+            vec![public_key.clone()],
+            wallet_service.public_keys("name", &passphrase).unwrap()
+        );
+        assert_eq!(None, wallet_service.view_key("name", &passphrase).unwrap());
+
+        let redeem_address = RedeemAddress::from(&public_key);
+
+        wallet_service
+            .add_redeem_address("name", &passphrase, redeem_address)
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                ExtendedAddr::OrTree([1u8; 32]),
+                ExtendedAddr::BasicRedeem(redeem_address)
+            ],
+            wallet_service
+                .transfer_addresses("name", &passphrase)
+                .unwrap()
+        );
+    }
 }
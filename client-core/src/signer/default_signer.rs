@@ -1,8 +1,10 @@
 use secstr::SecUtf8;
 
 use chain_core::common::H256;
+use chain_core::init::address::RedeemAddress;
+use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::output::TxOut;
-use chain_core::tx::witness::{TxInWitness, TxWitness};
+use chain_core::tx::witness::{EcdsaSignature, TxInWitness, TxWitness};
 use client_common::{Error, ErrorKind, Result, Storage};
 
 use crate::service::{KeyService, RootHashService, WalletService};
@@ -42,12 +44,60 @@ where
         message: T,
         output: &TxOut,
     ) -> Result<TxInWitness> {
-        let root_hash = self
+        match output.address {
+            ExtendedAddr::OrTree(_) => {
+                let root_hash = self
+                    .wallet_service
+                    .find_root_hash(name, passphrase, &output.address)?
+                    .ok_or_else(|| Error::from(ErrorKind::AddressNotFound))?;
+
+                self.sign_with_root_hash(passphrase, message, &root_hash)
+            }
+            ExtendedAddr::BasicRedeem(ref redeem_address) => self
+                .sign_with_redeem_address(name, passphrase, message, redeem_address)
+                .map(TxInWitness::BasicRedeem),
+            ExtendedAddr::HashTimeLock(_) => {
+                let (lock, preimage) = self
+                    .wallet_service
+                    .find_htlc(name, passphrase, &output.address)?
+                    .ok_or_else(|| Error::from(ErrorKind::AddressNotFound))?;
+
+                // redeems with the preimage if it's known (and the wallet is the recipient), otherwise refunds
+                let is_recipient = self
+                    .wallet_service
+                    .find_public_key(name, passphrase, &lock.recipient)?
+                    .is_some();
+                let preimage = preimage.filter(|_| is_recipient);
+                let signer = if preimage.is_some() {
+                    &lock.recipient
+                } else {
+                    &lock.refund
+                };
+
+                let signature = self.sign_with_redeem_address(name, passphrase, message, signer)?;
+                Ok(TxInWitness::HashTimeLock(lock, preimage, signature))
+            }
+        }
+    }
+
+    /// ECDSA signs message with private key corresponding to given redeem address
+    fn sign_with_redeem_address<T: AsRef<[u8]>>(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        message: T,
+        redeem_address: &RedeemAddress,
+    ) -> Result<EcdsaSignature> {
+        let public_key = self
             .wallet_service
-            .find_root_hash(name, passphrase, &output.address)?
+            .find_public_key(name, passphrase, redeem_address)?
             .ok_or_else(|| Error::from(ErrorKind::AddressNotFound))?;
+        let private_key = self
+            .key_service
+            .private_key(&public_key, passphrase)?
+            .ok_or_else(|| Error::from(ErrorKind::PrivateKeyNotFound))?;
 
-        self.sign_with_root_hash(passphrase, message, &root_hash)
+        private_key.sign(&message)
     }
 
     /// Schnorr signs message with private key corresponding to `self_public_key` in given 1-of-n root hash
@@ -163,6 +213,40 @@ mod tests {
         assert!(verify_tx_address(&witness[0], &message, &tree_address).is_ok());
     }
 
+    #[test]
+    fn check_redeem_signing_flow() {
+        let name = "name";
+        let passphrase = &SecUtf8::from("passphrase");
+        let message = Tx::new().id();
+
+        let storage = MemoryStorage::default();
+
+        let wallet_client = DefaultWalletClient::builder()
+            .with_wallet(storage.clone())
+            .build()
+            .unwrap();
+
+        wallet_client.new_wallet(name, passphrase).unwrap();
+
+        let redeem_address = wallet_client
+            .new_redeem_transfer_address(name, passphrase)
+            .unwrap();
+
+        let unspent_transactions = UnspentTransactions::new(vec![(
+            TxoPointer::new([0; 32], 0),
+            TxOut::new(redeem_address.clone(), Coin::zero()),
+        )]);
+        let selected_unspent_transactions = unspent_transactions.select_all();
+
+        let signer = DefaultSigner::new(storage);
+
+        let witness = signer
+            .sign(name, passphrase, message, selected_unspent_transactions)
+            .expect("Unable to sign transaction");
+
+        assert!(verify_tx_address(&witness[0], &message, &redeem_address).is_ok());
+    }
+
     #[test]
     fn check_2_of_3_invalid_signing_flow() {
         let name = "name";
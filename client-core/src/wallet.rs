@@ -6,12 +6,13 @@ pub use default_wallet_client::DefaultWalletClient;
 use secp256k1::schnorrsig::SchnorrSignature;
 use secstr::SecUtf8;
 
-use chain_core::common::{Proof, H256};
+use chain_core::common::{Proof, Timespec, H256};
 use chain_core::init::address::RedeemAddress;
 use chain_core::init::coin::Coin;
 use chain_core::state::account::StakedStateAddress;
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::htlc::{HashPreimage, HashTimeLock};
 use chain_core::tx::data::output::TxOut;
 use chain_core::tx::data::TxId;
 use chain_core::tx::witness::tree::RawPubkey;
@@ -19,6 +20,7 @@ use chain_core::tx::TxAux;
 use client_common::balance::TransactionChange;
 use client_common::{PrivateKey, PublicKey, Result};
 
+use crate::service::PendingTransaction;
 use crate::{InputSelectionStrategy, UnspentTransactions};
 
 /// Interface for a generic wallet
@@ -29,6 +31,9 @@ pub trait WalletClient: Send + Sync {
     /// Creates a new wallet with given name and passphrase
     fn new_wallet(&self, name: &str, passphrase: &SecUtf8) -> Result<()>;
 
+    /// Retrieves view key corresponding to given wallet
+    fn view_key(&self, name: &str, passphrase: &SecUtf8) -> Result<PublicKey>;
+
     /// Retrieves all public keys corresponding to given wallet
     fn public_keys(&self, name: &str, passphrase: &SecUtf8) -> Result<Vec<PublicKey>>;
 
@@ -42,7 +47,7 @@ pub trait WalletClient: Send + Sync {
         passphrase: &SecUtf8,
     ) -> Result<Vec<StakedStateAddress>>;
 
-    /// Returns all the multi-sig transfer addresses in current wallet
+    /// Returns all the multi-sig and single-key transfer addresses in current wallet
     fn transfer_addresses(&self, name: &str, passphrase: &SecUtf8) -> Result<Vec<ExtendedAddr>>;
 
     /// Finds public key corresponding to given redeem address
@@ -78,6 +83,10 @@ pub trait WalletClient: Send + Sync {
     fn new_single_transfer_address(&self, name: &str, passphrase: &SecUtf8)
         -> Result<ExtendedAddr>;
 
+    /// Generates a new single-key transfer address (spent with ECDSA signatures, without a merkle proof)
+    fn new_redeem_transfer_address(&self, name: &str, passphrase: &SecUtf8)
+        -> Result<ExtendedAddr>;
+
     /// Generates a new multi-sig transfer address for creating m-of-n transactions
     ///
     /// # Arguments
@@ -121,6 +130,14 @@ pub trait WalletClient: Send + Sync {
     /// Retrieves transaction history of wallet
     fn history(&self, name: &str, passphrase: &SecUtf8) -> Result<Vec<TransactionChange>>;
 
+    /// Retrieves transactions created by wallet which are not yet seen in a synced block (the ones which expired
+    /// before getting included are marked as failed)
+    fn pending_transactions(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+    ) -> Result<Vec<PendingTransaction>>;
+
     /// Retrieves all unspent transactions of wallet
     fn unspent_transactions(&self, name: &str, passphrase: &SecUtf8)
         -> Result<UnspentTransactions>;
@@ -148,14 +165,50 @@ pub trait WalletClient: Send + Sync {
         return_address: ExtendedAddr,
     ) -> Result<TxAux>;
 
+    /// Generates a new hash-time-locked address which can be spent by the recipient with the preimage of `hash`,
+    /// or refunded with a new key of current wallet after `timeout`
+    fn new_htlc_address(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        recipient: RedeemAddress,
+        hash: H256,
+        timeout: Timespec,
+    ) -> Result<HashTimeLock>;
+
+    /// Returns all the hash-time-lock terms in current wallet
+    fn htlcs(&self, name: &str, passphrase: &SecUtf8) -> Result<Vec<HashTimeLock>>;
+
+    /// Builds a transaction which redeems all unspent outputs locked with given terms (to `return_address`)
+    fn create_htlc_redeem_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        lock: HashTimeLock,
+        preimage: HashPreimage,
+        attributes: TxAttributes,
+        return_address: ExtendedAddr,
+    ) -> Result<TxAux>;
+
+    /// Builds a transaction which refunds all unspent outputs of given hash-time-locked address
+    /// (created by current wallet) to `return_address`
+    fn create_htlc_refund_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &ExtendedAddr,
+        attributes: TxAttributes,
+        return_address: ExtendedAddr,
+    ) -> Result<TxAux>;
+
     /// Broadcasts a transaction to Crypto.com Chain
     fn broadcast_transaction(&self, tx_aux: &TxAux) -> Result<()>;
 
-    /// Synchronizes index with Crypto.com Chain (from last known height)
-    fn sync(&self) -> Result<()>;
+    /// Synchronizes index with Crypto.com Chain (from last known height) using view key of given wallet
+    fn sync(&self, name: &str, passphrase: &SecUtf8) -> Result<()>;
 
-    /// Synchronizes index with Crypto.com Chain (from genesis)
-    fn sync_all(&self) -> Result<()>;
+    /// Synchronizes index with Crypto.com Chain (from genesis) using view key of given wallet
+    fn sync_all(&self, name: &str, passphrase: &SecUtf8) -> Result<()>;
 }
 
 /// Interface for a generic wallet for multi-signature transactions
@@ -1,14 +1,17 @@
 use failure::ResultExt;
 use parity_codec::Encode;
+use secp256k1::key::PublicKey as SecpPublicKey;
 use secp256k1::schnorrsig::SchnorrSignature;
 use secstr::SecUtf8;
 
-use chain_core::common::{Proof, H256};
+use chain_core::common::{Proof, Timespec, H256};
 use chain_core::init::address::RedeemAddress;
 use chain_core::init::coin::{sum_coins, Coin};
 use chain_core::state::account::StakedStateAddress;
+use chain_core::tx::data::access::{TxAccess, TxAccessPolicy};
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::htlc::{HashPreimage, HashTimeLock};
 use chain_core::tx::data::output::TxOut;
 use chain_core::tx::data::TxId;
 use chain_core::tx::witness::tree::RawPubkey;
@@ -37,6 +40,7 @@ where
     wallet_service: WalletService<S>,
     root_hash_service: RootHashService<S>,
     multi_sig_session_service: MultiSigSessionService<S>,
+    pending_transaction_service: PendingTransactionService<S>,
     index: I,
     transaction_builder: T,
 }
@@ -53,7 +57,8 @@ where
             key_service: KeyService::new(storage.clone()),
             wallet_service: WalletService::new(storage.clone()),
             root_hash_service: RootHashService::new(storage.clone()),
-            multi_sig_session_service: MultiSigSessionService::new(storage),
+            multi_sig_session_service: MultiSigSessionService::new(storage.clone()),
+            pending_transaction_service: PendingTransactionService::new(storage),
             index,
             transaction_builder,
         }
@@ -71,6 +76,103 @@ impl DefaultWalletClient<UnauthorizedStorage, UnauthorizedIndex, UnauthorizedTra
     }
 }
 
+impl<S, I, T> DefaultWalletClient<S, I, T>
+where
+    S: Storage,
+    I: Index,
+    T: TransactionBuilder,
+{
+    /// Wallet's own view key is always allowed so that change outputs can be indexed
+    fn allow_own_view_key(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        attributes: &mut TxAttributes,
+    ) -> Result<()> {
+        let view_key = SecpPublicKey::from(self.view_key(name, passphrase)?);
+        if !attributes
+            .allowed_view
+            .iter()
+            .any(|policy| policy.view_key == view_key)
+        {
+            attributes
+                .allowed_view
+                .push(TxAccessPolicy::new(view_key, TxAccess::AllData));
+        }
+        Ok(())
+    }
+
+    /// Spends all unspent outputs of given hash-time-locked address to `return_address`
+    /// (the signer decides whether they are redeemed or refunded)
+    fn spend_htlc(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &ExtendedAddr,
+        mut attributes: TxAttributes,
+        return_address: ExtendedAddr,
+    ) -> Result<TxAux> {
+        self.allow_own_view_key(name, passphrase, &mut attributes)?;
+
+        let unspent_transactions = self.index.unspent_transactions(address)?;
+        if unspent_transactions.is_empty() {
+            return Err(ErrorKind::InsufficientBalance.into());
+        }
+
+        self.build_transaction(
+            name,
+            passphrase,
+            Vec::new(),
+            attributes,
+            UnspentTransactions::new(unspent_transactions),
+            return_address,
+        )
+    }
+
+    /// Builds a transaction and keeps track of it as pending until it is seen in a synced block
+    fn build_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        outputs: Vec<TxOut>,
+        attributes: TxAttributes,
+        unspent_transactions: UnspentTransactions,
+        return_address: ExtendedAddr,
+    ) -> Result<TxAux> {
+        let expiry = attributes.expiry;
+        let tx_aux = self.transaction_builder.build(
+            name,
+            passphrase,
+            outputs,
+            attributes,
+            unspent_transactions,
+            return_address,
+        )?;
+
+        self.pending_transaction_service.add(
+            name,
+            passphrase,
+            PendingTransaction::new(tx_aux.tx_id(), expiry),
+        )?;
+
+        Ok(tx_aux)
+    }
+
+    /// Drops pending transactions which are seen in synced blocks and marks the expired ones as failed
+    fn update_pending_transactions(&self, name: &str, passphrase: &SecUtf8) -> Result<()> {
+        match self.index.last_block()? {
+            Some((last_block_height, last_block_time)) => self.pending_transaction_service.update(
+                name,
+                passphrase,
+                last_block_height,
+                last_block_time,
+                |transaction_id| Ok(self.index.transaction(transaction_id)?.is_some()),
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
 impl<S, I, T> WalletClient for DefaultWalletClient<S, I, T>
 where
     S: Storage,
@@ -82,7 +184,21 @@ where
     }
 
     fn new_wallet(&self, name: &str, passphrase: &SecUtf8) -> Result<()> {
-        self.wallet_service.create(name, passphrase)
+        let (view_key, _) = self.key_service.generate_keypair(passphrase)?;
+        self.wallet_service.create(name, passphrase, view_key)
+    }
+
+    fn view_key(&self, name: &str, passphrase: &SecUtf8) -> Result<PublicKey> {
+        match self.wallet_service.view_key(name, passphrase)? {
+            Some(view_key) => Ok(view_key),
+            None => {
+                // wallets created before view keys were added get one when it's first needed
+                let (view_key, _) = self.key_service.generate_keypair(passphrase)?;
+                self.wallet_service
+                    .set_view_key(name, passphrase, &view_key)?;
+                Ok(view_key)
+            }
+        }
     }
 
     fn public_keys(&self, name: &str, passphrase: &SecUtf8) -> Result<Vec<PublicKey>> {
@@ -157,6 +273,20 @@ where
         self.new_transfer_address(name, passphrase, vec![public_key.clone()], public_key, 1, 1)
     }
 
+    fn new_redeem_transfer_address(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+    ) -> Result<ExtendedAddr> {
+        let public_key = self.new_public_key(name, passphrase)?;
+        let redeem_address = RedeemAddress::from(&public_key);
+
+        self.wallet_service
+            .add_redeem_address(name, passphrase, redeem_address)?;
+
+        Ok(ExtendedAddr::BasicRedeem(redeem_address))
+    }
+
     fn new_transfer_address(
         &self,
         name: &str,
@@ -194,6 +324,9 @@ where
                 self.root_hash_service
                     .generate_proof(address, public_keys, passphrase)
             }
+            ExtendedAddr::BasicRedeem(_) | ExtendedAddr::HashTimeLock(_) => {
+                Err(ErrorKind::InvalidInput.into())
+            }
         }
     }
 
@@ -235,6 +368,14 @@ where
         Ok(history)
     }
 
+    fn pending_transactions(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+    ) -> Result<Vec<PendingTransaction>> {
+        self.pending_transaction_service.get(name, passphrase)
+    }
+
     fn unspent_transactions(
         &self,
         name: &str,
@@ -259,14 +400,16 @@ where
         name: &str,
         passphrase: &SecUtf8,
         outputs: Vec<TxOut>,
-        attributes: TxAttributes,
+        mut attributes: TxAttributes,
         input_selection_strategy: Option<InputSelectionStrategy>,
         return_address: ExtendedAddr,
     ) -> Result<TxAux> {
+        self.allow_own_view_key(name, passphrase, &mut attributes)?;
+
         let mut unspent_transactions = self.unspent_transactions(name, passphrase)?;
         unspent_transactions.apply_all(input_selection_strategy.unwrap_or_default().as_ref());
 
-        self.transaction_builder.build(
+        self.build_transaction(
             name,
             passphrase,
             outputs,
@@ -276,16 +419,99 @@ where
         )
     }
 
+    fn new_htlc_address(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        recipient: RedeemAddress,
+        hash: H256,
+        timeout: Timespec,
+    ) -> Result<HashTimeLock> {
+        let refund = RedeemAddress::from(&self.new_public_key(name, passphrase)?);
+        let lock = HashTimeLock::new(hash, recipient, refund, timeout);
+
+        self.wallet_service
+            .add_htlc(name, passphrase, lock.clone(), None)?;
+
+        Ok(lock)
+    }
+
+    fn htlcs(&self, name: &str, passphrase: &SecUtf8) -> Result<Vec<HashTimeLock>> {
+        self.wallet_service.htlcs(name, passphrase)
+    }
+
+    fn create_htlc_redeem_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        lock: HashTimeLock,
+        preimage: HashPreimage,
+        attributes: TxAttributes,
+        return_address: ExtendedAddr,
+    ) -> Result<TxAux> {
+        if !lock.verify_preimage(&preimage) {
+            return Err(ErrorKind::InvalidInput.into());
+        }
+
+        if self
+            .find_public_key(name, passphrase, &lock.recipient)?
+            .is_none()
+        {
+            return Err(ErrorKind::AddressNotFound.into());
+        }
+
+        let address = lock.address();
+        self.wallet_service
+            .add_htlc(name, passphrase, lock, Some(preimage))?;
+
+        self.spend_htlc(name, passphrase, &address, attributes, return_address)
+    }
+
+    fn create_htlc_refund_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &ExtendedAddr,
+        attributes: TxAttributes,
+        return_address: ExtendedAddr,
+    ) -> Result<TxAux> {
+        if self
+            .wallet_service
+            .find_htlc(name, passphrase, address)?
+            .is_none()
+        {
+            return Err(ErrorKind::AddressNotFound.into());
+        }
+
+        self.spend_htlc(name, passphrase, address, attributes, return_address)
+    }
+
     fn broadcast_transaction(&self, tx_aux: &TxAux) -> Result<()> {
         self.index.broadcast_transaction(&tx_aux.encode())
     }
 
-    fn sync(&self) -> Result<()> {
-        self.index.sync()
+    fn sync(&self, name: &str, passphrase: &SecUtf8) -> Result<()> {
+        let view_key = self.view_key(name, passphrase)?;
+        let private_key = self
+            .private_key(passphrase, &view_key)?
+            .ok_or_else(|| Error::from(ErrorKind::PrivateKeyNotFound))?;
+
+        let addresses = self.transfer_addresses(name, passphrase)?;
+
+        self.index.sync(&view_key, &private_key, &addresses)?;
+        self.update_pending_transactions(name, passphrase)
     }
 
-    fn sync_all(&self) -> Result<()> {
-        self.index.sync_all()
+    fn sync_all(&self, name: &str, passphrase: &SecUtf8) -> Result<()> {
+        let view_key = self.view_key(name, passphrase)?;
+        let private_key = self
+            .private_key(passphrase, &view_key)?
+            .ok_or_else(|| Error::from(ErrorKind::PrivateKeyNotFound))?;
+
+        let addresses = self.transfer_addresses(name, passphrase)?;
+
+        self.index.sync_all(&view_key, &private_key, &addresses)?;
+        self.update_pending_transactions(name, passphrase)
     }
 }
 
@@ -505,15 +731,18 @@ mod tests {
     use chrono::DateTime;
 
     use chain_core::init::coin::CoinError;
+    use chain_core::tx::data::attribute::TxExpiry;
+    use chain_core::tx::data::htlc::hash_lock;
     use chain_core::tx::data::input::TxoPointer;
     use chain_core::tx::data::Tx;
     use chain_core::tx::fee::{Fee, FeeAlgorithm};
     use chain_core::tx::witness::TxInWitness;
-    use chain_core::tx::TransactionId;
+    use chain_core::tx::{PlainTxAux, TransactionId};
     use chain_tx_validation::witness::verify_tx_address;
     use client_common::balance::BalanceChange;
     use client_common::storage::MemoryStorage;
     use client_common::Transaction;
+    use parity_codec::Decode;
 
     use crate::signer::DefaultSigner;
     use crate::transaction_builder::DefaultTransactionBuilder;
@@ -549,11 +778,21 @@ mod tests {
     }
 
     impl Index for MockIndex {
-        fn sync(&self) -> Result<()> {
+        fn sync(
+            &self,
+            _view_key: &PublicKey,
+            _private_key: &PrivateKey,
+            _addresses: &[ExtendedAddr],
+        ) -> Result<()> {
             Ok(())
         }
 
-        fn sync_all(&self) -> Result<()> {
+        fn sync_all(
+            &self,
+            _view_key: &PublicKey,
+            _private_key: &PrivateKey,
+            _addresses: &[ExtendedAddr],
+        ) -> Result<()> {
             Ok(())
         }
 
@@ -566,6 +805,7 @@ mod tests {
                         balance_change: BalanceChange::Incoming(Coin::new(30).unwrap()),
                         height: 1,
                         time: DateTime::from(SystemTime::now()),
+                        memo: None,
                     },
                     TransactionChange {
                         transaction_id: [1u8; 32],
@@ -573,6 +813,7 @@ mod tests {
                         balance_change: BalanceChange::Outgoing(Coin::new(30).unwrap()),
                         height: 2,
                         time: DateTime::from(SystemTime::now()),
+                        memo: None,
                     },
                 ])
             } else if address == &self.addr_2 {
@@ -584,6 +825,7 @@ mod tests {
                             balance_change: BalanceChange::Incoming(Coin::new(30).unwrap()),
                             height: 1,
                             time: DateTime::from(SystemTime::now()),
+                            memo: None,
                         },
                         TransactionChange {
                             transaction_id: [2u8; 32],
@@ -591,6 +833,7 @@ mod tests {
                             balance_change: BalanceChange::Outgoing(Coin::new(30).unwrap()),
                             height: 2,
                             time: DateTime::from(SystemTime::now()),
+                            memo: None,
                         },
                     ])
                 } else {
@@ -600,6 +843,7 @@ mod tests {
                         balance_change: BalanceChange::Incoming(Coin::new(30).unwrap()),
                         height: 2,
                         time: DateTime::from(SystemTime::now()),
+                        memo: None,
                     }])
                 }
             } else if *self.changed.read().unwrap() && address == &self.addr_3 {
@@ -609,6 +853,7 @@ mod tests {
                     balance_change: BalanceChange::Incoming(Coin::new(30).unwrap()),
                     height: 2,
                     time: DateTime::from(SystemTime::now()),
+                    memo: None,
                 }])
             } else {
                 Ok(Default::default())
@@ -644,6 +889,7 @@ mod tests {
                             address: self.addr_2.clone(),
                             value: Coin::new(30).unwrap(),
                             valid_from: None,
+                            relative_lock: None,
                         },
                     )])
                 }
@@ -654,6 +900,7 @@ mod tests {
                         address: self.addr_3.clone(),
                         value: Coin::new(30).unwrap(),
                         valid_from: None,
+                        relative_lock: None,
                     },
                 )])
             } else {
@@ -665,24 +912,31 @@ mod tests {
             unreachable!();
         }
 
+        fn last_block(&self) -> Result<Option<(u64, Timespec)>> {
+            Ok(None)
+        }
+
         fn output(&self, id: &TxId, index: usize) -> Result<TxOut> {
             if id == &[0u8; 32] && index == 0 {
                 Ok(TxOut {
                     address: self.addr_1.clone(),
                     value: Coin::new(30).unwrap(),
                     valid_from: None,
+                    relative_lock: None,
                 })
             } else if id == &[1u8; 32] && index == 0 {
                 Ok(TxOut {
                     address: self.addr_2.clone(),
                     value: Coin::new(30).unwrap(),
                     valid_from: None,
+                    relative_lock: None,
                 })
             } else if *self.changed.read().unwrap() && id == &[2u8; 32] && index == 0 {
                 Ok(TxOut {
                     address: self.addr_3.clone(),
                     value: Coin::new(30).unwrap(),
                     valid_from: None,
+                    relative_lock: None,
                 })
             } else {
                 Err(ErrorKind::TransactionNotFound.into())
@@ -750,6 +1004,22 @@ mod tests {
             .unwrap()
             .is_some());
 
+        let redeem_address = wallet
+            .new_redeem_transfer_address("name", &SecUtf8::from("passphrase"))
+            .expect("Unable to generate new address");
+
+        let addresses = wallet
+            .transfer_addresses("name", &SecUtf8::from("passphrase"))
+            .unwrap();
+
+        assert_eq!(2, addresses.len());
+        assert_eq!(redeem_address, addresses[1], "Addresses don't match");
+
+        assert!(wallet
+            .find_root_hash("name", &SecUtf8::from("passphrase"), &redeem_address)
+            .unwrap()
+            .is_none());
+
         assert_eq!(
             ErrorKind::WalletNotFound,
             wallet
@@ -855,8 +1125,12 @@ mod tests {
                 .len()
         );
 
-        assert!(wallet.sync().is_ok());
-        assert!(wallet.sync_all().is_ok());
+        assert!(wallet
+            .sync("wallet_1", &SecUtf8::from("passphrase"))
+            .is_ok());
+        assert!(wallet
+            .sync_all("wallet_1", &SecUtf8::from("passphrase"))
+            .is_ok());
 
         let signer = DefaultSigner::new(storage.clone());
 
@@ -878,14 +1152,24 @@ mod tests {
                     address: addr_3.clone(),
                     value: Coin::new(30).unwrap(),
                     valid_from: None,
+                    relative_lock: None,
                 }],
-                TxAttributes::new(171),
+                TxAttributes::new(171).with_expiry(TxExpiry::Height(10)),
                 None,
                 addr_1.clone(),
             )
             .unwrap();
 
         assert!(wallet.broadcast_transaction(&transaction).is_ok());
+        assert_eq!(
+            vec![PendingTransaction::new(
+                transaction.tx_id(),
+                Some(TxExpiry::Height(10))
+            )],
+            wallet
+                .pending_transactions("wallet_2", &SecUtf8::from("passphrase"))
+                .unwrap()
+        );
 
         assert_eq!(
             Coin::new(0).unwrap(),
@@ -936,6 +1220,7 @@ mod tests {
                     address: addr_2.clone(),
                     value: Coin::new(20).unwrap(),
                     valid_from: None,
+                    relative_lock: None,
                 }],
                 TxAttributes::new(171),
                 None,
@@ -955,6 +1240,7 @@ mod tests {
                         address: addr_3.clone(),
                         value: Coin::new(30).unwrap(),
                         valid_from: None,
+                        relative_lock: None,
                     }],
                     TxAttributes::new(171),
                     None,
@@ -965,6 +1251,107 @@ mod tests {
         );
     }
 
+    #[test]
+    fn check_htlc_flow() {
+        let storage = MemoryStorage::default();
+        let passphrase = &SecUtf8::from("passphrase");
+        let wallet = DefaultWalletClient::builder()
+            .with_wallet(storage.clone())
+            .build()
+            .unwrap();
+
+        wallet.new_wallet("sender", passphrase).unwrap();
+        wallet.new_wallet("recipient", passphrase).unwrap();
+        let recipient = match wallet
+            .new_redeem_transfer_address("recipient", passphrase)
+            .unwrap()
+        {
+            ExtendedAddr::BasicRedeem(recipient) => recipient,
+            _ => panic!("expected a basic redeem address"),
+        };
+        let return_address = wallet
+            .new_redeem_transfer_address("sender", passphrase)
+            .unwrap();
+
+        let preimage = [0xab; 32];
+        let lock = wallet
+            .new_htlc_address("sender", passphrase, recipient, hash_lock(&preimage), 100)
+            .unwrap();
+        assert_eq!(
+            vec![lock.clone()],
+            wallet.htlcs("sender", passphrase).unwrap()
+        );
+
+        let signer = DefaultSigner::new(storage.clone());
+        let wallet = DefaultWalletClient::builder()
+            .with_wallet(storage)
+            .with_transaction_read(MockIndex::new(
+                ExtendedAddr::OrTree([0; 32]),
+                lock.address(),
+                ExtendedAddr::OrTree([2; 32]),
+            ))
+            .with_transaction_write(DefaultTransactionBuilder::new(
+                signer,
+                ZeroFeeAlgorithm::default(),
+            ))
+            .build()
+            .unwrap();
+
+        let spend_witness = |transaction: TxAux| match transaction {
+            TxAux::TransferTx { txpayload, .. } => {
+                let PlainTxAux::TransferTx(tx, witness) =
+                    PlainTxAux::decode(&mut txpayload.as_slice()).unwrap();
+                assert!(verify_tx_address(&witness[0], &tx.id(), &lock.address()).is_ok());
+                witness[0].clone()
+            }
+            _ => panic!("expected a transfer transaction"),
+        };
+
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            wallet
+                .create_htlc_redeem_transaction(
+                    "recipient",
+                    passphrase,
+                    lock.clone(),
+                    [0xac; 32],
+                    TxAttributes::new(171),
+                    return_address.clone(),
+                )
+                .unwrap_err()
+                .kind()
+        );
+
+        let redeem = wallet
+            .create_htlc_redeem_transaction(
+                "recipient",
+                passphrase,
+                lock.clone(),
+                preimage,
+                TxAttributes::new(171),
+                return_address.clone(),
+            )
+            .unwrap();
+        match spend_witness(redeem) {
+            TxInWitness::HashTimeLock(_, Some(revealed), _) => assert_eq!(preimage, revealed),
+            _ => panic!("expected a redeem witness"),
+        }
+
+        let refund = wallet
+            .create_htlc_refund_transaction(
+                "sender",
+                passphrase,
+                &lock.address(),
+                TxAttributes::new(171),
+                return_address,
+            )
+            .unwrap();
+        match spend_witness(refund) {
+            TxInWitness::HashTimeLock(_, None, _) => {}
+            _ => panic!("expected a refund witness"),
+        }
+    }
+
     #[test]
     fn check_unauthorized_wallet() {
         let wallet = DefaultWalletClient::builder().build().unwrap();
@@ -1063,12 +1450,18 @@ mod tests {
 
         assert_eq!(
             ErrorKind::PermissionDenied,
-            wallet.sync().unwrap_err().kind()
+            wallet
+                .sync("name", &SecUtf8::from("passphrase"))
+                .unwrap_err()
+                .kind()
         );
 
         assert_eq!(
             ErrorKind::PermissionDenied,
-            wallet.sync_all().unwrap_err().kind()
+            wallet
+                .sync_all("name", &SecUtf8::from("passphrase"))
+                .unwrap_err()
+                .kind()
         );
     }
 
@@ -5,21 +5,36 @@ mod unauthorized_index;
 pub use default_index::DefaultIndex;
 pub use unauthorized_index::UnauthorizedIndex;
 
+use chain_core::common::Timespec;
 use chain_core::init::coin::Coin;
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::output::TxOut;
 use chain_core::tx::data::TxId;
 use client_common::balance::TransactionChange;
-use client_common::{Result, Transaction};
+use client_common::{PrivateKey, PublicKey, Result, Transaction};
 
 /// Interface for interacting with transaction index
 pub trait Index: Send + Sync {
-    /// Synchronizes transaction index with Crypto.com Chain (from last known height)
-    fn sync(&self) -> Result<()>;
-
-    /// Synchronizes transaction index with Crypto.com Chain (from genesis)
-    fn sync_all(&self) -> Result<()>;
+    /// Synchronizes transaction index with Crypto.com Chain (from last height synced for given view key). Transfer
+    /// transactions are only indexed if they are visible to given view key (`private_key` is used for requesting
+    /// their decryption) and transaction inputs only if they spend outputs of given (wallet) addresses
+    fn sync(
+        &self,
+        view_key: &PublicKey,
+        private_key: &PrivateKey,
+        addresses: &[ExtendedAddr],
+    ) -> Result<()>;
+
+    /// Synchronizes transaction index with Crypto.com Chain (from genesis). Transfer transactions are only indexed if
+    /// they are visible to given view key (`private_key` is used for requesting their decryption) and transaction
+    /// inputs only if they spend outputs of given (wallet) addresses
+    fn sync_all(
+        &self,
+        view_key: &PublicKey,
+        private_key: &PrivateKey,
+        addresses: &[ExtendedAddr],
+    ) -> Result<()>;
 
     /// Returns all transaction changes for given address
     fn transaction_changes(&self, address: &ExtendedAddr) -> Result<Vec<TransactionChange>>;
@@ -33,9 +48,13 @@ pub trait Index: Send + Sync {
     /// Returns transaction with given id
     fn transaction(&self, id: &TxId) -> Result<Option<Transaction>>;
 
+    /// Returns height and time of the last synced block (`None` if no block is synced yet)
+    fn last_block(&self) -> Result<Option<(u64, Timespec)>>;
+
     /// Returns output of transaction with given id and index
     fn output(&self, id: &TxId, index: usize) -> Result<TxOut>;
 
     /// Broadcasts a transaction to Crypto.com Chain
+    /// (if the node rejects it, the error kind is `TransactionRejected` with the reason)
     fn broadcast_transaction(&self, transaction: &[u8]) -> Result<()>;
 }
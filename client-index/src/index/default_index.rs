@@ -1,17 +1,26 @@
+use std::collections::HashMap;
+
 use chrono::offset::Utc;
 use chrono::DateTime;
+use failure::ResultExt;
+use parity_codec::{Decode, Encode};
 
+use chain_core::common::{Proof, Timespec, H256};
 use chain_core::init::coin::Coin;
-use chain_core::state::account::{DepositBondTx, WithdrawUnbondedTx};
+use chain_core::state::account::{ClaimRewardsTx, DepositBondTx, WithdrawUnbondedTx};
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::output::TxOut;
-use chain_core::tx::data::TxId;
+use chain_core::tx::data::{txid_hash, Tx, TxId};
 use chain_core::tx::TransactionId;
 use chain_core::tx::TxAux;
+use chain_core::AppStateRoots;
 use client_common::balance::{BalanceChange, TransactionChange};
+use client_common::tendermint::lite::{results_hash, transactions_hash, LightClientState};
+use client_common::tendermint::types::{Block, BlockResults, SignedHeader};
 use client_common::tendermint::Client;
-use client_common::{Error, ErrorKind, Result, Storage, Transaction};
+use client_common::{Error, ErrorKind, PrivateKey, PublicKey, Result, Storage, Transaction};
+use enclave_protocol::{DecryptionRequest, DecryptionRequestBody, DecryptionResponse};
 
 use crate::service::*;
 use crate::Index;
@@ -29,6 +38,7 @@ where
     transaction_service: TransactionService<S>,
     unspent_transaction_service: UnspentTransactionService<S>,
     client: C,
+    verify: bool,
 }
 
 impl<S, C> DefaultIndex<S, C>
@@ -45,6 +55,16 @@ where
             transaction_service: TransactionService::new(storage.clone()),
             unspent_transaction_service: UnspentTransactionService::new(storage),
             client,
+            verify: false,
+        }
+    }
+
+    /// Creates a new instance of `DefaultIndex` which verifies synced blocks and transactions (i.e., block headers
+    /// are checked against validator sets tracked from genesis and transactions against app hashes in them)
+    pub fn new_verifying(storage: S, client: C) -> Self {
+        Self {
+            verify: true,
+            ..Self::new(storage, client)
         }
     }
 }
@@ -62,18 +82,186 @@ where
         self.transaction_service.clear()
     }
 
+    /// Returns chain id and trusted light client state for verifying the block after last synced block
+    fn light_client_state(&self, last_block_height: u64) -> Result<(String, LightClientState)> {
+        let genesis = self.client.genesis()?;
+
+        let light_client_state = match self.global_state_service.light_client_state()? {
+            Some(light_client_state) => light_client_state,
+            None if last_block_height == 0 => LightClientState::genesis(genesis.validators()?),
+            // blocks were synced without verification (full resync is needed)
+            None => return Err(ErrorKind::VerifyError.into()),
+        };
+
+        if light_client_state.height != last_block_height + 1 {
+            return Err(ErrorKind::VerifyError.into());
+        }
+
+        Ok((genesis.chain_id().to_owned(), light_client_state))
+    }
+
+    /// Verifies block and its results (against its header signed by trusted validators and results hash in the
+    /// header of next block) and returns verified header of next block (with app hash after executing the block) --
+    /// `signed_header` is the header of the block if it was already verified as the next header of the previous block
+    fn verify_block(
+        &self,
+        chain_id: &str,
+        light_client_state: &mut LightClientState,
+        signed_header: Option<SignedHeader>,
+        block: &Block,
+        block_results: &BlockResults,
+    ) -> Result<SignedHeader> {
+        let signed_header = match signed_header {
+            Some(signed_header) => signed_header,
+            None => {
+                let signed_header = self.client.commit(light_client_state.height)?.signed_header;
+                light_client_state.verify(chain_id, &signed_header)?;
+                signed_header
+            }
+        };
+
+        let data_hash = hex::decode(&signed_header.header.data_hash)
+            .context(ErrorKind::DeserializationError)?;
+        if signed_header.height()? != light_client_state.height
+            || block.height()? != light_client_state.height
+            || block.time() != signed_header.header.time
+            || data_hash != transactions_hash(&block.raw_transactions()?)
+        {
+            return Err(ErrorKind::VerifyError.into());
+        }
+
+        // validator updates are checked against `next_validators_hash` in the next verified header
+        light_client_state.advance(&block_results.validator_updates()?);
+
+        let next_signed_header = self.client.commit(light_client_state.height)?.signed_header;
+        light_client_state.verify(chain_id, &next_signed_header)?;
+
+        let last_results_hash = hex::decode(&next_signed_header.header.last_results_hash)
+            .context(ErrorKind::DeserializationError)?;
+        if last_results_hash != results_hash(&block_results.raw_results()?) {
+            return Err(ErrorKind::VerifyError.into());
+        }
+
+        Ok(next_signed_header)
+    }
+
+    /// Verifies that transaction is included in valid transactions of application state with given app hash (after
+    /// executing block at given height)
+    fn verify_transaction(
+        &self,
+        transaction_id: &TxId,
+        height: u64,
+        app_hash: &H256,
+    ) -> Result<()> {
+        let response = self
+            .client
+            .query("store", &hex::encode(transaction_id), Some(height), true)?
+            .response;
+
+        let app_state_op = response
+            .proof_op("app_state")
+            .ok_or_else(|| Error::from(ErrorKind::VerifyError))?;
+        let app_state_roots = AppStateRoots::decode(
+            &mut base64::decode(&app_state_op.data)
+                .context(ErrorKind::DeserializationError)?
+                .as_slice(),
+        )
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+
+        let transaction_op = response
+            .proof_op("transaction")
+            .ok_or_else(|| Error::from(ErrorKind::VerifyError))?;
+        let proof = Proof::<TxId>::decode(
+            &mut base64::decode(&transaction_op.data)
+                .context(ErrorKind::DeserializationError)?
+                .as_slice(),
+        )
+        .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
+
+        if app_state_roots.app_hash() == *app_hash
+            && proof.value() == transaction_id
+            && proof.verify(&app_state_roots.valid_tx_root)
+        {
+            Ok(())
+        } else {
+            Err(ErrorKind::VerifyError.into())
+        }
+    }
+
+    /// Fetches bodies of transfer transactions in given (valid) transactions of a block which are visible to the view
+    /// key of `private_key` and not indexed yet (e.g., when synced for another view key with access to them)
+    fn decrypt_transfer_transactions(
+        &self,
+        transactions: &[TxAux],
+        private_key: &PrivateKey,
+    ) -> Result<HashMap<TxId, Tx>> {
+        let mut transfer_transaction_ids = Vec::new();
+        for transaction in transactions.iter() {
+            if let TxAux::TransferTx { txid, .. } = transaction {
+                if self.transaction(txid)?.is_none() {
+                    transfer_transaction_ids.push(*txid);
+                }
+            }
+        }
+
+        if transfer_transaction_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        Ok(self
+            .decrypt_transactions(transfer_transaction_ids, private_key)?
+            .into_iter()
+            .map(|transaction| (transaction.id(), transaction))
+            .collect())
+    }
+
+    /// Fetches bodies of transfer transactions with given ids (obfuscated on chain) by sending a decryption request
+    /// signed with given view key
+    fn decrypt_transactions(
+        &self,
+        transaction_ids: Vec<TxId>,
+        private_key: &PrivateKey,
+    ) -> Result<Vec<Tx>> {
+        let body = DecryptionRequestBody {
+            txs: transaction_ids,
+        };
+        let (_, view_key_sig) = private_key
+            .sign(txid_hash(&body.encode()))?
+            .serialize_compact();
+        let request = DecryptionRequest { body, view_key_sig };
+
+        // FIXME: replace with the enclave endpoint once available
+        let response = self
+            .client
+            .query("mockdecrypt", &hex::encode(request.encode()), None, false)?
+            .response
+            .value;
+        let bytes = base64::decode(&response).context(ErrorKind::DeserializationError)?;
+
+        DecryptionResponse::decode(&mut bytes.as_slice())
+            .map(|response| response.txs)
+            .ok_or_else(|| Error::from(ErrorKind::DeserializationError))
+    }
+
     fn handle_transaction(
         &self,
         transaction: TxAux,
+        addresses: &[ExtendedAddr],
         height: u64,
         time: DateTime<Utc>,
     ) -> Result<()> {
         match transaction {
-            TxAux::TransferTx {..} => {
-                unimplemented!("FIXME: indexing should be rethought, as it'll first check the filter and query (block data would be obfuscated)")
+            TxAux::TransferTx { .. } => {
+                // Body of transfer transaction is obfuscated. It has to be decrypted before handling.
+                Err(ErrorKind::InvalidTransaction.into())
             }
             TxAux::DepositStakeTx(deposit_bond_transaction, _) => {
-                self.handle_deposit_stake_transaction(&deposit_bond_transaction, height, time)?;
+                self.handle_deposit_stake_transaction(
+                    &deposit_bond_transaction,
+                    addresses,
+                    height,
+                    time,
+                )?;
                 self.transaction_service.set(
                     &deposit_bond_transaction.id(),
                     &Transaction::DepositStakeTransaction(deposit_bond_transaction),
@@ -94,34 +282,161 @@ where
                     &Transaction::WithdrawUnbondedStakeTransaction(withdraw_unbonded_transaction),
                 )
             }
+            TxAux::NodeJoinTx(node_join_transaction, _) => self.transaction_service.set(
+                &node_join_transaction.id(),
+                &Transaction::NodeJoinTransaction(node_join_transaction),
+            ),
+            TxAux::UnjailTx(unjail_transaction, _) => self.transaction_service.set(
+                &unjail_transaction.id(),
+                &Transaction::UnjailTransaction(unjail_transaction),
+            ),
+            TxAux::NodeLeaveTx(node_leave_transaction, _) => self.transaction_service.set(
+                &node_leave_transaction.id(),
+                &Transaction::NodeLeaveTransaction(node_leave_transaction),
+            ),
+            TxAux::ProposalTx(proposal_transaction, _) => self.transaction_service.set(
+                &proposal_transaction.id(),
+                &Transaction::ProposalTransaction(proposal_transaction),
+            ),
+            TxAux::VoteTx(vote_transaction, _) => self.transaction_service.set(
+                &vote_transaction.id(),
+                &Transaction::VoteTransaction(vote_transaction),
+            ),
+            TxAux::DelegateTx(delegate_transaction, _) => self.transaction_service.set(
+                &delegate_transaction.id(),
+                &Transaction::DelegateTransaction(delegate_transaction),
+            ),
+            TxAux::UndelegateTx(undelegate_transaction, _) => self.transaction_service.set(
+                &undelegate_transaction.id(),
+                &Transaction::UndelegateTransaction(undelegate_transaction),
+            ),
+            TxAux::ClaimRewardsTx(claim_rewards_transaction, _) => {
+                self.handle_claim_rewards_transaction(&claim_rewards_transaction, height, time)?;
+                self.transaction_service.set(
+                    &claim_rewards_transaction.id(),
+                    &Transaction::ClaimRewardsTransaction(claim_rewards_transaction),
+                )
+            }
         }
     }
 
+    fn handle_transfer_transaction(
+        &self,
+        transaction: Tx,
+        addresses: &[ExtendedAddr],
+        height: u64,
+        time: DateTime<Utc>,
+    ) -> Result<()> {
+        let transaction_id = transaction.id();
+
+        self.handle_transaction_inputs(
+            transaction_id,
+            &transaction.inputs,
+            addresses,
+            height,
+            time,
+            transaction.attributes.memo.as_ref(),
+        )?;
+
+        for (i, output) in transaction.outputs.iter().enumerate() {
+            self.handle_transaction_output(
+                transaction_id,
+                output,
+                i,
+                height,
+                time,
+                transaction.attributes.memo.as_ref(),
+            )?;
+        }
+
+        self.transaction_service.set(
+            &transaction_id,
+            &Transaction::TransferTransaction(transaction),
+        )
+    }
+
     fn handle_deposit_stake_transaction(
         &self,
         transaction: &DepositBondTx,
+        addresses: &[ExtendedAddr],
+        height: u64,
+        time: DateTime<Utc>,
+    ) -> Result<()> {
+        self.handle_transaction_inputs(
+            transaction.id(),
+            &transaction.inputs,
+            addresses,
+            height,
+            time,
+            None,
+        )
+    }
+
+    fn handle_withdraw_unbonded_stake_transaction(
+        &self,
+        transaction: &WithdrawUnbondedTx,
         height: u64,
         time: DateTime<Utc>,
     ) -> Result<()> {
         let transaction_id = transaction.id();
 
-        for input in transaction.inputs.iter() {
-            self.handle_transaction_input(transaction_id, input, height, time)?;
+        for (i, output) in transaction.outputs.iter().enumerate() {
+            self.handle_transaction_output(
+                transaction_id,
+                output,
+                i,
+                height,
+                time,
+                transaction.attributes.memo.as_ref(),
+            )?;
         }
 
         Ok(())
     }
 
-    fn handle_withdraw_unbonded_stake_transaction(
+    fn handle_claim_rewards_transaction(
         &self,
-        transaction: &WithdrawUnbondedTx,
+        transaction: &ClaimRewardsTx,
         height: u64,
         time: DateTime<Utc>,
     ) -> Result<()> {
         let transaction_id = transaction.id();
 
         for (i, output) in transaction.outputs.iter().enumerate() {
-            self.handle_transaction_output(transaction_id, output, i, height, time)?;
+            self.handle_transaction_output(
+                transaction_id,
+                output,
+                i,
+                height,
+                time,
+                transaction.attributes.memo.as_ref(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Handles inputs of a transaction which spend outputs of given (wallet) addresses. Other inputs are skipped
+    /// (e.g., inputs of a transfer received from another wallet, which may also spend indexed outputs sent to it)
+    fn handle_transaction_inputs(
+        &self,
+        transaction_id: TxId,
+        inputs: &[TxoPointer],
+        addresses: &[ExtendedAddr],
+        height: u64,
+        time: DateTime<Utc>,
+        memo: Option<&String>,
+    ) -> Result<()> {
+        for input in inputs.iter() {
+            if self.transaction(&input.id)?.is_none() {
+                continue;
+            }
+
+            let output = self.output(&input.id, input.index as usize)?;
+
+            if addresses.contains(&output.address) {
+                self.handle_transaction_input(transaction_id, input, output, height, time, memo)?;
+            }
         }
 
         Ok(())
@@ -131,17 +446,18 @@ where
         &self,
         transaction_id: TxId,
         input: &TxoPointer,
+        output: TxOut,
         height: u64,
         time: DateTime<Utc>,
+        memo: Option<&String>,
     ) -> Result<()> {
-        let output = self.output(&input.id, input.index as usize)?;
-
         let change = TransactionChange {
             transaction_id,
             address: output.address,
             balance_change: BalanceChange::Outgoing(output.value),
             height,
             time,
+            memo: memo.cloned(),
         };
 
         // Update balance
@@ -163,6 +479,7 @@ where
         index: usize,
         height: u64,
         time: DateTime<Utc>,
+        memo: Option<&String>,
     ) -> Result<()> {
         let change = TransactionChange {
             transaction_id,
@@ -170,6 +487,7 @@ where
             balance_change: BalanceChange::Incoming(output.value),
             height,
             time,
+            memo: memo.cloned(),
         };
 
         // Update balance
@@ -192,37 +510,197 @@ where
     S: Storage,
     C: Client,
 {
-    fn sync(&self) -> Result<()> {
+    fn sync(
+        &self,
+        view_key: &PublicKey,
+        private_key: &PrivateKey,
+        addresses: &[ExtendedAddr],
+    ) -> Result<()> {
         let last_block_height = self
             .global_state_service
             .last_block_height()?
             .unwrap_or_default();
+        let last_view_key_block_height = self
+            .global_state_service
+            .last_view_key_block_height(view_key)?
+            .unwrap_or_default()
+            .min(last_block_height);
+
+        // blocks already synced for other view keys are only checked for transfer transactions visible to this one
+        // and inputs (of transactions indexed for other view keys) spending outputs of given addresses
+        for height in (last_view_key_block_height + 1)..=last_block_height {
+            let block_results = self.client.block_results(height)?;
+            let valid_transaction_ids = block_results.ids()?;
+            let block = self.client.block(height)?;
+            let transactions = block
+                .transactions()?
+                .into_iter()
+                .filter(|transaction| valid_transaction_ids.contains(&transaction.tx_id()))
+                .collect::<Vec<TxAux>>();
+
+            let mut transfer_transactions = if block_results
+                .block_filter()?
+                .check_view_key(&view_key.into())
+            {
+                self.decrypt_transfer_transactions(&transactions, private_key)?
+            } else {
+                HashMap::new()
+            };
+            let app_hash = if self.verify && !transfer_transactions.is_empty() {
+                Some(
+                    self.global_state_service
+                        .app_hash(height)?
+                        .ok_or_else(|| Error::from(ErrorKind::VerifyError))?,
+                )
+            } else {
+                None
+            };
+
+            for transaction in transactions {
+                match transaction {
+                    TxAux::TransferTx { txid, .. } => {
+                        if let Some(transfer_transaction) = transfer_transactions.remove(&txid) {
+                            if let Some(app_hash) = app_hash.as_ref() {
+                                self.verify_transaction(&txid, height, app_hash)?;
+                            }
+                            self.handle_transfer_transaction(
+                                transfer_transaction,
+                                addresses,
+                                height,
+                                block.time(),
+                            )?;
+                        } else if let Some(Transaction::TransferTransaction(transfer_transaction)) =
+                            self.transaction(&txid)?
+                        {
+                            self.handle_transaction_inputs(
+                                txid,
+                                &transfer_transaction.inputs,
+                                addresses,
+                                height,
+                                block.time(),
+                                transfer_transaction.attributes.memo.as_ref(),
+                            )?;
+                        }
+                    }
+                    TxAux::DepositStakeTx(deposit_bond_transaction, _) => {
+                        self.handle_deposit_stake_transaction(
+                            &deposit_bond_transaction,
+                            addresses,
+                            height,
+                            block.time(),
+                        )?;
+                    }
+                    _ => {}
+                }
+            }
+
+            self.global_state_service
+                .set_last_view_key_block_height(view_key, height)?;
+        }
 
         let current_block_height = self.client.status()?.last_block_height()?;
 
-        for height in (last_block_height + 1)..=current_block_height {
-            let valid_transaction_ids = self.client.block_results(height)?.ids()?;
+        // app hash after executing a block is only committed to in the header of next block, so, the latest block
+        // is synced in verifying mode only after the next one is committed
+        let (mut light_client, sync_block_height) = if self.verify {
+            (
+                Some(self.light_client_state(last_block_height)?),
+                current_block_height.saturating_sub(1),
+            )
+        } else {
+            (None, current_block_height)
+        };
+
+        // verified header of next block is kept for verifying that block in the next iteration
+        let mut next_signed_header = None;
+
+        for height in (last_block_height + 1)..=sync_block_height {
+            let block_results = self.client.block_results(height)?;
+            let valid_transaction_ids = block_results.ids()?;
             let block = self.client.block(height)?;
-            let transactions = block.transactions()?;
 
-            for transaction in transactions {
-                let transaction_id = transaction.tx_id();
+            let app_hash = match light_client.as_mut() {
+                Some((chain_id, light_client_state)) => {
+                    let signed_header = self.verify_block(
+                        chain_id,
+                        light_client_state,
+                        next_signed_header.take(),
+                        &block,
+                        &block_results,
+                    )?;
+                    let app_hash = signed_header.app_hash()?;
+                    next_signed_header = Some(signed_header);
+                    Some(app_hash)
+                }
+                None => None,
+            };
+            let transactions = block
+                .transactions()?
+                .into_iter()
+                .filter(|transaction| valid_transaction_ids.contains(&transaction.tx_id()))
+                .collect::<Vec<TxAux>>();
+
+            let mut transfer_transactions = if block_results
+                .block_filter()?
+                .check_view_key(&view_key.into())
+            {
+                self.decrypt_transfer_transactions(&transactions, private_key)?
+            } else {
+                HashMap::new()
+            };
 
-                if valid_transaction_ids.contains(&transaction_id) {
-                    self.handle_transaction(transaction, height, block.time())?;
+            for transaction in transactions {
+                match transaction {
+                    TxAux::TransferTx { txid, .. } => {
+                        // Transfer transactions which are not decrypted are not visible to current view key
+                        if let Some(transfer_transaction) = transfer_transactions.remove(&txid) {
+                            if let Some(app_hash) = app_hash.as_ref() {
+                                self.verify_transaction(&txid, height, app_hash)?;
+                            }
+                            self.handle_transfer_transaction(
+                                transfer_transaction,
+                                addresses,
+                                height,
+                                block.time(),
+                            )?;
+                        }
+                    }
+                    _ => {
+                        if let Some(app_hash) = app_hash.as_ref() {
+                            self.verify_transaction(&transaction.tx_id(), height, app_hash)?;
+                        }
+                        self.handle_transaction(transaction, addresses, height, block.time())?
+                    }
                 }
             }
 
+            if let Some((_, light_client_state)) = light_client.as_ref() {
+                self.global_state_service
+                    .set_light_client_state(light_client_state)?;
+            }
+            if let Some(app_hash) = app_hash.as_ref() {
+                // kept for verifying transfer transactions of view keys synced later
+                self.global_state_service.set_app_hash(height, app_hash)?;
+            }
             self.global_state_service.set_last_block_height(height)?;
+            self.global_state_service
+                .set_last_view_key_block_height(view_key, height)?;
+            self.global_state_service
+                .set_last_block_time(block.time().timestamp())?;
         }
 
         Ok(())
     }
 
     #[inline]
-    fn sync_all(&self) -> Result<()> {
+    fn sync_all(
+        &self,
+        view_key: &PublicKey,
+        private_key: &PrivateKey,
+        addresses: &[ExtendedAddr],
+    ) -> Result<()> {
         self.clear()?;
-        self.sync()
+        self.sync(view_key, private_key, addresses)
     }
 
     #[inline]
@@ -245,6 +723,13 @@ where
         self.transaction_service.get(id)
     }
 
+    fn last_block(&self) -> Result<Option<(u64, Timespec)>> {
+        let last_block_height = self.global_state_service.last_block_height()?;
+        let last_block_time = self.global_state_service.last_block_time()?;
+
+        Ok(last_block_height.and_then(|height| last_block_time.map(|time| (height, time))))
+    }
+
     fn output(&self, id: &TxId, index: usize) -> Result<TxOut> {
         let transaction = self
             .transaction(id)?
@@ -269,6 +754,15 @@ where
 
                 Ok(output)
             }
+            Transaction::ClaimRewardsTransaction(claim_transaction) => {
+                let output = claim_transaction
+                    .outputs
+                    .into_iter()
+                    .nth(index)
+                    .ok_or_else(|| Error::from(ErrorKind::TransactionNotFound))?;
+
+                Ok(output)
+            }
             _ => Err(ErrorKind::InvalidTransaction.into()),
         }
     }
@@ -292,10 +786,11 @@ mod tests {
     use chain_core::common::TendermintEventType;
     use chain_core::init::coin::Coin;
     use chain_core::state::account::StakedStateOpWitness;
+    use chain_core::tx::data::access::{TxAccess, TxAccessPolicy};
     use chain_core::tx::data::address::ExtendedAddr;
     use chain_core::tx::data::attribute::TxAttributes;
-    use chain_core::tx::data::Tx;
     use chain_core::tx::PlainTxAux;
+    use chain_tx_filter::BlockFilter;
     use client_common::storage::MemoryStorage;
     use client_common::tendermint::types::*;
 
@@ -303,17 +798,55 @@ mod tests {
     #[derive(Clone)]
     pub struct MockClient {
         pub addresses: [ExtendedAddr; 2],
+        pub view_key: PublicKey,
+        pub unindexed_input: bool,
     }
 
     impl Default for MockClient {
         fn default() -> Self {
             Self {
                 addresses: [ExtendedAddr::OrTree([0; 32]), ExtendedAddr::OrTree([1; 32])],
+                view_key: PublicKey::from(&view_private_key()),
+                unindexed_input: false,
             }
         }
     }
 
+    fn view_private_key() -> PrivateKey {
+        PrivateKey::deserialize_from(&[1; 32]).unwrap()
+    }
+
     impl MockClient {
+        fn transfer_transaction(&self) -> Tx {
+            let mut inputs = vec![TxoPointer {
+                id: self.transaction(1).unwrap().tx_id(),
+                index: 0,
+            }];
+            if self.unindexed_input {
+                inputs.push(TxoPointer {
+                    id: [0xff; 32],
+                    index: 0,
+                });
+            }
+
+            Tx {
+                inputs,
+                outputs: vec![TxOut {
+                    address: self.addresses[1].clone(),
+                    value: Coin::new(100).unwrap(),
+                    valid_from: None,
+                    relative_lock: None,
+                }],
+                attributes: TxAttributes::new_with_access(
+                    171,
+                    vec![TxAccessPolicy::new(
+                        self.view_key.clone().into(),
+                        TxAccess::AllData,
+                    )],
+                ),
+            }
+        }
+
         fn transaction(&self, height: u64) -> Option<TxAux> {
             if height == 1 {
                 Some(TxAux::WithdrawUnbondedStakeTx(
@@ -323,6 +856,7 @@ mod tests {
                             address: self.addresses[0].clone(),
                             value: Coin::new(100).unwrap(),
                             valid_from: None,
+                            relative_lock: None,
                         }],
                         attributes: TxAttributes::new(171),
                     },
@@ -342,25 +876,13 @@ mod tests {
                     ),
                 ))
             } else if height == 2 {
-                let inputs = vec![TxoPointer {
-                    id: self.transaction(1).unwrap().tx_id(),
-                    index: 0,
-                }];
-                let tx = Tx {
-                    inputs: inputs.clone(),
-                    outputs: vec![TxOut {
-                        address: self.addresses[1].clone(),
-                        value: Coin::new(100).unwrap(),
-                        valid_from: None,
-                    }],
-                    attributes: TxAttributes::new(171),
-                };
+                let tx = self.transfer_transaction();
                 Some(TxAux::TransferTx {
                     txid: tx.id(),
-                    inputs,
+                    inputs: tx.inputs.clone(),
                     no_of_outputs: 1,
                     nonce: [0u8; 12],
-                    txpayload: PlainTxAux::TransferTx(tx.clone(), vec![].into()).encode(),
+                    txpayload: PlainTxAux::TransferTx(tx, vec![].into()).encode(),
                 })
             } else {
                 None
@@ -377,6 +899,8 @@ mod tests {
             Ok(Status {
                 sync_info: SyncInfo {
                     latest_block_height: "2".to_owned(),
+                    latest_app_hash: "".to_owned(),
+                    latest_block_time: DateTime::from_str("2019-04-10T09:38:41.735577Z").unwrap(),
                 },
             })
         }
@@ -417,6 +941,8 @@ mod tests {
                     height: "1".to_owned(),
                     results: Results {
                         deliver_tx: Some(vec![DeliverTx {
+                            code: 0,
+                            data: None,
                             events: vec![Event {
                                 event_type: TendermintEventType::ValidTransactions.to_string(),
                                 attributes: vec![Attribute {
@@ -427,13 +953,19 @@ mod tests {
                                 }],
                             }],
                         }]),
+                        end_block: None,
                     },
                 })
             } else if height == 2 {
+                let mut block_filter = BlockFilter::default();
+                block_filter.add_view_key(&self.view_key.clone().into());
+
                 Ok(BlockResults {
                     height: "2".to_owned(),
                     results: Results {
                         deliver_tx: Some(vec![DeliverTx {
+                            code: 0,
+                            data: None,
                             events: vec![Event {
                                 event_type: TendermintEventType::ValidTransactions.to_string(),
                                 attributes: vec![Attribute {
@@ -444,6 +976,18 @@ mod tests {
                                 }],
                             }],
                         }]),
+                        end_block: Some(EndBlock {
+                            validator_updates: None,
+                            events: vec![Event {
+                                event_type: TendermintEventType::BlockFilter.to_string(),
+                                attributes: vec![Attribute {
+                                    key: "ZXRoYmxvb20=".to_owned(),
+                                    value: base64::encode(
+                                        &block_filter.get_tendermint_kv().unwrap().1,
+                                    ),
+                                }],
+                            }],
+                        }),
                     },
                 })
             } else {
@@ -451,22 +995,40 @@ mod tests {
             }
         }
 
+        fn commit(&self, _: u64) -> Result<Commit> {
+            unreachable!()
+        }
+
         fn broadcast_transaction(&self, _: &[u8]) -> Result<()> {
             Ok(())
         }
 
         /// Get abci query
-        fn query(&self, _path: &str, _data: &str) -> Result<QueryResult> {
+        fn query(
+            &self,
+            path: &str,
+            _data: &str,
+            _height: Option<u64>,
+            _prove: bool,
+        ) -> Result<QueryResult> {
+            let value = if path == "mockdecrypt" {
+                let response = DecryptionResponse {
+                    txs: vec![self.transfer_transaction()],
+                };
+                base64::encode(&response.encode())
+            } else {
+                "".to_string()
+            };
+
             Ok(QueryResult {
                 response: Response {
-                    value: "".to_string(),
+                    value,
+                    ..Default::default()
                 },
             })
         }
     }
 
-    // FIXME: !!!
-    #[ignore]
     #[test]
     fn check_flow() {
         let client = MockClient::default();
@@ -474,7 +1036,9 @@ mod tests {
 
         let index = DefaultIndex::new(storage, client.clone());
 
-        assert!(index.sync_all().is_ok());
+        assert!(index
+            .sync_all(&client.view_key, &view_private_key(), &client.addresses)
+            .is_ok());
 
         assert_eq!(Coin::zero(), index.balance(&client.addresses[0]).unwrap());
         assert_eq!(
@@ -520,5 +1084,118 @@ mod tests {
             assert!(index.transaction(&change.transaction_id).unwrap().is_some());
             assert!(index.output(&change.transaction_id, 0).is_ok());
         }
+
+        assert_eq!(
+            Some((
+                2,
+                DateTime::<Utc>::from_str("2019-04-10T09:38:41.735577Z")
+                    .unwrap()
+                    .timestamp()
+            )),
+            index.last_block().unwrap()
+        );
+    }
+    #[test]
+    fn check_view_key_synced_after_another_one() {
+        let client = MockClient::default();
+        let storage = MemoryStorage::default();
+
+        let index = DefaultIndex::new(storage, client.clone());
+
+        // the transfer transaction is not visible to the other view key
+        let other_private_key = PrivateKey::deserialize_from(&[2; 32]).unwrap();
+        assert!(index
+            .sync(
+                &PublicKey::from(&other_private_key),
+                &other_private_key,
+                &[]
+            )
+            .is_ok());
+        assert_eq!(
+            Coin::new(100).unwrap(),
+            index.balance(&client.addresses[0]).unwrap()
+        );
+        assert_eq!(Coin::zero(), index.balance(&client.addresses[1]).unwrap());
+
+        // blocks which are already synced are checked again for the view key which has access to it
+        assert!(index
+            .sync(&client.view_key, &view_private_key(), &client.addresses)
+            .is_ok());
+        assert_eq!(Coin::zero(), index.balance(&client.addresses[0]).unwrap());
+        assert_eq!(
+            Coin::new(100).unwrap(),
+            index.balance(&client.addresses[1]).unwrap()
+        );
+
+        // syncing again does not index the transfer transaction twice
+        assert!(index
+            .sync(&client.view_key, &view_private_key(), &client.addresses)
+            .is_ok());
+        assert_eq!(
+            Coin::new(100).unwrap(),
+            index.balance(&client.addresses[1]).unwrap()
+        );
+        assert_eq!(
+            1,
+            index
+                .transaction_changes(&client.addresses[1])
+                .unwrap()
+                .len()
+        );
+    }
+
+    #[test]
+    fn check_transfer_spending_foreign_and_unindexed_inputs() {
+        // a transfer received from another wallet, which spends both an indexed output sent to it and its own
+        // (unindexed) output
+        let client = MockClient {
+            unindexed_input: true,
+            ..Default::default()
+        };
+        let storage = MemoryStorage::default();
+
+        let index = DefaultIndex::new(storage, client.clone());
+
+        assert!(index
+            .sync_all(
+                &client.view_key,
+                &view_private_key(),
+                &client.addresses[1..]
+            )
+            .is_ok());
+
+        // inputs spending outputs of other addresses are not recorded
+        assert_eq!(
+            Coin::new(100).unwrap(),
+            index.balance(&client.addresses[0]).unwrap()
+        );
+        assert_eq!(
+            1,
+            index
+                .transaction_changes(&client.addresses[0])
+                .unwrap()
+                .len()
+        );
+
+        assert_eq!(
+            Coin::new(100).unwrap(),
+            index.balance(&client.addresses[1]).unwrap()
+        );
+        assert_eq!(
+            1,
+            index
+                .unspent_transactions(&client.addresses[1])
+                .unwrap()
+                .len()
+        );
+
+        // syncing again does not fail on the same block
+        assert!(index
+            .sync(
+                &client.view_key,
+                &view_private_key(),
+                &client.addresses[1..]
+            )
+            .is_ok());
     }
 }
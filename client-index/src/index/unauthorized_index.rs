@@ -1,10 +1,11 @@
+use chain_core::common::Timespec;
 use chain_core::init::coin::Coin;
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::output::TxOut;
 use chain_core::tx::data::TxId;
 use client_common::balance::TransactionChange;
-use client_common::{ErrorKind, Result, Transaction};
+use client_common::{ErrorKind, PrivateKey, PublicKey, Result, Transaction};
 
 use crate::Index;
 
@@ -13,11 +14,21 @@ use crate::Index;
 pub struct UnauthorizedIndex;
 
 impl Index for UnauthorizedIndex {
-    fn sync(&self) -> Result<()> {
+    fn sync(
+        &self,
+        _view_key: &PublicKey,
+        _private_key: &PrivateKey,
+        _addresses: &[ExtendedAddr],
+    ) -> Result<()> {
         Err(ErrorKind::PermissionDenied.into())
     }
 
-    fn sync_all(&self) -> Result<()> {
+    fn sync_all(
+        &self,
+        _view_key: &PublicKey,
+        _private_key: &PrivateKey,
+        _addresses: &[ExtendedAddr],
+    ) -> Result<()> {
         Err(ErrorKind::PermissionDenied.into())
     }
 
@@ -37,6 +48,10 @@ impl Index for UnauthorizedIndex {
         Err(ErrorKind::PermissionDenied.into())
     }
 
+    fn last_block(&self) -> Result<Option<(u64, Timespec)>> {
+        Err(ErrorKind::PermissionDenied.into())
+    }
+
     fn output(&self, _id: &TxId, _index: usize) -> Result<TxOut> {
         Err(ErrorKind::PermissionDenied.into())
     }
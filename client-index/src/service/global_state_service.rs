@@ -1,8 +1,13 @@
-use client_common::{Result, Storage};
+use chain_core::common::{Timespec, H256};
+use client_common::tendermint::lite::LightClientState;
+use client_common::{PublicKey, Result, Storage};
 use parity_codec::{Decode, Encode};
 
 const KEYSPACE: &str = "index_global_state";
 const LAST_BLOCK_HEIGHT: &str = "last_block_height";
+const LAST_BLOCK_TIME: &str = "last_block_time";
+const LIGHT_CLIENT_STATE: &str = "light_client_state";
+const APP_HASH: &str = "app_hash";
 
 /// Exposes functionalities for managing client's global state
 #[derive(Default, Clone)]
@@ -41,6 +46,87 @@ where
         Ok(old_last_block_height)
     }
 
+    /// Returns last block height synced for given view key (transfer transactions visible to a view key are only
+    /// indexed when it's synced, so it can be behind the last block height of the index)
+    pub fn last_view_key_block_height(&self, view_key: &PublicKey) -> Result<Option<u64>> {
+        let last_block_height = self
+            .storage
+            .get(KEYSPACE, format!("{}_{}", LAST_BLOCK_HEIGHT, view_key))?
+            .and_then(|bytes| u64::decode(&mut bytes.as_slice()));
+
+        Ok(last_block_height)
+    }
+
+    /// Updates last block height synced for given view key
+    pub fn set_last_view_key_block_height(
+        &self,
+        view_key: &PublicKey,
+        last_block_height: u64,
+    ) -> Result<()> {
+        self.storage
+            .set(
+                KEYSPACE,
+                format!("{}_{}", LAST_BLOCK_HEIGHT, view_key),
+                last_block_height.encode(),
+            )
+            .map(|_| ())
+    }
+
+    /// Returns verified app hash after executing block at given height
+    pub fn app_hash(&self, height: u64) -> Result<Option<H256>> {
+        let app_hash = self
+            .storage
+            .get(KEYSPACE, format!("{}_{}", APP_HASH, height))?
+            .and_then(|bytes| H256::decode(&mut bytes.as_slice()));
+
+        Ok(app_hash)
+    }
+
+    /// Stores verified app hash after executing block at given height
+    pub fn set_app_hash(&self, height: u64, app_hash: &H256) -> Result<()> {
+        self.storage
+            .set(
+                KEYSPACE,
+                format!("{}_{}", APP_HASH, height),
+                app_hash.encode(),
+            )
+            .map(|_| ())
+    }
+
+    /// Returns currently stored last block time
+    pub fn last_block_time(&self) -> Result<Option<Timespec>> {
+        let last_block_time = self
+            .storage
+            .get(KEYSPACE, LAST_BLOCK_TIME)?
+            .and_then(|bytes| Timespec::decode(&mut bytes.as_slice()));
+
+        Ok(last_block_time)
+    }
+
+    /// Updates last block time with given value
+    pub fn set_last_block_time(&self, last_block_time: Timespec) -> Result<()> {
+        self.storage
+            .set(KEYSPACE, LAST_BLOCK_TIME, last_block_time.encode())
+            .map(|_| ())
+    }
+
+    /// Returns currently stored light client state (trusted validator sets)
+    pub fn light_client_state(&self) -> Result<Option<LightClientState>> {
+        let light_client_state = self
+            .storage
+            .get(KEYSPACE, LIGHT_CLIENT_STATE)?
+            .and_then(|bytes| LightClientState::decode(&mut bytes.as_slice()));
+
+        Ok(light_client_state)
+    }
+
+    /// Updates light client state with given value
+    pub fn set_light_client_state(&self, light_client_state: &LightClientState) -> Result<()> {
+        self.storage
+            .set(KEYSPACE, LIGHT_CLIENT_STATE, light_client_state.encode())
+            .map(|_| ())
+    }
+
     /// Clears all storage
     pub fn clear(&self) -> Result<()> {
         self.storage.clear(KEYSPACE)
@@ -52,6 +138,8 @@ mod tests {
     use super::*;
 
     use client_common::storage::MemoryStorage;
+    use client_common::tendermint::lite::Validator;
+    use client_common::PrivateKey;
 
     #[test]
     fn check_flow() {
@@ -66,4 +154,74 @@ mod tests {
         assert!(global_state_service.clear().is_ok());
         assert_eq!(None, global_state_service.last_block_height().unwrap());
     }
+
+    #[test]
+    fn check_view_key_flow() {
+        let global_state_service = GlobalStateService::new(MemoryStorage::default());
+        let view_key = PublicKey::from(&PrivateKey::deserialize_from(&[1; 32]).unwrap());
+        let other_view_key = PublicKey::from(&PrivateKey::deserialize_from(&[2; 32]).unwrap());
+
+        assert!(global_state_service
+            .set_last_view_key_block_height(&view_key, 5)
+            .is_ok());
+        assert_eq!(
+            Some(5),
+            global_state_service
+                .last_view_key_block_height(&view_key)
+                .unwrap()
+        );
+        assert_eq!(
+            None,
+            global_state_service
+                .last_view_key_block_height(&other_view_key)
+                .unwrap()
+        );
+        assert_eq!(None, global_state_service.last_block_height().unwrap());
+
+        assert!(global_state_service.set_app_hash(5, &[3; 32]).is_ok());
+        assert_eq!(Some([3; 32]), global_state_service.app_hash(5).unwrap());
+        assert_eq!(None, global_state_service.app_hash(4).unwrap());
+
+        assert!(global_state_service.clear().is_ok());
+        assert_eq!(
+            None,
+            global_state_service
+                .last_view_key_block_height(&view_key)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn check_last_block_time_flow() {
+        let global_state_service = GlobalStateService::new(MemoryStorage::default());
+
+        assert_eq!(None, global_state_service.last_block_time().unwrap());
+        assert!(global_state_service.set_last_block_time(1000).is_ok());
+        assert_eq!(
+            1000,
+            global_state_service.last_block_time().unwrap().unwrap()
+        );
+        assert!(global_state_service.clear().is_ok());
+        assert_eq!(None, global_state_service.last_block_time().unwrap());
+    }
+
+    #[test]
+    fn check_light_client_state_flow() {
+        let global_state_service = GlobalStateService::new(MemoryStorage::default());
+        let light_client_state = LightClientState::genesis(vec![Validator {
+            pub_key: [1u8; 32],
+            voting_power: 10,
+        }]);
+
+        assert_eq!(None, global_state_service.light_client_state().unwrap());
+        assert!(global_state_service
+            .set_light_client_state(&light_client_state)
+            .is_ok());
+        assert_eq!(
+            light_client_state,
+            global_state_service.light_client_state().unwrap().unwrap()
+        );
+        assert!(global_state_service.clear().is_ok());
+        assert_eq!(None, global_state_service.light_client_state().unwrap());
+    }
 }
@@ -79,6 +79,7 @@ mod tests {
             ),
             height: 1,
             time: DateTime::from(SystemTime::now()),
+            memo: None,
         };
 
         assert_eq!(0, transaction_change_service.get(&address).unwrap().len());
@@ -7,6 +7,7 @@ use secstr::SecUtf8;
 
 use chain_core::init::coin::Coin;
 use chain_core::state::account::{StakedState, StakedStateAddress, StakedStateOpAttributes};
+use chain_core::state::tendermint::TendermintValidatorPubKey;
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::attribute::TxAttributes;
 use chain_core::tx::data::input::TxoPointer;
@@ -56,6 +57,67 @@ pub trait NetworkOpsClient {
         attributes: TxAttributes,
     ) -> Result<TxAux>;
 
+    /// Creates a new transaction for registering a staked state as a council node
+    fn create_node_join_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        consensus_pubkey: TendermintValidatorPubKey,
+        attributes: StakedStateOpAttributes,
+    ) -> Result<TxAux>;
+
+    /// Creates a new transaction for unjailing a council node
+    fn create_unjail_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        attributes: StakedStateOpAttributes,
+    ) -> Result<TxAux>;
+
+    /// Creates a new transaction for removing a council node from the council
+    fn create_node_leave_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        attributes: StakedStateOpAttributes,
+    ) -> Result<TxAux>;
+
+    /// Creates a new transaction for delegating some bonded stake to a council node
+    fn create_delegate_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        council_node: StakedStateAddress,
+        value: Coin,
+        attributes: StakedStateOpAttributes,
+    ) -> Result<TxAux>;
+
+    /// Creates a new transaction for undelegating some stake from a council node (it becomes unbonded)
+    fn create_undelegate_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        council_node: StakedStateAddress,
+        value: Coin,
+        attributes: StakedStateOpAttributes,
+    ) -> Result<TxAux>;
+
+    /// Creates a new transaction for claiming all the accrued rewards of a staked state: they're compounded into
+    /// its bonded amount, or (if `to_address` is given) withdrawn to an output locked for the unbonding period
+    fn create_claim_rewards_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        to_address: Option<ExtendedAddr>,
+        attributes: TxAttributes,
+    ) -> Result<TxAux>;
+
     /// Returns staked stake corresponding to given address
     fn get_staked_state(
         &self,
@@ -1,18 +1,27 @@
+use std::sync::Mutex;
+
 use failure::ResultExt;
 use parity_codec::Decode;
 use secstr::SecUtf8;
 
+use chain_core::common::H256;
 use chain_core::init::coin::Coin;
 use chain_core::state::account::{
-    DepositBondTx, StakedState, StakedStateAddress, StakedStateOpAttributes, StakedStateOpWitness,
-    UnbondTx, WithdrawUnbondedTx,
+    ClaimRewardsTx, DelegateTx, DepositBondTx, NodeJoinRequestTx, NodeLeaveTx, StakedState,
+    StakedStateAddress, StakedStateOpAttributes, StakedStateOpWitness, UnbondTx, UndelegateTx,
+    UnjailTx, WithdrawUnbondedTx,
 };
+use chain_core::state::account_proof::{AccountProof, AppHashParts};
+use chain_core::state::tendermint::TendermintValidatorPubKey;
 use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::attribute::TxAttributes;
 use chain_core::tx::data::input::TxoPointer;
-use chain_core::tx::data::output::TxOut;
+use chain_core::tx::data::output::{RelativeTimelock, TxOut};
+use chain_core::tx::data::TxId;
 use chain_core::tx::fee::FeeAlgorithm;
 use chain_core::tx::{TransactionId, TxAux};
+use client_common::tendermint::lite::{verify_signed_header, LightClientState};
+use client_common::tendermint::types::Response;
 use client_common::tendermint::Client;
 use client_common::{Error, ErrorKind, Result};
 use client_core::{Signer, UnspentTransactions, WalletClient};
@@ -31,6 +40,8 @@ where
     signer: &'a S,
     client: &'a C,
     fee_algorithm: &'a F,
+    /// Chain ID and trusted state of light client (moved forward as more recent headers are verified)
+    light_client: Mutex<Option<(String, LightClientState)>>,
 }
 
 impl<'a, W, S, C, F> DefaultNetworkOpsClient<'a, W, S, C, F>
@@ -47,33 +58,124 @@ where
             signer,
             client,
             fee_algorithm,
+            light_client: Mutex::new(None),
         }
     }
 
-    /// Get account info
-    fn get_account(&self, staked_state_address: &[u8]) -> Result<StakedState> {
+    /// Get account info at the given height (with a proof)
+    fn get_account(&self, staked_state_address: &[u8], height: u64) -> Result<Response> {
         self.client
-            .query("account", hex::encode(staked_state_address).as_str())
-            .map(|x| x.response.value)
-            .and_then(|value| match base64::decode(value.as_bytes()) {
-                Ok(a) => Ok(a),
-                Err(_b) => Err(Error::from(ErrorKind::RpcError)),
-            })
-            .and_then(|data| match StakedState::decode(&mut data.as_slice()) {
-                Some(a) => Ok(a),
-                None => Err(Error::from(ErrorKind::RpcError)),
-            })
+            .query(
+                "account",
+                hex::encode(staked_state_address).as_str(),
+                Some(height),
+                true,
+            )
+            .map(|x| x.response)
+    }
+
+    /// Returns the app hash in the header of the block at `height` (verified by light client from genesis or from
+    /// the last verified header)
+    fn get_verified_app_hash(&self, height: u64) -> Result<H256> {
+        let mut light_client = self
+            .light_client
+            .lock()
+            .map_err(|_| Error::from(ErrorKind::LockError))?;
+
+        // the trusted state is only kept if the verification succeeds (otherwise it starts from genesis again)
+        let (chain_id, mut state) = match light_client.take() {
+            Some((chain_id, state)) if state.height <= height => (chain_id, state),
+            _ => {
+                let genesis = self.client.genesis()?;
+                (
+                    genesis.chain_id().to_owned(),
+                    LightClientState::genesis(genesis.validators()?),
+                )
+            }
+        };
+        let signed_header = verify_signed_header(self.client, &chain_id, &mut state, height)?;
+        *light_client = Some((chain_id, state));
+
+        signed_header.app_hash()
     }
 
-    /// Get staked state info
+    /// Get staked state info (verified against the app hash committed to in the header of the next block, so the
+    /// state is the one after the block before the last one)
     fn get_staked_state_account(
         &self,
         to_staked_account: &StakedStateAddress,
     ) -> Result<StakedState> {
-        match to_staked_account {
-            StakedStateAddress::BasicRedeem(ref a) => self.get_account(&a.0),
+        // height 0 is the latest state in queries (and the app hash after the genesis is not in any later header)
+        let last_block_height = self.client.status()?.last_block_height()?;
+        if last_block_height < 2 {
+            return Err(ErrorKind::VerifyError.into());
+        }
+        let height = last_block_height - 1;
+
+        let response = match to_staked_account {
+            StakedStateAddress::BasicRedeem(ref a) => self.get_account(&a.0, height)?,
+        };
+        let staked_state: StakedState = decode_base64(&response.value)?;
+
+        let response_height = response
+            .height
+            .parse::<u64>()
+            .context(ErrorKind::DeserializationError)?;
+        if response_height != height {
+            return Err(ErrorKind::VerifyError.into());
+        }
+        let app_hash = self.get_verified_app_hash(height + 1)?;
+
+        let account_proof: AccountProof = match response.proof_op("account") {
+            Some(op) => decode_base64(&op.data)?,
+            None => return Err(ErrorKind::VerifyError.into()),
+        };
+        let app_hash_parts: AppHashParts = match response.proof_op("app_hash") {
+            Some(op) => decode_base64(&op.data)?,
+            None => return Err(ErrorKind::VerifyError.into()),
+        };
+
+        if app_hash_parts.verify_account(
+            &app_hash,
+            &account_proof,
+            to_staked_account,
+            Some(&staked_state),
+        ) {
+            Ok(staked_state)
+        } else {
+            Err(ErrorKind::VerifyError.into())
         }
     }
+
+    /// Signs a staked state operation with the key of the staked state address
+    fn sign_staked_state_operation(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        transaction_id: TxId,
+    ) -> Result<StakedStateOpWitness> {
+        let public_key = match address {
+            StakedStateAddress::BasicRedeem(ref redeem_address) => self
+                .wallet_client
+                .find_public_key(name, passphrase, redeem_address)?
+                .ok_or_else(|| Error::from(ErrorKind::AddressNotFound))?,
+        };
+        let private_key = self
+            .wallet_client
+            .private_key(passphrase, &public_key)?
+            .ok_or_else(|| Error::from(ErrorKind::PrivateKeyNotFound))?;
+
+        private_key
+            .sign(transaction_id)
+            .map(StakedStateOpWitness::new)
+    }
+}
+
+/// Decodes a base64-encoded value from a query response
+fn decode_base64<T: Decode>(value: &str) -> Result<T> {
+    let data = base64::decode(value.as_bytes()).context(ErrorKind::RpcError)?;
+    T::decode(&mut data.as_slice()).ok_or_else(|| Error::from(ErrorKind::RpcError))
 }
 
 impl<'a, W, S, C, F> NetworkOpsClient for DefaultNetworkOpsClient<'a, W, S, C, F>
@@ -185,8 +287,16 @@ where
     ) -> Result<TxAux> {
         let staked_state = self.get_staked_state(name, passphrase, from_address)?;
 
-        let temp_output =
-            TxOut::new_with_timelock(to_address.clone(), Coin::zero(), staked_state.unbonded_from);
+        // the unbonding entries matured at the last block are withdrawn (the later ones remain in the staked state)
+        let last_block_time = self.client.status()?.last_block_time().timestamp();
+        let matured_at = staked_state
+            .last_matured(last_block_time)
+            .ok_or_else(|| Error::from(ErrorKind::InsufficientBalance))?;
+        let withdrawable = (staked_state.matured_unbonded(matured_at)
+            - staked_state.withdrawal_locked(matured_at, last_block_time))
+        .context(ErrorKind::BalanceAdditionError)?;
+
+        let temp_output = TxOut::new_with_timelock(to_address.clone(), Coin::zero(), matured_at);
 
         let temp_transaction = self.create_withdraw_unbonded_stake_transaction(
             name,
@@ -202,8 +312,8 @@ where
             .context(ErrorKind::BalanceAdditionError)?
             .to_coin();
 
-        let amount = (staked_state.unbonded - fee).context(ErrorKind::BalanceAdditionError)?;
-        let output = TxOut::new_with_timelock(to_address, amount, staked_state.unbonded_from);
+        let amount = (withdrawable - fee).context(ErrorKind::BalanceAdditionError)?;
+        let output = TxOut::new_with_timelock(to_address, amount, matured_at);
 
         self.create_withdraw_unbonded_stake_transaction(
             name,
@@ -214,6 +324,135 @@ where
         )
     }
 
+    fn create_node_join_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        consensus_pubkey: TendermintValidatorPubKey,
+        attributes: StakedStateOpAttributes,
+    ) -> Result<TxAux> {
+        let staked_state = self.get_staked_state(name, passphrase, address)?;
+        let transaction = NodeJoinRequestTx::new(staked_state.nonce, consensus_pubkey, attributes);
+        let signature =
+            self.sign_staked_state_operation(name, passphrase, address, transaction.id())?;
+
+        Ok(TxAux::NodeJoinTx(transaction, signature))
+    }
+
+    fn create_unjail_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        attributes: StakedStateOpAttributes,
+    ) -> Result<TxAux> {
+        let staked_state = self.get_staked_state(name, passphrase, address)?;
+        let transaction = UnjailTx::new(staked_state.nonce, attributes);
+        let signature =
+            self.sign_staked_state_operation(name, passphrase, address, transaction.id())?;
+
+        Ok(TxAux::UnjailTx(transaction, signature))
+    }
+
+    fn create_node_leave_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        attributes: StakedStateOpAttributes,
+    ) -> Result<TxAux> {
+        let staked_state = self.get_staked_state(name, passphrase, address)?;
+        let transaction = NodeLeaveTx::new(staked_state.nonce, attributes);
+        let signature =
+            self.sign_staked_state_operation(name, passphrase, address, transaction.id())?;
+
+        Ok(TxAux::NodeLeaveTx(transaction, signature))
+    }
+
+    fn create_delegate_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        council_node: StakedStateAddress,
+        value: Coin,
+        attributes: StakedStateOpAttributes,
+    ) -> Result<TxAux> {
+        let staked_state = self.get_staked_state(name, passphrase, address)?;
+        let transaction = DelegateTx::new(value, staked_state.nonce, council_node, attributes);
+        let signature =
+            self.sign_staked_state_operation(name, passphrase, address, transaction.id())?;
+
+        Ok(TxAux::DelegateTx(transaction, signature))
+    }
+
+    fn create_undelegate_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        council_node: StakedStateAddress,
+        value: Coin,
+        attributes: StakedStateOpAttributes,
+    ) -> Result<TxAux> {
+        let staked_state = self.get_staked_state(name, passphrase, address)?;
+        let transaction = UndelegateTx::new(value, staked_state.nonce, council_node, attributes);
+        let signature =
+            self.sign_staked_state_operation(name, passphrase, address, transaction.id())?;
+
+        Ok(TxAux::UndelegateTx(transaction, signature))
+    }
+
+    fn create_claim_rewards_transaction(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        address: &StakedStateAddress,
+        to_address: Option<ExtendedAddr>,
+        attributes: TxAttributes,
+    ) -> Result<TxAux> {
+        let staked_state = self.get_staked_state(name, passphrase, address)?;
+        if staked_state.rewards == Coin::zero() {
+            return Err(ErrorKind::InsufficientBalance.into());
+        }
+
+        let outputs = match to_address {
+            None => Vec::new(),
+            Some(to_address) => {
+                // the claimed rewards are locked for (at least) the unbonding period
+                let lock = RelativeTimelock::Seconds(self.client.genesis()?.unbonding_period());
+
+                let temp_output =
+                    TxOut::new_with_relative_timelock(to_address.clone(), Coin::zero(), lock);
+                let temp_transaction =
+                    ClaimRewardsTx::new(staked_state.nonce, vec![temp_output], attributes.clone());
+                let temp_signature = self.sign_staked_state_operation(
+                    name,
+                    passphrase,
+                    address,
+                    temp_transaction.id(),
+                )?;
+
+                let fee = self
+                    .fee_algorithm
+                    .calculate_for_txaux(&TxAux::ClaimRewardsTx(temp_transaction, temp_signature))
+                    .context(ErrorKind::BalanceAdditionError)?
+                    .to_coin();
+
+                let amount =
+                    (staked_state.rewards - fee).context(ErrorKind::BalanceAdditionError)?;
+                vec![TxOut::new_with_relative_timelock(to_address, amount, lock)]
+            }
+        };
+
+        let transaction = ClaimRewardsTx::new(staked_state.nonce, outputs, attributes);
+        let signature =
+            self.sign_staked_state_operation(name, passphrase, address, transaction.id())?;
+
+        Ok(TxAux::ClaimRewardsTx(transaction, signature))
+    }
+
     fn get_staked_state(
         &self,
         name: &str,
@@ -236,15 +475,29 @@ where
 mod tests {
     use super::*;
 
+    use std::collections::BTreeMap;
+
+    use chain_core::compute_app_hash_from_parts;
     use chain_core::init::address::RedeemAddress;
     use chain_core::init::coin::CoinError;
-    use chain_core::tx::fee::Fee;
+    use chain_core::init::config::{
+        AccountType, InitConfig, InitNetworkParameters, InitialValidator, SlashingParameters,
+        ValidatorKeyType,
+    };
+    use chain_core::state::account_proof::{account_data_hash, AccountTrieLeaf};
+    use chain_core::tx::fee::{Fee, FeePolicy, LinearFee, Milli};
     use chain_tx_validation::witness::verify_tx_recover_address;
+    use chrono::DateTime;
     use client_common::storage::MemoryStorage;
+    use client_common::tendermint::lite::{header_hash, vote_sign_bytes, ValidatorSet};
     use client_common::tendermint::types::*;
     use client_common::{PrivateKey, PublicKey};
     use client_core::signer::DefaultSigner;
     use client_core::wallet::DefaultWalletClient;
+    use ed25519_dalek::{ExpandedSecretKey, SecretKey};
+    use parity_codec::Encode;
+    use std::convert::TryFrom;
+    use std::str::FromStr;
 
     #[derive(Debug, Default)]
     struct UnitFeeAlgorithm;
@@ -259,36 +512,257 @@ mod tests {
         }
     }
 
-    #[derive(Default, Clone)]
-    pub struct MockClient;
+    fn mock_account(address: &RedeemAddress) -> StakedState {
+        let mut account = StakedState::new(
+            0,
+            Coin::zero(),
+            Coin::new(2500000000000000000).unwrap(),
+            1553135211,
+            StakedStateAddress::BasicRedeem(*address),
+        );
+        account.add_reward(Coin::new(1000).unwrap());
+        account
+    }
+
+    /// proof of the account in a trie with a single leaf (+ the trie root)
+    fn mock_account_proof(account: &StakedState) -> (AccountProof, H256) {
+        let key = account.key();
+        let leaf = AccountTrieLeaf {
+            key,
+            data: account_data_hash(&key, account),
+        };
+        let root = leaf.hash();
+        (
+            AccountProof {
+                branches: Vec::new(),
+                leaf,
+            },
+            root,
+        )
+    }
+
+    fn mock_app_hash_parts() -> AppHashParts {
+        AppHashParts {
+            valid_tx_root: [1u8; 32],
+            rewards_pool_hash: [2u8; 32],
+            network_params_hash: [3u8; 32],
+            council_state_hash: [4u8; 32],
+        }
+    }
+
+    fn mock_app_hash(address: &RedeemAddress) -> H256 {
+        let (_, account_root) = mock_account_proof(&mock_account(address));
+        let parts = mock_app_hash_parts();
+        compute_app_hash_from_parts(
+            &parts.valid_tx_root,
+            &account_root,
+            &parts.rewards_pool_hash,
+            &parts.network_params_hash,
+            &parts.council_state_hash,
+        )
+    }
+
+    const CHAIN_ID: &str = "test-chain-y3m1e6-AB";
+
+    fn validator_secret_key() -> SecretKey {
+        SecretKey::from_bytes(&[1u8; 32]).unwrap()
+    }
+
+    /// genesis with a single council node (signing all the blocks)
+    fn mock_genesis() -> Genesis {
+        let staking_address = RedeemAddress::default();
+        let mut distribution = BTreeMap::new();
+        distribution.insert(
+            staking_address,
+            (Coin::max(), AccountType::ExternallyOwnedAccount),
+        );
+        let params = InitNetworkParameters {
+            initial_fee_policy: FeePolicy::Linear(LinearFee::new(
+                Milli::new(1, 1),
+                Milli::new(1, 1),
+            )),
+            required_council_node_stake: Coin::unit(),
+            unbonding_period: 1,
+            block_reward_share: Milli::new(0, 500),
+            slashing_config: SlashingParameters {
+                byzantine_slash_ratio: Milli::new(0, 200),
+                liveness_slash_ratio: Milli::new(0, 10),
+                block_signing_window: 3,
+                missed_block_threshold: 1,
+                jail_duration: 10,
+            },
+            chain_id_hash_required_from: None,
+        };
+        let validator_key = ed25519_dalek::PublicKey::from(&validator_secret_key());
+
+        Genesis {
+            genesis: GenesisInner {
+                genesis_time: DateTime::from_str("2019-04-09T09:38:41.735577Z").unwrap(),
+                chain_id: CHAIN_ID.to_owned(),
+                app_state: InitConfig::new(
+                    distribution,
+                    RedeemAddress::default(),
+                    RedeemAddress::default(),
+                    RedeemAddress::default(),
+                    params,
+                    vec![InitialValidator {
+                        staking_account_address: staking_address,
+                        consensus_pubkey_type: ValidatorKeyType::Ed25519,
+                        consensus_pubkey_b64: base64::encode(validator_key.as_bytes()),
+                    }],
+                ),
+            },
+        }
+    }
+
+    fn mock_block_id(header: &FullHeader) -> BlockId {
+        BlockId {
+            hash: hex::encode_upper(header_hash(header).unwrap()),
+            parts: PartSetHeader {
+                total: "1".to_owned(),
+                hash: hex::encode_upper(&[1u8; 32]),
+            },
+        }
+    }
+
+    /// header of the block at `height` signed by the genesis council node
+    fn mock_signed_header(height: u64, app_hash: &H256) -> SignedHeader {
+        let validators = ValidatorSet::new(mock_genesis().validators().unwrap());
+        let header = FullHeader {
+            version: Version {
+                block: "10".to_owned(),
+                app: "0".to_owned(),
+            },
+            chain_id: CHAIN_ID.to_owned(),
+            height: height.to_string(),
+            time: DateTime::from_str("2019-04-09T09:38:41.735577Z").unwrap(),
+            num_txs: "0".to_owned(),
+            total_txs: "0".to_owned(),
+            last_block_id: BlockId {
+                hash: hex::encode_upper(&[2u8; 32]),
+                parts: PartSetHeader {
+                    total: "1".to_owned(),
+                    hash: hex::encode_upper(&[1u8; 32]),
+                },
+            },
+            last_commit_hash: hex::encode_upper(&[3u8; 32]),
+            data_hash: "".to_owned(),
+            validators_hash: hex::encode_upper(validators.hash()),
+            next_validators_hash: hex::encode_upper(validators.hash()),
+            consensus_hash: hex::encode_upper(&[4u8; 32]),
+            app_hash: hex::encode_upper(app_hash),
+            last_results_hash: "".to_owned(),
+            evidence_hash: "".to_owned(),
+            proposer_address: hex::encode_upper(validators.validators()[0].address()),
+        };
+
+        let mut vote = Vote {
+            vote_type: 2,
+            height: height.to_string(),
+            round: "0".to_owned(),
+            block_id: mock_block_id(&header),
+            timestamp: DateTime::from_str("2019-04-09T09:38:42.735577Z").unwrap(),
+            validator_address: hex::encode_upper(validators.validators()[0].address()),
+            validator_index: "0".to_owned(),
+            signature: "".to_owned(),
+        };
+        let secret_key = validator_secret_key();
+        let signature = ExpandedSecretKey::from(&secret_key).sign(
+            &vote_sign_bytes(CHAIN_ID, &vote).unwrap(),
+            &ed25519_dalek::PublicKey::from(&secret_key),
+        );
+        vote.signature = base64::encode(&signature.to_bytes()[..]);
+
+        SignedHeader {
+            commit: CommitSignatures {
+                block_id: mock_block_id(&header),
+                precommits: vec![Some(vote)],
+            },
+            header,
+        }
+    }
+
+    #[derive(Default)]
+    pub struct MockClient {
+        /// the app hash in block headers depends on the queried account (a single-leaf account trie)
+        last_queried_address: Mutex<Option<RedeemAddress>>,
+    }
 
     impl Client for MockClient {
         fn genesis(&self) -> Result<Genesis> {
-            unreachable!()
+            Ok(mock_genesis())
         }
 
         fn status(&self) -> Result<Status> {
-            unreachable!()
+            Ok(Status {
+                sync_info: SyncInfo {
+                    latest_block_height: "2".to_owned(),
+                    latest_app_hash: "".to_owned(),
+                    latest_block_time: DateTime::from_str("2019-04-09T09:38:41.735577Z").unwrap(),
+                },
+            })
         }
 
         fn block(&self, _: u64) -> Result<Block> {
             unreachable!()
         }
 
-        fn block_results(&self, _: u64) -> Result<BlockResults> {
-            unreachable!()
+        fn block_results(&self, height: u64) -> Result<BlockResults> {
+            Ok(BlockResults {
+                height: height.to_string(),
+                results: Results {
+                    deliver_tx: None,
+                    end_block: None,
+                },
+            })
+        }
+
+        fn commit(&self, height: u64) -> Result<Commit> {
+            let address = self
+                .last_queried_address
+                .lock()
+                .unwrap()
+                .expect("account should be queried before its app hash is verified");
+            Ok(Commit {
+                signed_header: mock_signed_header(height, &mock_app_hash(&address)),
+                canonical: true,
+            })
         }
 
         fn broadcast_transaction(&self, _: &[u8]) -> Result<()> {
             unreachable!()
         }
 
-        fn query(&self, _path: &str, _data: &str) -> Result<QueryResult> {
+        fn query(
+            &self,
+            _path: &str,
+            data: &str,
+            height: Option<u64>,
+            _prove: bool,
+        ) -> Result<QueryResult> {
+            assert_eq!(Some(1), height);
+            let address = RedeemAddress::try_from(hex::decode(data).unwrap().as_slice()).unwrap();
+            *self.last_queried_address.lock().unwrap() = Some(address);
+            let account = mock_account(&address);
+            let (proof, _) = mock_account_proof(&account);
             Ok(QueryResult {
                 response: Response {
-                    value:
-                        "AAAAAAAAAAAAAAAAAAAAAAAAeiLByLEia/aSXAAAAAAADbIhxPV9XTi5aBOcBukTKq+E6N8="
-                            .to_string(),
+                    value: base64::encode(&account.encode()),
+                    height: "1".to_owned(),
+                    proof: Some(Proof {
+                        ops: vec![
+                            ProofOp {
+                                field_type: "account".to_owned(),
+                                key: "".to_owned(),
+                                data: base64::encode(&proof.encode()),
+                            },
+                            ProofOp {
+                                field_type: "app_hash".to_owned(),
+                                key: "".to_owned(),
+                                data: base64::encode(&mock_app_hash_parts().encode()),
+                            },
+                        ],
+                    }),
                 },
             })
         }
@@ -320,7 +794,7 @@ mod tests {
         );
 
         let inputs: Vec<TxoPointer> = vec![];
-        let to_staked_account = wallet_client.new_staking_address(name, passphrase).unwrap();;
+        let to_staked_account = wallet_client.new_staking_address(name, passphrase).unwrap();
 
         let attributes = StakedStateOpAttributes::new(0);
         assert!(network_ops_client
@@ -419,6 +893,163 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_create_node_join_transaction() {
+        let name = "name";
+        let passphrase = &SecUtf8::from("passphrase");
+
+        let storage = MemoryStorage::default();
+        let signer = DefaultSigner::new(storage.clone());
+
+        let fee_algorithm = UnitFeeAlgorithm::default();
+
+        let wallet_client = DefaultWalletClient::builder()
+            .with_wallet(storage)
+            .build()
+            .unwrap();
+
+        let tendermint_client = MockClient::default();
+        let network_ops_client = DefaultNetworkOpsClient::new(
+            &wallet_client,
+            &signer,
+            &tendermint_client,
+            &fee_algorithm,
+        );
+
+        wallet_client.new_wallet(name, passphrase).unwrap();
+
+        let address = wallet_client.new_staking_address(name, passphrase).unwrap();
+        let consensus_pubkey = TendermintValidatorPubKey::Ed25519([1; 32]);
+
+        let transaction = network_ops_client
+            .create_node_join_transaction(
+                name,
+                passphrase,
+                &address,
+                consensus_pubkey.clone(),
+                StakedStateOpAttributes::new(0),
+            )
+            .unwrap();
+
+        match transaction {
+            TxAux::NodeJoinTx(transaction, witness) => {
+                assert_eq!(consensus_pubkey, transaction.consensus_pubkey);
+                let id = transaction.id();
+                let account_address =
+                    verify_tx_recover_address(&witness, &id).expect("Unable to verify transaction");
+
+                assert_eq!(account_address, address)
+            }
+            _ => unreachable!("`create_node_join_transaction()` created invalid transaction type"),
+        }
+    }
+
+    #[test]
+    fn check_create_delegate_transaction() {
+        let name = "name";
+        let passphrase = &SecUtf8::from("passphrase");
+
+        let storage = MemoryStorage::default();
+        let signer = DefaultSigner::new(storage.clone());
+
+        let fee_algorithm = UnitFeeAlgorithm::default();
+
+        let wallet_client = DefaultWalletClient::builder()
+            .with_wallet(storage)
+            .build()
+            .unwrap();
+
+        let tendermint_client = MockClient::default();
+        let network_ops_client = DefaultNetworkOpsClient::new(
+            &wallet_client,
+            &signer,
+            &tendermint_client,
+            &fee_algorithm,
+        );
+
+        wallet_client.new_wallet(name, passphrase).unwrap();
+
+        let address = wallet_client.new_staking_address(name, passphrase).unwrap();
+        let council_node = StakedStateAddress::BasicRedeem(RedeemAddress::from([1; 20]));
+        let value = Coin::new(10).unwrap();
+
+        let transaction = network_ops_client
+            .create_delegate_transaction(
+                name,
+                passphrase,
+                &address,
+                council_node,
+                value,
+                StakedStateOpAttributes::new(0),
+            )
+            .unwrap();
+
+        match transaction {
+            TxAux::DelegateTx(transaction, witness) => {
+                assert_eq!(council_node, transaction.council_node);
+                assert_eq!(value, transaction.value);
+                let id = transaction.id();
+                let account_address =
+                    verify_tx_recover_address(&witness, &id).expect("Unable to verify transaction");
+
+                assert_eq!(account_address, address)
+            }
+            _ => unreachable!("`create_delegate_transaction()` created invalid transaction type"),
+        }
+    }
+
+    #[test]
+    fn check_create_claim_rewards_transaction() {
+        let name = "name";
+        let passphrase = &SecUtf8::from("passphrase");
+
+        let storage = MemoryStorage::default();
+        let signer = DefaultSigner::new(storage.clone());
+
+        let fee_algorithm = UnitFeeAlgorithm::default();
+
+        let wallet_client = DefaultWalletClient::builder()
+            .with_wallet(storage)
+            .build()
+            .unwrap();
+
+        let tendermint_client = MockClient::default();
+        let network_ops_client = DefaultNetworkOpsClient::new(
+            &wallet_client,
+            &signer,
+            &tendermint_client,
+            &fee_algorithm,
+        );
+
+        wallet_client.new_wallet(name, passphrase).unwrap();
+
+        let address = wallet_client.new_staking_address(name, passphrase).unwrap();
+
+        let transaction = network_ops_client
+            .create_claim_rewards_transaction(
+                name,
+                passphrase,
+                &address,
+                None,
+                TxAttributes::new(171),
+            )
+            .unwrap();
+
+        match transaction {
+            TxAux::ClaimRewardsTx(transaction, witness) => {
+                assert!(transaction.is_compounding());
+                let id = transaction.id();
+                let account_address =
+                    verify_tx_recover_address(&witness, &id).expect("Unable to verify transaction");
+
+                assert_eq!(account_address, address)
+            }
+            _ => unreachable!(
+                "`create_claim_rewards_transaction()` created invalid transaction type"
+            ),
+        }
+    }
+
     #[test]
     fn check_withdraw_all_unbonded_stake_transaction() {
         let name = "name";
@@ -467,6 +1098,7 @@ mod tests {
 
                 assert_eq!(account_address, from_address);
                 assert_eq!(amount, Coin::new(2500000000000000000 - 1).unwrap());
+                assert_eq!(transaction.outputs[0].valid_from, Some(1553135211));
             }
             _ => unreachable!(
                 "`create_withdraw_unbonded_stake_transaction()` created invalid transaction type"
@@ -33,7 +33,7 @@ pub(crate) struct Options {
         name = "chain-id",
         short,
         long,
-        help = "Chain ID (Last two hex digits of chain-id)"
+        help = "Full chain ID (ending with two hex digits)"
     )]
     chain_id: String,
 
@@ -1,9 +1,8 @@
-use failure::ResultExt;
-use hex;
 use jsonrpc_core::{self, IoHandler};
 use jsonrpc_http_server::{AccessControlAllowOrigin, DomainsValidation, ServerBuilder};
 use std::net::SocketAddr;
 
+use chain_core::tx::data::attribute::chain_hex_id;
 use client_common::error::{Error, ErrorKind, Result};
 use client_common::storage::SledStorage;
 use client_common::tendermint::{Client, RpcClient};
@@ -18,18 +17,19 @@ use crate::Options;
 pub(crate) struct Server {
     host: String,
     port: u16,
-    chain_id: u8,
+    chain_id: String,
     storage_dir: String,
     tendermint_url: String,
 }
 
 impl Server {
     pub(crate) fn new(options: Options) -> Result<Server> {
-        let chain_id = hex::decode(&options.chain_id).context(ErrorKind::SerializationError)?[0];
+        chain_hex_id(&options.chain_id)
+            .ok_or_else(|| Error::from(ErrorKind::DeserializationError))?;
         Ok(Server {
             host: options.host,
             port: options.port,
-            chain_id,
+            chain_id: options.chain_id,
             storage_dir: options.storage_dir,
             tendermint_url: options.tendermint_url,
         })
@@ -47,7 +47,7 @@ impl Server {
             .with_transaction_read(index)
             .with_transaction_write(transaction_builder)
             .build()?;
-        let wallet_rpc = WalletRpcImpl::new(wallet_client, self.chain_id);
+        let wallet_rpc = WalletRpcImpl::new(wallet_client, self.chain_id.clone());
 
         let mut io = IoHandler::new();
 
@@ -7,8 +7,9 @@ use serde::{Deserialize, Serialize};
 
 use chain_core::common::{H256, HASH_SIZE_256};
 use chain_core::init::coin::Coin;
+use chain_core::tx::data::access::{TxAccess, TxAccessPolicy};
 use chain_core::tx::data::address::ExtendedAddr;
-use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::attribute::{TxAttributes, MAX_MEMO_LEN};
 use chain_core::tx::data::output::TxOut;
 use client_common::balance::TransactionChange;
 use client_common::{Error, ErrorKind, PublicKey, Result as CommonResult};
@@ -31,13 +32,23 @@ pub trait WalletRpc {
     fn list(&self) -> Result<Vec<String>>;
 
     #[rpc(name = "wallet_sendtoaddress")]
-    fn sendtoaddress(&self, request: WalletRequest, to_address: String, amount: u64) -> Result<()>;
+    fn sendtoaddress(
+        &self,
+        request: WalletRequest,
+        to_address: String,
+        amount: u64,
+        view_keys: Option<Vec<String>>,
+        memo: Option<String>,
+    ) -> Result<()>;
+
+    #[rpc(name = "wallet_view_key")]
+    fn view_key(&self, request: WalletRequest) -> Result<String>;
 
     #[rpc(name = "sync")]
-    fn sync(&self) -> Result<()>;
+    fn sync(&self, request: WalletRequest) -> Result<()>;
 
     #[rpc(name = "sync_all")]
-    fn sync_all(&self) -> Result<()>;
+    fn sync_all(&self, request: WalletRequest) -> Result<()>;
 
     #[rpc(name = "wallet_transactions")]
     fn transactions(&self, request: WalletRequest) -> Result<Vec<TransactionChange>>;
@@ -93,16 +104,24 @@ pub trait WalletRpc {
 
 pub struct WalletRpcImpl<T: WalletClient + Send + Sync> {
     client: T,
-    chain_id: u8,
+    chain_id: String,
 }
 
 impl<T> WalletRpcImpl<T>
 where
     T: WalletClient + Send + Sync,
 {
-    pub fn new(client: T, chain_id: u8) -> Self {
+    pub fn new(client: T, chain_id: String) -> Self {
         WalletRpcImpl { client, chain_id }
     }
+
+    fn sync_wallet(&self, request: &WalletRequest) -> Result<()> {
+        if let Err(e) = self.client.sync(&request.name, &request.passphrase) {
+            Err(to_rpc_error(e))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 impl<T> WalletRpc for WalletRpcImpl<T>
@@ -124,7 +143,7 @@ where
     }
 
     fn balance(&self, request: WalletRequest) -> Result<Coin> {
-        self.sync()?;
+        self.sync_wallet(&request)?;
 
         match self.client.balance(&request.name, &request.passphrase) {
             Ok(balance) => Ok(balance),
@@ -154,15 +173,40 @@ where
         }
     }
 
-    fn sendtoaddress(&self, request: WalletRequest, to_address: String, amount: u64) -> Result<()> {
-        self.sync()?;
+    fn sendtoaddress(
+        &self,
+        request: WalletRequest,
+        to_address: String,
+        amount: u64,
+        view_keys: Option<Vec<String>>,
+        memo: Option<String>,
+    ) -> Result<()> {
+        self.sync_wallet(&request)?;
 
         let address = to_address
             .parse::<ExtendedAddr>()
             .map_err(|err| rpc_error_from_string(format!("{}", err)))?;
         let coin = Coin::new(amount).map_err(|err| rpc_error_from_string(format!("{}", err)))?;
         let tx_out = TxOut::new(address, coin);
-        let tx_attributes = TxAttributes::new(self.chain_id);
+        let access_policies = view_keys
+            .unwrap_or_default()
+            .into_iter()
+            .map(|view_key| {
+                Ok(TxAccessPolicy::new(
+                    parse_public_key(view_key)?.into(),
+                    TxAccess::AllData,
+                ))
+            })
+            .collect::<CommonResult<Vec<TxAccessPolicy>>>()
+            .map_err(to_rpc_error)?;
+        let mut tx_attributes = TxAttributes::new_for_chain(&self.chain_id)
+            .ok_or_else(|| rpc_error_from_string(format!("Invalid chain ID: {}", self.chain_id)))?;
+        tx_attributes.allowed_view = access_policies;
+        if let Some(memo) = memo {
+            tx_attributes = tx_attributes.with_memo(memo).ok_or_else(|| {
+                rpc_error_from_string(format!("Memo is longer than {} bytes", MAX_MEMO_LEN))
+            })?;
+        }
 
         let return_address = self
             .client
@@ -186,16 +230,19 @@ where
             .map_err(to_rpc_error)
     }
 
-    fn sync(&self) -> Result<()> {
-        if let Err(e) = self.client.sync() {
-            Err(to_rpc_error(e))
-        } else {
-            Ok(())
+    fn view_key(&self, request: WalletRequest) -> Result<String> {
+        match self.client.view_key(&request.name, &request.passphrase) {
+            Ok(view_key) => Ok(serialize_public_key(view_key)),
+            Err(e) => Err(to_rpc_error(e)),
         }
     }
 
-    fn sync_all(&self) -> Result<()> {
-        if let Err(e) = self.client.sync_all() {
+    fn sync(&self, request: WalletRequest) -> Result<()> {
+        self.sync_wallet(&request)
+    }
+
+    fn sync_all(&self, request: WalletRequest) -> Result<()> {
+        if let Err(e) = self.client.sync_all(&request.name, &request.passphrase) {
             Err(to_rpc_error(e))
         } else {
             Ok(())
@@ -203,7 +250,7 @@ where
     }
 
     fn transactions(&self, request: WalletRequest) -> Result<Vec<TransactionChange>> {
-        self.sync()?;
+        self.sync_wallet(&request)?;
 
         match self.client.history(&request.name, &request.passphrase) {
             Ok(transaction_change) => Ok(transaction_change),
@@ -362,6 +409,7 @@ mod tests {
     use chrono::DateTime;
     use std::time::SystemTime;
 
+    use chain_core::common::Timespec;
     use chain_core::init::coin::CoinError;
     use chain_core::tx::data::input::TxoPointer;
     use chain_core::tx::data::{Tx, TxId};
@@ -369,7 +417,7 @@ mod tests {
     use chain_core::tx::TxAux;
     use client_common::balance::BalanceChange;
     use client_common::storage::MemoryStorage;
-    use client_common::Transaction;
+    use client_common::{PrivateKey, Transaction};
     use client_core::signer::DefaultSigner;
     use client_core::transaction_builder::DefaultTransactionBuilder;
     use client_core::wallet::DefaultWalletClient;
@@ -379,11 +427,21 @@ mod tests {
     pub struct MockIndex;
 
     impl Index for MockIndex {
-        fn sync(&self) -> CommonResult<()> {
+        fn sync(
+            &self,
+            _view_key: &PublicKey,
+            _private_key: &PrivateKey,
+            _addresses: &[ExtendedAddr],
+        ) -> CommonResult<()> {
             Ok(())
         }
 
-        fn sync_all(&self) -> CommonResult<()> {
+        fn sync_all(
+            &self,
+            _view_key: &PublicKey,
+            _private_key: &PrivateKey,
+            _addresses: &[ExtendedAddr],
+        ) -> CommonResult<()> {
             Ok(())
         }
 
@@ -397,6 +455,7 @@ mod tests {
                 balance_change: BalanceChange::Incoming(Coin::new(30).unwrap()),
                 height: 1,
                 time: DateTime::from(SystemTime::now()),
+                memo: None,
             }])
         }
 
@@ -422,11 +481,16 @@ mod tests {
             })))
         }
 
+        fn last_block(&self) -> CommonResult<Option<(u64, Timespec)>> {
+            Ok(None)
+        }
+
         fn output(&self, _id: &TxId, _index: usize) -> CommonResult<TxOut> {
             Ok(TxOut {
                 address: ExtendedAddr::OrTree([0; 32]),
                 value: Coin::new(10000000000000000000).unwrap(),
                 valid_from: None,
+                relative_lock: None,
             })
         }
 
@@ -571,7 +635,7 @@ mod tests {
             ))
             .build()
             .unwrap();
-        let chain_id = 171u8;
+        let chain_id = "test-chain-y3m1e6-AB".to_owned();
 
         WalletRpcImpl::new(wallet_client, chain_id)
     }
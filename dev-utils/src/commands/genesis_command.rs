@@ -4,15 +4,19 @@ use failure::{format_err, Error, ResultExt};
 use hex::encode_upper;
 use structopt::StructOpt;
 
+use chain_abci::app::compute_council_state_hash;
 use chain_abci::storage::account::{AccountStorage, AccountWrapper};
 use chain_abci::storage::tx::StarlingFixedKey;
 use chain_abci::storage::Storage;
 use chain_core::common::MerkleTree;
 use chain_core::compute_app_hash;
-use chain_core::init::config::{AccountType, InitNetworkParameters, InitialValidator};
+use chain_core::init::config::{
+    AccountType, InitNetworkParameters, InitialValidator, SlashingParameters,
+};
 use chain_core::init::{address::RedeemAddress, coin::Coin, config::InitConfig};
 use chain_core::state::account::StakedState;
-use chain_core::tx::fee::{LinearFee, Milli};
+use chain_core::state::tendermint::BlockHeight;
+use chain_core::tx::fee::{DynamicFee, FeePolicy, InputOutputFee, LinearFee, Milli, TxTypeFees};
 use chrono::offset::Utc;
 use chrono::DateTime;
 use kvdb_memorydb::create;
@@ -28,6 +32,10 @@ pub struct GenesisDevConfig {
     unbonding_period: u32,
     required_council_node_stake: Coin,
     initial_fee_policy: InitialFeePolicy,
+    block_reward_share: String,
+    slashing_config: SlashingDevConfig,
+    #[serde(default)]
+    chain_id_hash_required_from: Option<BlockHeight>,
     council_nodes: Vec<InitialValidator>,
     launch_incentive_from: RedeemAddress,
     launch_incentive_to: RedeemAddress,
@@ -36,11 +44,99 @@ pub struct GenesisDevConfig {
 }
 
 #[derive(Deserialize)]
-pub struct InitialFeePolicy {
+pub struct LinearFeeDevConfig {
     base_fee: String,
     per_byte_fee: String,
 }
 
+fn parse_linear_fee(base_fee: &str, per_byte_fee: &str) -> Result<LinearFee, Error> {
+    let constant_fee = Milli::from_str(base_fee).context(format_err!("Invalid constant fee"))?;
+    let coefficient_fee =
+        Milli::from_str(per_byte_fee).context(format_err!("Invalid per byte fee"))?;
+    Ok(LinearFee::new(constant_fee, coefficient_fee))
+}
+
+impl LinearFeeDevConfig {
+    fn to_linear_fee(&self) -> Result<LinearFee, Error> {
+        parse_linear_fee(&self.base_fee, &self.per_byte_fee)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TxTypeFeesDevConfig {
+    transfer: LinearFeeDevConfig,
+    deposit: LinearFeeDevConfig,
+    unbond: LinearFeeDevConfig,
+    withdraw: LinearFeeDevConfig,
+    council_node_op: LinearFeeDevConfig,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InitialFeePolicy {
+    Linear(LinearFeeDevConfig),
+    PerTxType(TxTypeFeesDevConfig),
+    PerInputOutput {
+        base_fee: String,
+        per_byte_fee: String,
+        per_input_fee: String,
+        per_output_fee: String,
+    },
+    Dynamic {
+        base_fee: String,
+        per_byte_fee: String,
+        target_block_size: u64,
+        max_multiplier: String,
+    },
+}
+
+impl InitialFeePolicy {
+    fn to_fee_policy(&self) -> Result<FeePolicy, Error> {
+        let fee_policy = match self {
+            InitialFeePolicy::Linear(linear) => FeePolicy::Linear(linear.to_linear_fee()?),
+            InitialFeePolicy::PerTxType(fees) => FeePolicy::PerTxType(TxTypeFees {
+                transfer: fees.transfer.to_linear_fee()?,
+                deposit: fees.deposit.to_linear_fee()?,
+                unbond: fees.unbond.to_linear_fee()?,
+                withdraw: fees.withdraw.to_linear_fee()?,
+                council_node_op: fees.council_node_op.to_linear_fee()?,
+            }),
+            InitialFeePolicy::PerInputOutput {
+                base_fee,
+                per_byte_fee,
+                per_input_fee,
+                per_output_fee,
+            } => FeePolicy::PerInputOutput(InputOutputFee {
+                linear: parse_linear_fee(base_fee, per_byte_fee)?,
+                per_input: Milli::from_str(per_input_fee)
+                    .context(format_err!("Invalid per input fee"))?,
+                per_output: Milli::from_str(per_output_fee)
+                    .context(format_err!("Invalid per output fee"))?,
+            }),
+            InitialFeePolicy::Dynamic {
+                base_fee,
+                per_byte_fee,
+                target_block_size,
+                max_multiplier,
+            } => FeePolicy::Dynamic(DynamicFee::new(
+                parse_linear_fee(base_fee, per_byte_fee)?,
+                *target_block_size,
+                Milli::from_str(max_multiplier).context(format_err!("Invalid max multiplier"))?,
+            )),
+        };
+        Ok(fee_policy)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SlashingDevConfig {
+    byzantine_slash_ratio: String,
+    liveness_slash_ratio: String,
+    block_signing_window: u16,
+    missed_block_threshold: u16,
+    jail_duration: u32,
+}
+
 #[derive(Debug, StructOpt)]
 pub enum GenesisCommand {
     #[structopt(
@@ -77,15 +173,26 @@ impl GenesisCommand {
         for (address, amount) in genesis_dev.distribution.iter() {
             dist.insert(*address, (*amount, AccountType::ExternallyOwnedAccount));
         }
-        let constant_fee = Milli::from_str(&genesis_dev.initial_fee_policy.base_fee)
-            .context(format_err!("Invalid constant fee"))?;
-        let coefficient_fee = Milli::from_str(&genesis_dev.initial_fee_policy.per_byte_fee)
-            .context(format_err!("Invalid per byte fee"))?;
-        let fee_policy = LinearFee::new(constant_fee, coefficient_fee);
+        let fee_policy = genesis_dev.initial_fee_policy.to_fee_policy()?;
+        let block_reward_share = Milli::from_str(&genesis_dev.block_reward_share)
+            .context(format_err!("Invalid block reward share"))?;
+        let slashing_dev = &genesis_dev.slashing_config;
+        let slashing_config = SlashingParameters {
+            byzantine_slash_ratio: Milli::from_str(&slashing_dev.byzantine_slash_ratio)
+                .context(format_err!("Invalid byzantine slash ratio"))?,
+            liveness_slash_ratio: Milli::from_str(&slashing_dev.liveness_slash_ratio)
+                .context(format_err!("Invalid liveness slash ratio"))?,
+            block_signing_window: slashing_dev.block_signing_window,
+            missed_block_threshold: slashing_dev.missed_block_threshold,
+            jail_duration: slashing_dev.jail_duration,
+        };
         let params = InitNetworkParameters {
             initial_fee_policy: fee_policy,
             required_council_node_stake: genesis_dev.required_council_node_stake,
             unbonding_period: genesis_dev.unbonding_period,
+            block_reward_share,
+            slashing_config,
+            chain_id_hash_required_from: genesis_dev.chain_id_hash_required_from,
         };
         let config = InitConfig::new(
             dist,
@@ -113,7 +220,13 @@ impl GenesisCommand {
                 )
                 .expect("initial insert");
 
-            let genesis_app_hash = compute_app_hash(&tx_tree, &new_account_root, &rp);
+            let genesis_app_hash = compute_app_hash(
+                &tx_tree,
+                &new_account_root,
+                &rp,
+                &config.network_params.governed_params(),
+                &compute_council_state_hash(&config.network_params.slashing_config, &[], &[]),
+            );
             println!("\"app_hash\": \"{}\",", encode_upper(genesis_app_hash));
             let config_str =
                 serde_json::to_string(&config).context(format_err!("Invalid config"))?;
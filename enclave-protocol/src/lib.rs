@@ -1,11 +1,12 @@
 //! This crate contains messages exchanged in REQ-REP socket between chain-abci app to enclave wrapper server
 
-use chain_core::common::Timespec;
+use chain_core::common::{Timespec, H256};
 use chain_core::init::coin::Coin;
+use chain_core::state::tendermint::BlockHeight;
 use chain_core::tx::data::Tx;
 use chain_core::tx::data::TxId;
 use chain_core::tx::{fee::Fee, PlainTxAux, TxAux};
-use chain_tx_validation::TxWithOutputs;
+use chain_tx_validation::{TxInclusion, TxWithOutputs};
 
 use parity_codec::{Decode, Encode, Input, Output};
 
@@ -21,10 +22,13 @@ pub enum EnclaveRequest {
     /// FIXME: only certain Tx types should be sent -> create a datatype / enum for it (probably after encrypted Tx data types)
     VerifyTx {
         tx: TxAux,
-        inputs: Vec<TxWithOutputs>,
+        inputs: Vec<(TxWithOutputs, TxInclusion)>,
         min_fee_computed: Fee,
         previous_block_time: Timespec,
+        previous_block_height: BlockHeight,
         unbonding_period: u32,
+        chain_id_hash: H256,
+        chain_id_hash_required: bool,
     },
 }
 
@@ -40,14 +44,20 @@ impl Encode for EnclaveRequest {
                 inputs,
                 min_fee_computed,
                 previous_block_time,
+                previous_block_height,
                 unbonding_period,
+                chain_id_hash,
+                chain_id_hash_required,
             } => {
                 dest.push_byte(1);
                 tx.encode_to(dest);
                 inputs.encode_to(dest);
                 min_fee_computed.to_coin().encode_to(dest);
                 previous_block_time.encode_to(dest);
+                previous_block_height.encode_to(dest);
                 unbonding_period.encode_to(dest);
+                chain_id_hash.encode_to(dest);
+                chain_id_hash_required.encode_to(dest);
             }
         }
     }
@@ -63,16 +73,22 @@ impl Decode for EnclaveRequest {
             }
             1 => {
                 let tx = TxAux::decode(input)?;
-                let inputs: Vec<TxWithOutputs> = Vec::decode(input)?;
+                let inputs: Vec<(TxWithOutputs, TxInclusion)> = Vec::decode(input)?;
                 let fee = Coin::decode(input)?;
                 let previous_block_time = Timespec::decode(input)?;
+                let previous_block_height = BlockHeight::decode(input)?;
                 let unbonding_period = u32::decode(input)?;
+                let chain_id_hash = H256::decode(input)?;
+                let chain_id_hash_required = bool::decode(input)?;
                 Some(EnclaveRequest::VerifyTx {
                     tx,
                     inputs,
                     min_fee_computed: Fee::new(fee),
                     previous_block_time,
+                    previous_block_height,
                     unbonding_period,
+                    chain_id_hash,
+                    chain_id_hash_required,
                 })
             }
             _ => None,